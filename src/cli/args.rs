@@ -1,7 +1,29 @@
 use crate::cli::fields::EDITABLE_FIELDS;
+use crate::config::BackupFormat;
 use crate::i18n::{tr, tr_s};
+use crate::utils::{CitationFormat, OnConflict, OutputFormat};
 use clap::{Arg, ArgAction, Command};
 
+/// Formati di `export`, mutuamente esclusivi: (flag, chiave della guida).
+const EXPORT_FORMATS: [(&str, &str); 10] = [
+    ("csv", "export_csv_help"),
+    ("xlsx", "export_xlsx_help"),
+    ("ods", "export_ods_help"),
+    ("json", "export_json_help"),
+    ("marc", "export_marc_help"),
+    ("marcxml", "export_marcxml_help"),
+    ("bibtex", "export_bibtex_help"),
+    ("ris", "export_ris_help"),
+    ("csl-json", "export_csl_json_help"),
+    ("org", "export_org_help"),
+];
+
+/// Formati di `export` scritti in una cartella (`--html <DIR>`).
+const EXPORT_DIR_FORMATS: [(&str, &str); 2] = [
+    ("html", "export_html_help"),
+    ("markdown", "export_markdown_help"),
+];
+
 /// Costruisce la CLI localizzata usando le stringhe già caricate in memoria.
 pub fn build_cli() -> Command {
     Command::new(tr_s("app_name"))
@@ -39,6 +61,36 @@ pub fn build_cli() -> Command {
                 .help_heading(tr_s("help.global_options"))
                 .display_order(3),
         )
+        .arg(
+            Arg::new("library")
+                .long("library")
+                .help(tr_s("help_library"))
+                .value_name("NAME")
+                .global(true)
+                .num_args(1)
+                .help_heading(tr_s("help.global_options"))
+                .display_order(4),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help(tr_s("help_config_file"))
+                .value_name("FILE")
+                .global(true)
+                .num_args(1)
+                .help_heading(tr_s("help.global_options"))
+                .display_order(5),
+        )
+        .arg(
+            Arg::new("db")
+                .long("db")
+                .help(tr_s("help_db_path"))
+                .value_name("PATH")
+                .global(true)
+                .num_args(1)
+                .help_heading(tr_s("help.global_options"))
+                .display_order(6),
+        )
         // 📘 list command
         .subcommand(
             Command::new("list")
@@ -179,8 +231,220 @@ pub fn build_cli() -> Command {
                         .action(ArgAction::SetTrue)
                         .num_args(0)
                         .display_order(52),
+                )
+                .arg(
+                    Arg::new("purge")
+                        .long("purge")
+                        .help(tr("help.del.purge"))
+                        .action(ArgAction::SetTrue)
+                        .num_args(0)
+                        .display_order(53),
+                ),
+        )
+        // 🗑️ trash command
+        .subcommand(
+            Command::new("trash")
+                .about(tr_s("trash_about"))
+                .display_order(54)
+                .subcommand(Command::new("list").about(tr_s("trash_list_about")))
+                .subcommand(
+                    Command::new("restore")
+                        .about(tr_s("trash_restore_about"))
+                        .arg(
+                            Arg::new("id")
+                                .help(tr_s("trash_restore_id_help"))
+                                .required(true)
+                                .value_name("ID")
+                                .num_args(1)
+                                .value_parser(clap::value_parser!(i64)),
+                        ),
+                )
+                .subcommand(
+                    Command::new("empty")
+                        .about(tr_s("trash_empty_about"))
+                        .arg(
+                            Arg::new("older-than")
+                                .long("older-than")
+                                .help(tr_s("trash_older_than_help"))
+                                .value_name("AGE")
+                                .num_args(1),
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .short('f')
+                                .help(tr_s("trash_empty_force_help"))
+                                .action(ArgAction::SetTrue),
+                        ),
                 ),
         )
+        // 🕘 history command
+        .subcommand(
+            Command::new("history")
+                .about(tr_s("history_about"))
+                .display_order(55)
+                .arg(
+                    Arg::new("id")
+                        .help(tr_s("history_id_help"))
+                        .required(true)
+                        .value_name("ID")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(i64))
+                        .display_order(56),
+                ),
+        )
+        // ↩️ undo command
+        .subcommand(
+            Command::new("undo")
+                .about(tr_s("undo_about"))
+                .display_order(57)
+                .arg(
+                    Arg::new("count")
+                        .help(tr_s("undo_count_help"))
+                        .value_name("N")
+                        .num_args(1)
+                        .default_value("1")
+                        .value_parser(clap::value_parser!(usize))
+                        .display_order(58),
+                ),
+        )
+        // 📜 log command
+        .subcommand(
+            Command::new("log")
+                .about(tr_s("log_about"))
+                .display_order(58)
+                .subcommand(log_filter_args(
+                    Command::new("list").about(tr_s("log_list_about")).arg(
+                        Arg::new("limit")
+                            .long("limit")
+                            .short('n')
+                            .help(tr_s("log_limit_help"))
+                            .value_name("N")
+                            .num_args(1)
+                            .value_parser(clap::value_parser!(usize)),
+                    ),
+                    true,
+                ))
+                .subcommand(log_filter_args(
+                    Command::new("tail")
+                        .about(tr_s("log_tail_about"))
+                        .arg(
+                            Arg::new("count")
+                                .help(tr_s("log_tail_count_help"))
+                                .value_name("N")
+                                .num_args(1)
+                                .default_value("10")
+                                .value_parser(clap::value_parser!(usize)),
+                        )
+                        .arg(
+                            Arg::new("follow")
+                                .long("follow")
+                                .short('f')
+                                .help(tr_s("log_follow_help"))
+                                .action(ArgAction::SetTrue),
+                        ),
+                    false,
+                ))
+                .subcommand(log_filter_args(
+                    Command::new("stats").about(tr_s("log_stats_about")),
+                    true,
+                ))
+                .subcommand(
+                    Command::new("prune")
+                        .about(tr_s("log_prune_about"))
+                        .arg(
+                            Arg::new("older-than")
+                                .long("older-than")
+                                .help(tr_s("log_older_than_help"))
+                                .required(true)
+                                .value_name("AGE")
+                                .num_args(1),
+                        )
+                        .arg(
+                            Arg::new("dry-run")
+                                .long("dry-run")
+                                .help(tr_s("log_prune_dry_run_help"))
+                                .action(ArgAction::SetTrue),
+                        ),
+                ),
+        )
+        // 🏛️ library command
+        .subcommand(
+            Command::new("library")
+                .about(tr_s("library_about"))
+                .display_order(59)
+                .subcommand(Command::new("list").about(tr_s("library_list_about")))
+                .subcommand(
+                    Command::new("add")
+                        .about(tr_s("library_add_about"))
+                        .arg(
+                            Arg::new("name")
+                                .help(tr_s("library_name_help"))
+                                .required(true)
+                                .value_name("NAME")
+                                .num_args(1),
+                        )
+                        .arg(
+                            Arg::new("database")
+                                .long("database")
+                                .help(tr_s("library_db_help"))
+                                .value_name("PATH")
+                                .num_args(1),
+                        )
+                        .arg(
+                            Arg::new("language")
+                                .long("language")
+                                .help(tr_s("library_language_help"))
+                                .value_name("CODE")
+                                .num_args(1),
+                        ),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about(tr_s("library_remove_about"))
+                        .arg(
+                            Arg::new("name")
+                                .help(tr_s("library_name_help"))
+                                .required(true)
+                                .value_name("NAME")
+                                .num_args(1),
+                        ),
+                )
+                .subcommand(
+                    Command::new("use").about(tr_s("library_use_about")).arg(
+                        Arg::new("name")
+                            .help(tr_s("library_name_help"))
+                            .required(true)
+                            .value_name("NAME")
+                            .num_args(1),
+                    ),
+                )
+                .subcommand(
+                    Command::new("rename")
+                        .about(tr_s("library_rename_about"))
+                        .arg(
+                            Arg::new("old")
+                                .help(tr_s("library_name_help"))
+                                .required(true)
+                                .value_name("OLD")
+                                .num_args(1),
+                        )
+                        .arg(
+                            Arg::new("new")
+                                .help(tr_s("library_new_name_help"))
+                                .required(true)
+                                .value_name("NEW")
+                                .num_args(1),
+                        ),
+                ),
+        )
+        // 🌍 lang command
+        .subcommand(
+            Command::new("lang")
+                .about(tr_s("lang_about"))
+                .display_order(59)
+                .subcommand(Command::new("list").about(tr_s("lang_list_about"))),
+        )
         // ⚙️ config command
         .subcommand(
             Command::new("config")
@@ -218,6 +482,50 @@ pub fn build_cli() -> Command {
                         .help(tr_s("config_editor_help"))
                         .help_heading(tr_s("help.config_specific_options"))
                         .display_order(64),
+                )
+                .subcommand(
+                    Command::new("show").about(tr_s("config_show_about")).arg(
+                        Arg::new("effective")
+                            .long("effective")
+                            .help(tr_s("config_show_effective_help"))
+                            .action(ArgAction::SetTrue),
+                    ),
+                )
+                .subcommand(
+                    Command::new("get").about(tr_s("config_get_about")).arg(
+                        Arg::new("key")
+                            .help(tr_s("config_key_help"))
+                            .required(true)
+                            .value_name("KEY")
+                            .num_args(1),
+                    ),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about(tr_s("config_set_about"))
+                        .arg(
+                            Arg::new("key")
+                                .help(tr_s("config_key_help"))
+                                .required(true)
+                                .value_name("KEY")
+                                .num_args(1),
+                        )
+                        .arg(
+                            Arg::new("value")
+                                .help(tr_s("config_value_help"))
+                                .required(true)
+                                .value_name("VALUE")
+                                .num_args(1),
+                        ),
+                )
+                .subcommand(
+                    Command::new("unset").about(tr_s("config_unset_about")).arg(
+                        Arg::new("key")
+                            .help(tr_s("config_key_help"))
+                            .required(true)
+                            .value_name("KEY")
+                            .num_args(1),
+                    ),
                 ),
         )
         .subcommand(
@@ -261,6 +569,72 @@ pub fn build_cli() -> Command {
                         .value_name("PATH")
                         .help_heading(tr_s("help.db_specific_options"))
                         .display_order(69),
+                )
+                .subcommand(
+                    Command::new("migrate")
+                        .about(tr_s("db_migrate_about"))
+                        .display_order(70)
+                        .arg(
+                            Arg::new("status")
+                                .long("status")
+                                .help(tr_s("db_migrate_status_help"))
+                                .action(ArgAction::SetTrue)
+                                .conflicts_with_all(["to", "dry-run"])
+                                .help_heading(tr_s("help.db_specific_options"))
+                                .display_order(71),
+                        )
+                        .arg(
+                            Arg::new("to")
+                                .long("to")
+                                .help(tr_s("db_migrate_to_help"))
+                                .num_args(1)
+                                .value_name("N")
+                                .value_parser(clap::value_parser!(u32))
+                                .help_heading(tr_s("help.db_specific_options"))
+                                .display_order(72),
+                        )
+                        .arg(
+                            Arg::new("dry-run")
+                                .long("dry-run")
+                                .help(tr_s("db_migrate_dry_run_help"))
+                                .action(ArgAction::SetTrue)
+                                .help_heading(tr_s("help.db_specific_options"))
+                                .display_order(73),
+                        ),
+                )
+                .subcommand(
+                    Command::new("check")
+                        .about(tr_s("db_check_about"))
+                        .display_order(74),
+                )
+                .subcommand(
+                    Command::new("repair")
+                        .about(tr_s("db_repair_about"))
+                        .display_order(75)
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .short('f')
+                                .help(tr_s("db_repair_force_help"))
+                                .action(ArgAction::SetTrue)
+                                .help_heading(tr_s("help.db_specific_options"))
+                                .display_order(76),
+                        ),
+                )
+                .subcommand(
+                    Command::new("stats")
+                        .about(tr_s("db_stats_about"))
+                        .display_order(77),
+                )
+                .subcommand(
+                    Command::new("vacuum")
+                        .about(tr_s("db_vacuum_about"))
+                        .display_order(78),
+                )
+                .subcommand(
+                    Command::new("optimize")
+                        .about(tr_s("db_optimize_about"))
+                        .display_order(79),
                 ),
         )
         // 💾 backup command
@@ -275,49 +649,125 @@ pub fn build_cli() -> Command {
                         .action(ArgAction::SetTrue)
                         .help_heading(tr_s("help.backup_specific_options"))
                         .display_order(76),
-                ),
-        )
-        // 📤 export command
-        .subcommand(
-            Command::new("export")
-                .about(tr_s("export_about"))
-                .display_order(80)
+                )
                 .arg(
-                    Arg::new("csv")
-                        .long("csv")
-                        .help(tr_s("export_csv_help"))
-                        .action(ArgAction::SetTrue)
-                        .conflicts_with_all(["xlsx", "json"])
-                        .help_heading(tr_s("help.export_specific_options"))
-                        .display_order(81),
+                    Arg::new("format")
+                        .long("format")
+                        .help(tr_s("backup_format_help"))
+                        .num_args(1)
+                        .value_name("FORMAT")
+                        .value_parser(BackupFormat::NAMES)
+                        .conflicts_with("compress")
+                        .help_heading(tr_s("help.backup_specific_options"))
+                        .display_order(76),
                 )
                 .arg(
-                    Arg::new("xlsx")
-                        .long("xlsx")
-                        .help(tr_s("export_xlsx_help"))
-                        .conflicts_with_all(["csv", "json"])
+                    Arg::new("encrypt")
+                        .long("encrypt")
+                        .help(tr_s("backup_encrypt_help"))
                         .action(ArgAction::SetTrue)
-                        .help_heading(tr_s("help.export_specific_options"))
-                        .display_order(82),
+                        .help_heading(tr_s("help.backup_specific_options"))
+                        .display_order(77),
+                )
+                .subcommand(
+                    Command::new("restore")
+                        .about(tr_s("backup_restore_about"))
+                        .display_order(79)
+                        .arg(
+                            Arg::new("file")
+                                .help(tr_s("backup_restore_file_help"))
+                                .required(true)
+                                .value_name("FILE")
+                                .num_args(1)
+                                .help_heading(tr_s("help.backup_specific_options"))
+                                .display_order(80),
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .short('f')
+                                .help(tr_s("backup_restore_force_help"))
+                                .action(ArgAction::SetTrue)
+                                .help_heading(tr_s("help.backup_specific_options"))
+                                .display_order(81),
+                        ),
                 )
+                .subcommand(
+                    Command::new("prune")
+                        .about(tr_s("backup_prune_about"))
+                        .display_order(77)
+                        .arg(
+                            Arg::new("dry-run")
+                                .long("dry-run")
+                                .help(tr_s("backup_prune_dry_run_help"))
+                                .action(ArgAction::SetTrue)
+                                .help_heading(tr_s("help.backup_specific_options"))
+                                .display_order(78),
+                        ),
+                ),
+        )
+        // 📤 export command
+        .subcommand({
+            let mut cmd = Command::new("export")
+                .about(tr_s("export_about"))
+                .display_order(80);
+
+            // Un formato per volta: ogni formato esclude tutti gli altri
+            let formats = || EXPORT_FORMATS.iter().chain(&EXPORT_DIR_FORMATS);
+            for (i, (name, help)) in formats().enumerate() {
+                let others: Vec<&str> = formats()
+                    .map(|(other, _)| *other)
+                    .filter(|other| other != name)
+                    .collect();
+                let arg = Arg::new(*name)
+                    .long(*name)
+                    .help(tr_s(help))
+                    .help_heading(tr_s("help.export_specific_options"))
+                    .display_order(81 + i);
+                cmd = cmd.arg(if EXPORT_FORMATS.iter().any(|(n, _)| n == name) {
+                    arg.action(ArgAction::SetTrue).conflicts_with_all(others)
+                } else {
+                    // la cartella sostituisce --output
+                    arg.value_name("DIR")
+                        .num_args(1)
+                        .conflicts_with_all(others)
+                        .conflicts_with("output")
+                });
+            }
+
+            cmd.arg(
+                Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .help(tr_s("export_output_help"))
+                    .value_name("FILE")
+                    .required(false)
+                    .help_heading(tr_s("help.export_specific_options"))
+                    .display_order(81 + EXPORT_FORMATS.len() + EXPORT_DIR_FORMATS.len()),
+            )
+        })
+        // 📚 cite command
+        .subcommand(
+            Command::new("cite")
+                .about(tr_s("cite_about"))
+                .display_order(85)
                 .arg(
-                    Arg::new("json")
-                        .long("json")
-                        .help(tr_s("export_json_help"))
-                        .conflicts_with_all(["csv", "xlsx"])
-                        .action(ArgAction::SetTrue)
-                        .help_heading(tr_s("help.export_specific_options"))
-                        .display_order(83),
+                    Arg::new("key")
+                        .help(tr_s("cite_key_help"))
+                        .required(true)
+                        .value_name("ID|ISBN")
+                        .num_args(1)
+                        .display_order(86),
                 )
                 .arg(
-                    Arg::new("output")
-                        .short('o')
-                        .long("output")
-                        .help(tr_s("export_output_help"))
-                        .value_name("FILE")
-                        .required(false)
-                        .help_heading(tr_s("help.export_specific_options"))
-                        .display_order(84),
+                    Arg::new("format")
+                        .long("format")
+                        .help(tr_s("cite_format_help"))
+                        .value_name("FORMAT")
+                        .num_args(1)
+                        .default_value("bibtex")
+                        .value_parser(CitationFormat::NAMES)
+                        .display_order(87),
                 ),
         )
         // 📥 import command
@@ -330,7 +780,7 @@ pub fn build_cli() -> Command {
                         .short('f')
                         .long("file")
                         .help(tr_s("import_file_help"))
-                        .required(true)
+                        .required_unless_present_any(["goodreads", "calibre", "marc"])
                         .value_name("PATH")
                         .help_heading(tr_s("help.import_specific_options"))
                         .display_order(91),
@@ -364,6 +814,176 @@ pub fn build_cli() -> Command {
                         .value_parser(clap::builder::NonEmptyStringValueParser::new())
                         .help_heading(tr_s("help.import_specific_options"))
                         .display_order(94),
+                )
+                .arg(
+                    Arg::new("goodreads")
+                        .long("goodreads")
+                        .help(tr_s("import_goodreads_help"))
+                        .num_args(1)
+                        .value_name("EXPORT.CSV")
+                        .conflicts_with_all(["file", "csv", "json", "delimiter"])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(95),
+                )
+                .arg(
+                    Arg::new("calibre")
+                        .long("calibre")
+                        .help(tr_s("import_calibre_help"))
+                        .num_args(1)
+                        .value_name("LIBRARY_DIR")
+                        .conflicts_with_all(["file", "csv", "json", "delimiter", "goodreads"])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(96),
+                )
+                .arg(
+                    Arg::new("xlsx")
+                        .long("xlsx")
+                        .help(tr_s("import_xlsx_help"))
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "csv",
+                            "json",
+                            "delimiter",
+                            "goodreads",
+                            "calibre",
+                            "marc",
+                        ])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(97),
+                )
+                .arg(
+                    Arg::new("ods")
+                        .long("ods")
+                        .help(tr_s("import_ods_help"))
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "csv",
+                            "json",
+                            "delimiter",
+                            "goodreads",
+                            "calibre",
+                            "marc",
+                            "xlsx",
+                        ])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(97),
+                )
+                .arg(
+                    Arg::new("map-file")
+                        .long("map-file")
+                        .help(tr_s("import_map_file_help"))
+                        .num_args(1)
+                        .value_name("FILE.YAML")
+                        .conflicts_with_all(["json", "goodreads", "calibre", "marc"])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(98),
+                )
+                .arg(
+                    Arg::new("map")
+                        .long("map")
+                        .help(tr_s("import_map_help"))
+                        .num_args(1)
+                        .value_name("COLUMN=FIELD[:TRANSFORM],...")
+                        .action(ArgAction::Append)
+                        .conflicts_with_all(["json", "goodreads", "calibre", "marc"])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(99),
+                )
+                .arg(
+                    Arg::new("default")
+                        .long("default")
+                        .help(tr_s("import_default_help"))
+                        .num_args(1)
+                        .value_name("FIELD=VALUE")
+                        .action(ArgAction::Append)
+                        .conflicts_with_all(["json", "goodreads", "calibre", "marc"])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(100),
+                )
+                .arg(
+                    Arg::new("infer")
+                        .long("infer")
+                        .help(tr_s("import_infer_help"))
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["json", "goodreads", "calibre", "marc"])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(101),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help(tr_s("import_dry_run_help"))
+                        .action(ArgAction::SetTrue)
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(102),
+                )
+                .arg(
+                    Arg::new("on-conflict")
+                        .long("on-conflict")
+                        .help(tr_s("import_on_conflict_help"))
+                        .value_name("MODE")
+                        .num_args(1)
+                        .default_value("skip")
+                        .value_parser(OnConflict::NAMES)
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(103),
+                )
+                .arg(
+                    Arg::new("partial")
+                        .long("partial")
+                        .help(tr_s("import_partial_help"))
+                        .action(ArgAction::SetTrue)
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(104),
+                )
+                .arg(
+                    Arg::new("marc")
+                        .long("marc")
+                        .help(tr_s("import_marc_help"))
+                        .num_args(1)
+                        .value_name("FILE.MRC|FILE.XML")
+                        .conflicts_with_all([
+                            "file",
+                            "csv",
+                            "json",
+                            "delimiter",
+                            "goodreads",
+                            "calibre",
+                        ])
+                        .help_heading(tr_s("help.import_specific_options"))
+                        .display_order(105),
+                ),
+        )
+        // 🛠️ dev command
+        .subcommand(
+            Command::new("dev")
+                .about(tr_s("dev_about"))
+                .display_order(190)
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("i18n-check")
+                        .about(tr_s("dev_i18n_check_about"))
+                        .display_order(191)
+                        .arg(
+                            Arg::new("root")
+                                .long("root")
+                                .help(tr_s("dev_root_help"))
+                                .num_args(1)
+                                .value_name("DIR")
+                                .default_value(".")
+                                .help_heading(tr_s("help.dev_specific_options"))
+                                .display_order(192),
+                        )
+                        .arg(
+                            Arg::new("locale")
+                                .long("locale")
+                                .help(tr_s("dev_locale_help"))
+                                .num_args(1)
+                                .value_name("FILE")
+                                .action(ArgAction::Append)
+                                .help_heading(tr_s("help.dev_specific_options"))
+                                .display_order(193),
+                        ),
                 ),
         )
         .subcommand(
@@ -379,3 +999,56 @@ pub fn build_cli() -> Command {
                 ),
         )
 }
+
+/// Filtri comuni di `log list|tail|stats` e il formato di output.
+fn log_filter_args(cmd: Command, with_dates: bool) -> Command {
+    let cmd = cmd
+        .arg(
+            Arg::new("operation")
+                .long("operation")
+                .help(tr_s("log_operation_help"))
+                .value_name("OP")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("target")
+                .long("target")
+                .help(tr_s("log_target_help"))
+                .value_name("TARGET")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("book")
+                .long("book")
+                .help(tr_s("log_book_help"))
+                .value_name("ID")
+                .num_args(1)
+                .value_parser(clap::value_parser!(i64)),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help(tr_s("output_format_help"))
+                .value_name("FORMAT")
+                .num_args(1)
+                .default_value("table")
+                .value_parser(OutputFormat::NAMES),
+        );
+    if !with_dates {
+        return cmd;
+    }
+    cmd.arg(
+        Arg::new("since")
+            .long("since")
+            .help(tr_s("log_since_help"))
+            .value_name("WHEN")
+            .num_args(1),
+    )
+    .arg(
+        Arg::new("until")
+            .long("until")
+            .help(tr_s("log_until_help"))
+            .value_name("WHEN")
+            .num_args(1),
+    )
+}
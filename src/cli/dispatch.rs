@@ -1,9 +1,11 @@
 use crate::cli::build_cli;
 use crate::commands::{handle_config, handle_edit_book, handle_list, handle_search};
-use crate::config::AppConfig;
+use crate::config::{AppConfig, BackupFormat, CliOverrides};
+use crate::db::log::LogFilter;
 use crate::i18n::{tr, tr_with};
-use crate::utils::print_err;
+use crate::utils::{OutputFormat, parse_instant, print_err};
 use rusqlite::Connection;
+use std::path::PathBuf;
 
 /// Dispatch principale dei comandi
 pub fn run_cli(
@@ -16,7 +18,8 @@ pub fn run_cli(
         let id = matches.get_one::<i32>("id").copied();
         let details = matches.get_flag("details");
         let compact = matches.get_flag("compact");
-        handle_list(conn, short, id, details, compact)?;
+        let library = config.active_library.as_deref();
+        handle_list(conn, short, id, details, compact, library)?;
         Ok(())
     } else if let Some(("search", sub_m)) = matches.subcommand() {
         if let Some(query) = sub_m.get_one::<String>("query") {
@@ -27,19 +30,66 @@ pub fn run_cli(
         }
         Ok(())
     } else if let Some(("config", sub_m)) = matches.subcommand() {
-        let init = sub_m.get_flag("init");
-        let print = sub_m.get_flag("print");
-        let edit = sub_m.get_flag("edit");
-        let editor = sub_m.get_one::<String>("editor").cloned();
+        use crate::commands::config::{
+            handle_config_get, handle_config_set, handle_config_show, handle_config_unset,
+        };
+        let key = |m: &clap::ArgMatches| m.get_one::<String>("key").cloned().unwrap_or_default();
+        match sub_m.subcommand() {
+            Some(("show", show_m)) => {
+                let global = |id: &str| matches.get_one::<String>(id).cloned();
+                let overrides = CliOverrides {
+                    config_file: global("config").map(PathBuf::from),
+                    database: global("db"),
+                    library: global("library"),
+                    language: global("lang"),
+                };
+                handle_config_show(overrides, show_m.get_flag("effective"))?;
+            }
+            Some(("get", get_m)) => handle_config_get(&key(get_m))?,
+            Some(("set", set_m)) => {
+                let value = set_m
+                    .get_one::<String>("value")
+                    .cloned()
+                    .unwrap_or_default();
+                handle_config_set(conn, &key(set_m), &value)?;
+            }
+            Some(("unset", unset_m)) => handle_config_unset(conn, &key(unset_m))?,
+            _ => {
+                let init = sub_m.get_flag("init");
+                let print = sub_m.get_flag("print");
+                let edit = sub_m.get_flag("edit");
+                let editor = sub_m.get_one::<String>("editor").cloned();
 
-        Ok(handle_config(init, print, edit, editor)?)
+                handle_config(init, print, edit, editor)?;
+            }
+        }
+        Ok(())
     } else if let Some(("db", sub_m)) = matches.subcommand() {
-        let init = sub_m.get_flag("init");
-        let reset = sub_m.get_flag("reset");
-        let copy = sub_m.get_flag("copy");
-        let file = sub_m.get_one::<String>("file").map(|s| s.as_str());
+        use crate::commands::db::{
+            handle_db, handle_db_check, handle_db_migrate, handle_db_optimize, handle_db_repair,
+            handle_db_stats, handle_db_vacuum,
+        };
+        match sub_m.subcommand() {
+            Some(("migrate", migrate_m)) => {
+                let status = migrate_m.get_flag("status");
+                let to = migrate_m.get_one::<u32>("to").copied();
+                let dry_run = migrate_m.get_flag("dry-run");
+                handle_db_migrate(conn, status, to, dry_run)?;
+            }
+            Some(("check", _)) => handle_db_check(conn)?,
+            Some(("repair", repair_m)) => handle_db_repair(conn, repair_m.get_flag("force"))?,
+            Some(("stats", _)) => handle_db_stats(conn)?,
+            Some(("vacuum", _)) => handle_db_vacuum(conn)?,
+            Some(("optimize", _)) => handle_db_optimize(conn)?,
+            _ => {
+                let init = sub_m.get_flag("init");
+                let reset = sub_m.get_flag("reset");
+                let copy = sub_m.get_flag("copy");
+                let file = sub_m.get_one::<String>("file").map(|s| s.as_str());
 
-        crate::commands::db::handle_db(config, init, reset, copy, file)?;
+                handle_db(config, init, reset, copy, file)?;
+            }
+        }
         Ok(())
     } else if let Some(("edit", sub_m)) = matches.subcommand() {
         if let Some(("book", book_m)) = sub_m.subcommand() {
@@ -49,20 +99,161 @@ pub fn run_cli(
     } else if let Some(("del", sub_m)) = matches.subcommand() {
         if let Some(key) = sub_m.get_one::<String>("key") {
             let force = sub_m.get_flag("force");
-            crate::commands::handle_del_book(conn, key, force)?;
+            let purge = sub_m.get_flag("purge");
+            crate::commands::handle_del_book(conn, key, force, purge)?;
+        }
+        Ok(())
+    } else if let Some(("trash", sub_m)) = matches.subcommand() {
+        match sub_m.subcommand() {
+            Some(("restore", restore_m)) => {
+                if let Some(id) = restore_m.get_one::<i64>("id") {
+                    crate::commands::handle_trash_restore(conn, *id)?;
+                }
+            }
+            Some(("empty", empty_m)) => {
+                let older_than = empty_m.get_one::<String>("older-than").map(String::as_str);
+                let force = empty_m.get_flag("force");
+                crate::commands::handle_trash_empty(conn, older_than, force)?;
+            }
+            // `trash` senza sottocomando equivale a `trash list`
+            _ => crate::commands::handle_trash_list(conn)?,
+        }
+        Ok(())
+    } else if let Some(("log", sub_m)) = matches.subcommand() {
+        use crate::commands::log::{
+            handle_log_list, handle_log_prune, handle_log_stats, handle_log_tail,
+        };
+        if let Some(("prune", prune_m)) = sub_m.subcommand() {
+            let older_than = prune_m
+                .get_one::<String>("older-than")
+                .map(String::as_str)
+                .unwrap_or_default();
+            handle_log_prune(conn, older_than, prune_m.get_flag("dry-run"))?;
+            return Ok(());
+        }
+
+        let Some(filter) = sub_m
+            .subcommand()
+            .map_or(Some(LogFilter::default()), |(_, m)| log_filter(m))
+        else {
+            return Ok(());
+        };
+        let format = |m: &clap::ArgMatches| {
+            m.get_one::<String>("format")
+                .and_then(|f| f.parse::<OutputFormat>().ok())
+                .unwrap_or_default()
+        };
+        match sub_m.subcommand() {
+            Some(("tail", tail_m)) => {
+                let count = tail_m.get_one::<usize>("count").copied().unwrap_or(10);
+                let follow = tail_m.get_flag("follow");
+                handle_log_tail(conn, &filter, count, follow, format(tail_m))?;
+            }
+            Some(("stats", stats_m)) => handle_log_stats(conn, &filter, format(stats_m))?,
+            Some(("list", list_m)) => handle_log_list(conn, &filter, format(list_m))?,
+            // `log` senza sottocomando: le ultime voci, come `log tail`
+            _ => handle_log_tail(conn, &filter, 10, false, OutputFormat::Table)?,
+        }
+        Ok(())
+    } else if let Some(("library", sub_m)) = matches.subcommand() {
+        use crate::commands::library::{
+            handle_library_add, handle_library_list, handle_library_remove, handle_library_rename,
+            handle_library_use,
+        };
+        let name =
+            |m: &clap::ArgMatches, id: &str| m.get_one::<String>(id).cloned().unwrap_or_default();
+        match sub_m.subcommand() {
+            Some(("add", add_m)) => {
+                let db = add_m.get_one::<String>("database").map(String::as_str);
+                let language = add_m.get_one::<String>("language").map(String::as_str);
+                handle_library_add(conn, &name(add_m, "name"), db, language)?;
+            }
+            Some(("remove", remove_m)) => handle_library_remove(conn, &name(remove_m, "name"))?,
+            Some(("use", use_m)) => handle_library_use(conn, &name(use_m, "name"))?,
+            Some(("rename", rename_m)) => {
+                handle_library_rename(conn, &name(rename_m, "old"), &name(rename_m, "new"))?
+            }
+            // `library` senza sottocomando equivale a `library list`
+            _ => handle_library_list()?,
+        }
+        Ok(())
+    } else if let Some(("lang", _)) = matches.subcommand() {
+        // `lang` senza sottocomando equivale a `lang list`
+        crate::commands::handle_lang_list(&config.language)?;
+        Ok(())
+    } else if let Some(("dev", sub_m)) = matches.subcommand() {
+        if let Some(("i18n-check", check_m)) = sub_m.subcommand() {
+            let root = check_m
+                .get_one::<String>("root")
+                .map(PathBuf::from)
+                .unwrap_or_default();
+            let extra: Vec<PathBuf> = check_m
+                .get_many::<String>("locale")
+                .map(|v| v.map(PathBuf::from).collect())
+                .unwrap_or_default();
+            crate::commands::handle_dev_i18n_check(&root, &extra)?;
         }
         Ok(())
+    } else if let Some(("history", sub_m)) = matches.subcommand() {
+        if let Some(id) = sub_m.get_one::<i64>("id") {
+            crate::commands::handle_history(conn, *id)?;
+        }
+        Ok(())
+    } else if let Some(("undo", sub_m)) = matches.subcommand() {
+        let count = sub_m.get_one::<usize>("count").copied().unwrap_or(1);
+        crate::commands::handle_undo(conn, count)?;
+        Ok(())
     } else if let Some(("backup", sub_m)) = matches.subcommand() {
-        let compress = sub_m.get_flag("compress");
-        crate::commands::handle_backup(conn, compress)?;
+        if let Some(("prune", prune_m)) = sub_m.subcommand() {
+            let dry_run = prune_m.get_flag("dry-run");
+            crate::commands::handle_backup_prune(conn, config, dry_run)?;
+            return Ok(());
+        }
+        if let Some(("restore", restore_m)) = sub_m.subcommand() {
+            if let Some(file) = restore_m.get_one::<String>("file") {
+                let force = restore_m.get_flag("force");
+                crate::commands::handle_backup_restore(conn, config, file, force)?;
+            }
+            return Ok(());
+        }
+        // --format wins; --compress picks the configured archive format (tar.gz if
+        // the configured default is a plain copy)
+        let configured = config.backup.format;
+        let format = match sub_m.get_one::<String>("format") {
+            Some(name) => name.parse::<BackupFormat>().unwrap_or(configured),
+            None if sub_m.get_flag("compress") && !configured.is_archive() => BackupFormat::TarGz,
+            None => configured,
+        };
+        let encrypt = sub_m.get_flag("encrypt");
+        crate::commands::handle_backup(conn, config, format, encrypt)?;
         Ok(())
     } else if let Some(("export", sub_m)) = matches.subcommand() {
         let output_path = sub_m.get_one::<String>("output").cloned();
         let export_csv = sub_m.get_flag("csv");
         let export_xlsx = sub_m.get_flag("xlsx");
         let export_json = sub_m.get_flag("json");
+        let export_marc = sub_m.get_flag("marc");
+        let export_marcxml = sub_m.get_flag("marcxml");
+
+        let citations = crate::utils::CitationFormat::NAMES
+            .into_iter()
+            .find(|name| sub_m.get_flag(name))
+            .and_then(|name| name.parse().ok());
 
-        if export_csv || (!export_xlsx && !export_json) {
+        let library = config.active_library.as_deref();
+        if let Some(dir) = sub_m.get_one::<String>("html") {
+            crate::commands::handle_export_html(conn, dir, library)?;
+        } else if let Some(dir) = sub_m.get_one::<String>("markdown") {
+            crate::commands::handle_export_markdown(conn, dir, library)?;
+        } else if sub_m.get_flag("org") {
+            crate::commands::handle_export_org(conn, output_path, library)?;
+        } else if export_marc || export_marcxml {
+            crate::commands::handle_export_marc(conn, output_path, export_marcxml)?;
+        } else if let Some(format) = citations {
+            crate::commands::handle_export_citations(conn, output_path, format)?;
+        } else if sub_m.get_flag("ods") {
+            crate::commands::handle_export_ods(conn, output_path)?;
+        } else if export_csv || (!export_xlsx && !export_json) {
             crate::commands::handle_export_csv(conn, output_path)?;
         } else if export_xlsx {
             crate::commands::handle_export_xlsx(conn, output_path)?;
@@ -70,7 +261,53 @@ pub fn run_cli(
             crate::commands::handle_export_json(conn, output_path)?;
         }
         Ok(())
+    } else if let Some(("cite", sub_m)) = matches.subcommand() {
+        if let Some(key) = sub_m.get_one::<String>("key") {
+            let format = sub_m
+                .get_one::<String>("format")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default();
+            crate::commands::handle_cite(conn, key, format)?;
+        }
+        Ok(())
     } else if let Some(("import", sub_m)) = matches.subcommand() {
+        let options = crate::utils::ImportOptions {
+            dry_run: sub_m.get_flag("dry-run"),
+            on_conflict: sub_m
+                .get_one::<String>("on-conflict")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default(),
+            partial: sub_m.get_flag("partial"),
+        };
+
+        if let Some(export) = sub_m.get_one::<String>("goodreads") {
+            if let Err(e) = crate::commands::handle_import_goodreads(conn, export, &options) {
+                print_err(&tr_with(
+                    "import.error.unexpected",
+                    &[("error", &e.to_string())],
+                ));
+            }
+            return Ok(());
+        }
+        if let Some(marc) = sub_m.get_one::<String>("marc") {
+            if let Err(e) = crate::commands::handle_import_marc(conn, marc, &options) {
+                print_err(&tr_with(
+                    "import.error.unexpected",
+                    &[("error", &e.to_string())],
+                ));
+            }
+            return Ok(());
+        }
+        if let Some(library) = sub_m.get_one::<String>("calibre") {
+            if let Err(e) = crate::commands::handle_import_calibre(conn, library, &options) {
+                print_err(&tr_with(
+                    "import.error.unexpected",
+                    &[("error", &e.to_string())],
+                ));
+            }
+            return Ok(());
+        }
+
         let file_path = sub_m.get_one::<String>("file").cloned();
         if file_path.is_none() {
             print_err(&tr("import.error.missing_file"));
@@ -84,10 +321,27 @@ pub fn run_cli(
             .and_then(|s| s.chars().next())
             .unwrap_or(',');
 
+        let mapping = crate::utils::MappingOptions {
+            map_file: sub_m.get_one::<String>("map-file").map(PathBuf::from),
+            rules: sub_m
+                .get_many::<String>("map")
+                .map(|v| v.cloned().collect())
+                .unwrap_or_default(),
+            defaults: sub_m
+                .get_many::<String>("default")
+                .map(|v| v.cloned().collect())
+                .unwrap_or_default(),
+            infer: sub_m.get_flag("infer"),
+        };
+
         let result = if import_json {
-            crate::commands::handle_import_json(conn, &file)
+            crate::commands::handle_import_json(conn, &file, &options)
+        } else if sub_m.get_flag("xlsx") {
+            crate::commands::handle_import_xlsx(conn, &file, &mapping, &options)
+        } else if sub_m.get_flag("ods") {
+            crate::commands::handle_import_ods(conn, &file, &mapping, &options)
         } else {
-            crate::commands::handle_import_csv(conn, &file, delimiter_char)
+            crate::commands::handle_import_csv(conn, &file, delimiter_char, &mapping, &options)
         };
 
         if let Err(e) = result {
@@ -124,3 +378,34 @@ pub fn run_cli(
         Ok(())
     }
 }
+
+/// Builds the filter of `log list|tail|stats`; `None` (after printing the
+/// error) if `--since` or `--until` is not a valid instant.
+fn log_filter(m: &clap::ArgMatches) -> Option<LogFilter> {
+    let text = |id: &str| {
+        m.try_get_one::<String>(id)
+            .ok()
+            .flatten()
+            .map(String::to_string)
+    };
+    let instant = |id: &str, end_of_day: bool| match text(id) {
+        Some(value) => match parse_instant(&value, end_of_day) {
+            Some(dt) => Ok(Some(dt)),
+            None => {
+                print_err(&tr_with("log.invalid_instant", &[("value", &value)]));
+                Err(())
+            }
+        },
+        None => Ok(None),
+    };
+
+    Some(LogFilter {
+        operation: text("operation"),
+        target: text("target"),
+        book_id: m.get_one::<i64>("book").copied(),
+        since: instant("since", false).ok()?,
+        until: instant("until", true).ok()?,
+        after_id: None,
+        limit: m.try_get_one::<usize>("limit").ok().flatten().copied(),
+    })
+}
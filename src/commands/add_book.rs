@@ -1,11 +1,16 @@
+use crate::db::history::record_insert;
+use crate::db::trashed_id;
 use crate::i18n::{tr, tr_with};
 use crate::models::book::Book;
 use crate::utils::isbn::normalize_isbn;
-use crate::utils::{is_verbose, lang_code_to_name, print_err, print_info, print_ok, print_warn};
+use crate::utils::{
+    is_verbose, lang_code_to_name, print_err, print_info, print_ok, print_warn, write_log_with,
+};
 use chrono::Utc;
 use reqwest::blocking::get;
 use rusqlite::{Connection, Error as RusqliteError, ErrorCode};
 use serde::Deserialize;
+use serde_json::json;
 use std::error::Error;
 
 #[derive(Debug, Deserialize, Default)]
@@ -91,6 +96,12 @@ pub fn handle_add_book(conn: &Connection, isbn: &str) -> Result<(), Box<dyn Erro
                     row: None,
                     position: None,
                     added_at: Some(Utc::now()),
+                    rating: None,
+                    read_status: None,
+                    read_at: None,
+                    tags: None,
+                    review: None,
+                    format: None,
                 };
 
                 match conn.execute(
@@ -109,13 +120,33 @@ pub fn handle_add_book(conn: &Connection, isbn: &str) -> Result<(), Box<dyn Erro
                     ],
                 ) {
                     Ok(_) => {
+                        let book_id = conn.last_insert_rowid();
+                        if let Err(e) = record_insert(conn, book_id) {
+                            print_err(&tr_with("history.record_failed", &[("error", &e.to_string())]));
+                        }
+                        let details = json!({ "title": new_book.title, "isbn": new_book.isbn });
+                        let _ = write_log_with(
+                            conn,
+                            "ADD_BOOK",
+                            "BOOKS",
+                            &format!("Book {} added", new_book.isbn),
+                            Some(book_id),
+                            Some(&details),
+                        );
                         print_ok(&tr_with("add.success", &[("title", &new_book.title)]), true);
 
                     },
                     Err(e) => {
                         if let RusqliteError::SqliteFailure(err, _) = &e {
                             if err.code == ErrorCode::ConstraintViolation {
-                                print_warn(&tr("add.duplicate_isbn"));
+                                // L'ISBN può appartenere a un libro nel cestino
+                                match trashed_id(conn, &new_book.isbn, true) {
+                                    Ok(Some(id)) => print_warn(&tr_with(
+                                        "add.duplicate_isbn_trashed",
+                                        &[("id", &id.to_string())],
+                                    )),
+                                    _ => print_warn(&tr("add.duplicate_isbn")),
+                                }
                             } else {
                                 print_err(&tr("add.sql_error"));
                             }
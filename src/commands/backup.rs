@@ -1,111 +1,343 @@
-use crate::config;
-use crate::i18n::{tr, tr_with};
-use crate::utils::{print_err, print_ok};
-use chrono::Local;
+use crate::config::{AppConfig, BackupFormat};
+use crate::db::migrations::{MigrationError, latest_version};
+use crate::i18n::{tr, tr_fmt, tr_with};
+use crate::utils::archive::{BackupManifest, read_archive, sha256_hex, write_archive};
+use crate::utils::backup::{BACKUP_PREFIX, backup_timestamp, snapshot_file};
+use crate::utils::crypto::{
+    CryptoError, ENCRYPTED_EXT, decrypt_bytes, encrypt_bytes, is_encrypted,
+};
+use crate::utils::{backup_dir_for, list_backups, print_err, print_info, print_ok, print_warn};
+use crate::utils::{is_verbose, select_prunable, write_log};
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, MAIN_DB, OpenFlags};
 use std::fs;
-use std::fs::File;
-use std::io::{self};
-use std::path::PathBuf;
-
-#[cfg(target_os = "windows")]
-use std::io::Write;
-#[cfg(target_os = "windows")]
-use zip::{CompressionMethod, ZipWriter, write::FileOptions};
-
-use crate::utils::icons::ERR;
-#[cfg(not(target_os = "windows"))]
-use flate2::{Compression, write::GzEncoder};
-#[cfg(not(target_os = "windows"))]
-use std::path::Path;
-#[cfg(not(target_os = "windows"))]
-use tar::Builder;
-
-pub fn handle_backup(_conn: &rusqlite::Connection, compress: bool) -> io::Result<()> {
-    let fail_mess = tr("app.config.load_failed");
-    let conf = config::load_or_init().unwrap_or_else(|_| panic!("{}{}", ERR, fail_mess));
-    let db_path = PathBuf::from(&conf.database);
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Environment variable read before prompting for a backup passphrase.
+pub const PASSPHRASE_ENV: &str = "LIBRIUS_BACKUP_PASSPHRASE";
+
+/// Handles `backup`: writes the database to the `backups` directory in the
+/// requested `format`, optionally encrypted.
+///
+/// Archive formats (zip, tar.gz, tar.zst) also contain a `manifest.json`
+/// with the Librius version, applied schema patches, book count and the
+/// SHA-256 of the database file.
+pub fn handle_backup(
+    conn: &Connection,
+    config: &AppConfig,
+    format: BackupFormat,
+    encrypt: bool,
+) -> io::Result<()> {
+    let db_path = PathBuf::from(&config.database);
 
     if !db_path.exists() {
         print_err(&tr("backup.error.not_found"));
         return Ok(());
     }
 
-    let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
-    let backup_dir = db_path.parent().unwrap().join("backups");
+    let timestamp = backup_timestamp();
+    let backup_dir = backup_dir_for(&db_path);
     fs::create_dir_all(&backup_dir)?;
 
-    if compress {
-        // --- compressed backup
-        #[cfg(target_os = "windows")]
-        {
-            let zip_name = format!("librius_backup_{}.zip", timestamp);
-            let zip_path = backup_dir.join(&zip_name);
-            compress_zip(&db_path, &zip_path)?;
-            print_ok(
-                &tr_with(
-                    "backup.ok.compressed",
-                    &[("path", &zip_path.display().to_string())],
-                ),
-                true,
-            );
-        }
+    // --- archive content (plain copy or archive + manifest)
+    let db_bytes = fs::read(&db_path)?;
+    let db_file = db_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "librius.sqlite".to_string());
+    let manifest = BackupManifest::build(conn, &db_file, &db_bytes);
+    let data = write_archive(format, &db_bytes, &manifest)?;
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            let tar_name = format!("librius_backup_{}.tar.gz", timestamp);
-            let tar_path = backup_dir.join(&tar_name);
-            compress_tar_gz(&db_path, &tar_path)?;
-            print_ok(
-                &tr_with(
-                    "backup.ok.compressed",
-                    &[("path", &tar_path.display().to_string())],
-                ),
-                true,
-            );
-        }
-    } else {
-        // --- plain copy
-        let backup_name = format!("librius_backup_{}.sqlite", timestamp);
-        let backup_path = backup_dir.join(&backup_name);
-        fs::copy(&db_path, &backup_path)?;
+    // --- optional encryption
+    if encrypt {
+        let passphrase = match read_passphrase(true)? {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let encrypted = match encrypt_bytes(&data, &passphrase) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                print_err(&e.to_string());
+                return Ok(());
+            }
+        };
+
+        let name = format!(
+            "{}{}.{}.{}",
+            BACKUP_PREFIX,
+            timestamp,
+            format.ext(),
+            ENCRYPTED_EXT
+        );
+        let path = backup_dir.join(&name);
+        fs::write(&path, encrypted)?;
         print_ok(
             &tr_with(
-                "backup.ok.plain",
-                &[("path", &backup_path.display().to_string())],
+                "backup.ok.encrypted",
+                &[("path", &path.display().to_string())],
             ),
             true,
         );
+        return Ok(());
     }
 
+    let name = format!("{}{}.{}", BACKUP_PREFIX, timestamp, format.ext());
+    let path = backup_dir.join(&name);
+    fs::write(&path, data)?;
+
+    let msg_key = if format.is_archive() {
+        "backup.ok.compressed"
+    } else {
+        "backup.ok.plain"
+    };
+    print_ok(
+        &tr_with(msg_key, &[("path", &path.display().to_string())]),
+        true,
+    );
+
     Ok(())
 }
 
-#[cfg(target_os = "windows")]
-fn compress_zip(src: &PathBuf, dest_zip: &PathBuf) -> io::Result<()> {
-    let file = File::create(dest_zip)?;
-    let mut zip = ZipWriter::new(file);
-    let options = FileOptions::<()>::default().compression_method(CompressionMethod::Deflated);
-
-    let db_name = src.file_name().unwrap().to_string_lossy();
-    zip.start_file(&db_name, options)?;
-    let data = fs::read(src)?;
-    zip.write_all(&data)?;
-    zip.finish()?;
+/// Handles `backup restore <FILE>`.
+///
+/// Detects the archive type from its content (plain SQLite, compressed or
+/// encrypted), extracts the database and copies it into `conn` through the
+/// SQLite backup API, so the open connection never points at a replaced
+/// file. A backup with a newer schema than this binary supports is refused;
+/// otherwise the restore is confirmed (unless `force`) and the current
+/// database is snapshotted first, so a restore can be undone.
+pub fn handle_backup_restore(
+    conn: &mut Connection,
+    config: &AppConfig,
+    file: &str,
+    force: bool,
+) -> io::Result<()> {
+    println!();
+
+    let src = Path::new(file);
+    if !src.exists() {
+        print_err(&tr_with("backup.restore.not_found", &[("path", file)]));
+        return Ok(());
+    }
+
+    let mut data = fs::read(src)?;
+
+    // --- decrypt if needed
+    if is_encrypted(&data) {
+        let passphrase = match read_passphrase(false)? {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        data = match decrypt_bytes(&data, &passphrase) {
+            Ok(plain) => plain,
+            Err(e @ CryptoError::WrongPassphrase) => {
+                print_err(&e.to_string());
+                return Ok(());
+            }
+            Err(e) => {
+                print_err(&tr_with(
+                    "backup.restore.failed",
+                    &[("error", &e.to_string())],
+                ));
+                return Ok(());
+            }
+        };
+    }
+
+    // --- extract the SQLite file and verify it against the manifest
+    let db_bytes = match read_archive(&data)? {
+        Some((bytes, manifest)) => {
+            if let Some(m) = manifest
+                && m.sha256 != sha256_hex(&bytes)
+            {
+                print_err(&tr_with(
+                    "backup.restore.checksum_mismatch",
+                    &[("path", file)],
+                ));
+                return Ok(());
+            }
+            bytes
+        }
+        None => {
+            print_err(&tr_with("backup.restore.unknown_format", &[("path", file)]));
+            return Ok(());
+        }
+    };
+
+    // --- check the copy, then replace the current database (after a safety snapshot)
+    let db_path = PathBuf::from(&config.database);
+    let tmp_path = db_path.with_extension("restore.tmp");
+    fs::write(&tmp_path, &db_bytes)?;
+    let result = restore_from(conn, &db_path, &tmp_path, file, force);
+    let _ = fs::remove_file(&tmp_path);
+    result
+}
+
+/// Copies the extracted database `tmp_path` into `conn`, unless its schema is
+/// newer than this binary supports or the user does not confirm.
+fn restore_from(
+    conn: &mut Connection,
+    db_path: &Path,
+    tmp_path: &Path,
+    file: &str,
+    force: bool,
+) -> io::Result<()> {
+    match backup_schema_version(tmp_path) {
+        Ok(found) if found > latest_version() => {
+            let e = MigrationError::SchemaTooNew {
+                found,
+                supported: latest_version(),
+            };
+            print_err(&tr_with(
+                "backup.restore.too_new",
+                &[("path", file), ("error", &e.to_string())],
+            ));
+            return Ok(());
+        }
+        Ok(_) => {}
+        Err(e) => {
+            print_err(&tr_with(
+                "backup.restore.failed",
+                &[("error", &e.to_string())],
+            ));
+            return Ok(());
+        }
+    }
+
+    if !force {
+        print!("{} ", tr_with("backup.restore.confirm", &[("path", file)]));
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            print_info(&tr("backup.restore.cancelled"), true);
+            return Ok(());
+        }
+        println!();
+    }
+
+    if db_path.exists()
+        && let Err(e) = snapshot_file(db_path, "restore")
+    {
+        print_err(&tr_with("backup.auto.failed", &[("error", &e.to_string())]));
+        return Ok(());
+    }
+
+    // Il backup API copia le pagine nel database aperto, senza sostituire il file
+    if let Err(e) = conn.restore(MAIN_DB, tmp_path, None::<fn(Progress)>) {
+        print_err(&tr_with(
+            "backup.restore.failed",
+            &[("error", &e.to_string())],
+        ));
+        return Ok(());
+    }
+
+    let _ = write_log(
+        conn,
+        "BACKUP_RESTORE",
+        "DB",
+        &tr_with("log.backup.restore", &[("path", file)]),
+    );
+
+    print_ok(&tr_with("backup.restore.done", &[("path", file)]), true);
     Ok(())
 }
 
-#[cfg(not(target_os = "windows"))]
-pub fn compress_tar_gz(src_path: &Path, dest_path: &Path) -> io::Result<()> {
-    let tar_gz = File::create(dest_path)?;
-    let encoder = GzEncoder::new(tar_gz, Compression::default());
-    let mut tar = Builder::new(encoder);
+/// Schema version recorded in a database file, read without modifying it
+/// (0 if it predates `schema_migrations`).
+fn backup_schema_version(path: &Path) -> rusqlite::Result<u32> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let tracked: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !tracked {
+        return Ok(0);
+    }
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Handles `backup prune`: deletes the backups not kept by the retention
+/// policy configured in `AppConfig::backup`.
+pub fn handle_backup_prune(conn: &Connection, config: &AppConfig, dry_run: bool) -> io::Result<()> {
+    println!();
 
-    // ✅ Usa solo il nome del file (relativo) dentro l’archivio
-    let file_name = src_path
-        .file_name()
-        .unwrap_or_else(|| std::ffi::OsStr::new("librius.db"));
-    tar.append_path_with_name(src_path, file_name)?;
+    let policy = &config.backup;
+    if policy.is_empty() {
+        print_warn(&tr("backup.prune.no_policy"));
+        return Ok(());
+    }
+
+    let backup_dir = backup_dir_for(&PathBuf::from(&config.database));
+    let entries = list_backups(&backup_dir)?;
+    let prunable = select_prunable(&entries, policy);
+
+    if prunable.is_empty() {
+        print_info(&tr("backup.prune.none"), true);
+        return Ok(());
+    }
+
+    for entry in entries.iter().filter(|e| prunable.contains(&e.path)) {
+        // Data di creazione nel formato della lingua attiva
+        let args = [
+            ("path", entry.path.display().to_string().into()),
+            ("date", entry.created.into()),
+        ];
+        if dry_run {
+            print_info(&tr_fmt("backup.prune.would_delete", &args), true);
+        } else {
+            fs::remove_file(&entry.path)?;
+            print_info(&tr_fmt("backup.prune.deleted", &args), is_verbose());
+        }
+    }
+
+    if !dry_run {
+        let msg = tr_fmt(
+            "backup.prune.summary",
+            &[
+                ("count", prunable.len().into()),
+                ("kept", (entries.len() - prunable.len()).into()),
+            ],
+        );
+        let _ = write_log(conn, "BACKUP_PRUNE", "DB", &msg);
+        print_ok(&msg, true);
+    }
 
-    tar.finish()?;
     Ok(())
 }
+
+/// Reads the backup passphrase from `LIBRIUS_BACKUP_PASSPHRASE` or, if unset,
+/// prompts for it (twice when `confirm` is true).
+///
+/// Returns `Ok(None)` after printing a localized error if the passphrase is
+/// empty or the confirmation does not match.
+fn read_passphrase(confirm: bool) -> io::Result<Option<String>> {
+    if let Ok(p) = std::env::var(PASSPHRASE_ENV)
+        && !p.is_empty()
+    {
+        return Ok(Some(p));
+    }
+
+    let passphrase = rpassword::prompt_password(tr("backup.passphrase.prompt"))?;
+    if passphrase.is_empty() {
+        print_err(&tr("backup.passphrase.empty"));
+        return Ok(None);
+    }
+
+    if confirm {
+        let again = rpassword::prompt_password(tr("backup.passphrase.confirm"))?;
+        if again != passphrase {
+            print_err(&tr("backup.passphrase.mismatch"));
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(passphrase))
+}
@@ -0,0 +1,40 @@
+use crate::commands::export::fetch_cited_books;
+use crate::i18n::tr_with;
+use crate::utils::isbn::normalize_isbn;
+use crate::utils::{CitationFormat, print_err, write_citations};
+use rusqlite::Connection;
+use std::error::Error;
+use std::io;
+
+/// Handle the `cite <ID|ISBN>` command: prints the citation of one book to
+/// stdout, with the same key it gets from `export --bibtex|--ris|--csl-json`.
+///
+/// Keys are deduplicated over the whole catalog, trashed books included, so
+/// a book keeps its key when others are moved to the trash or restored.
+pub fn handle_cite(
+    conn: &Connection,
+    key: &str,
+    format: CitationFormat,
+) -> Result<(), Box<dyn Error>> {
+    // ID numerico breve oppure ISBN, anche con trattini
+    let is_isbn = key.len() >= 10 || !key.chars().all(|c| c.is_ascii_digit());
+    let isbn = normalize_isbn(key, true).unwrap_or_else(|_| key.to_string());
+
+    let books = fetch_cited_books(conn)?;
+    let entry = books.iter().find(|(_, book)| {
+        if is_isbn {
+            book.isbn == isbn
+        } else {
+            book.id.map(|id| id.to_string()).as_deref() == Some(key)
+        }
+    });
+
+    match entry {
+        Some((cite_key, book)) => {
+            write_citations(io::stdout().lock(), format, &[(cite_key.clone(), book)])?
+        }
+        // su stderr: lo stdout resta pulito per le redirezioni
+        None => print_err(&tr_with("cite.not_found", &[("key", key)])),
+    }
+    Ok(())
+}
@@ -1,6 +1,12 @@
 use crate::config;
+use crate::config::keys::{get_value, parse_config, set_value, unset_value};
+use crate::config::layered::effective_keys;
+use crate::config::{CliOverrides, Layers};
 use crate::i18n::{tr, tr_with};
-use crate::utils::{print_err, print_info, print_ok, print_warn};
+use crate::models::ConfigRow;
+use crate::utils::{build_table, print_err, print_info, print_ok, print_warn, write_log};
+use rusqlite::Connection;
+use std::error::Error;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
@@ -89,6 +95,7 @@ pub fn handle_config(
                     ),
                     true,
                 );
+                verify_edited(&config_path);
             }
             Ok(_) | Err(_) => {
                 print_err(&tr_with(
@@ -109,6 +116,7 @@ pub fn handle_config(
                             ),
                             true,
                         );
+                        verify_edited(&config_path);
                     }
                     Ok(_) | Err(_) => {
                         print_err(&tr_with(
@@ -123,3 +131,133 @@ pub fn handle_config(
 
     Ok(())
 }
+
+/// Re-parses the file after `--edit`, so that mistakes are reported now
+/// instead of when the next command loads the configuration.
+fn verify_edited(config_path: &Path) {
+    let result = fs::read_to_string(config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse_config(&text).map_err(|e| e.to_string()));
+    match result {
+        Ok(_) => print_ok(&tr("config.file.valid"), true),
+        Err(e) => {
+            print_err(&e);
+            print_warn(&tr("config.file.fix_hint"));
+        }
+    }
+}
+
+/// Handle `config show [--effective]`: prints every key with its value and
+/// source. Without `--effective` only the defaults and the file are
+/// considered; with it, also the named library, `LIBRIUS_*` variables and flags.
+pub fn handle_config_show(overrides: CliOverrides, effective: bool) -> Result<(), Box<dyn Error>> {
+    let mut layers = config::read_layers(overrides)?;
+    if !effective {
+        layers = Layers {
+            file: layers.file,
+            ..Layers::default()
+        };
+    }
+    let resolved = match config::resolve(&layers) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            print_err(&e.to_string());
+            return Ok(());
+        }
+    };
+
+    let mut rows = Vec::new();
+    for key in effective_keys() {
+        let value = match get_value(&resolved.config, key)? {
+            Some(serde_yaml::Value::String(s)) => s,
+            Some(other) => serde_yaml::to_string(&other)?.trim_end().to_string(),
+            None => "-".to_string(),
+        };
+        rows.push(ConfigRow {
+            key,
+            value,
+            source: resolved.source(key).to_string(),
+        });
+    }
+
+    println!();
+    print_info(
+        &tr_with(
+            "config.show.file",
+            &[
+                ("path", &resolved.config_file.display().to_string()),
+                ("source", &resolved.config_file_source.to_string()),
+            ],
+        ),
+        true,
+    );
+    // Gli avvisi sugli override ignorati sono già stati mostrati all'avvio
+    println!("\n{}", build_table(rows));
+    Ok(())
+}
+
+/// Handle `config get <KEY>`: prints a value (defaults applied) or a section.
+pub fn handle_config_get(key: &str) -> Result<(), Box<dyn Error>> {
+    let cfg = config::load_config()?;
+    match get_value(&cfg, key) {
+        Ok(Some(value)) => match value {
+            serde_yaml::Value::String(s) => println!("{}", s),
+            other => print!("{}", serde_yaml::to_string(&other)?),
+        },
+        Ok(None) => print_warn(&tr_with("config.key.not_set", &[("key", key)])),
+        Err(e) => print_err(&e.to_string()),
+    }
+    Ok(())
+}
+
+/// Handle `config set <KEY> <VALUE>`: validates the value against the key
+/// schema and updates the file, keeping its comments where possible.
+pub fn handle_config_set(conn: &Connection, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    // Il file si crea solo quando si scrive una chiave
+    config::load_or_init()?;
+    let config_path = config::config_file_path();
+    let text = fs::read_to_string(&config_path)?;
+
+    match set_value(&text, key, value) {
+        Ok(updated) => {
+            fs::write(&config_path, updated)?;
+            log_config_set(conn, &format!("{} = {}", key, value));
+            print_ok(
+                &tr_with("config.key.set", &[("key", key), ("value", value)]),
+                true,
+            );
+        }
+        Err(e) => print_err(&e.to_string()),
+    }
+    Ok(())
+}
+
+/// Handle `config unset <KEY>`: removes an optional key so that its default applies.
+pub fn handle_config_unset(conn: &Connection, key: &str) -> Result<(), Box<dyn Error>> {
+    let config_path = config::config_file_path();
+    // Senza file non c'è nulla da rimuovere: valgono già i predefiniti
+    if !config_path.exists() {
+        print_warn(&tr_with("config.key.not_set", &[("key", key)]));
+        return Ok(());
+    }
+    let text = fs::read_to_string(&config_path)?;
+
+    match unset_value(&text, key) {
+        Ok(updated) => {
+            fs::write(&config_path, updated)?;
+            log_config_set(conn, &format!("{} unset", key));
+            print_ok(&tr_with("config.key.unset", &[("key", key)]), true);
+        }
+        Err(e) => print_err(&e.to_string()),
+    }
+    Ok(())
+}
+
+fn log_config_set(conn: &Connection, message: &str) {
+    if let Err(e) = write_log(conn, "CONFIG_SET", "CONFIG", message) {
+        print_err(&tr_with(
+            "log.record.unable_to_write",
+            &[("log_error", &e.to_string())],
+        ));
+    }
+}
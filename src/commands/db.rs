@@ -1,10 +1,20 @@
 use crate::config::AppConfig;
 use crate::db::connection::ensure_schema;
-use crate::i18n::{tr, tr_with};
-use crate::utils::{print_err, print_ok, print_warn};
+use crate::db::maintenance::{
+    Issue, check_database, collect_stats, optimize, repair_issues, vacuum,
+};
+use crate::db::migrations::{
+    MigrationStep, check_not_rolled_back, current_version, latest_version, migrate_to,
+    migration_status, plan_migration,
+};
+use crate::i18n::{format_decimal, format_number, tr, tr_fmt, tr_with};
+use crate::utils::backup::snapshot_file;
+use crate::utils::{auto_snapshot, print_err, print_info, print_ok, print_warn, write_log};
+use colored::*;
 use rusqlite::Connection;
 use std::error::Error;
 use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
 
 pub fn handle_db(
@@ -33,6 +43,20 @@ pub fn handle_db(
 fn init_db(config: &AppConfig) -> Result<(), Box<dyn Error>> {
     let path = Path::new(&config.database);
     if path.exists() {
+        // Snapshot before wiping, so a mistaken reset can always be restored
+        match snapshot_file(path, "reset") {
+            Ok(dest) => print_info(
+                &tr_with(
+                    "backup.auto.created",
+                    &[("path", &dest.display().to_string())],
+                ),
+                true,
+            ),
+            Err(e) => {
+                print_err(&tr_with("backup.auto.failed", &[("error", &e.to_string())]));
+                return Ok(());
+            }
+        }
         fs::remove_file(path)?;
         print_ok(
             &tr_with("db_reset_done", &[("path", &path.to_string_lossy())]),
@@ -71,3 +95,298 @@ fn copy_db(config: &AppConfig, dest: &str) -> Result<(), Box<dyn Error>> {
     );
     Ok(())
 }
+
+/// Handle `db migrate [--status] [--to N] [--dry-run]`.
+///
+/// Without `--to`, migrates to the newest schema. Rolling back takes an
+/// automatic snapshot first, since `down` steps may drop data, and keeps the
+/// database at that version until `db migrate` is run again.
+pub fn handle_db_migrate(
+    conn: &Connection,
+    status: bool,
+    to: Option<u32>,
+    dry_run: bool,
+) -> Result<(), Box<dyn Error>> {
+    println!();
+
+    if status {
+        return print_migration_status(conn);
+    }
+
+    let target = to.unwrap_or_else(latest_version);
+    let steps = match plan_migration(conn, target) {
+        Ok(steps) => steps,
+        Err(e) => {
+            print_err(&e.to_string());
+            return Ok(());
+        }
+    };
+
+    if steps.is_empty() {
+        print_ok(
+            &tr_with(
+                "db.migrate.nothing_to_do",
+                &[("version", &current_version(conn)?.to_string())],
+            ),
+            true,
+        );
+        return Ok(());
+    }
+
+    if dry_run {
+        print_info(&tr("db.migrate.dry_run"), true);
+        for step in &steps {
+            println!("   {}", describe_step(step));
+        }
+        return Ok(());
+    }
+
+    match migrate_to(conn, target) {
+        Ok(applied) => {
+            for step in &applied {
+                print_ok(&describe_step(step), true);
+            }
+            print_ok(
+                &tr_with(
+                    "db.migrate.now_at",
+                    &[("version", &current_version(conn)?.to_string())],
+                ),
+                true,
+            );
+        }
+        Err(e) => print_err(&tr_with("db.migrate.failed", &[("error", &e.to_string())])),
+    }
+    Ok(())
+}
+
+fn describe_step(step: &MigrationStep) -> String {
+    let (key, m) = match step {
+        MigrationStep::Up(m) => ("db.migrate.step.up", m),
+        MigrationStep::Down(m) => ("db.migrate.step.down", m),
+    };
+    tr_with(
+        key,
+        &[
+            ("version", &m.version.to_string()),
+            ("name", m.name),
+            ("description", &tr(m.description)),
+        ],
+    )
+}
+
+fn print_migration_status(conn: &Connection) -> Result<(), Box<dyn Error>> {
+    println!(
+        "{}\n",
+        tr_with(
+            "db.migrate.status_header",
+            &[
+                ("version", &current_version(conn)?.to_string()),
+                ("latest", &latest_version().to_string()),
+            ],
+        )
+    );
+    if let Err(e) = check_not_rolled_back(conn) {
+        print_warn(&e.to_string());
+        println!();
+    }
+
+    for s in migration_status(conn)? {
+        let m = s.migration;
+        let state = match &s.applied_at {
+            Some(date) => tr_with("db.migrate.status.applied", &[("date", date)]),
+            None => tr("db.migrate.status.pending"),
+        };
+        let rollback = if m.down.is_some() {
+            String::new()
+        } else {
+            format!(" ({})", tr("db.migrate.status.irreversible"))
+        };
+        println!(
+            "   {:>3}  {:<10} {:<45} {}{}",
+            m.version,
+            m.name,
+            tr(m.description),
+            state,
+            rollback
+        );
+    }
+    Ok(())
+}
+
+/// Handle `db check`: reports every issue found, marking the ones
+/// `db repair` can fix.
+pub fn handle_db_check(conn: &Connection) -> Result<(), Box<dyn Error>> {
+    println!();
+    print_info(&tr("db.check.running"), true);
+
+    let issues = check_database(conn)?;
+    if issues.is_empty() {
+        print_ok(&tr("db.check.healthy"), true);
+        return Ok(());
+    }
+
+    print_issues(&issues);
+    let repairable = issues.iter().filter(|i| i.is_repairable()).count();
+    println!();
+    print_warn(&tr_fmt(
+        "db.check.summary",
+        &[
+            ("count", issues.len().into()),
+            ("repairable", repairable.into()),
+        ],
+    ));
+    Ok(())
+}
+
+/// Handle `db repair`: fixes the safe classes of issues found by `check`
+/// after confirmation (skipped with `force`) and an automatic snapshot.
+pub fn handle_db_repair(conn: &Connection, force: bool) -> Result<(), Box<dyn Error>> {
+    println!();
+
+    let issues: Vec<Issue> = check_database(conn)?
+        .into_iter()
+        .filter(Issue::is_repairable)
+        .collect();
+
+    if issues.is_empty() {
+        print_ok(&tr("db.repair.nothing"), true);
+        return Ok(());
+    }
+
+    print_issues(&issues);
+    println!();
+
+    if !force {
+        print!(
+            "{} ",
+            tr_fmt("db.repair.confirm", &[("count", issues.len().into())])
+        );
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            print_info(&tr("db.repair.cancelled"), true);
+            return Ok(());
+        }
+        println!();
+    }
+
+    if auto_snapshot(conn, "repair").is_err() {
+        return Ok(());
+    }
+
+    let fixed = repair_issues(conn, &issues)?;
+    let _ = write_log(
+        conn,
+        "DB_REPAIR",
+        "DB",
+        &format!("{} of {} issue(s) repaired", fixed, issues.len()),
+    );
+    print_ok(
+        &tr_fmt(
+            "db.repair.done",
+            &[("fixed", fixed.into()), ("count", issues.len().into())],
+        ),
+        true,
+    );
+    if fixed < issues.len() {
+        print_warn(&tr("db.repair.partial"));
+    }
+    Ok(())
+}
+
+/// Handle `db stats`: file size, rows per table, schema version and log size.
+pub fn handle_db_stats(conn: &Connection) -> Result<(), Box<dyn Error>> {
+    let stats = collect_stats(conn)?;
+
+    println!("\n{}\n", tr("db.stats.title").bold());
+    let size = stats
+        .file_size
+        .map(format_size)
+        .unwrap_or_else(|| "-".to_string());
+    println!("   {:<22} {}", tr("db.stats.file_size"), size);
+    println!(
+        "   {:<22} {} / {}",
+        tr("db.stats.schema_version"),
+        stats.schema_version,
+        stats.latest_version
+    );
+    println!(
+        "   {:<22} {}",
+        tr("db.stats.migrations"),
+        stats.migrations.join(", ")
+    );
+    println!(
+        "   {:<22} {}",
+        tr("db.stats.log_rows"),
+        format_number(stats.log_rows)
+    );
+
+    println!("\n   {}", tr("db.stats.tables").bold());
+    for (name, rows) in &stats.tables {
+        println!("   {:<22} {}", name, format_number(*rows));
+    }
+    Ok(())
+}
+
+/// Handle `db vacuum`: rebuilds the file and reports the space reclaimed.
+pub fn handle_db_vacuum(conn: &Connection) -> Result<(), Box<dyn Error>> {
+    println!();
+    let before = collect_stats(conn)?.file_size.unwrap_or(0);
+    vacuum(conn)?;
+    let after = collect_stats(conn)?.file_size.unwrap_or(0);
+
+    let _ = write_log(
+        conn,
+        "DB_VACUUM",
+        "DB",
+        &format!("{} -> {} bytes", before, after),
+    );
+    print_ok(
+        &tr_with(
+            "db.vacuum.done",
+            &[
+                ("before", &format_size(before)),
+                ("after", &format_size(after)),
+            ],
+        ),
+        true,
+    );
+    Ok(())
+}
+
+/// Handle `db optimize`: refreshes query planner statistics.
+pub fn handle_db_optimize(conn: &Connection) -> Result<(), Box<dyn Error>> {
+    println!();
+    optimize(conn)?;
+    let _ = write_log(conn, "DB_OPTIMIZE", "DB", "ANALYZE + PRAGMA optimize");
+    print_ok(&tr("db.optimize.done"), true);
+    Ok(())
+}
+
+fn print_issues(issues: &[Issue]) {
+    for issue in issues {
+        let marker = if issue.is_repairable() {
+            format!(" [{}]", tr("db.check.repairable"))
+        } else {
+            String::new()
+        };
+        println!("   • {}{}", issue, marker.green());
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", format_number(bytes as i64), UNITS[0])
+    } else {
+        format!("{} {}", format_decimal(size, 1), UNITS[unit])
+    }
+}
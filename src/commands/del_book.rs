@@ -1,34 +1,78 @@
+use crate::db::books::find_book;
+use crate::db::trash::{purge_book, trash_book};
 use crate::i18n::tr_with;
-use crate::utils::{print_err, print_info, print_ok, print_warn, write_log};
+use crate::utils::{auto_snapshot, print_err, print_info, print_ok, print_warn, write_log_with};
 use colored::*;
 use rusqlite::Connection;
+use serde_json::json;
 use std::io::{self, Write};
 
-pub fn handle_del_book(conn: &Connection, key: &str, force: bool) -> rusqlite::Result<()> {
+/// Handle the `del` command.
+///
+/// By default the book is moved to the trash (reversible, no confirmation);
+/// with `purge` it is deleted permanently after an interactive confirmation,
+/// which only `force` can skip.
+pub fn handle_del_book(
+    conn: &Connection,
+    key: &str,
+    force: bool,
+    purge: bool,
+) -> rusqlite::Result<()> {
     println!();
 
     // 1️⃣ Determina se è ISBN o ID
     let is_isbn = key.len() >= 10 && !key.chars().all(|c| c.is_ascii_digit());
 
-    // 2️⃣ Controlla se il libro esiste
-    let exists_query = if is_isbn {
-        "SELECT COUNT(*) FROM books WHERE isbn = ?1"
-    } else {
-        "SELECT COUNT(*) FROM books WHERE id = ?1"
+    // 2️⃣ Controlla se il libro esiste (anche se già nel cestino)
+    let Some(book) = find_book(conn, key, is_isbn)? else {
+        print_warn(&tr_with("del.book.not_found", &[("key", key)]).yellow());
+        return Ok(());
     };
+    let id = book.id.unwrap_or_default().to_string();
 
-    let exists: i64 = conn.query_row(exists_query, [key], |row| row.get(0))?;
+    // 3️⃣ Senza --purge: sposta nel cestino, nessuna conferma necessaria
+    if !purge {
+        let tx = conn.unchecked_transaction()?;
+        let trashed = trash_book(conn, &book)?;
+        tx.commit()?;
 
-    if exists == 0 {
-        print_warn(&tr_with("del.book.not_found", &[("key", key)]).yellow());
+        if !trashed {
+            print_warn(&tr_with("del.book.already_trashed", &[("key", key)]).yellow());
+            return Ok(());
+        }
+
+        let log_msg = format!("Book {} moved to trash", key);
+        let details = json!({ "title": book.title, "isbn": book.isbn });
+        if let Err(e) = write_log_with(
+            conn,
+            "TRASH_BOOK",
+            "BOOKS",
+            &log_msg,
+            book.id.map(i64::from),
+            Some(&details),
+        ) {
+            print_err(
+                &tr_with(
+                    "log.record.unable_to_write",
+                    &[("log_error", &e.to_string())],
+                )
+                .red()
+                .bold(),
+            );
+        }
+
+        print_ok(
+            &tr_with("del.book.trashed", &[("key", key), ("id", &id)]).green(),
+            true,
+        );
         return Ok(());
     }
 
-    // 3️⃣ Conferma interattiva (solo se il libro esiste), se non forzato
+    // 4️⃣ Eliminazione definitiva: conferma interattiva obbligatoria, se non forzato
     if !force {
         print!(
             "{} ",
-            tr_with("del.book.confirm", &[("key", key)]) // es. "Are you sure you want to delete book {key}? [y/N]:"
+            tr_with("del.book.confirm", &[("key", key)]) // es. "Permanently delete book {key}? [y/N]:"
         );
         io::stdout().flush().unwrap();
 
@@ -43,20 +87,29 @@ pub fn handle_del_book(conn: &Connection, key: &str, force: bool) -> rusqlite::R
         println!();
     }
 
-    // 4️⃣ Esegue la DELETE solo dopo conferma
-    let delete_sql = if is_isbn {
-        "DELETE FROM books WHERE isbn = ?1"
-    } else {
-        "DELETE FROM books WHERE id = ?1"
-    };
+    // Snapshot automatico prima di ogni eliminazione definitiva, confermata o forzata
+    if auto_snapshot(conn, "delete").is_err() {
+        return Ok(());
+    }
 
-    let affected = conn.execute(delete_sql, [key])?;
+    // 5️⃣ DELETE e book_history nella stessa transazione
+    let tx = conn.unchecked_transaction()?;
+    let deleted = purge_book(conn, &book)?;
+    tx.commit()?;
 
-    if affected > 0 {
+    if deleted {
         // Log the action
         let action_type = if force { "forced" } else { "confirmed" };
         let log_msg = format!("Book {} deleted ({})", key, action_type);
-        if let Err(e) = write_log(conn, "DELETE_BOOK", "", &log_msg) {
+        let details = json!({ "title": book.title, "isbn": book.isbn, "forced": force });
+        if let Err(e) = write_log_with(
+            conn,
+            "DELETE_BOOK",
+            "BOOKS",
+            &log_msg,
+            book.id.map(i64::from),
+            Some(&details),
+        ) {
             print_err(
                 &tr_with(
                     "log.record.unable_to_write",
@@ -0,0 +1,54 @@
+use crate::i18n::{check_translations, tr, tr_fmt, tr_with};
+use crate::utils::{print_err, print_info, print_ok, print_warn};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Handle `dev i18n-check`: compares the translation keys used under
+/// `root/src` with every locale file and fails when errors are found.
+pub fn handle_dev_i18n_check(root: &Path, extra: &[PathBuf]) -> Result<(), Box<dyn Error>> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let root = root.as_path();
+    print_info(
+        &tr_with(
+            "dev.i18n.checking",
+            &[("root", &root.display().to_string())],
+        ),
+        true,
+    );
+    let report = check_translations(root, extra).map_err(|e| {
+        tr_with(
+            "dev.i18n.scan_failed",
+            &[
+                ("root", &root.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+
+    for issue in report.errors() {
+        print_err(&issue.to_string());
+    }
+    for issue in report.warnings() {
+        print_warn(&issue.to_string());
+    }
+
+    let errors = report.errors().count();
+    println!(
+        "{}",
+        tr_fmt(
+            "dev.i18n.summary",
+            &[
+                ("files", report.files.into()),
+                ("keys", report.keys_used.into()),
+                ("locales", report.locales.join(", ").into()),
+                ("errors", errors.into()),
+                ("warnings", report.warnings().count().into()),
+            ],
+        )
+    );
+    if errors > 0 {
+        return Err(tr_fmt("dev.i18n.failed", &[("count", errors.into())]).into());
+    }
+    print_ok(&tr("dev.i18n.ok"), true);
+    Ok(())
+}
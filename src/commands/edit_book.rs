@@ -1,8 +1,16 @@
 use crate::cli::fields::EDITABLE_FIELDS;
-use crate::db::books::{get_book_fields, update_book_by_id, update_book_by_isbn};
-use crate::i18n::{tr, tr_with};
-use crate::utils::{lang_code_to_name, print_err, print_info, print_ok, print_warn};
+use crate::db::books::{
+    find_book, find_book_by_id, get_book_fields, update_book_by_id, update_book_by_isbn,
+};
+use crate::db::history::record_history;
+use crate::db::trashed_id;
+use crate::i18n::{tr, tr_fmt, tr_with};
+use crate::models::HistoryOperation;
+use crate::utils::{
+    auto_snapshot, lang_code_to_name, print_err, print_info, print_ok, print_warn, write_log_with,
+};
 use rusqlite::Connection;
+use serde_json::json;
 use std::collections::HashMap;
 
 pub fn handle_edit_book(conn: &Connection, matches: &clap::ArgMatches) -> rusqlite::Result<()> {
@@ -43,6 +51,15 @@ pub fn handle_edit_book(conn: &Connection, matches: &clap::ArgMatches) -> rusqli
     // Heuristic: if contains letters, dash, or 13+ digits → ISBN; otherwise ID
     let is_isbn = key.len() >= 10 && !key.chars().all(|c| c.is_ascii_digit());
 
+    // I libri nel cestino non si modificano: vanno prima ripristinati
+    if let Some(id) = trashed_id(conn, key, is_isbn)? {
+        print_warn(&tr_with(
+            "edit.book.trashed",
+            &[("key", key), ("id", &id.to_string())],
+        ));
+        return Ok(());
+    }
+
     // Recupera i valori precedenti dal DB
     let old_values = get_book_fields(
         conn,
@@ -51,6 +68,16 @@ pub fn handle_edit_book(conn: &Connection, matches: &clap::ArgMatches) -> rusqli
         is_isbn,
     )?;
 
+    // Snapshot automatico prima di ogni modifica: i valori sovrascritti si perdono
+    if auto_snapshot(conn, "edit").is_err() {
+        return Ok(());
+    }
+
+    // Snapshot completo per la cronologia (book_history)
+    let before = find_book(conn, key, is_isbn)?;
+
+    // Update e cronologia nella stessa transazione
+    let tx = conn.unchecked_transaction()?;
     let result = if is_isbn {
         update_book_by_isbn(conn, key, &fields)
     } else {
@@ -63,9 +90,25 @@ pub fn handle_edit_book(conn: &Connection, matches: &clap::ArgMatches) -> rusqli
         }
     };
 
+    if let (Ok(rows), Some(before)) = (&result, &before)
+        && *rows > 0
+        && let Some(id) = before.id
+    {
+        let after = find_book_by_id(conn, id as i64)?;
+        record_history(
+            conn,
+            HistoryOperation::Update,
+            id as i64,
+            Some(before),
+            after.as_ref(),
+        )?;
+    }
+    tx.commit()?;
+
     match result {
         Ok(rows) if rows > 0 => {
             let mut modified_count = 0;
+            let mut changes = serde_json::Map::new();
 
             // Confronta valori e stampa diff
             for (field, new_val) in &fields {
@@ -73,6 +116,7 @@ pub fn handle_edit_book(conn: &Connection, matches: &clap::ArgMatches) -> rusqli
 
                 match old_val {
                     Some(old) if old != *new_val => {
+                        changes.insert(field.clone(), json!({ "old": old, "new": new_val }));
                         print_ok(
                             &tr_with(
                                 "edit.field.updated",
@@ -84,6 +128,7 @@ pub fn handle_edit_book(conn: &Connection, matches: &clap::ArgMatches) -> rusqli
                         modified_count += 1;
                     }
                     None => {
+                        changes.insert(field.clone(), json!({ "old": null, "new": new_val }));
                         print_ok(
                             &tr_with("edit.field.set", &[("field", field), ("new", new_val)]),
                             true,
@@ -98,16 +143,20 @@ pub fn handle_edit_book(conn: &Connection, matches: &clap::ArgMatches) -> rusqli
             }
 
             if modified_count > 0 {
-                let key_variant = if modified_count == 1 {
-                    "edit.book.updated_one"
-                } else {
-                    "edit.book.updated_many"
-                };
+                let book_id = before.as_ref().and_then(|b| b.id).map(i64::from);
+                let _ = write_log_with(
+                    conn,
+                    "EDIT_BOOK",
+                    "BOOKS",
+                    &format!("Book {} updated ({} field(s))", key, modified_count),
+                    book_id,
+                    Some(&json!({ "fields": changes })),
+                );
 
                 print_ok(
-                    &tr_with(
-                        key_variant,
-                        &[("key", key), ("count", &modified_count.to_string())],
+                    &tr_fmt(
+                        "edit.book.updated",
+                        &[("key", key.into()), ("count", modified_count.into())],
                     ),
                     true,
                 );
@@ -1,15 +1,26 @@
 use chrono::Local;
 use rusqlite::Connection;
-use serde_json::json;
+use std::collections::BTreeMap;
 use std::fs::{self, File};
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
 
-use crate::i18n::tr_with;
-use crate::utils::print_ok;
+use crate::config::DEFAULT_LIBRARY;
+use crate::db::BOOK_COLUMNS;
+use crate::i18n::{tr_fmt, tr_with};
+use crate::models::Book;
+use crate::utils::column_map::{IMPORT_FIELDS, field_value};
+use crate::utils::{
+    CitationFormat, OdsCell, book_to_record, covers_dir_for, lost_on_export, org_document,
+    print_ok, print_warn, with_citation_keys, write_citations, write_iso2709, write_marcxml,
+    write_markdown_notes, write_ods, write_site,
+};
 
-// Alias per ridurre la complessità del tipo (Clippy: type_complexity)
-type BookRow = (i64, String, String, Option<i64>);
+/// Colonne esportate: l'ID seguito da tutti i campi importabili, con i nomi
+/// che l'import riconosce senza mappatura.
+fn export_columns() -> impl Iterator<Item = &'static str> {
+    std::iter::once("id").chain(IMPORT_FIELDS)
+}
 
 // 🔧 comuni: directory export + query
 fn get_export_path(ext: &str, output: Option<String>) -> io::Result<PathBuf> {
@@ -30,20 +41,47 @@ fn get_export_path(ext: &str, output: Option<String>) -> io::Result<PathBuf> {
     Ok(export_dir.join(filename))
 }
 
-// 🔧 query generica
-fn fetch_books(conn: &Connection) -> rusqlite::Result<Vec<BookRow>> {
-    let mut stmt = conn.prepare("SELECT id, title, author, year FROM books ORDER BY id;")?;
-    let rows = stmt
-        .query_map([], |row| {
-            Ok((
-                row.get::<_, i64>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, Option<i64>>(3)?,
-            ))
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(rows)
+// 🔧 query generica (esclusi i libri nel cestino), in ordine di ID
+pub(crate) fn fetch_books(conn: &Connection) -> io::Result<Vec<Book>> {
+    let query = format!(
+        "SELECT {} FROM books WHERE deleted_at IS NULL ORDER BY id;",
+        BOOK_COLUMNS
+    );
+    let books = conn
+        .prepare(&query)
+        .and_then(|mut stmt| {
+            stmt.query_map([], Book::from_row)?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(books)
+}
+
+/// Books not in the trash with their citation keys, in order of ID.
+///
+/// Keys are assigned over every row, trashed books included, so moving a
+/// book to the trash and back never changes the suffixes of the others; only
+/// purging it frees its key.
+pub(crate) fn fetch_cited_books(conn: &Connection) -> io::Result<Vec<(String, Book)>> {
+    let query = format!(
+        "SELECT {}, deleted_at IS NOT NULL AS trashed FROM books ORDER BY id;",
+        BOOK_COLUMNS
+    );
+    let rows: Vec<(Book, bool)> = conn
+        .prepare(&query)
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((Book::from_row(row)?, row.get("trashed")?)))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+    let (books, trashed): (Vec<Book>, Vec<bool>) = rows.into_iter().unzip();
+    Ok(with_citation_keys(&books)
+        .into_iter()
+        .zip(trashed)
+        .filter(|(_, trashed)| !trashed)
+        .map(|((key, book), _)| (key, book.clone()))
+        .collect())
 }
 
 //
@@ -51,14 +89,17 @@ fn fetch_books(conn: &Connection) -> rusqlite::Result<Vec<BookRow>> {
 //
 pub fn handle_export_csv(conn: &Connection, output: Option<String>) -> io::Result<()> {
     let export_path = get_export_path("csv", output)?;
-    let rows = fetch_books(conn).map_err(|e| io::Error::other(e.to_string()))?;
+    let books = fetch_books(conn)?;
 
-    let mut file = File::create(&export_path)?;
-    writeln!(file, "id;title;author;year")?;
-
-    for (id, title, author, year) in rows {
-        writeln!(file, "{};{};{};{}", id, title, author, year.unwrap_or(0))?;
+    // Delimitatore `;`: si reimporta con `import -d ';'`
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(b';')
+        .from_path(&export_path)?;
+    writer.write_record(export_columns())?;
+    for book in &books {
+        writer.write_record(export_columns().map(|field| field_value(book, field)))?;
     }
+    writer.flush()?;
 
     println!();
     print_ok(
@@ -77,22 +118,10 @@ pub fn handle_export_csv(conn: &Connection, output: Option<String>) -> io::Resul
 //
 pub fn handle_export_json(conn: &Connection, output: Option<String>) -> io::Result<()> {
     let export_path = get_export_path("json", output)?;
-    let rows = fetch_books(conn).map_err(|e| io::Error::other(e.to_string()))?;
-
-    let json_rows: Vec<_> = rows
-        .into_iter()
-        .map(|(id, title, author, year)| {
-            json!({
-                "id": id,
-                "title": title,
-                "author": author,
-                "year": year.unwrap_or_default()
-            })
-        })
-        .collect();
+    let books = fetch_books(conn)?;
 
     let file = File::create(&export_path)?;
-    serde_json::to_writer_pretty(file, &json_rows).map_err(|e| io::Error::other(e.to_string()))?;
+    serde_json::to_writer_pretty(file, &books).map_err(|e| io::Error::other(e.to_string()))?;
 
     println!();
     print_ok(
@@ -111,32 +140,33 @@ pub fn handle_export_json(conn: &Connection, output: Option<String>) -> io::Resu
 //
 pub fn handle_export_xlsx(conn: &Connection, output: Option<String>) -> io::Result<()> {
     let export_path = get_export_path("xlsx", output)?;
-    let rows = fetch_books(conn).map_err(|e| io::Error::other(e.to_string()))?;
+    let books = fetch_books(conn)?;
 
     let mut workbook = umya_spreadsheet::new_file();
     let sheet = workbook.get_sheet_by_name_mut("Sheet1").unwrap();
 
-    // intestazioni
-    sheet.get_cell_mut("A1").set_value("ID");
-    sheet.get_cell_mut("B1").set_value("Title");
-    sheet.get_cell_mut("C1").set_value("Author");
-    sheet.get_cell_mut("D1").set_value("Year");
-
-    // righe
-    for (i, (id, title, author, year)) in rows.iter().enumerate() {
-        let row_index = i + 2; // Excel rows start at 1, first data row is 2
-        sheet
-            .get_cell_mut(format!("A{}", row_index))
-            .set_value(id.to_string());
-        sheet
-            .get_cell_mut(format!("B{}", row_index))
-            .set_value(title);
-        sheet
-            .get_cell_mut(format!("C{}", row_index))
-            .set_value(author);
-        sheet
-            .get_cell_mut(format!("D{}", row_index))
-            .set_value(year.unwrap_or(0).to_string());
+    // intestazioni in grassetto (riga 1), poi un libro per riga; le celle vuote restano vuote
+    for (column, field) in export_columns().enumerate() {
+        let cell = sheet.get_cell_mut((column as u32 + 1, 1));
+        cell.set_value(field);
+        cell.get_style_mut().get_font_mut().set_bold(true);
+    }
+    for (i, book) in books.iter().enumerate() {
+        let row_index = i as u32 + 2;
+        for (column, field) in export_columns().enumerate() {
+            let value = field_value(book, field);
+            if value.is_empty() {
+                continue;
+            }
+            let cell = sheet.get_cell_mut((column as u32 + 1, row_index));
+            match field {
+                "id" | "year" | "pages" | "rating" => {
+                    cell.set_value_number(value.parse::<f64>().unwrap_or_default())
+                }
+                // testo esplicito: ISBN e date non diventano numeri
+                _ => cell.set_value_string(value),
+            };
+        }
     }
 
     umya_spreadsheet::writer::xlsx::write(&workbook, &export_path)
@@ -153,3 +183,240 @@ pub fn handle_export_xlsx(conn: &Connection, output: Option<String>) -> io::Resu
 
     Ok(())
 }
+
+//
+// 🧩 ODS EXPORT (OpenDocument)
+//
+pub fn handle_export_ods(conn: &Connection, output: Option<String>) -> io::Result<()> {
+    let export_path = get_export_path("ods", output)?;
+    let books = fetch_books(conn)?;
+
+    // stesse colonne dell'XLSX; numeri e date come celle tipizzate
+    let headers: Vec<&str> = export_columns().collect();
+    let rows: Vec<Vec<OdsCell>> = books
+        .iter()
+        .map(|book| {
+            export_columns()
+                .map(|field| match field {
+                    "added_at" => book
+                        .added_at
+                        .map(|d| OdsCell::DateTime(d.naive_utc()))
+                        .unwrap_or(OdsCell::Empty),
+                    "read_at" => book.read_at.map(OdsCell::Date).unwrap_or(OdsCell::Empty),
+                    _ => match field_value(book, field) {
+                        value if value.is_empty() => OdsCell::Empty,
+                        value => match field {
+                            "id" | "year" | "pages" | "rating" => {
+                                OdsCell::Number(value.parse().unwrap_or_default())
+                            }
+                            _ => OdsCell::Text(value),
+                        },
+                    },
+                })
+                .collect()
+        })
+        .collect();
+    write_ods(&export_path, "Sheet1", &headers, &rows)?;
+
+    println!();
+    print_ok(
+        &tr_with(
+            "export.ods.ok",
+            &[("path", &export_path.display().to_string())],
+        ),
+        true,
+    );
+
+    Ok(())
+}
+
+//
+// 🧩 MARC EXPORT (ISO 2709 o MARCXML)
+//
+pub fn handle_export_marc(conn: &Connection, output: Option<String>, xml: bool) -> io::Result<()> {
+    let export_path = get_export_path(if xml { "xml" } else { "mrc" }, output)?;
+    let books = fetch_books(conn)?;
+
+    let records: Vec<_> = books.iter().map(book_to_record).collect();
+    let (data, too_long) = if xml {
+        (write_marcxml(&records)?, Vec::new())
+    } else {
+        write_iso2709(&records)
+    };
+    fs::write(&export_path, data)?;
+
+    println!();
+    print_ok(
+        &tr_with(
+            "export.marc.ok",
+            &[("path", &export_path.display().to_string())],
+        ),
+        true,
+    );
+
+    // Record che superano i limiti di lunghezza di ISO 2709
+    for i in too_long {
+        print_warn(&tr_with(
+            "export.marc.too_long",
+            &[("title", &books[i].title)],
+        ));
+    }
+
+    // Campi senza equivalente MARC, con il numero di libri interessati
+    let mut lost: BTreeMap<&str, u32> = BTreeMap::new();
+    for field in books.iter().flat_map(lost_on_export) {
+        *lost.entry(field).or_default() += 1;
+    }
+    for (field, count) in lost {
+        print_warn(&tr_fmt(
+            "export.marc.lost",
+            &[("field", field.into()), ("count", count.into())],
+        ));
+    }
+
+    Ok(())
+}
+
+//
+// 🧩 CITAZIONI (BibTeX, RIS o CSL-JSON)
+//
+pub fn handle_export_citations(
+    conn: &Connection,
+    output: Option<String>,
+    format: CitationFormat,
+) -> io::Result<()> {
+    let export_path = get_export_path(format.extension(), output)?;
+    let books = fetch_cited_books(conn)?;
+
+    let file = BufWriter::new(File::create(&export_path)?);
+    let entries: Vec<(String, &Book)> = books
+        .iter()
+        .map(|(key, book)| (key.clone(), book))
+        .collect();
+    write_citations(file, format, &entries)?;
+
+    println!();
+    print_ok(
+        &tr_fmt(
+            "export.citations.ok",
+            &[
+                ("count", books.len().into()),
+                ("path", export_path.display().to_string().into()),
+            ],
+        ),
+        true,
+    );
+
+    Ok(())
+}
+
+//
+// 🧩 SITO STATICO HTML
+//
+pub fn handle_export_html(conn: &Connection, dir: &str, library: Option<&str>) -> io::Result<()> {
+    let books = fetch_books(conn)?;
+    // Copertine dalla cartella `covers` accanto al database
+    let covers = conn
+        .path()
+        .filter(|p| !p.is_empty())
+        .map(|p| covers_dir_for(Path::new(p)));
+    let report = write_site(Path::new(dir), &books, covers.as_deref(), library)?;
+
+    println!();
+    print_ok(
+        &tr_fmt(
+            "export.html.ok",
+            &[
+                ("count", report.books.into()),
+                ("covers", report.covers.into()),
+                (
+                    "path",
+                    Path::new(dir)
+                        .join("index.html")
+                        .display()
+                        .to_string()
+                        .into(),
+                ),
+            ],
+        ),
+        true,
+    );
+
+    Ok(())
+}
+
+//
+// 🧩 NOTE MARKDOWN (una per libro, con nota indice)
+//
+pub fn handle_export_markdown(
+    conn: &Connection,
+    dir: &str,
+    library: Option<&str>,
+) -> io::Result<()> {
+    let books = fetch_books(conn)?;
+    let report = write_markdown_notes(Path::new(dir), &books, library)?;
+
+    println!();
+    for name in &report.skipped {
+        print_warn(&tr_with("export.markdown.no_marker", &[("file", name)]));
+    }
+    if report.orphaned > 0 {
+        print_warn(&tr_fmt(
+            "export.markdown.orphaned",
+            &[("count", report.orphaned.into())],
+        ));
+    }
+    print_ok(
+        &tr_fmt(
+            "export.markdown.ok",
+            &[
+                ("created", report.created.into()),
+                ("updated", report.updated.into()),
+                ("unchanged", report.unchanged.into()),
+                ("path", dir.to_string().into()),
+            ],
+        ),
+        true,
+    );
+
+    Ok(())
+}
+
+//
+// 🧩 ORG (un unico file, un titolo per libro)
+//
+pub fn handle_export_org(
+    conn: &Connection,
+    output: Option<String>,
+    library: Option<&str>,
+) -> io::Result<()> {
+    // Nome stabile (non datato): riesportare aggiorna lo stesso file
+    let default_name = match library {
+        Some(name) if name != DEFAULT_LIBRARY => format!("librius_{}.org", name),
+        _ => "librius.org".to_string(),
+    };
+    let export_path = get_export_path("org", output.or(Some(default_name)))?;
+    let books = fetch_books(conn)?;
+
+    // Le note scritte sotto il segnaposto passano alla nuova versione
+    let previous = fs::read_to_string(&export_path).ok();
+    let document = org_document(&books, previous.as_deref(), library);
+    fs::write(&export_path, &document.text)?;
+
+    println!();
+    for title in &document.skipped {
+        print_warn(&tr_with("export.org.no_marker", &[("title", title)]));
+    }
+    print_ok(
+        &tr_fmt(
+            "export.org.ok",
+            &[
+                ("count", books.len().into()),
+                ("path", export_path.display().to_string().into()),
+            ],
+        ),
+        true,
+    );
+
+    Ok(())
+}
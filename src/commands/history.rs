@@ -0,0 +1,91 @@
+use crate::db::history::{get_history, last_undoable, undo_entry};
+use crate::i18n::{tr, tr_fmt, tr_with};
+use crate::models::{HistoryOperation, HistoryRow};
+use crate::utils::{auto_snapshot, build_table, print_ok, print_warn, write_log_with};
+use rusqlite::Connection;
+use serde_json::json;
+use std::error::Error;
+
+/// Handle the `history <ID>` command: prints the full timeline of a book.
+pub fn handle_history(conn: &Connection, book_id: i64) -> Result<(), Box<dyn Error>> {
+    let entries = get_history(conn, book_id)?;
+
+    if entries.is_empty() {
+        print_warn(&tr_with("history.empty", &[("id", &book_id.to_string())]));
+        return Ok(());
+    }
+
+    println!(
+        "\n{}\n",
+        tr_with("history.title", &[("id", &book_id.to_string())])
+    );
+    println!("{}", build_table(entries.iter().map(HistoryRow)));
+    Ok(())
+}
+
+/// Handle the `undo [N]` command: reverts the last `n` operations recorded
+/// in `book_history`, newest first, inside a single transaction.
+///
+/// If any entry cannot be reverted nothing is changed and the error is
+/// returned; the reverted operations are only reported after the commit.
+pub fn handle_undo(conn: &Connection, n: usize) -> Result<(), Box<dyn Error>> {
+    println!();
+
+    let entries = last_undoable(conn, n)?;
+    if entries.is_empty() {
+        print_warn(&tr("undo.nothing"));
+        return Ok(());
+    }
+
+    if auto_snapshot(conn, "undo").is_err() {
+        return Ok(());
+    }
+
+    // I messaggi si stampano solo dopo il commit: un errore annulla tutto
+    let tx = conn.unchecked_transaction()?;
+    let mut reverted = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        if let Err(e) = undo_entry(conn, entry) {
+            return Err(tr_with(
+                "undo.failed",
+                &[("entry", &entry.id.to_string()), ("error", &e.to_string())],
+            )
+            .into()); // tx dropped → rollback
+        }
+
+        let msg_key = match entry.operation {
+            HistoryOperation::Insert => "undo.reverted.insert",
+            HistoryOperation::Update => "undo.reverted.update",
+            HistoryOperation::Delete if entry.deleted_at.is_some() => "undo.reverted.purge",
+            HistoryOperation::Delete => "undo.reverted.delete",
+            HistoryOperation::Trash => "undo.reverted.trash",
+            HistoryOperation::Restore => "undo.reverted.restore",
+        };
+        reverted.push(tr_with(msg_key, &[("id", &entry.book_id.to_string())]));
+    }
+    tx.commit()?;
+    for msg in &reverted {
+        print_ok(msg, true);
+    }
+
+    let details = json!({
+        "history": entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+        "books": entries.iter().map(|e| e.book_id).collect::<Vec<_>>(),
+    });
+    // Con un solo libro coinvolto la voce resta collegata al libro
+    let book_id = match entries.as_slice() {
+        [first, rest @ ..] if rest.iter().all(|e| e.book_id == first.book_id) => {
+            Some(first.book_id)
+        }
+        _ => None,
+    };
+    let _ = write_log_with(
+        conn,
+        "UNDO",
+        "BOOKS",
+        &tr_fmt("log.undo", &[("count", entries.len().into())]),
+        book_id,
+        Some(&details),
+    );
+    Ok(())
+}
@@ -1,96 +1,451 @@
-use crate::i18n::tr_with;
-use crate::models::Book;
-use crate::utils::{is_verbose, print_err, print_ok};
-use csv::ReaderBuilder;
+use crate::db::history::{record_history, record_insert};
+use crate::db::{
+    find_book, find_book_by_id, find_book_without_isbn, insert_book, overwrite_book, trashed_id,
+};
+use crate::i18n::{tr_fmt, tr_with};
+use crate::models::{Book, HistoryOperation};
+use crate::utils::{
+    ColumnMap, ImportOptions, MappedImport, MappingOptions, MarcImport, MarcRecord, OnConflict,
+    RejectReason, RejectedRow, TableRows, auto_snapshot, calibre_db_path, is_marcxml, is_verbose,
+    map_marc_records, merge_book, parse_marc, print_err, print_info, print_ok, print_warn,
+    read_calibre, read_csv_table, read_goodreads, read_ods_rows, read_xlsx_rows,
+    rejected_file_path, validate_import, write_iso2709, write_marcxml, write_rejected_json,
+    write_rejected_table,
+};
 use rusqlite::Connection;
-use std::io::BufReader;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
 
-/// 🧩 Importa dati da file CSV (usa `csv` + `serde`)
+/// Outcome of an import (or of a dry run).
+#[derive(Debug, Default, Clone, Copy)]
+struct ImportCounts {
+    inserted: u32,
+    updated: u32,
+    /// Already in the catalog and left as they are
+    skipped: u32,
+    /// Rejected by the mapping or by `validate_book`
+    rejected: u32,
+    /// Valid records the database refused
+    failed: u32,
+    /// Matching a book in the trash, left untouched whatever `on_conflict` says
+    trashed: u32,
+}
+
+/// 🧩 Importa dati da file CSV, con la mappatura delle colonne di `mapping`
 pub fn handle_import_csv(
     conn: &mut Connection,
     file: &str,
     delimiter: char,
+    mapping: &MappingOptions,
+    options: &ImportOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let file_display = file.to_string();
 
     // ✅ Attempt to open the file
     let file_handle = crate::utils::open_import_file(file)?;
 
-    // ✅ Build CSV reader
-    let mut reader = ReaderBuilder::new()
-        .delimiter(delimiter as u8)
-        .from_reader(file_handle);
-
-    let mut imported = 0;
-    let mut failed = 0;
-
-    // ✅ Read and process each record
-    for (index, record) in reader.deserialize::<Book>().enumerate() {
-        match record {
-            Ok(book) => {
-                let result = conn.execute(
-                    "INSERT INTO books (title, author, editor, year, isbn, genre, language, pages, summary)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-                    (
-                        &book.title,
-                        &book.author,
-                        &book.editor,
-                        &book.year,
-                        &book.isbn,
-                        &book.genre,
-                        &book.language,
-                        &book.pages,
-                        &book.summary,
-                    ),
-                );
-
-                crate::utils::handle_import_result(
-                    &result,
-                    &mut imported,
-                    &mut failed,
-                    &book.title,
-                );
-            }
-            Err(e) => {
-                failed += 1;
-                print_err(&tr_with(
-                    "import.error.parse_failed",
-                    &[("line", &index.to_string()), ("error", &e.to_string())],
-                ));
-            }
+    let (headers, rows) = match read_csv_table(file_handle, delimiter as u8) {
+        Ok(table) => table,
+        Err(e) => {
+            print_err(&tr_with(
+                "import.error.parse_failed",
+                &[("line", "1"), ("error", &e.to_string())],
+            ));
+            return Ok(());
         }
+    };
+    let Some(map) = resolve_map(&headers, mapping) else {
+        return Ok(());
+    };
+
+    let mut import = map_table(&map, &headers, &rows);
+    validate_import(&mut import);
+    for rejected in &import.rejected {
+        print_warn(&rejected.to_string());
     }
+    let counts = write_import(conn, &import, options)?;
+    save_rejected(&import.rejected, options, || {
+        let path = rejected_file_path(Path::new(file), "csv");
+        write_rejected_table(&path, delimiter as u8, &headers, &rows, &import.rejected)?;
+        Ok(path)
+    });
+    let Some(counts) = counts else {
+        return Ok(());
+    };
 
     // ✅ Summary message
-    if imported > 0 {
+    if counts.inserted > 0 && !options.dry_run {
         print_ok(
-            &tr_with(
+            &tr_fmt(
                 "import.summary.ok",
                 &[
-                    ("count", &imported.to_string()),
-                    ("file", &file_display),
-                    ("delimiter", &delimiter.to_string()),
+                    ("count", counts.inserted.into()),
+                    ("file", file_display.as_str().into()),
+                    ("delimiter", delimiter.to_string().into()),
                 ],
             ),
             is_verbose(),
         );
     }
+    report_counts(&counts, options, &file_display);
 
-    if failed > 0 {
-        print_err(&tr_with(
-            "import.summary.failed",
-            &[("count", &failed.to_string()), ("file", &file_display)],
-        ));
+    Ok(())
+}
+
+/// Handles `import --xlsx`: the first worksheet is read like a CSV table
+/// (headers on the first row), with the same column mapping. Rejected rows
+/// are saved as CSV.
+pub fn handle_import_xlsx(
+    conn: &mut Connection,
+    file: &str,
+    mapping: &MappingOptions,
+    options: &ImportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sheet = read_xlsx_rows(Path::new(file));
+    import_sheet(
+        conn,
+        file,
+        sheet,
+        mapping,
+        options,
+        "import.summary.ok_xlsx",
+    )
+}
+
+/// Handles `import --ods`: the first sheet of an OpenDocument spreadsheet,
+/// imported like `import --xlsx`.
+pub fn handle_import_ods(
+    conn: &mut Connection,
+    file: &str,
+    mapping: &MappingOptions,
+    options: &ImportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sheet = read_ods_rows(Path::new(file));
+    import_sheet(conn, file, sheet, mapping, options, "import.summary.ok_ods")
+}
+
+/// Import comune ai fogli di calcolo (XLSX, ODS), già letti riga per riga.
+fn import_sheet(
+    conn: &mut Connection,
+    file: &str,
+    sheet: io::Result<Vec<Vec<String>>>,
+    mapping: &MappingOptions,
+    options: &ImportOptions,
+    summary_key: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut sheet = match sheet {
+        Ok(rows) => rows.into_iter(),
+        Err(e) => {
+            print_err(&tr_with(
+                "import.error.open_failed",
+                &[("file", file), ("error", &e.to_string())],
+            ));
+            return Ok(());
+        }
+    };
+    let headers = sheet.next().unwrap_or_default();
+    let rows: TableRows = sheet
+        .enumerate()
+        .filter(|(_, cells)| cells.iter().any(|c| !c.trim().is_empty()))
+        .map(|(i, cells)| (i as u64 + 2, Ok(cells)))
+        .collect();
+    let Some(map) = resolve_map(&headers, mapping) else {
+        return Ok(());
+    };
+
+    let mut import = map_table(&map, &headers, &rows);
+    validate_import(&mut import);
+    for rejected in &import.rejected {
+        print_warn(&rejected.to_string());
+    }
+    let counts = write_import(conn, &import, options)?;
+    save_rejected(&import.rejected, options, || {
+        let path = rejected_file_path(Path::new(file), "csv");
+        write_rejected_table(&path, b',', &headers, &rows, &import.rejected)?;
+        Ok(path)
+    });
+    let Some(counts) = counts else {
+        return Ok(());
+    };
+
+    if counts.inserted > 0 && !options.dry_run {
+        print_ok(
+            &tr_fmt(
+                summary_key,
+                &[("count", counts.inserted.into()), ("file", file.into())],
+            ),
+            is_verbose(),
+        );
     }
+    report_counts(&counts, options, file);
 
     Ok(())
 }
 
+/// Builds the column mapping of a table, printing it with `--infer`.
+/// Returns `None` if the mapping is invalid (already reported).
+fn resolve_map(headers: &[String], mapping: &MappingOptions) -> Option<ColumnMap> {
+    let map = match ColumnMap::resolve(headers, mapping) {
+        Ok(map) => map,
+        Err(e) => {
+            print_err(&e.to_string());
+            return None;
+        }
+    };
+    if mapping.infer {
+        for rule in &map.rules {
+            print_info(
+                &tr_with(
+                    "import.map.column",
+                    &[("column", &rule.column), ("field", &rule.field)],
+                ),
+                true,
+            );
+        }
+        for column in map.unmapped(headers) {
+            print_warn(&tr_with("import.map.unmapped", &[("column", column)]));
+        }
+    }
+    Some(map)
+}
+
+/// Maps the rows of a CSV/XLSX table onto books.
+fn map_table(map: &ColumnMap, headers: &[String], rows: &TableRows) -> MappedImport {
+    let mut import = MappedImport::default();
+    for (line, cells) in rows {
+        let line = *line;
+        let cells = match cells {
+            Ok(cells) => cells,
+            Err(e) => {
+                import.rejected.push(RejectedRow {
+                    line,
+                    title: String::new(),
+                    reason: RejectReason::Unreadable(e.clone()),
+                });
+                continue;
+            }
+        };
+        match map.map_row(headers, cells) {
+            Ok(book) => import.books.push((line, book)),
+            Err(reason) => import.rejected.push(RejectedRow {
+                line,
+                title: map
+                    .raw_value("title", headers, cells)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                reason,
+            }),
+        }
+    }
+    import
+}
+
+fn isbn_or_dash(isbn: &str) -> String {
+    if isbn.is_empty() {
+        "—".to_string()
+    } else {
+        isbn.to_string()
+    }
+}
+
+/// Writes the validated books in a single transaction, resolving ISBN
+/// conflicts with `options.on_conflict`; books without ISBN match on title,
+/// author and year instead. Books matching a book in the trash are counted
+/// apart and never written. Unless `--partial` is given, any rejected or
+/// failed record cancels the whole import. With `--dry-run` everything runs
+/// and is then rolled back. Returns `None` when the import is cancelled
+/// (already reported).
+fn write_import(
+    conn: &mut Connection,
+    import: &MappedImport,
+    options: &ImportOptions,
+) -> Result<Option<ImportCounts>, Box<dyn std::error::Error>> {
+    let mut counts = ImportCounts {
+        rejected: import.rejected.len() as u32,
+        ..Default::default()
+    };
+    if counts.rejected > 0 && !options.partial && !options.dry_run {
+        print_err(&tr_fmt(
+            "import.aborted",
+            &[("count", counts.rejected.into())],
+        ));
+        return Ok(None);
+    }
+
+    // ✅ Safety snapshot before writing
+    if !options.dry_run && !import.books.is_empty() && auto_snapshot(conn, "import").is_err() {
+        return Ok(None);
+    }
+
+    let tx = conn.transaction()?;
+    for (line, book) in &import.books {
+        // Senza ISBN il libro si riconosce da titolo, autore e anno
+        let existing = if book.isbn.is_empty() {
+            find_book_without_isbn(&tx, &book.title, &book.author, book.year)?
+        } else {
+            find_book(&tx, &book.isbn, true)?
+        };
+        let Some(existing) = existing else {
+            match insert_book(&tx, book).and_then(|id| record_insert(&tx, id)) {
+                Ok(()) => counts.inserted += 1,
+                Err(e) => {
+                    counts.failed += 1;
+                    print_err(&tr_with(
+                        "import.error.insert_failed",
+                        &[("title", &book.title), ("error", &e.to_string())],
+                    ));
+                }
+            }
+            continue;
+        };
+
+        let id = existing.id.unwrap_or_default() as i64;
+        let conflict = [
+            ("line", line.to_string()),
+            ("title", book.title.clone()),
+            ("isbn", isbn_or_dash(&book.isbn)),
+            ("id", id.to_string()),
+        ];
+        let conflict: Vec<(&str, &str)> = conflict.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        // Un libro nel cestino non si aggiorna né si conta fra i già presenti
+        if trashed_id(&tx, &id.to_string(), false)?.is_some() {
+            counts.trashed += 1;
+            print_info(&tr_with("import.conflict.trashed", &conflict), is_verbose());
+            continue;
+        }
+        match options.on_conflict {
+            OnConflict::Skip => {
+                counts.skipped += 1;
+                print_info(&tr_with("import.conflict.skipped", &conflict), is_verbose());
+            }
+            OnConflict::Update => {
+                let merged = merge_book(&existing, book);
+                if merged == existing {
+                    counts.skipped += 1;
+                    continue;
+                }
+                overwrite_book(&tx, &merged)?;
+                let after = find_book_by_id(&tx, id)?;
+                record_history(
+                    &tx,
+                    HistoryOperation::Update,
+                    id,
+                    Some(&existing),
+                    after.as_ref(),
+                )?;
+                counts.updated += 1;
+                print_info(&tr_with("import.conflict.updated", &conflict), is_verbose());
+            }
+            OnConflict::Fail => {
+                print_err(&tr_with("import.conflict.failed", &conflict));
+                return Ok(None);
+            }
+        }
+    }
+
+    if counts.failed > 0 && !options.partial && !options.dry_run {
+        print_err(&tr_fmt(
+            "import.aborted",
+            &[("count", counts.failed.into())],
+        ));
+        return Ok(None);
+    }
+    if options.dry_run {
+        tx.rollback()?;
+    } else {
+        tx.commit()?;
+    }
+    Ok(Some(counts))
+}
+
+/// Saves the rejected records next to the source for fixing and re-import
+/// (never in dry-run mode).
+fn save_rejected(
+    rejected: &[RejectedRow],
+    options: &ImportOptions,
+    write: impl FnOnce() -> io::Result<PathBuf>,
+) {
+    if rejected.is_empty() || options.dry_run {
+        return;
+    }
+    match write() {
+        Ok(path) => print_info(
+            &tr_with(
+                "import.rejected_file.saved",
+                &[("path", &path.display().to_string())],
+            ),
+            true,
+        ),
+        Err(e) => print_err(&tr_with(
+            "import.rejected_file.failed",
+            &[("error", &e.to_string())],
+        )),
+    }
+}
+
+/// Prints updates, skipped and rejected records, or the dry-run report.
+fn report_counts(counts: &ImportCounts, options: &ImportOptions, source: &str) {
+    if options.dry_run {
+        print_info(
+            &tr_fmt(
+                "import.dry_run.summary",
+                &[
+                    ("file", source.into()),
+                    ("inserted", counts.inserted.into()),
+                    ("updated", counts.updated.into()),
+                    ("skipped", counts.skipped.into()),
+                    ("trashed", counts.trashed.into()),
+                    ("rejected", (counts.rejected + counts.failed).into()),
+                ],
+            ),
+            true,
+        );
+        if counts.rejected + counts.failed > 0 && !options.partial {
+            print_warn(&tr_with("import.dry_run.would_abort", &[]));
+        }
+        return;
+    }
+
+    if counts.updated > 0 {
+        print_ok(
+            &tr_fmt(
+                "import.summary.updated",
+                &[("count", counts.updated.into())],
+            ),
+            true,
+        );
+    }
+    if counts.skipped > 0 {
+        print_warn(&tr_fmt(
+            "import.summary.skipped",
+            &[("count", counts.skipped.into())],
+        ));
+    }
+    if counts.trashed > 0 {
+        print_warn(&tr_fmt(
+            "import.summary.trashed",
+            &[("count", counts.trashed.into())],
+        ));
+    }
+    if counts.rejected + counts.failed > 0 {
+        print_err(&tr_fmt(
+            "import.summary.failed",
+            &[
+                ("count", (counts.rejected + counts.failed).into()),
+                ("file", source.into()),
+            ],
+        ));
+    }
+}
+
 /// Handles importing a JSON file into the database.
 /// Expects a top-level array of book objects.
 pub fn handle_import_json(
     conn: &mut Connection,
     file: &str,
+    options: &ImportOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let file_display = file.to_string();
 
@@ -98,7 +453,7 @@ pub fn handle_import_json(
     let file_handle = crate::utils::open_import_file(file)?;
 
     let reader = BufReader::new(file_handle);
-    let books: Vec<Book> = match serde_json::from_reader(reader) {
+    let values: Vec<serde_json::Value> = match serde_json::from_reader(reader) {
         Ok(data) => data,
         Err(e) => {
             print_err(&tr_with(
@@ -109,47 +464,249 @@ pub fn handle_import_json(
         }
     };
 
-    let mut imported = 0;
-    let mut failed = 0;
-
-    // ✅ Iterate through records
-    for book in books {
-        let result = conn.execute(
-            "INSERT INTO books (title, author, editor, year, isbn, genre, language, pages, summary)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            (
-                &book.title,
-                &book.author,
-                &book.editor,
-                &book.year,
-                &book.isbn,
-                &book.genre,
-                &book.language,
-                &book.pages,
-                &book.summary,
-            ),
-        );
-
-        crate::utils::handle_import_result(&result, &mut imported, &mut failed, &book.title);
+    // ✅ Un oggetto non valido diventa un record scartato (numerato da 1)
+    let mut import = MappedImport::default();
+    for (index, value) in values.iter().enumerate() {
+        let line = index as u64 + 1;
+        match serde_json::from_value::<Book>(value.clone()) {
+            Ok(book) => import.books.push((line, book)),
+            Err(e) => import.rejected.push(RejectedRow {
+                line,
+                title: value["title"].as_str().unwrap_or_default().to_string(),
+                reason: RejectReason::Unreadable(e.to_string()),
+            }),
+        }
     }
+    validate_import(&mut import);
+    for rejected in &import.rejected {
+        print_warn(&rejected.to_string());
+    }
+    let counts = write_import(conn, &import, options)?;
+    save_rejected(&import.rejected, options, || {
+        let path = rejected_file_path(Path::new(file), "json");
+        write_rejected_json(&path, &values, &import.rejected)?;
+        Ok(path)
+    });
+    let Some(counts) = counts else {
+        return Ok(());
+    };
 
     // ✅ Summary output
-    if imported > 0 {
+    if counts.inserted > 0 && !options.dry_run {
         print_ok(
-            &tr_with(
+            &tr_fmt(
                 "import.summary.ok_json",
-                &[("count", &imported.to_string()), ("file", &file_display)],
+                &[
+                    ("count", counts.inserted.into()),
+                    ("file", file_display.as_str().into()),
+                ],
             ),
             is_verbose(),
         );
     }
+    report_counts(&counts, options, &file_display);
 
-    if failed > 0 {
+    Ok(())
+}
+
+/// Handles `import --goodreads`: maps a Goodreads library export (ratings,
+/// reading status, shelves and reviews included) and reports the rows that
+/// could not be mapped.
+pub fn handle_import_goodreads(
+    conn: &mut Connection,
+    file: &str,
+    options: &ImportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file_handle = crate::utils::open_import_file(file)?;
+
+    let mut import = match read_goodreads(BufReader::new(file_handle)) {
+        Ok(import) => import,
+        Err(e) => {
+            print_err(&tr_with(
+                "import.goodreads.invalid",
+                &[("file", file), ("error", &e.to_string())],
+            ));
+            return Ok(());
+        }
+    };
+
+    validate_import(&mut import);
+    for rejected in &import.rejected {
+        print_warn(&rejected.to_string());
+    }
+    let counts = write_import(conn, &import, options)?;
+    save_rejected(&import.rejected, options, || {
+        let (headers, rows) = read_csv_table(File::open(file)?, b',')?;
+        let path = rejected_file_path(Path::new(file), "csv");
+        write_rejected_table(&path, b',', &headers, &rows, &import.rejected)?;
+        Ok(path)
+    });
+    let Some(counts) = counts else {
+        return Ok(());
+    };
+
+    if counts.inserted > 0 && !options.dry_run {
+        print_ok(
+            &tr_fmt(
+                "import.summary.ok_goodreads",
+                &[("count", counts.inserted.into()), ("file", file.into())],
+            ),
+            true,
+        );
+    }
+    report_counts(&counts, options, file);
+
+    Ok(())
+}
+
+/// Handles `import --calibre`: reads a Calibre library (`metadata.db`) and
+/// adds its books as ebooks; ISBNs already in the catalog follow
+/// `--on-conflict` (skipped by default).
+pub fn handle_import_calibre(
+    conn: &mut Connection,
+    library: &str,
+    options: &ImportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = calibre_db_path(Path::new(library));
+    if !db_path.is_file() {
         print_err(&tr_with(
-            "import.summary.failed",
-            &[("count", &failed.to_string()), ("file", &file_display)],
+            "import.calibre.not_found",
+            &[("path", &db_path.display().to_string())],
+        ));
+        return Ok(());
+    }
+
+    let mut import = match read_calibre(&db_path) {
+        Ok(import) => import,
+        Err(e) => {
+            print_err(&tr_with(
+                "import.calibre.invalid",
+                &[
+                    ("path", &db_path.display().to_string()),
+                    ("error", &e.to_string()),
+                ],
+            ));
+            return Ok(());
+        }
+    };
+
+    validate_import(&mut import);
+    for rejected in &import.rejected {
+        print_warn(&tr_with(
+            "import.calibre.rejected",
+            &[
+                ("id", &rejected.line.to_string()),
+                ("title", &rejected.title),
+                ("reason", &rejected.reason.to_string()),
+            ],
         ));
     }
 
+    // Nessun file degli scartati: la sorgente è il database di Calibre
+    let Some(counts) = write_import(conn, &import, options)? else {
+        return Ok(());
+    };
+
+    if counts.inserted > 0 && !options.dry_run {
+        print_ok(
+            &tr_fmt(
+                "import.summary.ok_calibre",
+                &[
+                    ("count", counts.inserted.into()),
+                    ("library", library.into()),
+                ],
+            ),
+            true,
+        );
+    }
+    report_counts(&counts, options, library);
+
+    Ok(())
+}
+
+/// Handles `import --marc`: reads MARC 21 records (binary ISO 2709 or
+/// MARCXML) and reports the MARC fields Librius has no place for. Rejected
+/// records are saved in the same format.
+pub fn handle_import_marc(
+    conn: &mut Connection,
+    file: &str,
+    options: &ImportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new(file);
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) => {
+            print_err(&tr_with(
+                "import.error.open_failed",
+                &[("file", file), ("error", &e.to_string())],
+            ));
+            return Ok(());
+        }
+    };
+    let xml = is_marcxml(path, &data);
+    let records = match parse_marc(path, &data) {
+        Ok(records) => records,
+        Err(e) => {
+            print_err(&tr_with(
+                "import.marc.invalid",
+                &[("file", file), ("error", &e.to_string())],
+            ));
+            return Ok(());
+        }
+    };
+
+    let MarcImport {
+        mut import,
+        lost_tags,
+    } = map_marc_records(&records);
+    for (tag, count) in &lost_tags {
+        print_warn(&tr_fmt(
+            "import.marc.lost",
+            &[("tag", tag.as_str().into()), ("count", (*count).into())],
+        ));
+    }
+    validate_import(&mut import);
+    for rejected in &import.rejected {
+        print_warn(&rejected.to_string());
+    }
+    let counts = write_import(conn, &import, options)?;
+    save_rejected(&import.rejected, options, || {
+        // Record `n` è `records[n - 1]`; quelli illeggibili non si possono riscrivere
+        let rejected: Vec<MarcRecord> = import
+            .rejected
+            .iter()
+            .filter_map(|r| records.get(r.line as usize - 1)?.as_ref().ok().cloned())
+            .collect();
+        let (ext, data) = if xml {
+            ("xml", write_marcxml(&rejected)?)
+        } else {
+            let (data, too_long) = write_iso2709(&rejected);
+            if !too_long.is_empty() {
+                print_warn(&tr_fmt(
+                    "import.marc.too_long",
+                    &[("count", too_long.len().into())],
+                ));
+            }
+            ("mrc", data)
+        };
+        let target = rejected_file_path(path, ext);
+        std::fs::write(&target, data)?;
+        Ok(target)
+    });
+    let Some(counts) = counts else {
+        return Ok(());
+    };
+
+    if counts.inserted > 0 && !options.dry_run {
+        print_ok(
+            &tr_fmt(
+                "import.summary.ok_marc",
+                &[("count", counts.inserted.into()), ("file", file.into())],
+            ),
+            true,
+        );
+    }
+    report_counts(&counts, options, file);
+
     Ok(())
 }
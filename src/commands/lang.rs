@@ -0,0 +1,43 @@
+use crate::i18n::{
+    available_languages, check_locale_files, fallback_chain, locales_dir, tr, tr_with,
+};
+use crate::models::LanguageRow;
+use crate::utils::{build_table, print_info, print_warn};
+use std::error::Error;
+
+/// Handle `lang list`: embedded and external languages, with the fallback
+/// chain of the active one.
+pub fn handle_lang_list(active: &str) -> Result<(), Box<dyn Error>> {
+    let languages = available_languages();
+    let chain = fallback_chain(active);
+    // La lingua effettivamente usata: la prima disponibile della catena
+    let used = chain
+        .iter()
+        .find(|code| languages.iter().any(|l| &l.code == *code));
+
+    println!("\n{}\n", tr("lang.title"));
+    println!(
+        "{}",
+        build_table(languages.iter().map(|info| LanguageRow {
+            info,
+            active: used == Some(&info.code),
+        }))
+    );
+    println!(
+        "{}",
+        tr_with("lang.active_chain", &[("chain", &chain.join(" → "))])
+    );
+
+    for warning in check_locale_files() {
+        print_warn(&warning.to_string());
+    }
+
+    // Dove aggiungere o correggere le traduzioni
+    if let Some(dir) = locales_dir() {
+        print_info(
+            &tr_with("lang.locales_dir", &[("dir", &dir.display().to_string())]),
+            true,
+        );
+    }
+    Ok(())
+}
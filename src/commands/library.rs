@@ -0,0 +1,184 @@
+use crate::config::migrate_config::backup_path;
+use crate::config::{
+    self, AppConfig, DEFAULT_LIBRARY, LibraryError, LibraryProfile, write_libraries,
+};
+use crate::i18n::{tr, tr_with};
+use crate::models::LibraryRow;
+use crate::utils::{build_table, print_err, print_info, print_ok, write_log};
+use colored::*;
+use rusqlite::Connection;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Handle `library list`: shows the default library and every named profile.
+pub fn handle_library_list() -> Result<(), Box<dyn Error>> {
+    let cfg = config::load_config()?;
+    let active = cfg.library_name();
+
+    let mut rows = vec![LibraryRow {
+        name: DEFAULT_LIBRARY,
+        database: &cfg.database,
+        language: &cfg.language,
+        active: active == DEFAULT_LIBRARY,
+    }];
+    for (name, profile) in &cfg.libraries {
+        rows.push(LibraryRow {
+            name,
+            database: &profile.database,
+            language: profile.language.as_deref().unwrap_or(&cfg.language),
+            active: active == name,
+        });
+    }
+
+    println!("\n{}\n", tr("library.title"));
+    println!("{}", build_table(rows));
+    Ok(())
+}
+
+/// Handle `library add <NAME> [--database PATH] [--language CODE]`.
+///
+/// Without `--database` the database is placed in its own directory under the
+/// configuration folder; it is created the first time the library is used.
+pub fn handle_library_add(
+    conn: &Connection,
+    name: &str,
+    db: Option<&str>,
+    language: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    println!();
+    let db_path = match db {
+        Some(path) => absolute(Path::new(path))?,
+        None => config::library_db_path(name),
+    };
+    let profile = LibraryProfile {
+        database: db_path.display().to_string(),
+        language: language.map(str::to_string),
+        backup: None,
+    };
+
+    update_config(
+        conn,
+        "LIBRARY_ADD",
+        |cfg| cfg.add_library(name, profile),
+        |_| {
+            if let Some(dir) = db_path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            print_ok(
+                &tr_with(
+                    "library.added",
+                    &[("name", name), ("db_path", &db_path.display().to_string())],
+                )
+                .green(),
+                true,
+            );
+            print_info(&tr_with("library.added.hint", &[("name", name)]), true);
+            Ok(format!("Library {} added ({})", name, db_path.display()))
+        },
+    )
+}
+
+/// Handle `library remove <NAME>`: forgets the profile, keeping its database.
+pub fn handle_library_remove(conn: &Connection, name: &str) -> Result<(), Box<dyn Error>> {
+    println!();
+    update_config(
+        conn,
+        "LIBRARY_REMOVE",
+        |cfg| cfg.remove_library(name),
+        |profile| {
+            print_ok(&tr_with("library.removed", &[("name", name)]).green(), true);
+            print_info(
+                &tr_with("library.removed.kept_db", &[("db_path", &profile.database)]),
+                true,
+            );
+            Ok(format!("Library {} removed", name))
+        },
+    )
+}
+
+/// Handle `library use <NAME>`: makes it the library used by default.
+pub fn handle_library_use(conn: &Connection, name: &str) -> Result<(), Box<dyn Error>> {
+    println!();
+    update_config(
+        conn,
+        "LIBRARY_USE",
+        |cfg| cfg.use_library(name),
+        |_| {
+            print_ok(
+                &tr_with("library.switched", &[("name", name)]).green(),
+                true,
+            );
+            Ok(format!("Active library set to {}", name))
+        },
+    )
+}
+
+/// Handle `library rename <OLD> <NEW>`.
+pub fn handle_library_rename(
+    conn: &Connection,
+    old: &str,
+    new: &str,
+) -> Result<(), Box<dyn Error>> {
+    println!();
+    update_config(
+        conn,
+        "LIBRARY_RENAME",
+        |cfg| cfg.rename_library(old, new),
+        |_| {
+            print_ok(
+                &tr_with("library.renamed", &[("old", old), ("new", new)]).green(),
+                true,
+            );
+            Ok(format!("Library {} renamed to {}", old, new))
+        },
+    )
+}
+
+/// Loads the config file, applies `change` and saves it, keeping the previous
+/// contents in `librius.conf.bak`. On success `done` reports the result and
+/// returns the message written to the log.
+fn update_config<T>(
+    conn: &Connection,
+    operation: &str,
+    change: impl FnOnce(&mut AppConfig) -> Result<T, LibraryError>,
+    done: impl FnOnce(T) -> Result<String, Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut cfg = config::load_or_init()?;
+    let value = match change(&mut cfg) {
+        Ok(value) => value,
+        Err(e) => {
+            print_err(&e.to_string());
+            return Ok(());
+        }
+    };
+    // Solo le chiavi dei profili cambiano: commenti e ordine restano
+    let config_path = config::config_file_path();
+    let text = fs::read_to_string(&config_path)?;
+    let updated = match write_libraries(&text, &cfg) {
+        Ok(updated) => updated,
+        Err(e) => {
+            print_err(&e.to_string());
+            return Ok(());
+        }
+    };
+    fs::write(backup_path(&config_path), &text)?;
+    fs::write(&config_path, updated)?;
+
+    let log_msg = done(value)?;
+    if let Err(e) = write_log(conn, operation, "CONFIG", &log_msg) {
+        print_err(&tr_with(
+            "log.record.unable_to_write",
+            &[("log_error", &e.to_string())],
+        ));
+    }
+    Ok(())
+}
+
+fn absolute(path: &Path) -> std::io::Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
@@ -1,4 +1,5 @@
-use crate::i18n::tr;
+use crate::db::BOOK_COLUMNS;
+use crate::i18n::{tr, tr_with};
 use crate::models::book::Book;
 use crate::models::display::{BookFull, BookShort};
 use crate::utils::isbn::normalize_isbn;
@@ -25,13 +26,15 @@ fn row_to_book(row: &Row) -> rusqlite::Result<Book> {
 /// Handle the `list` subcommand.
 ///
 /// Lists all books from the database using localized tabular output.
-/// Supports the `--short` flag for compact view.
+/// Supports the `--short` flag for compact view. `library` is the name of the
+/// active named library, shown in the header (`None` for the default one).
 pub fn handle_list(
     conn: &Connection,
     _short: bool,
     id: Option<i32>,
     _details: bool,
     compact: bool,
+    library: Option<&str>,
 ) -> Result<(), Box<dyn Error>> {
     // If user asked for details without specifying an id, show a localized
     // error message and do not display the list.
@@ -42,9 +45,13 @@ pub fn handle_list(
     }
 
     // Build base query and optionally filter by id if provided
-    let base_query = "SELECT id, title, author, editor, year, isbn, language, pages, genre, summary, room, shelf, row, position, added_at FROM books";
+    // Books in the trash are never listed (see `trash list`)
+    let base_query = format!(
+        "SELECT {} FROM books WHERE deleted_at IS NULL",
+        BOOK_COLUMNS
+    );
     let query = if id.is_some() {
-        format!("{} WHERE id = ?1 ORDER BY id;", base_query)
+        format!("{} AND id = ?1 ORDER BY id;", base_query)
     } else {
         format!("{} ORDER BY id;", base_query)
     };
@@ -78,11 +85,19 @@ pub fn handle_list(
     // If an ID was requested, show detailed vertical view for that specific record.
     if id.is_some() {
         let book = &books[0];
-        println!("\n📖  {} {:?}\n", tr("list.book_details_for_id"), book.id);
+        let id = book.id.map(|id| id.to_string()).unwrap_or_default();
+        println!(
+            "\n📖  {}\n",
+            tr_with("list.book_details_for_id", &[("id", &id)])
+        );
         build_vertical_table(book, compact);
     } else {
         // Otherwise show the list (short or full)
-        println!("\n{}\n", tr("app.library.info"));
+        let header = match library {
+            Some(name) => tr_with("app.library.info_named", &[("name", name)]),
+            None => tr("app.library.info"),
+        };
+        println!("\n{}\n", header);
 
         let table = if _short {
             build_table(books.iter().map(BookShort))
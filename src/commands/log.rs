@@ -0,0 +1,143 @@
+use crate::db::log::{LogFilter, count_log_before, list_log, log_stats, prune_log};
+use crate::i18n::{tr, tr_fmt, tr_with};
+use crate::models::{LogEntry, LogRow, LogStatRow};
+use crate::utils::{
+    OutputFormat, build_table, parse_age, print_err, print_info, print_ok, write_log_with,
+    write_records,
+};
+use chrono::Utc;
+use rusqlite::Connection;
+use serde_json::json;
+use std::error::Error;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// Polling interval of `log tail --follow`.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+fn print_entries(entries: &[LogEntry], format: OutputFormat) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => {
+            println!("{}", build_table(entries.iter().map(LogRow)));
+            Ok(())
+        }
+        _ => write_records(io::stdout().lock(), format, entries),
+    }
+}
+
+/// Handle `log list`: prints the entries matching `filter`, oldest first.
+pub fn handle_log_list(
+    conn: &Connection,
+    filter: &LogFilter,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let entries = list_log(conn, filter)?;
+
+    if format == OutputFormat::Table {
+        if entries.is_empty() {
+            println!();
+            print_info(&tr("log.empty"), true);
+            return Ok(());
+        }
+        println!("\n{}\n", tr("log.title"));
+    }
+    print_entries(&entries, format)?;
+    Ok(())
+}
+
+/// Handle `log tail [N] [--follow]`: prints the last `n` entries and, with
+/// `follow`, keeps printing new ones until interrupted.
+pub fn handle_log_tail(
+    conn: &Connection,
+    filter: &LogFilter,
+    n: usize,
+    follow: bool,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let mut filter = LogFilter {
+        limit: Some(n),
+        ..filter.clone()
+    };
+    let entries = list_log(conn, &filter)?;
+    if entries.is_empty() && format == OutputFormat::Table {
+        print_info(&tr("log.empty"), true);
+    } else {
+        print_entries(&entries, format)?;
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    // JSON non è componibile riga per riga: in follow si usa JSON Lines
+    let format = match format {
+        OutputFormat::Json => OutputFormat::Jsonl,
+        other => other,
+    };
+    filter.limit = None;
+    filter.after_id = entries.last().map(|e| e.id).or(filter.after_id);
+    loop {
+        thread::sleep(FOLLOW_INTERVAL);
+        let new = list_log(conn, &filter)?;
+        if let Some(last) = new.last() {
+            filter.after_id = Some(last.id);
+            print_entries(&new, format)?;
+        }
+    }
+}
+
+/// Handle `log stats`: number of entries per operation.
+pub fn handle_log_stats(
+    conn: &Connection,
+    filter: &LogFilter,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let stats = log_stats(conn, filter)?;
+
+    if format != OutputFormat::Table {
+        write_records(io::stdout().lock(), format, &stats)?;
+        return Ok(());
+    }
+    if stats.is_empty() {
+        println!();
+        print_info(&tr("log.empty"), true);
+        return Ok(());
+    }
+
+    let total: i64 = stats.iter().map(|s| s.count).sum();
+    println!("\n{}\n", tr("log.stats.title"));
+    println!("{}", build_table(stats.iter().map(LogStatRow)));
+    println!("{}", tr_fmt("log.stats.total", &[("count", total.into())]));
+    Ok(())
+}
+
+/// Handle `log prune --older-than AGE [--dry-run]`: deletes old entries.
+pub fn handle_log_prune(
+    conn: &Connection,
+    older_than: &str,
+    dry_run: bool,
+) -> Result<(), Box<dyn Error>> {
+    println!();
+
+    let Some(age) = parse_age(older_than) else {
+        print_err(&tr_with("log.invalid_age", &[("value", older_than)]));
+        return Ok(());
+    };
+    let cutoff = Utc::now() - age;
+
+    if dry_run {
+        let count = count_log_before(conn, &cutoff)?;
+        print_info(
+            &tr_fmt("log.prune.dry_run", &[("count", count.into())]),
+            true,
+        );
+        return Ok(());
+    }
+
+    let deleted = prune_log(conn, &cutoff)?;
+    let msg = tr_fmt("log.prune.done", &[("count", deleted.into())]);
+    let details = json!({ "older_than": older_than, "deleted": deleted });
+    let _ = write_log_with(conn, "LOG_PRUNE", "LOG", &msg, None, Some(&details));
+    print_ok(&msg, true);
+    Ok(())
+}
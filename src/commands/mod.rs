@@ -6,25 +6,54 @@
 
 pub mod add_book;
 pub mod backup;
+pub mod cite;
 pub mod config;
 pub mod db;
 pub mod del_book;
+pub mod dev;
 pub mod edit_book;
 pub mod export;
+pub mod history;
 pub mod import;
+pub mod lang;
+pub mod library;
 pub mod list;
+pub mod log;
 pub mod search_book;
+pub mod trash;
 
 pub use add_book::handle_add_book;
 pub use backup::handle_backup;
+pub use backup::handle_backup_prune;
+pub use backup::handle_backup_restore;
+pub use cite::handle_cite;
 pub use config::handle_config;
 pub use db::handle_db;
 pub use del_book::handle_del_book;
+pub use dev::handle_dev_i18n_check;
 pub use edit_book::handle_edit_book;
+pub use export::handle_export_citations;
 pub use export::handle_export_csv;
+pub use export::handle_export_html;
 pub use export::handle_export_json;
+pub use export::handle_export_marc;
+pub use export::handle_export_markdown;
+pub use export::handle_export_ods;
+pub use export::handle_export_org;
 pub use export::handle_export_xlsx;
+pub use history::handle_history;
+pub use history::handle_undo;
 pub use import::handle_import_csv;
 pub use import::handle_import_json;
+pub use import::{
+    handle_import_calibre, handle_import_goodreads, handle_import_marc, handle_import_ods,
+    handle_import_xlsx,
+};
+pub use lang::handle_lang_list;
+pub use library::handle_library_list;
 pub use list::handle_list;
+pub use log::handle_log_list;
 pub use search_book::handle_search;
+pub use trash::handle_trash_empty;
+pub use trash::handle_trash_list;
+pub use trash::handle_trash_restore;
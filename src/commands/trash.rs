@@ -0,0 +1,133 @@
+use crate::db::trash::{list_trash, purge_book, restore_book};
+use crate::i18n::{tr, tr_fmt, tr_with};
+use crate::models::TrashRow;
+use crate::utils::{
+    auto_snapshot, build_table, parse_age, print_err, print_info, print_ok, print_warn,
+    write_log_with,
+};
+use chrono::Utc;
+use colored::*;
+use rusqlite::Connection;
+use serde_json::json;
+use std::error::Error;
+use std::io::{self, Write};
+
+/// Handle `trash list`: shows every book currently in the trash.
+pub fn handle_trash_list(conn: &Connection) -> Result<(), Box<dyn Error>> {
+    let trashed = list_trash(conn)?;
+
+    if trashed.is_empty() {
+        println!();
+        print_info(&tr("trash.empty_bin"), true);
+        return Ok(());
+    }
+
+    println!("\n{}\n", tr("trash.title"));
+    println!("{}", build_table(trashed.iter().map(TrashRow)));
+    Ok(())
+}
+
+/// Handle `trash restore <ID>`: puts a trashed book back into the library.
+pub fn handle_trash_restore(conn: &Connection, id: i64) -> Result<(), Box<dyn Error>> {
+    println!();
+    let id_str = id.to_string();
+
+    let tx = conn.unchecked_transaction()?;
+    let restored = restore_book(conn, id)?;
+    tx.commit()?;
+
+    if !restored {
+        print_warn(&tr_with("trash.restore.not_found", &[("id", &id_str)]));
+        return Ok(());
+    }
+
+    let _ = write_log_with(
+        conn,
+        "RESTORE_BOOK",
+        "BOOKS",
+        &format!("Book {} restored from trash", id),
+        Some(id),
+        None,
+    );
+    print_ok(
+        &tr_with("trash.restore.ok", &[("id", &id_str)]).green(),
+        true,
+    );
+    Ok(())
+}
+
+/// Handle `trash empty [--older-than AGE]`: permanently deletes trashed books.
+/// Asks for confirmation unless `force` is set.
+pub fn handle_trash_empty(
+    conn: &Connection,
+    older_than: Option<&str>,
+    force: bool,
+) -> Result<(), Box<dyn Error>> {
+    println!();
+
+    let cutoff = match older_than {
+        Some(age) => match parse_age(age) {
+            Some(d) => Some(Utc::now() - d),
+            None => {
+                print_err(&tr_with("trash.invalid_age", &[("value", age)]));
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let to_purge: Vec<_> = list_trash(conn)?
+        .into_iter()
+        .filter(|t| cutoff.is_none_or(|c| t.deleted_at < c))
+        .collect();
+
+    if to_purge.is_empty() {
+        print_info(&tr("trash.nothing_to_empty"), true);
+        return Ok(());
+    }
+
+    if !force {
+        print!(
+            "{} ",
+            tr_fmt("trash.empty.confirm", &[("count", to_purge.len().into())])
+        );
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            print_info(&tr("trash.empty.cancelled"), true);
+            return Ok(());
+        }
+        println!();
+    }
+
+    if auto_snapshot(conn, "trash-empty").is_err() {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let mut purged = Vec::new();
+    for trashed in &to_purge {
+        if purge_book(conn, &trashed.book)? {
+            purged.extend(trashed.book.id);
+        }
+    }
+    tx.commit()?;
+    let purged_count = purged.len();
+
+    let _ = write_log_with(
+        conn,
+        "TRASH_EMPTY",
+        "BOOKS",
+        &format!("{} book(s) permanently deleted from trash", purged_count),
+        None,
+        Some(&json!({ "books": purged, "older_than": older_than })),
+    );
+    print_ok(
+        &tr_fmt("trash.empty.ok", &[("count", purged_count.into())]),
+        true,
+    );
+    Ok(())
+}
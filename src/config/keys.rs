@@ -0,0 +1,275 @@
+//! Typed schema of the keys accepted by `config get|set|unset`.
+//!
+//! Every settable key of [`AppConfig`] is listed in [`CONFIG_KEYS`] with the
+//! kind of value it accepts; `*` stands for the name of a named library.
+//! Edits go through [`crate::config::yaml_edit`] so that comments survive, and
+//! the result is always parsed back into an `AppConfig` before being accepted.
+
+use crate::config::{AppConfig, BackupFormat, yaml_edit};
+use crate::i18n::{available_languages, is_language_available, normalize_locale_code, tr, tr_with};
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+
+/// Kind of value accepted by a config key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// Filesystem path (non-empty string)
+    Path,
+    /// Available language code (embedded or external locale)
+    Language,
+    /// Non-negative integer
+    Count,
+    BackupFormat,
+    /// Name of an existing named library
+    Library,
+}
+
+/// A key of `librius.conf` that can be read and changed from the CLI.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigKey {
+    /// Dotted path (`backup.keep_last`, `libraries.*.database`)
+    pub path: &'static str,
+    pub kind: ValueKind,
+    /// Required keys can be changed but not unset
+    pub required: bool,
+}
+
+const fn key(path: &'static str, kind: ValueKind, required: bool) -> ConfigKey {
+    ConfigKey {
+        path,
+        kind,
+        required,
+    }
+}
+
+/// Every key accepted by `config set` and `config unset`.
+pub const CONFIG_KEYS: &[ConfigKey] = &[
+    key("database", ValueKind::Path, true),
+    key("language", ValueKind::Language, true),
+    key("backup.keep_last", ValueKind::Count, false),
+    key("backup.keep_daily", ValueKind::Count, false),
+    key("backup.keep_weekly", ValueKind::Count, false),
+    key("backup.format", ValueKind::BackupFormat, false),
+    key("active_library", ValueKind::Library, false),
+    key("libraries.*.database", ValueKind::Path, true),
+    key("libraries.*.language", ValueKind::Language, false),
+    key("libraries.*.backup.keep_last", ValueKind::Count, false),
+    key("libraries.*.backup.keep_daily", ValueKind::Count, false),
+    key("libraries.*.backup.keep_weekly", ValueKind::Count, false),
+    key("libraries.*.backup.format", ValueKind::BackupFormat, false),
+];
+
+/// Error returned by the `config get|set|unset` operations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    UnknownKey(String),
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: String,
+    },
+    RequiredKey(String),
+    /// The current file, or the file after the change, is not a valid config
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            ConfigError::UnknownKey(key) => tr_with("config.key.unknown", &[("key", key)]),
+            ConfigError::InvalidValue {
+                key,
+                value,
+                expected,
+            } => tr_with(
+                "config.key.invalid_value",
+                &[("key", key), ("value", value), ("expected", expected)],
+            ),
+            ConfigError::RequiredKey(key) => tr_with("config.key.required", &[("key", key)]),
+            ConfigError::Invalid(error) => tr_with("config.file.invalid", &[("error", error)]),
+        };
+        f.write_str(&msg)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Returns true if `key` matches the schema path `pattern`, or one of its
+/// sections when `section` is set.
+fn matches(pattern: &str, key: &str, section: bool) -> bool {
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let parts: Vec<&str> = key.split('.').collect();
+    let len_ok = if section {
+        parts.len() <= pattern.len()
+    } else {
+        parts.len() == pattern.len()
+    };
+    len_ok
+        && pattern
+            .iter()
+            .zip(&parts)
+            .all(|(p, s)| p == s || (*p == "*" && !s.is_empty()))
+}
+
+/// Looks up `key` in [`CONFIG_KEYS`].
+pub fn find_key(key: &str) -> Option<&'static ConfigKey> {
+    CONFIG_KEYS.iter().find(|k| matches(k.path, key, false))
+}
+
+/// Parses the text of a config file.
+pub fn parse_config(text: &str) -> Result<AppConfig, ConfigError> {
+    serde_yaml::from_str(text).map_err(|e| ConfigError::Invalid(e.to_string()))
+}
+
+/// Returns the value of `key` (a leaf or a section such as `backup`), with
+/// defaults applied; `None` if it is not set.
+pub fn get_value(cfg: &AppConfig, key: &str) -> Result<Option<Value>, ConfigError> {
+    if !CONFIG_KEYS.iter().any(|k| matches(k.path, key, true)) {
+        return Err(ConfigError::UnknownKey(key.to_string()));
+    }
+
+    let mut value = serde_yaml::to_value(cfg).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+    for part in key.split('.') {
+        match value.get(part) {
+            Some(v) => value = v.clone(),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+/// Validates `raw` for `key` and converts it to its YAML value.
+pub(crate) fn parse_value(
+    cfg: &AppConfig,
+    key: &ConfigKey,
+    name: &str,
+    raw: &str,
+) -> Result<Value, ConfigError> {
+    let invalid = |expected: String| ConfigError::InvalidValue {
+        key: name.to_string(),
+        value: raw.to_string(),
+        expected,
+    };
+    match key.kind {
+        ValueKind::Path if !raw.trim().is_empty() => Ok(Value::String(raw.to_string())),
+        ValueKind::Path => Err(invalid(tr("config.expected.path"))),
+        ValueKind::Language if is_language_available(raw) => {
+            Ok(Value::String(normalize_locale_code(raw)))
+        }
+        ValueKind::Language => Err(invalid(
+            available_languages()
+                .into_iter()
+                .map(|l| l.code)
+                .collect::<Vec<_>>()
+                .join(", "),
+        )),
+        ValueKind::Count => raw
+            .parse::<usize>()
+            .map(|n| Value::Number(n.into()))
+            .map_err(|_| invalid(tr("config.expected.count"))),
+        ValueKind::BackupFormat => raw
+            .parse::<BackupFormat>()
+            .map(|f| Value::String(f.ext().to_string()))
+            .map_err(|_| invalid(BackupFormat::NAMES.join(", "))),
+        ValueKind::Library if cfg.libraries.contains_key(raw) => Ok(Value::String(raw.to_string())),
+        ValueKind::Library => {
+            let names: Vec<&str> = cfg.libraries.keys().map(String::as_str).collect();
+            Err(invalid(names.join(", ")))
+        }
+    }
+}
+
+/// Returns `text` with `key` set to `raw`, keeping comments when the file
+/// layout allows it.
+pub fn set_value(text: &str, key: &str, raw: &str) -> Result<String, ConfigError> {
+    let schema = find_key(key).ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+    let cfg = parse_config(text)?;
+    let value = parse_value(&cfg, schema, key, raw)?;
+    let path: Vec<&str> = key.split('.').collect();
+
+    // La modifica riga per riga è accettata solo se rilegge il valore impostato
+    let scalar = serde_yaml::to_string(&value).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+    let edited = yaml_edit::set_scalar(text, &path, scalar.trim_end())
+        .filter(|t| parse_config(t).is_ok_and(|c| get_value(&c, key) == Ok(Some(value.clone()))))
+        .map_or_else(|| rewrite(text, |root| insert_path(root, &path, value)), Ok)?;
+    parse_config(&edited)?;
+    Ok(edited)
+}
+
+/// Returns `text` without `key`, so that its default applies again.
+pub fn unset_value(text: &str, key: &str) -> Result<String, ConfigError> {
+    let schema = find_key(key).ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+    if schema.required {
+        return Err(ConfigError::RequiredKey(key.to_string()));
+    }
+    parse_config(text)?;
+    let path: Vec<&str> = key.split('.').collect();
+
+    let edited = yaml_edit::remove_key(text, &path)
+        .filter(|t| {
+            parse_config(t).is_ok()
+                && serde_yaml::from_str::<Value>(t).is_ok_and(|v| !is_present(&v, &path))
+        })
+        .map_or_else(|| rewrite(text, |root| remove_path(root, &path)), Ok)?;
+    parse_config(&edited)?;
+    Ok(edited)
+}
+
+/// Returns true if `path` is written in `root` (defaults not applied).
+pub(crate) fn is_present(root: &Value, path: &[&str]) -> bool {
+    let mut node = root;
+    for part in path {
+        match node.get(part) {
+            Some(v) => node = v,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Full rewrite through `serde_yaml::Value` (comments are lost).
+pub(crate) fn rewrite(text: &str, change: impl FnOnce(&mut Value)) -> Result<String, ConfigError> {
+    let mut root: Value =
+        serde_yaml::from_str(text).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+    change(&mut root);
+    serde_yaml::to_string(&root).map_err(|e| ConfigError::Invalid(e.to_string()))
+}
+
+pub(crate) fn insert_path(root: &mut Value, path: &[&str], value: Value) {
+    let mut node = root;
+    for part in &path[..path.len() - 1] {
+        if !node.is_mapping() {
+            *node = Value::Mapping(Mapping::new());
+        }
+        node = node
+            .as_mapping_mut()
+            .expect("mapping")
+            .entry(Value::String(part.to_string()))
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+    }
+    if let Some(map) = node.as_mapping_mut() {
+        map.insert(Value::String(path[path.len() - 1].to_string()), value);
+    }
+}
+
+/// Removes `path`, then the parent mappings left empty.
+fn remove_path(root: &mut Value, path: &[&str]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let Some(map) = root.as_mapping_mut() else {
+        return;
+    };
+    if parents.is_empty() {
+        map.remove(*last);
+        return;
+    }
+    if let Some(child) = map.get_mut(parents[0]) {
+        let mut sub_path = parents[1..].to_vec();
+        sub_path.push(last);
+        remove_path(child, &sub_path);
+        if child.as_mapping().is_some_and(Mapping::is_empty) {
+            map.remove(parents[0]);
+        }
+    }
+}
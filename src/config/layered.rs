@@ -0,0 +1,268 @@
+//! Layered configuration: defaults, then `librius.conf` (with the selected
+//! named library), then `LIBRIUS_*` environment variables, then CLI flags.
+//!
+//! Every key listed in [`CONFIG_KEYS`] without a `*` can be overridden by an
+//! environment variable named `LIBRIUS_` + the key in upper case with dots
+//! replaced by `_` (`backup.keep_last` → `LIBRIUS_BACKUP_KEEP_LAST`).
+
+use crate::config::keys::{CONFIG_KEYS, ConfigError, insert_path, is_present, parse_value};
+use crate::config::load_config::with_defaults;
+use crate::config::{AppConfig, DEFAULT_LIBRARY, LibraryError, config_file_path};
+use crate::i18n::{tr, tr_with};
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// Legacy variable, accepted as an alias of `LIBRIUS_DATABASE`.
+pub const LEGACY_DB_ENV: &str = "LIBRIUS_DB_PATH";
+
+/// Where an effective value comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File,
+    /// Override of the named library
+    Library(String),
+    /// Environment variable
+    Env(String),
+    /// Command-line flag
+    Cli(String),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            ConfigSource::Default => tr("config.source.default"),
+            ConfigSource::File => tr("config.source.file"),
+            ConfigSource::Library(name) => tr_with("config.source.library", &[("name", name)]),
+            ConfigSource::Env(var) => tr_with("config.source.env", &[("var", var)]),
+            ConfigSource::Cli(flag) => tr_with("config.source.cli", &[("flag", flag)]),
+        };
+        f.write_str(&msg)
+    }
+}
+
+/// An override that was ignored. Kept unformatted, so that it is translated
+/// in the language resolved with the configuration itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigWarning {
+    /// `active_library` in the file names a missing library
+    UnknownLibrary(LibraryError),
+    /// Environment variable with an invalid value
+    InvalidOverride(ConfigError),
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigWarning::UnknownLibrary(e) => e.fmt(f),
+            ConfigWarning::InvalidOverride(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Values given on the command line (`--config`, `--db`, `--library`, `--lang`).
+#[derive(Debug, Clone, Default)]
+pub struct CliOverrides {
+    pub config_file: Option<PathBuf>,
+    pub database: Option<String>,
+    pub library: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Raw input of every layer.
+#[derive(Debug, Clone, Default)]
+pub struct Layers {
+    /// Contents of the config file (`None` if missing)
+    pub file: Option<Value>,
+    /// `LIBRIUS_*` environment variables
+    pub env: HashMap<String, String>,
+    pub cli: CliOverrides,
+}
+
+/// The configuration to run with, and where each value comes from.
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    pub config: AppConfig,
+    pub config_file: PathBuf,
+    pub config_file_source: ConfigSource,
+    /// `(key, source)` for every key of [`effective_keys`]
+    pub sources: Vec<(&'static str, ConfigSource)>,
+    pub warnings: Vec<ConfigWarning>,
+}
+
+impl EffectiveConfig {
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, s)| s.clone())
+            .unwrap_or(ConfigSource::Default)
+    }
+
+    fn set_source(&mut self, key: &str, source: ConfigSource) {
+        if let Some(entry) = self.sources.iter_mut().find(|(k, _)| *k == key) {
+            entry.1 = source;
+        }
+    }
+}
+
+/// Keys that have an effective value (the ones not specific to a library).
+pub fn effective_keys() -> impl Iterator<Item = &'static str> {
+    CONFIG_KEYS
+        .iter()
+        .map(|k| k.path)
+        .filter(|p| !p.contains('*'))
+}
+
+/// Name of the environment variable overriding `key`.
+pub fn env_var_for(key: &str) -> String {
+    format!("LIBRIUS_{}", key.to_uppercase().replace('.', "_"))
+}
+
+/// Reads the config file and the `LIBRIUS_*` environment variables. A missing
+/// file leaves the defaults in place and is not created (`config init` does).
+pub fn read_layers(cli: CliOverrides) -> Result<Layers, Box<dyn Error>> {
+    if let Some(path) = &cli.config_file {
+        crate::config::set_config_file(Some(path.clone()));
+    }
+    let path = config_file_path();
+    let file = if path.exists() {
+        let text = fs::read_to_string(path)?;
+        let file: Value =
+            serde_yaml::from_str(&text).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        Some(file)
+    } else {
+        None
+    };
+    let env = std::env::vars()
+        .filter(|(k, _)| k.starts_with("LIBRIUS_"))
+        .collect();
+
+    Ok(Layers { file, env, cli })
+}
+
+/// Applies the layers in order and returns the effective configuration.
+///
+/// An unknown library selected with `--library` or `LIBRIUS_ACTIVE_LIBRARY` is
+/// an error; one selected by the file only produces a warning.
+pub fn resolve(layers: &Layers) -> Result<EffectiveConfig, Box<dyn Error>> {
+    let config_file_source = if layers.cli.config_file.is_some() {
+        ConfigSource::Cli("--config".to_string())
+    } else if layers.env.contains_key("LIBRIUS_CONFIG") {
+        ConfigSource::Env("LIBRIUS_CONFIG".to_string())
+    } else {
+        ConfigSource::Default
+    };
+
+    // 1️⃣ Predefiniti + file di configurazione
+    let file = layers.file.clone().unwrap_or(Value::Null);
+    let file_config = with_defaults(&file).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+
+    let mut effective = EffectiveConfig {
+        config: file_config.clone(),
+        config_file: config_file_path(),
+        config_file_source,
+        sources: effective_keys()
+            .map(|key| {
+                let path: Vec<&str> = key.split('.').collect();
+                let source = if is_present(&file, &path) {
+                    ConfigSource::File
+                } else {
+                    ConfigSource::Default
+                };
+                (key, source)
+            })
+            .collect(),
+        warnings: Vec::new(),
+    };
+
+    // 2️⃣ Libreria con nome: --library, poi LIBRIUS_ACTIVE_LIBRARY, poi il file
+    let env_library = env_var_for("active_library");
+    let (library, library_source) = if let Some(name) = &layers.cli.library {
+        (name.clone(), ConfigSource::Cli("--library".to_string()))
+    } else if let Some(name) = layers.env.get(&env_library) {
+        (name.clone(), ConfigSource::Env(env_library))
+    } else {
+        let source = effective.source("active_library");
+        (file_config.library_name().to_string(), source)
+    };
+
+    match file_config.for_library(Some(&library)) {
+        Ok(cfg) => effective.config = cfg,
+        Err(e) if library_source == ConfigSource::File => {
+            effective.warnings.push(ConfigWarning::UnknownLibrary(e));
+            effective.config = file_config.for_library(Some(DEFAULT_LIBRARY))?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    if library != DEFAULT_LIBRARY && effective.config.active_library.is_some() {
+        effective.set_source("active_library", library_source);
+        if let Some(profile) = file_config.libraries.get(&library) {
+            let source = ConfigSource::Library(library.clone());
+            effective.set_source("database", source.clone());
+            if profile.language.is_some() {
+                effective.set_source("language", source.clone());
+            }
+            if profile.backup.is_some() {
+                for key in [
+                    "backup.keep_last",
+                    "backup.keep_daily",
+                    "backup.keep_weekly",
+                    "backup.format",
+                ] {
+                    effective.set_source(key, source.clone());
+                }
+            }
+        }
+    } else if library == DEFAULT_LIBRARY && library_source != ConfigSource::File {
+        effective.set_source("active_library", library_source);
+    }
+
+    // 3️⃣ Variabili d'ambiente LIBRIUS_*
+    let mut value = serde_yaml::to_value(&effective.config)?;
+    let mut env_sources = Vec::new();
+    for key in effective_keys().filter(|k| *k != "active_library") {
+        let mut var = env_var_for(key);
+        let mut raw = layers.env.get(&var);
+        if raw.is_none() && key == "database" {
+            var = LEGACY_DB_ENV.to_string();
+            raw = layers.env.get(&var);
+        }
+        let Some(raw) = raw else {
+            continue;
+        };
+        let schema = CONFIG_KEYS
+            .iter()
+            .find(|k| k.path == key)
+            .expect("schema key");
+        match parse_value(&file_config, schema, &var, raw) {
+            Ok(v) => {
+                let path: Vec<&str> = key.split('.').collect();
+                insert_path(&mut value, &path, v);
+                env_sources.push((key, ConfigSource::Env(var)));
+            }
+            Err(e) => effective.warnings.push(ConfigWarning::InvalidOverride(e)),
+        }
+    }
+    effective.config =
+        serde_yaml::from_value(value).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+    for (key, source) in env_sources {
+        effective.set_source(key, source);
+    }
+
+    // 4️⃣ Opzioni da riga di comando
+    if let Some(db) = &layers.cli.database {
+        effective.config.database = db.clone();
+        effective.set_source("database", ConfigSource::Cli("--db".to_string()));
+    }
+    if let Some(lang) = &layers.cli.language {
+        effective.config.language = lang.clone();
+        effective.set_source("language", ConfigSource::Cli("--lang".to_string()));
+    }
+
+    Ok(effective)
+}
@@ -0,0 +1,254 @@
+//! Named libraries (profiles) stored in `librius.conf`.
+//!
+//! The top-level `database` / `language` / `backup` keys form the implicit
+//! [`DEFAULT_LIBRARY`]; every entry of `libraries` is an additional profile
+//! that overrides them. `active_library` selects the profile used when
+//! `--library` is not given.
+
+use crate::config::keys::{parse_config, rewrite};
+use crate::config::{AppConfig, BackupConfig, ConfigError, yaml_edit};
+use crate::i18n::tr_with;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::fmt;
+
+/// Name of the library described by the top-level config keys.
+pub const DEFAULT_LIBRARY: &str = "default";
+
+/// A named library: its own database and optional overrides of the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryProfile {
+    /// Path to the SQLite database of this library
+    pub database: String,
+    /// Interface language (falls back to the top-level `language`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Backup settings (fall back to the top-level `backup`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<BackupConfig>,
+}
+
+/// Error returned by the library management operations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LibraryError {
+    NotFound(String),
+    AlreadyExists(String),
+    /// Empty name or characters other than letters, digits, `-` and `_`
+    InvalidName(String),
+    /// The default library cannot be added, removed or renamed
+    Reserved,
+}
+
+impl fmt::Display for LibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            LibraryError::NotFound(name) => tr_with("library.error.not_found", &[("name", name)]),
+            LibraryError::AlreadyExists(name) => tr_with("library.error.exists", &[("name", name)]),
+            LibraryError::InvalidName(name) => {
+                tr_with("library.error.invalid_name", &[("name", name)])
+            }
+            LibraryError::Reserved => {
+                tr_with("library.error.reserved", &[("name", DEFAULT_LIBRARY)])
+            }
+        };
+        f.write_str(&msg)
+    }
+}
+
+impl std::error::Error for LibraryError {}
+
+/// Checks that `name` can be used for a new library.
+fn validate_name(name: &str) -> Result<(), LibraryError> {
+    if name == DEFAULT_LIBRARY {
+        return Err(LibraryError::Reserved);
+    }
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(LibraryError::InvalidName(name.to_string()))
+    }
+}
+
+impl AppConfig {
+    /// Name of the active library (`default` when none is selected).
+    pub fn library_name(&self) -> &str {
+        self.active_library.as_deref().unwrap_or(DEFAULT_LIBRARY)
+    }
+
+    /// Returns the configuration to run with: `name` (or the active library
+    /// when `None`) with its overrides applied on top of the defaults.
+    pub fn for_library(&self, name: Option<&str>) -> Result<AppConfig, LibraryError> {
+        let name = name.unwrap_or_else(|| self.library_name());
+        let mut effective = self.clone();
+
+        if name == DEFAULT_LIBRARY {
+            effective.active_library = None;
+            return Ok(effective);
+        }
+
+        let profile = self
+            .libraries
+            .get(name)
+            .ok_or_else(|| LibraryError::NotFound(name.to_string()))?;
+        effective.database = profile.database.clone();
+        if let Some(language) = &profile.language {
+            effective.language = language.clone();
+        }
+        if let Some(backup) = &profile.backup {
+            effective.backup = backup.clone();
+        }
+        effective.active_library = Some(name.to_string());
+        Ok(effective)
+    }
+
+    pub fn add_library(&mut self, name: &str, profile: LibraryProfile) -> Result<(), LibraryError> {
+        validate_name(name)?;
+        if self.libraries.contains_key(name) {
+            return Err(LibraryError::AlreadyExists(name.to_string()));
+        }
+        self.libraries.insert(name.to_string(), profile);
+        Ok(())
+    }
+
+    /// Removes a profile (the database file is left on disk). If it was the
+    /// active one, the default library becomes active again.
+    pub fn remove_library(&mut self, name: &str) -> Result<LibraryProfile, LibraryError> {
+        if name == DEFAULT_LIBRARY {
+            return Err(LibraryError::Reserved);
+        }
+        let profile = self
+            .libraries
+            .remove(name)
+            .ok_or_else(|| LibraryError::NotFound(name.to_string()))?;
+        if self.active_library.as_deref() == Some(name) {
+            self.active_library = None;
+        }
+        Ok(profile)
+    }
+
+    pub fn rename_library(&mut self, old: &str, new: &str) -> Result<(), LibraryError> {
+        if old == DEFAULT_LIBRARY {
+            return Err(LibraryError::Reserved);
+        }
+        if !self.libraries.contains_key(old) {
+            return Err(LibraryError::NotFound(old.to_string()));
+        }
+        validate_name(new)?;
+        if self.libraries.contains_key(new) {
+            return Err(LibraryError::AlreadyExists(new.to_string()));
+        }
+        if let Some(profile) = self.libraries.remove(old) {
+            self.libraries.insert(new.to_string(), profile);
+        }
+        if self.active_library.as_deref() == Some(old) {
+            self.active_library = Some(new.to_string());
+        }
+        Ok(())
+    }
+
+    /// Makes `name` the library used when `--library` is not given.
+    pub fn use_library(&mut self, name: &str) -> Result<(), LibraryError> {
+        if name == DEFAULT_LIBRARY {
+            self.active_library = None;
+            return Ok(());
+        }
+        if !self.libraries.contains_key(name) {
+            return Err(LibraryError::NotFound(name.to_string()));
+        }
+        self.active_library = Some(name.to_string());
+        Ok(())
+    }
+}
+
+/// Returns `text` with the `libraries` and `active_library` keys of `cfg`.
+///
+/// Only the profiles that changed are edited through [`yaml_edit`], so the
+/// comments and key order of the rest of the file are kept. When the layout
+/// is not understood, the two keys are rewritten through `serde_yaml::Value`.
+pub fn write_libraries(text: &str, cfg: &AppConfig) -> Result<String, ConfigError> {
+    let before = parse_config(text)?;
+    let edited = edit_libraries(text, &before, cfg).filter(|t| {
+        parse_config(t)
+            .is_ok_and(|c| c.libraries == cfg.libraries && c.active_library == cfg.active_library)
+    });
+    if let Some(edited) = edited {
+        return Ok(edited);
+    }
+
+    let libraries =
+        serde_yaml::to_value(&cfg.libraries).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+    let edited = rewrite(text, |root| {
+        if let Some(map) = root.as_mapping_mut() {
+            map.remove("libraries");
+            map.remove("active_library");
+            if !cfg.libraries.is_empty() {
+                map.insert("libraries".into(), libraries);
+            }
+            if let Some(name) = &cfg.active_library {
+                map.insert("active_library".into(), name.as_str().into());
+            }
+        }
+    })?;
+    parse_config(&edited)?;
+    Ok(edited)
+}
+
+/// Line-based edit from `before` to `after`; `None` if [`yaml_edit`] cannot
+/// apply it.
+fn edit_libraries(text: &str, before: &AppConfig, after: &AppConfig) -> Option<String> {
+    let mut text = text.to_string();
+    // Un profilo modificato o rinominato si rimuove e si riscrive
+    for (name, profile) in &before.libraries {
+        if after.libraries.get(name) != Some(profile) {
+            text = yaml_edit::remove_key(&text, &["libraries", name])?;
+        }
+    }
+    for (name, profile) in &after.libraries {
+        if before.libraries.get(name) == Some(profile) {
+            continue;
+        }
+        let mut leaves = Vec::new();
+        collect_leaves(
+            &serde_yaml::to_value(profile).ok()?,
+            &mut Vec::new(),
+            &mut leaves,
+        );
+        for (path, scalar) in leaves {
+            let mut full = vec!["libraries", name.as_str()];
+            full.extend(path.iter().map(String::as_str));
+            text = yaml_edit::set_scalar(&text, &full, &scalar)?;
+        }
+    }
+    if before.active_library != after.active_library {
+        text = match &after.active_library {
+            Some(name) => {
+                let scalar = serde_yaml::to_string(&Value::from(name.as_str())).ok()?;
+                yaml_edit::set_scalar(&text, &["active_library"], scalar.trim_end())?
+            }
+            None => yaml_edit::remove_key(&text, &["active_library"])?,
+        };
+    }
+    Some(text)
+}
+
+/// Scalar values of `value` with their key paths, in document order.
+fn collect_leaves(value: &Value, path: &mut Vec<String>, out: &mut Vec<(Vec<String>, String)>) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, child) in map {
+                path.push(key.as_str().unwrap_or_default().to_string());
+                collect_leaves(child, path, out);
+                path.pop();
+            }
+        }
+        scalar => {
+            if let Ok(text) = serde_yaml::to_string(scalar) {
+                out.push((path.clone(), text.trim_end().to_string()));
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
+use crate::config::library::LibraryProfile;
+use crate::config::migrate_config::latest_config_version;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::collections::BTreeMap;
 use std::io::Write;
+use std::path::Path;
+use std::sync::RwLock;
 use std::{env, fs, path::PathBuf};
 
 /// Application configuration stored in YAML format.
@@ -9,31 +15,160 @@ use std::{env, fs, path::PathBuf};
 /// simple YAML file placed inside the per-user configuration directory.
 ///
 /// Fields:
+/// - `config_version`: layout version of the file (see [`crate::config::migrate_config`]).
 /// - `database`: filesystem path to the SQLite database used by Librius.
-#[derive(Debug, Serialize, Deserialize)]
+/// - `language`: interface language code.
+/// - `backup`: retention policy applied by `librius backup prune`.
+/// - `active_library` / `libraries`: named profiles (see [`crate::config::library`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// Last config migration applied (0 for files older than the field)
+    #[serde(default)]
+    pub config_version: u32,
     /// Path to the local SQLite database
     pub database: String,
     pub language: String,
+    /// Backup retention settings (missing in older config files)
+    #[serde(default)]
+    pub backup: BackupConfig,
+    /// Library used when `--library` is not given (`None` = default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_library: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub libraries: BTreeMap<String, LibraryProfile>,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         let database = default_db_path();
         Self {
+            config_version: latest_config_version(),
             database: database.to_string_lossy().to_string(),
             language: "en".to_string(),
+            backup: BackupConfig::default(),
+            active_library: None,
+            libraries: BTreeMap::new(),
         }
     }
 }
 
+/// Retention policy for the `backups` directory.
+///
+/// A backup survives `backup prune` if it matches at least one rule:
+/// - `keep_last`: it is one of the N most recent backups;
+/// - `keep_daily`: it is the newest backup of one of the last D days that have backups;
+/// - `keep_weekly`: it is the newest backup of one of the last W ISO weeks that have backups.
+///
+/// `format` is the archive format used by `librius backup` when `--format`
+/// is not given on the command line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    pub keep_last: usize,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+    pub format: BackupFormat,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            keep_last: 5,
+            keep_daily: 7,
+            keep_weekly: 4,
+            format: BackupFormat::Sqlite,
+        }
+    }
+}
+
+/// Backup archive format, identical on every platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackupFormat {
+    /// Plain copy of the SQLite file
+    #[serde(rename = "sqlite")]
+    Sqlite,
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+    #[serde(rename = "tar.zst")]
+    TarZst,
+}
+
+impl BackupFormat {
+    /// Every accepted value, as written in the config file and on the CLI.
+    pub const NAMES: [&'static str; 4] = ["sqlite", "zip", "tar.gz", "tar.zst"];
+
+    /// File extension (and config/CLI name) of the format.
+    pub fn ext(&self) -> &'static str {
+        match self {
+            BackupFormat::Sqlite => "sqlite",
+            BackupFormat::Zip => "zip",
+            BackupFormat::TarGz => "tar.gz",
+            BackupFormat::TarZst => "tar.zst",
+        }
+    }
+
+    /// Returns true for compressed archive formats.
+    pub fn is_archive(&self) -> bool {
+        *self != BackupFormat::Sqlite
+    }
+}
+
+impl std::str::FromStr for BackupFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sqlite" => Ok(BackupFormat::Sqlite),
+            "zip" => Ok(BackupFormat::Zip),
+            "tar.gz" => Ok(BackupFormat::TarGz),
+            "tar.zst" => Ok(BackupFormat::TarZst),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+impl BackupConfig {
+    /// Returns true when every rule is disabled (nothing would be kept).
+    pub fn is_empty(&self) -> bool {
+        self.keep_last == 0 && self.keep_daily == 0 && self.keep_weekly == 0
+    }
+}
+
+/// Config file chosen with `--config` (see [`set_config_file`]).
+static CONFIG_FILE_OVERRIDE: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+/// Uses `path` as configuration file instead of the default one (`--config`).
+pub fn set_config_file(path: Option<PathBuf>) {
+    *CONFIG_FILE_OVERRIDE.write().unwrap() = path;
+}
+
+/// Config file given with `--config`, or else with `LIBRIUS_CONFIG`.
+fn custom_config_file() -> Option<PathBuf> {
+    CONFIG_FILE_OVERRIDE.read().unwrap().clone().or_else(|| {
+        env::var("LIBRIUS_CONFIG")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from)
+    })
+}
+
 /// Return the directory used to store Librius configuration and database.
 ///
+/// When a custom config file is given (`--config` or `LIBRIUS_CONFIG`) this is
+/// the directory containing it, so that nothing is written to the default one.
 /// On Unix-like systems this is `$HOME/.librius`. On Windows the function
 /// attempts to use `%APPDATA%/Roaming/librius`. If neither environment
 /// variable is available, the function falls back to a `.librius` folder in
 /// the current working directory.
 fn config_dir() -> PathBuf {
+    if let Some(file) = custom_config_file() {
+        return match file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+    }
     if cfg!(target_os = "windows") {
         if let Ok(appdata) = env::var("APPDATA") {
             let mut path = PathBuf::from(appdata);
@@ -49,13 +184,33 @@ fn config_dir() -> PathBuf {
     PathBuf::from(".librius")
 }
 
-/// Construct the default path for the SQLite database file.
+/// Construct the default path for the SQLite database file
+/// (`librius.sqlite` inside the configuration directory).
 ///
-/// The function ensures the configuration directory exists and returns the
-/// full path to `librius.sqlite` inside it.
+/// Nothing is created here: the directory is made when the database is
+/// actually opened there (see [`crate::db::open_db`]).
 fn default_db_path() -> PathBuf {
     let mut path = config_dir();
-    fs::create_dir_all(&path).ok();
+    path.push("librius.sqlite");
+    path
+}
+
+/// Directory with the user-provided locale files (`locales/` in the
+/// configuration directory).
+pub fn locales_dir() -> PathBuf {
+    let mut path = config_dir();
+    path.push("locales");
+    path
+}
+
+/// Default database path of the named library `name`.
+///
+/// Each library gets its own directory (`libraries/<name>/librius.sqlite`), so
+/// that its `backups` folder is not shared with the other libraries.
+pub fn library_db_path(name: &str) -> PathBuf {
+    let mut path = config_dir();
+    path.push("libraries");
+    path.push(name);
     path.push("librius.sqlite");
     path
 }
@@ -63,13 +218,17 @@ fn default_db_path() -> PathBuf {
 /// Construct the path to the YAML configuration file used by the app.
 ///
 /// The returned path points to `librius.conf` inside the configuration
-/// directory. The function also attempts to create the directory if it does
-/// not already exist.
+/// directory, or to the file given with `--config` / `LIBRIUS_CONFIG`. The
+/// directory is only created when the file is written.
 pub fn config_file_path() -> PathBuf {
     let mut path = config_dir();
-    fs::create_dir_all(&path).ok();
-    path.push("librius.conf");
-    path
+    match custom_config_file() {
+        Some(file) => file,
+        None => {
+            path.push("librius.conf");
+            path
+        }
+    }
 }
 
 /// Load the YAML configuration from disk, or create a default one if not
@@ -92,16 +251,68 @@ pub fn config_file_path() -> PathBuf {
 pub fn load_or_init() -> Result<AppConfig, Box<dyn std::error::Error>> {
     let config_path = config_file_path();
 
-    if config_path.exists() {
-        let contents = fs::read_to_string(&config_path)?;
-        let cfg: AppConfig = serde_yaml::from_str(&contents)?;
-        Ok(cfg)
-    } else {
-        let cfg = AppConfig::default();
-        let yaml = serde_yaml::to_string(&cfg)?;
+    if !config_path.exists() {
+        let yaml = serde_yaml::to_string(&AppConfig::default())?;
+        create_parent_dir(&config_path)?;
         let mut file = fs::File::create(&config_path)?;
         file.write_all(yaml.as_bytes())?;
-        Ok(cfg)
+    }
+    load_config()
+}
+
+/// Like [`load_or_init`], but read-only: a missing configuration file gives
+/// the defaults and is not created.
+pub fn load_config() -> Result<AppConfig, Box<dyn std::error::Error>> {
+    let config_path = config_file_path();
+    if !config_path.exists() {
+        return Ok(AppConfig::default());
+    }
+    let contents = fs::read_to_string(&config_path)?;
+    let file: Value = serde_yaml::from_str(&contents)?;
+    Ok(with_defaults(&file)?)
+}
+
+/// Builds an `AppConfig` from the contents of a config file, taking the
+/// missing keys from [`AppConfig::default`].
+pub fn with_defaults(file: &Value) -> Result<AppConfig, serde_yaml::Error> {
+    let mut value = serde_yaml::to_value(AppConfig::default())?;
+    if file.is_mapping() {
+        merge(&mut value, file);
+    }
+    serde_yaml::from_value(value)
+}
+
+/// Merges `overlay` into `base`, recursively for mappings.
+fn merge(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (k, v) in overlay {
+                match base.get_mut(k) {
+                    Some(existing) if existing.is_mapping() && v.is_mapping() => merge(existing, v),
+                    _ => {
+                        base.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// Writes `cfg` to the configuration file, replacing its contents.
+pub fn save_config(cfg: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let yaml = serde_yaml::to_string(cfg)?;
+    let path = config_file_path();
+    create_parent_dir(&path)?;
+    fs::write(path, yaml)?;
+    Ok(())
+}
+
+/// Creates the directory that will contain `path`, if missing.
+fn create_parent_dir(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+        _ => Ok(()),
     }
 }
 
@@ -1,55 +1,466 @@
-use crate::i18n::tr;
-use crate::utils::icons::ERR;
-use crate::utils::{print_ok, write_log};
+//! Versioned migrations of `librius.conf`.
+//!
+//! Mirrors [`crate::db::migrations`]: every step has a version and the file
+//! records the last one applied in `config_version` (files written before the
+//! field existed are version 0). Steps work on the parsed YAML mapping and can
+//! add, rename, move or transform keys; they must be harmless on files that
+//! already have the newer layout, since unversioned files may come from any
+//! older release.
+//!
+//! The previous contents are kept in `librius.conf.bak`, and the new file is
+//! written line by line through [`crate::config::yaml_edit`] when possible,
+//! so that comments survive the migration.
+
+use crate::config::{BackupConfig, yaml_edit};
+use crate::i18n::{normalize_locale_code, tr, tr_with};
+use crate::utils::{is_verbose, print_info, print_ok, write_log};
 use rusqlite::Connection;
 use serde_yaml::{Mapping, Value};
+use std::fmt;
 use std::fs;
-use std::io::{self};
-use std::path::Path;
-
-/// Checks and updates the configuration file structure if needed.
-pub fn migrate_config(conn: &Connection, conf_path: &Path) -> io::Result<()> {
-    let content = fs::read_to_string(conf_path)?;
-    let mut yaml: Value = serde_yaml::from_str(&content)
-        .unwrap_or_else(|_| panic!("{}{}", ERR, &tr("app.yaml.parse_error")));
-
-    // Ensure we have a Mapping; if not, replace yaml with an empty mapping
-    let map: &mut Mapping = match yaml.as_mapping_mut() {
-        Some(m) => m,
-        None => {
-            yaml = Value::Mapping(Mapping::new());
-            // Now it's guaranteed to be a mapping
-            let mapping_error_msg = tr("app.mapping.just_created");
-            yaml.as_mapping_mut().expect(&mapping_error_msg)
+use std::io;
+use std::path::{Path, PathBuf};
+
+type Step = fn(&mut Mapping);
+
+/// A single config file migration.
+pub struct ConfigMigration {
+    /// `config_version` reached once this step is applied (1-based, contiguous)
+    pub version: u32,
+    pub name: &'static str,
+    /// i18n key describing the change
+    pub description: &'static str,
+    pub apply: Step,
+}
+
+/// All config migrations, in version order.
+pub const CONFIG_MIGRATIONS: &[ConfigMigration] = &[
+    ConfigMigration {
+        version: 1,
+        name: "language",
+        description: "config.migration.desc.001",
+        apply: step_001_add_language,
+    },
+    ConfigMigration {
+        version: 2,
+        name: "language_code",
+        description: "config.migration.desc.002",
+        apply: step_002_normalize_language,
+    },
+    ConfigMigration {
+        version: 3,
+        name: "backup",
+        description: "config.migration.desc.003",
+        apply: step_003_add_backup,
+    },
+    ConfigMigration {
+        version: 4,
+        name: "backup_format",
+        description: "config.migration.desc.004",
+        apply: step_004_backup_format,
+    },
+];
+
+/// Newest `config_version` supported by this binary.
+pub fn latest_config_version() -> u32 {
+    CONFIG_MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Errors raised while migrating the config file.
+#[derive(Debug)]
+pub enum ConfigMigrationError {
+    Io(io::Error),
+    /// The file is not valid YAML
+    Parse(String),
+    /// The file is valid YAML but not a `key: value` mapping
+    NotAMapping,
+    /// The file was written by a newer Librius
+    TooNew {
+        found: u32,
+        supported: u32,
+    },
+}
+
+impl fmt::Display for ConfigMigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            ConfigMigrationError::Io(e) => e.to_string(),
+            ConfigMigrationError::Parse(error) => {
+                tr_with("config.migrate.parse_error", &[("error", error)])
+            }
+            ConfigMigrationError::NotAMapping => tr("config.migrate.not_mapping"),
+            ConfigMigrationError::TooNew { found, supported } => tr_with(
+                "config.migrate.too_new",
+                &[
+                    ("found", &found.to_string()),
+                    ("supported", &supported.to_string()),
+                ],
+            ),
+        };
+        f.write_str(&msg)
+    }
+}
+
+impl std::error::Error for ConfigMigrationError {}
+
+impl From<io::Error> for ConfigMigrationError {
+    fn from(e: io::Error) -> Self {
+        ConfigMigrationError::Io(e)
+    }
+}
+
+/// Text of a config file after the pending migrations.
+pub struct MigratedConfig {
+    pub text: String,
+    /// `config_version` of the original text
+    pub from: u32,
+    pub applied: Vec<&'static ConfigMigration>,
+}
+
+/// Outcome of [`migrate_config_file`].
+pub enum ConfigMigrationResult {
+    /// Missing file, or already at the latest version
+    UpToDate,
+    Applied {
+        from: u32,
+        to: u32,
+        applied: Vec<&'static ConfigMigration>,
+        /// Copy of the file before the migration
+        backup: PathBuf,
+    },
+}
+
+/// Version recorded in a parsed config file (0 when missing).
+pub fn config_version(root: &Value) -> u32 {
+    root.get("config_version")
+        .and_then(Value::as_u64)
+        .map_or(0, |v| v as u32)
+}
+
+/// Applies the pending migrations to a parsed config file and updates its
+/// `config_version`. Returns the version found and the steps applied.
+pub fn migrate_value(
+    root: &mut Value,
+) -> Result<(u32, Vec<&'static ConfigMigration>), ConfigMigrationError> {
+    if root.is_null() {
+        *root = Value::Mapping(Mapping::new());
+    }
+    let from = config_version(root);
+    let map = root
+        .as_mapping_mut()
+        .ok_or(ConfigMigrationError::NotAMapping)?;
+
+    let latest = latest_config_version();
+    if from > latest {
+        return Err(ConfigMigrationError::TooNew {
+            found: from,
+            supported: latest,
+        });
+    }
+
+    let applied: Vec<&'static ConfigMigration> = CONFIG_MIGRATIONS
+        .iter()
+        .filter(|m| m.version > from)
+        .collect();
+    for m in &applied {
+        (m.apply)(map);
+    }
+    if !applied.is_empty() {
+        map.insert(
+            Value::String("config_version".to_string()),
+            Value::Number(latest.into()),
+        );
+    }
+    Ok((from, applied))
+}
+
+/// Migrates the text of a config file; `None` if it is already up to date.
+pub fn migrate_text(text: &str) -> Result<Option<MigratedConfig>, ConfigMigrationError> {
+    let original: Value =
+        serde_yaml::from_str(text).map_err(|e| ConfigMigrationError::Parse(e.to_string()))?;
+    let mut migrated = original.clone();
+    let (from, applied) = migrate_value(&mut migrated)?;
+    if applied.is_empty() {
+        return Ok(None);
+    }
+
+    let text = match edit_in_place(text, &original, &migrated) {
+        Some(edited) => edited,
+        None => serde_yaml::to_string(&migrated)
+            .map_err(|e| ConfigMigrationError::Parse(e.to_string()))?,
+    };
+    Ok(Some(MigratedConfig {
+        text,
+        from,
+        applied,
+    }))
+}
+
+/// Migrates the config file at `path`, keeping the previous contents in
+/// `<file>.bak`. A missing file is left alone (it is created up to date).
+pub fn migrate_config_file(path: &Path) -> Result<ConfigMigrationResult, ConfigMigrationError> {
+    if !path.exists() {
+        return Ok(ConfigMigrationResult::UpToDate);
+    }
+    let content = fs::read_to_string(path)?;
+    let Some(migrated) = migrate_text(&content)? else {
+        return Ok(ConfigMigrationResult::UpToDate);
+    };
+
+    let backup = backup_path(path);
+    fs::write(&backup, &content)?;
+    fs::write(path, &migrated.text)?;
+
+    Ok(ConfigMigrationResult::Applied {
+        from: migrated.from,
+        to: latest_config_version(),
+        applied: migrated.applied,
+        backup,
+    })
+}
+
+/// Path of the copy kept before a migration (`librius.conf.bak`).
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Reports a migration of the config file and records it in the log.
+pub fn log_config_migration(conn: &Connection, result: &ConfigMigrationResult) {
+    let ConfigMigrationResult::Applied {
+        from,
+        to,
+        applied,
+        backup,
+    } = result
+    else {
+        print_ok(&tr("config.schema.verified"), is_verbose());
+        return;
+    };
+
+    for m in applied {
+        print_info(
+            &format!("config v{} ({}): {}", m.version, m.name, tr(m.description)),
+            is_verbose(),
+        );
+    }
+    let msg = tr_with(
+        "config.file.migrated",
+        &[
+            ("from", &from.to_string()),
+            ("to", &to.to_string()),
+            ("backup", &backup.display().to_string()),
+        ],
+    );
+    print_ok(&msg, true);
+    let names: Vec<&str> = applied.iter().map(|m| m.name).collect();
+    let _ = write_log(
+        conn,
+        "MIGRATE_CONFIG",
+        "CONFIG",
+        &format!("v{} -> v{} ({})", from, to, names.join(", ")),
+    );
+}
+
+// ------------------------------------------------------------
+// Operazioni sulle chiavi, usate dai passi di migrazione
+// ------------------------------------------------------------
+
+fn key(name: &str) -> Value {
+    Value::String(name.to_string())
+}
+
+/// Inserts `key` with `value` if missing. Returns true if it was added.
+pub fn insert_if_missing(map: &mut Mapping, name: &str, value: Value) -> bool {
+    if map.contains_key(name) {
+        return false;
+    }
+    map.insert(key(name), value);
+    true
+}
+
+/// Renames `from` to `to` in the same mapping, unless `to` already exists.
+pub fn rename_key(map: &mut Mapping, from: &str, to: &str) -> bool {
+    if map.contains_key(to) {
+        return false;
+    }
+    match map.remove(from) {
+        Some(value) => {
+            map.insert(key(to), value);
+            true
         }
+        None => false,
+    }
+}
+
+/// Moves the value at the dotted path `from` to the dotted path `to`,
+/// creating the missing sections. An existing value at `to` wins; the
+/// sections left empty by the move are removed.
+pub fn move_key(map: &mut Mapping, from: &str, to: &str) -> bool {
+    let to_path: Vec<&str> = to.split('.').collect();
+    if get_path(map, &to_path).is_some() {
+        return false;
+    }
+    let from_path: Vec<&str> = from.split('.').collect();
+    let Some(value) = take_path(map, &from_path) else {
+        return false;
     };
 
-    // Add missing keys with defaults
-    let migrated = insert_if_missing(map, "language", "en");
+    let (last, parents) = to_path.split_last().expect("non-empty path");
+    let mut node = map;
+    for part in parents {
+        let entry = node
+            .entry(key(part))
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+        if !entry.is_mapping() {
+            *entry = Value::Mapping(Mapping::new());
+        }
+        node = entry.as_mapping_mut().expect("mapping");
+    }
+    node.insert(key(last), value);
+    true
+}
 
-    let updated = serde_yaml::to_string(&yaml)
-        .unwrap_or_else(|_| panic!("{}{}", ERR, &tr("app.yaml.serialize_error")));
-    fs::write(conf_path, updated)?;
+/// Replaces the value at the dotted path `path` with `change(value)`.
+/// Returns true if the value changed.
+pub fn transform_key(map: &mut Mapping, path: &str, change: impl FnOnce(&Value) -> Value) -> bool {
+    let path: Vec<&str> = path.split('.').collect();
+    let (last, parents) = path.split_last().expect("non-empty path");
+    let mut node = map;
+    for part in parents {
+        match node.get_mut(*part).and_then(Value::as_mapping_mut) {
+            Some(child) => node = child,
+            None => return false,
+        }
+    }
+    let Some(value) = node.get_mut(*last) else {
+        return false;
+    };
+    let new = change(value);
+    if new == *value {
+        return false;
+    }
+    *value = new;
+    true
+}
 
-    if migrated {
-        // Log the config migration
-        write_log(
-            conn,
-            "MIGRATE_CONFIG",
-            "CONFIG",
-            &tr("config.file.migrated"),
-        )
-        .unwrap_or_else(|_| panic!("{}Could not write log message", ERR));
-        print_ok(&tr("config.file.migrated"), true);
+fn get_path<'a>(map: &'a Mapping, path: &[&str]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    let value = map.get(*first)?;
+    if rest.is_empty() {
+        return Some(value);
     }
-    Ok(())
+    get_path(value.as_mapping()?, rest)
 }
 
-fn insert_if_missing(map: &mut Mapping, key: &str, default: &str) -> bool {
-    let k = Value::String(key.to_string());
-    if !map.contains_key(&k) {
-        map.insert(k, Value::String(default.to_string()));
-        return true;
+/// Removes and returns the value at `path`, dropping the parents left empty.
+fn take_path(map: &mut Mapping, path: &[&str]) -> Option<Value> {
+    let (first, rest) = path.split_first()?;
+    if rest.is_empty() {
+        return map.remove(*first);
     }
-    false
+    let child = map.get_mut(*first)?.as_mapping_mut()?;
+    let value = take_path(child, rest)?;
+    if child.is_empty() {
+        map.remove(*first);
+    }
+    Some(value)
+}
+
+// ------------------------------------------------------------
+// Passi di migrazione
+// ------------------------------------------------------------
+
+/// v1: files written before 0.2.3 only had `database`.
+fn step_001_add_language(map: &mut Mapping) {
+    insert_if_missing(map, "language", key("en"));
+}
+
+/// v2: `language` is a canonical language tag (`IT` → `it`, `pt_br` → `pt-BR`).
+fn step_002_normalize_language(map: &mut Mapping) {
+    transform_key(map, "language", |v| match v.as_str() {
+        Some(s) if !normalize_locale_code(s).is_empty() => key(&normalize_locale_code(s)),
+        _ => key("en"),
+    });
+}
+
+/// v3: retention policy used by `backup prune`.
+fn step_003_add_backup(map: &mut Mapping) {
+    if let Ok(backup) = serde_yaml::to_value(BackupConfig::default()) {
+        insert_if_missing(map, "backup", backup);
+    }
+}
+
+/// v4: explicit `backup.format`, with the common spellings normalized
+/// (`ZIP`, `.zip`, `tgz`, `tar.zstd`, ...).
+fn step_004_backup_format(map: &mut Mapping) {
+    let Some(backup) = map.get_mut("backup").and_then(Value::as_mapping_mut) else {
+        return;
+    };
+    insert_if_missing(backup, "format", key("sqlite"));
+    transform_key(backup, "format", |v| {
+        let Some(s) = v.as_str() else {
+            return v.clone();
+        };
+        let normalized = match s.trim().trim_start_matches('.').to_lowercase().as_str() {
+            "db" | "sqlite3" => "sqlite".to_string(),
+            "tgz" | "targz" | "tar_gz" => "tar.gz".to_string(),
+            "tzst" | "tar.zstd" | "tar_zst" => "tar.zst".to_string(),
+            other => other.to_string(),
+        };
+        key(&normalized)
+    });
+}
+
+// ------------------------------------------------------------
+// Scrittura del file migrato
+// ------------------------------------------------------------
+
+/// Scalar leaves of a parsed file, as `(path, value)`.
+fn leaves(value: &Value, path: &mut Vec<String>, out: &mut Vec<(Vec<String>, Value)>) {
+    match value {
+        Value::Mapping(map) if !map.is_empty() || path.is_empty() => {
+            for (k, v) in map {
+                let Some(k) = k.as_str() else {
+                    out.push((path.clone(), value.clone()));
+                    return;
+                };
+                path.push(k.to_string());
+                leaves(v, path, out);
+                path.pop();
+            }
+        }
+        _ => out.push((path.clone(), value.clone())),
+    }
+}
+
+/// Applies the differences between `original` and `migrated` to `text` line
+/// by line; `None` if the file layout does not allow it.
+fn edit_in_place(text: &str, original: &Value, migrated: &Value) -> Option<String> {
+    let (mut old, mut new) = (Vec::new(), Vec::new());
+    leaves(original, &mut Vec::new(), &mut old);
+    leaves(migrated, &mut Vec::new(), &mut new);
+
+    let mut edited = text.to_string();
+    for (path, _) in &old {
+        if !new.iter().any(|(p, _)| p == path) {
+            let refs: Vec<&str> = path.iter().map(String::as_str).collect();
+            edited = yaml_edit::remove_key(&edited, &refs)?;
+        }
+    }
+    for (path, value) in &new {
+        if old.iter().any(|(p, v)| p == path && v == value) {
+            continue;
+        }
+        if value.is_mapping() || value.is_sequence() || path.is_empty() {
+            return None;
+        }
+        let refs: Vec<&str> = path.iter().map(String::as_str).collect();
+        let scalar = serde_yaml::to_string(value).ok()?;
+        edited = yaml_edit::set_scalar(&edited, &refs, scalar.trim_end())?;
+    }
+
+    let reparsed: Value = serde_yaml::from_str(&edited).ok()?;
+    (reparsed == *migrated).then_some(edited)
 }
@@ -2,15 +2,30 @@
 //!
 //! This module contains types and helper functions to locate, create and load
 //! the YAML configuration file used by the application. The configuration is
-//! intentionally small: it currently only stores the path to the SQLite
-//! database file used by the CLI.
+//! intentionally small: it stores the path to the SQLite database, the
+//! interface language, backup settings and optional named libraries.
 //!
 //! The functions here are used by the binary at startup to ensure a
 //! deterministic configuration directory and to persist a default
 //! configuration when none exists.
 
+pub mod keys;
+pub mod layered;
+pub mod library;
 pub mod load_config;
 pub mod migrate_config;
+pub mod yaml_edit;
 
-pub use load_config::{AppConfig, config_file_path, load_or_init};
-pub use migrate_config::migrate_config;
+pub use keys::{CONFIG_KEYS, ConfigError, ConfigKey, ValueKind};
+pub use layered::{
+    CliOverrides, ConfigSource, ConfigWarning, EffectiveConfig, Layers, read_layers, resolve,
+};
+pub use library::{DEFAULT_LIBRARY, LibraryError, LibraryProfile, write_libraries};
+pub use load_config::{
+    AppConfig, BackupConfig, BackupFormat, config_file_path, library_db_path, load_config,
+    load_or_init, locales_dir, save_config, set_config_file,
+};
+pub use migrate_config::{
+    CONFIG_MIGRATIONS, ConfigMigration, ConfigMigrationError, ConfigMigrationResult,
+    latest_config_version, log_config_migration, migrate_config_file,
+};
@@ -0,0 +1,193 @@
+//! Line-based editing of `librius.conf` that keeps comments and key order.
+//!
+//! Only the block-style YAML written by Librius (nested `key: value`
+//! mappings) is understood; every function returns `None` when the file uses
+//! other constructs, so that the caller can fall back to a full rewrite.
+
+/// A `key:` line of the file.
+struct Entry {
+    line: usize,
+    indent: usize,
+    path: Vec<String>,
+    /// Text after `key:` (value and comment), trimmed
+    rest: String,
+}
+
+/// Scans the `key:` lines, or returns `None` on unsupported syntax.
+fn scan(lines: &[&str]) -> Option<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let mut stack: Vec<(usize, String)> = Vec::new();
+
+    for (i, raw) in lines.iter().enumerate() {
+        let trimmed = raw.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || raw.trim_end() == "---" {
+            continue;
+        }
+        if raw.starts_with('\t') || trimmed.starts_with('-') {
+            return None;
+        }
+        let indent = raw.len() - trimmed.len();
+        let (key, rest) = split_key(trimmed)?;
+
+        while stack.last().is_some_and(|(ind, _)| *ind >= indent) {
+            stack.pop();
+        }
+        // Il valore scalare di una chiave non può avere figli
+        if let Some(parent) = entries.last().filter(|e: &&Entry| e.indent < indent)
+            && !parent.rest.is_empty()
+            && !parent.rest.starts_with('#')
+        {
+            return None;
+        }
+        stack.push((indent, key));
+        entries.push(Entry {
+            line: i,
+            indent,
+            path: stack.iter().map(|(_, k)| k.clone()).collect(),
+            rest: rest.trim().to_string(),
+        });
+    }
+    Some(entries)
+}
+
+/// Splits `key: rest`, removing quotes around the key.
+fn split_key(line: &str) -> Option<(String, &str)> {
+    let pos = line
+        .find(": ")
+        .or_else(|| line.strip_suffix(':').map(|k| k.len()))?;
+    let key = line[..pos].trim().trim_matches(|c| c == '"' || c == '\'');
+    if key.is_empty() || key.contains(['{', '[', '&', '*', '|', '>']) {
+        return None;
+    }
+    Some((key.to_string(), &line[pos + 1..]))
+}
+
+/// Returns the ` # comment` trailing a scalar value, if any.
+fn trailing_comment(rest: &str) -> Option<&str> {
+    let mut quote: Option<char> = None;
+    let mut prev_space = true;
+    for (i, c) in rest.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if (c == '"' || c == '\'') && prev_space => quote = Some(c),
+            None if c == '#' && prev_space => return Some(&rest[i..]),
+            None => {}
+        }
+        prev_space = c.is_whitespace();
+    }
+    None
+}
+
+/// Index of the line after the last descendant of `entries[idx]`.
+fn block_end(entries: &[Entry], idx: usize) -> usize {
+    let entry = &entries[idx];
+    entries[idx + 1..]
+        .iter()
+        .take_while(|e| e.indent > entry.indent)
+        .last()
+        .map_or(entry.line, |e| e.line)
+        + 1
+}
+
+fn has_children(entries: &[Entry], idx: usize) -> bool {
+    entries
+        .get(idx + 1)
+        .is_some_and(|e| e.indent > entries[idx].indent)
+}
+
+fn find(entries: &[Entry], path: &[&str]) -> Option<usize> {
+    entries.iter().position(|e| e.path == path)
+}
+
+fn join(lines: Vec<String>) -> String {
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Sets the scalar at `path` to `scalar` (already in YAML form), adding the
+/// missing parent mappings. A trailing comment on the line is kept.
+pub fn set_scalar(text: &str, path: &[&str], scalar: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    let entries = scan(&lines)?;
+    let mut out: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+
+    // 1️⃣ La chiave esiste: sostituisce solo il valore
+    if let Some(idx) = find(&entries, path) {
+        if has_children(&entries, idx) {
+            return None;
+        }
+        let line = lines[entries[idx].line];
+        let (key_part, rest) = line.split_at(line.find(':')? + 1);
+        // Il commento conserva la spaziatura originale che lo precede
+        let comment = trailing_comment(rest)
+            .map(|c| {
+                let before = &rest[..rest.len() - c.len()];
+                format!("{}{}", &before[before.trim_end().len()..], c)
+            })
+            .unwrap_or_default();
+        out[entries[idx].line] = format!("{} {}{}", key_part, scalar, comment);
+        return Some(join(out));
+    }
+
+    // 2️⃣ Altrimenti la aggiunge sotto l'antenato più vicino che esiste
+    let (depth, at, indent) = (1..path.len())
+        .rev()
+        .find_map(|depth| {
+            let idx = find(&entries, &path[..depth])?;
+            let e = &entries[idx];
+            let indent = if has_children(&entries, idx) {
+                entries[idx + 1].indent
+            } else {
+                e.indent + 2
+            };
+            Some((depth, block_end(&entries, idx), indent, e.rest.is_empty()))
+        })
+        .map_or(
+            Some((0, out.len(), 0)),
+            |(depth, at, indent, is_mapping)| is_mapping.then_some((depth, at, indent)),
+        )?;
+
+    let mut new_lines = Vec::new();
+    for (level, key) in path.iter().enumerate().skip(depth) {
+        let pad = " ".repeat(indent + (level - depth) * 2);
+        if level + 1 == path.len() {
+            new_lines.push(format!("{}{}: {}", pad, key, scalar));
+        } else {
+            new_lines.push(format!("{}{}:", pad, key));
+        }
+    }
+    out.splice(at..at, new_lines);
+    Some(join(out))
+}
+
+/// Removes `path` (with its children); parent mappings left empty are removed
+/// too. Returns the text unchanged if the key is not present.
+pub fn remove_key(text: &str, path: &[&str]) -> Option<String> {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut path = path.to_vec();
+
+    while !path.is_empty() {
+        let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let entries = scan(&refs)?;
+        let Some(idx) = find(&entries, &path) else {
+            break;
+        };
+        let (start, end) = (entries[idx].line, block_end(&entries, idx));
+        lines.drain(start..end);
+
+        // Il genitore rimasto senza figli diventerebbe `null`: si rimuove anch'esso
+        path.pop();
+        if path.is_empty() {
+            break;
+        }
+        let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let entries = scan(&refs)?;
+        match find(&entries, &path) {
+            Some(parent) if !has_children(&entries, parent) && entries[parent].rest.is_empty() => {}
+            _ => break,
+        }
+    }
+    Some(join(lines))
+}
@@ -35,7 +35,10 @@ pub fn update_book_by_id(
 
     let (set_clause, mut params_vec) = build_update_clause(fields);
 
-    let sql = format!("UPDATE books SET {} WHERE id = ?", set_clause);
+    let sql = format!(
+        "UPDATE books SET {} WHERE id = ? AND deleted_at IS NULL",
+        set_clause
+    );
     params_vec.push(id.to_string());
 
     let mut stmt = conn.prepare(&sql)?;
@@ -54,7 +57,10 @@ pub fn update_book_by_isbn(
 
     let (set_clause, mut params_vec) = build_update_clause(fields);
 
-    let sql = format!("UPDATE books SET {} WHERE isbn = ?", set_clause);
+    let sql = format!(
+        "UPDATE books SET {} WHERE isbn = ? AND deleted_at IS NULL",
+        set_clause
+    );
     params_vec.push(isbn.to_string());
 
     let mut stmt = conn.prepare(&sql)?;
@@ -63,6 +69,7 @@ pub fn update_book_by_isbn(
 }
 
 /// Retrieve current values of the specified fields for a given book (by ID or ISBN).
+/// Books in the trash are excluded.
 pub fn get_book_fields(
     conn: &Connection,
     key: &str,
@@ -73,9 +80,15 @@ pub fn get_book_fields(
 
     for field in fields {
         let query = if is_isbn {
-            format!("SELECT {} FROM books WHERE isbn = ?", field)
+            format!(
+                "SELECT {} FROM books WHERE isbn = ? AND deleted_at IS NULL",
+                field
+            )
         } else {
-            format!("SELECT {} FROM books WHERE id = ?", field)
+            format!(
+                "SELECT {} FROM books WHERE id = ? AND deleted_at IS NULL",
+                field
+            )
         };
 
         let result: Option<String> = conn
@@ -95,22 +108,25 @@ pub fn get_book_fields(
 }
 
 /// Search books by a full-text query across title, author, editor, genre and language.
+/// Books in the trash are excluded.
 pub fn search_books(conn: &Connection, query: &str) -> Result<Vec<Book>> {
     let like = format!("%{}%", query);
 
-    let mut stmt = conn.prepare(
+    let sql = format!(
         r#"
-        SELECT id, title, author, editor, year, isbn, language, pages,
-               genre, summary, room, shelf, row, position, added_at
+        SELECT {}
         FROM books
-        WHERE title    LIKE ?1
-           OR author   LIKE ?1
-           OR editor   LIKE ?1
-           OR genre    LIKE ?1
-           OR language LIKE ?1
+        WHERE deleted_at IS NULL
+          AND (title    LIKE ?1
+            OR author   LIKE ?1
+            OR editor   LIKE ?1
+            OR genre    LIKE ?1
+            OR language LIKE ?1)
         ORDER BY title COLLATE NOCASE ASC;
     "#,
-    )?;
+        BOOK_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
 
     let rows = stmt.query_map([like], Book::from_row)?;
 
@@ -120,3 +136,159 @@ pub fn search_books(conn: &Connection, query: &str) -> Result<Vec<Book>> {
     }
     Ok(out)
 }
+
+/// Column list used by every `SELECT` that maps rows with `Book::from_row`.
+pub const BOOK_COLUMNS: &str = "id, title, author, editor, year, isbn, language, pages, genre, \
+     summary, room, shelf, row, position, added_at, rating, read_status, read_at, tags, review, format";
+
+/// Fetch a full book by numeric ID.
+pub fn find_book_by_id(conn: &Connection, id: i64) -> Result<Option<Book>> {
+    let sql = format!("SELECT {} FROM books WHERE id = ?1", BOOK_COLUMNS);
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query_map([id], Book::from_row)?;
+    rows.next().transpose()
+}
+
+/// Fetch a full book by ID or ISBN (same key semantics as `get_book_fields`).
+pub fn find_book(conn: &Connection, key: &str, is_isbn: bool) -> Result<Option<Book>> {
+    let sql = if is_isbn {
+        format!("SELECT {} FROM books WHERE isbn = ?1", BOOK_COLUMNS)
+    } else {
+        format!("SELECT {} FROM books WHERE id = ?1", BOOK_COLUMNS)
+    };
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query_map([key], Book::from_row)?;
+    rows.next().transpose()
+}
+
+/// Fetch a book without ISBN by title, author (case-insensitive) and year:
+/// how the imports recognize a record that has no ISBN.
+pub fn find_book_without_isbn(
+    conn: &Connection,
+    title: &str,
+    author: &str,
+    year: i32,
+) -> Result<Option<Book>> {
+    let sql = format!(
+        "SELECT {} FROM books
+         WHERE isbn = '' AND title = ?1 COLLATE NOCASE AND author = ?2 COLLATE NOCASE AND year = ?3
+         ORDER BY id",
+        BOOK_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query_map(params![title.trim(), author.trim(), year], Book::from_row)?;
+    rows.next().transpose()
+}
+
+/// Insert a new book with every field (used by the imports) and return its
+/// ID; `added_at` defaults to now.
+pub fn insert_book(conn: &Connection, book: &Book) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO books (title, author, editor, year, isbn, language, pages, genre,
+                            summary, room, shelf, row, position, added_at,
+                            rating, read_status, read_at, tags, review, format)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13,
+                 COALESCE(?14, CURRENT_TIMESTAMP), ?15, ?16, ?17, ?18, ?19, ?20)",
+        params![
+            book.title,
+            book.author,
+            book.editor,
+            book.year,
+            book.isbn,
+            book.language,
+            book.pages,
+            book.genre,
+            book.summary,
+            book.room,
+            book.shelf,
+            book.row,
+            book.position,
+            book.added_at.map(|d| d.to_rfc3339()),
+            book.rating,
+            book.read_status,
+            book.read_at.map(|d| d.to_string()),
+            book.tags,
+            book.review,
+            book.format,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Insert a book keeping its original `id` and `added_at` (used by `undo`
+/// to resurrect deleted records).
+pub fn insert_book_with_id(conn: &Connection, book: &Book) -> Result<usize> {
+    conn.execute(
+        "INSERT INTO books (id, title, author, editor, year, isbn, language, pages, genre,
+                            summary, room, shelf, row, position, added_at,
+                            rating, read_status, read_at, tags, review, format)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                 ?16, ?17, ?18, ?19, ?20, ?21)",
+        params![
+            book.id,
+            book.title,
+            book.author,
+            book.editor,
+            book.year,
+            book.isbn,
+            book.language,
+            book.pages,
+            book.genre,
+            book.summary,
+            book.room,
+            book.shelf,
+            book.row,
+            book.position,
+            book.added_at.map(|d| d.to_rfc3339()),
+            book.rating,
+            book.read_status,
+            book.read_at.map(|d| d.to_string()),
+            book.tags,
+            book.review,
+            book.format,
+        ],
+    )
+}
+
+/// Overwrite every field of an existing book with the given snapshot.
+pub fn overwrite_book(conn: &Connection, book: &Book) -> Result<usize> {
+    conn.execute(
+        "UPDATE books SET title = ?2, author = ?3, editor = ?4, year = ?5, isbn = ?6,
+                          language = ?7, pages = ?8, genre = ?9, summary = ?10, room = ?11,
+                          shelf = ?12, row = ?13, position = ?14, added_at = ?15,
+                          rating = ?16, read_status = ?17, read_at = ?18, tags = ?19,
+                          review = ?20, format = ?21
+         WHERE id = ?1",
+        params![
+            book.id,
+            book.title,
+            book.author,
+            book.editor,
+            book.year,
+            book.isbn,
+            book.language,
+            book.pages,
+            book.genre,
+            book.summary,
+            book.room,
+            book.shelf,
+            book.row,
+            book.position,
+            book.added_at.map(|d| d.to_rfc3339()),
+            book.rating,
+            book.read_status,
+            book.read_at.map(|d| d.to_string()),
+            book.tags,
+            book.review,
+            book.format,
+        ],
+    )
+}
+
+/// Set (`Some`) or clear (`None`) the trash timestamp of a book.
+pub fn set_deleted_at(conn: &Connection, id: i64, deleted_at: Option<&str>) -> Result<usize> {
+    conn.execute(
+        "UPDATE books SET deleted_at = ?1 WHERE id = ?2",
+        params![deleted_at, id],
+    )
+}
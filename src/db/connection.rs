@@ -2,7 +2,8 @@ use crate::config::AppConfig;
 use crate::db::migrations;
 use crate::i18n::{tr, tr_with};
 use crate::utils::{is_verbose, print_err, print_info, print_ok, write_log};
-use rusqlite::{Connection, Result};
+use rusqlite::Connection;
+use std::error::Error;
 use std::path::Path;
 
 use std::fs;
@@ -40,7 +41,16 @@ pub fn get_db_path() -> PathBuf {
 /// - If the DB file does not exist, it is created and initialized.
 /// - Pending migrations are applied.
 /// - Each operation is logged in the `log` table.
-pub fn start_db(config: &AppConfig) -> Result<Connection> {
+pub fn start_db(config: &AppConfig) -> Result<Connection, Box<dyn Error>> {
+    open_db(config, true)
+}
+
+/// Like [`start_db`], but applies pending migrations only if `migrate` is set
+/// (`db migrate` manages them explicitly).
+///
+/// A database whose schema is newer than this binary supports is always
+/// refused.
+pub fn open_db(config: &AppConfig, migrate: bool) -> Result<Connection, Box<dyn Error>> {
     // 1️⃣ Determina il percorso del database
     let db_path = if config.database.trim().is_empty() {
         get_db_path()
@@ -69,7 +79,13 @@ pub fn start_db(config: &AppConfig) -> Result<Connection> {
         );
     }
 
-    // 3️⃣ Apertura connessione
+    // 3️⃣ Apertura connessione (la cartella del database si crea solo ora)
+    if !db_exists
+        && let Some(dir) = db_path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        fs::create_dir_all(dir)?;
+    }
     let conn = Connection::open(&db_path)?;
 
     // 4️⃣ Tabella log (sempre disponibile)
@@ -84,37 +100,47 @@ pub fn start_db(config: &AppConfig) -> Result<Connection> {
         [],
     )?;
 
-    // 5️⃣ Inizializza schema se nuovo DB
+    // 5️⃣ Rifiuta database creati da una versione più recente
+    migrations::check_schema_version(&conn)?;
+
+    if !migrate {
+        return Ok(conn);
+    }
+
+    // Un rollback voluto (`db migrate --to N`) non viene annullato all'apertura
+    migrations::check_not_rolled_back(&conn)?;
+
+    // 6️⃣ Nuovo DB: lo schema è creato dalle migrazioni stesse
     if !db_exists {
         print_info(&tr("db.schema.initializing"), is_verbose());
-        if let Err(e) = ensure_schema(&conn) {
-            print_err(&tr_with(
-                "db.schema.init_failed",
-                &[("error", &e.to_string())],
-            ));
-            let _ = write_log(&conn, "DB_INIT_FAIL", "DB", &e.to_string());
-            return Err(e);
-        }
-        print_ok(&tr("db.schema.created"), is_verbose());
-        let _ = write_log(&conn, "DB_INIT_OK", "DB", &tr("log.db.schema.init"));
     }
 
-    // 6️⃣ Esegui eventuali migrazioni
+    // 7️⃣ Esegui eventuali migrazioni
     match migrations::run_migrations(&conn) {
         Err(e) => {
             print_err(&tr_with("db.migrate.failed", &[("error", &e.to_string())]));
             let _ = write_log(&conn, "DB_MIGRATION_FAIL", "DB", &e.to_string());
+            if !db_exists {
+                let _ = write_log(&conn, "DB_INIT_FAIL", "DB", &e.to_string());
+                return Err(e.into());
+            }
         }
-        Ok(result) => match result {
-            migrations::MigrationResult::Applied(patches) => {
-                print_ok(&tr("db.migrate.applied"), is_verbose());
-                let msg = &tr_with("log.db.patch_applied", &[("patches", &patches.join(", "))]);
-                let _ = write_log(&conn, "DB_MIGRATION_OK", "DB", msg);
+        Ok(result) => {
+            if !db_exists {
+                print_ok(&tr("db.schema.created"), is_verbose());
+                let _ = write_log(&conn, "DB_INIT_OK", "DB", &tr("log.db.schema.init"));
             }
-            migrations::MigrationResult::None => {
-                print_ok(&tr("db.schema.already_update"), is_verbose());
+            match result {
+                migrations::MigrationResult::Applied(patches) => {
+                    print_ok(&tr("db.migrate.applied"), is_verbose());
+                    let msg = &tr_with("log.db.patch_applied", &[("patch", &patches.join(", "))]);
+                    let _ = write_log(&conn, "DB_MIGRATION_OK", "DB", msg);
+                }
+                migrations::MigrationResult::None => {
+                    print_ok(&tr("db.schema.already_update"), is_verbose());
+                }
             }
-        },
+        }
     }
 
     Ok(conn)
@@ -122,30 +148,15 @@ pub fn start_db(config: &AppConfig) -> Result<Connection> {
 
 /// Public compatibility function expected by docs and external callers.
 /// Previous API used `db::init_db(&cfg)` returning a `Connection`.
-pub fn init_db(config: &AppConfig) -> Result<Connection> {
+pub fn init_db(config: &AppConfig) -> Result<Connection, Box<dyn Error>> {
     start_db(config)
 }
 
-/// Ensure required tables exist in an opened connection.
-pub fn ensure_schema(conn: &Connection) -> Result<()> {
-    conn.execute_batch(
-        "CREATE TABLE IF NOT EXISTS books (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            title TEXT NOT NULL,
-            author TEXT NOT NULL,
-            editor TEXT NOT NULL,
-            year INTEGER NOT NULL,
-            isbn TEXT NOT NULL,
-            language TEXT,
-            pages INTEGER,
-            genre TEXT,
-            summary TEXT,
-            room TEXT,
-            shelf TEXT,
-            row TEXT,
-            position TEXT,
-            added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-        );",
-    )?;
+/// Ensure the full schema exists in an opened connection.
+///
+/// The schema is defined only by the migrations: this simply applies every
+/// pending one, so a fresh database and a migrated one cannot drift.
+pub fn ensure_schema(conn: &Connection) -> Result<(), migrations::MigrationError> {
+    migrations::migrate_to(conn, migrations::latest_version())?;
     Ok(())
 }
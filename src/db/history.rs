@@ -0,0 +1,153 @@
+//! Book change history (`book_history` table) and undo support.
+//!
+//! Every insert, update and delete on `books` stores full before/after JSON
+//! snapshots, so any operation can be reverted — including resurrecting a
+//! deleted book with its original id.
+
+use crate::db::books::{find_book_by_id, insert_book_with_id, overwrite_book, set_deleted_at};
+use crate::models::{Book, HistoryEntry, HistoryOperation};
+use crate::utils::now_str;
+use rusqlite::types::Type;
+use rusqlite::{Connection, Result, params};
+
+const HISTORY_COLUMNS: &str =
+    "id, book_id, operation, before_json, after_json, created_at, undone_at";
+
+fn to_json(book: Option<&Book>) -> Result<Option<String>> {
+    book.map(|b| {
+        serde_json::to_string(b).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+    })
+    .transpose()
+}
+
+/// Records one operation with its before/after snapshots.
+pub fn record_history(
+    conn: &Connection,
+    operation: HistoryOperation,
+    book_id: i64,
+    before: Option<&Book>,
+    after: Option<&Book>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO book_history (book_id, operation, before_json, after_json, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            book_id,
+            operation.as_str(),
+            to_json(before)?,
+            to_json(after)?,
+            now_str()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Records the permanent deletion of a book. `deleted_at` is the trash date
+/// of a book purged from the trash; it is kept in the `before` snapshot so
+/// that `undo` restores the book to the trash rather than to the library.
+pub fn record_delete(
+    conn: &Connection,
+    book_id: i64,
+    before: &Book,
+    deleted_at: Option<&str>,
+) -> Result<()> {
+    let to_err = |e: serde_json::Error| rusqlite::Error::ToSqlConversionFailure(Box::new(e));
+    let mut snapshot = serde_json::to_value(before).map_err(to_err)?;
+    if let (Some(date), Some(map)) = (deleted_at, snapshot.as_object_mut()) {
+        map.insert("deleted_at".to_string(), date.into());
+    }
+
+    conn.execute(
+        "INSERT INTO book_history (book_id, operation, before_json, after_json, created_at)
+         VALUES (?1, ?2, ?3, NULL, ?4)",
+        params![
+            book_id,
+            HistoryOperation::Delete.as_str(),
+            snapshot.to_string(),
+            now_str()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Records the insertion of the book with the given id (reads it back to
+/// store the complete snapshot, including defaults set by SQLite).
+pub fn record_insert(conn: &Connection, book_id: i64) -> Result<()> {
+    let after = find_book_by_id(conn, book_id)?;
+    record_history(
+        conn,
+        HistoryOperation::Insert,
+        book_id,
+        None,
+        after.as_ref(),
+    )
+}
+
+/// Returns the full timeline of a book, oldest first.
+pub fn get_history(conn: &Connection, book_id: i64) -> Result<Vec<HistoryEntry>> {
+    let sql = format!(
+        "SELECT {} FROM book_history WHERE book_id = ?1 ORDER BY id",
+        HISTORY_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([book_id], HistoryEntry::from_row)?;
+    rows.collect()
+}
+
+/// Returns the last `n` operations that have not been undone, newest first.
+pub fn last_undoable(conn: &Connection, n: usize) -> Result<Vec<HistoryEntry>> {
+    let sql = format!(
+        "SELECT {} FROM book_history WHERE undone_at IS NULL ORDER BY id DESC LIMIT ?1",
+        HISTORY_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([n as i64], HistoryEntry::from_row)?;
+    rows.collect()
+}
+
+/// Reverts a single history entry and marks it as undone.
+///
+/// - INSERT → the book is deleted
+/// - UPDATE → the `before` snapshot is written back
+/// - DELETE → the book is re-inserted with its original id (back in the
+///   trash if it was purged from there)
+/// - TRASH → the book is taken out of the trash
+/// - RESTORE → the book is moved back to the trash
+pub fn undo_entry(conn: &Connection, entry: &HistoryEntry) -> Result<()> {
+    let missing_snapshot = || {
+        rusqlite::Error::FromSqlConversionFailure(
+            0,
+            Type::Null,
+            format!("history entry {} has no snapshot", entry.id).into(),
+        )
+    };
+
+    match entry.operation {
+        HistoryOperation::Insert => {
+            conn.execute("DELETE FROM books WHERE id = ?1", [entry.book_id])?;
+        }
+        HistoryOperation::Update => {
+            let before = entry.before.as_ref().ok_or_else(missing_snapshot)?;
+            overwrite_book(conn, before)?;
+        }
+        HistoryOperation::Delete => {
+            let before = entry.before.as_ref().ok_or_else(missing_snapshot)?;
+            insert_book_with_id(conn, before)?;
+            if let Some(date) = &entry.deleted_at {
+                set_deleted_at(conn, entry.book_id, Some(date))?;
+            }
+        }
+        HistoryOperation::Trash => {
+            set_deleted_at(conn, entry.book_id, None)?;
+        }
+        HistoryOperation::Restore => {
+            set_deleted_at(conn, entry.book_id, Some(&now_str()))?;
+        }
+    }
+
+    conn.execute(
+        "UPDATE book_history SET undone_at = ?1 WHERE id = ?2",
+        params![now_str(), entry.id],
+    )?;
+    Ok(())
+}
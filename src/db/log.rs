@@ -0,0 +1,144 @@
+//! Queries on the `log` table, used by `log list|tail|stats|prune`.
+//!
+//! Dates are stored as ISO 8601 strings with their own offset, so they are
+//! compared through `julianday()` rather than as text.
+
+use crate::models::{LogEntry, LogStat};
+use crate::utils::log::create_log_table;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{Connection, Result, params_from_iter};
+
+const LOG_COLUMNS: &str = "id, date, operation, target, message, book_id, details";
+
+/// Filters shared by `log list`, `log tail` and `log stats`.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Operation name; `*` and `?` work as wildcards (`DB_*`)
+    pub operation: Option<String>,
+    /// Target name, with the same wildcards
+    pub target: Option<String>,
+    pub book_id: Option<i64>,
+    /// Entries at or after this instant
+    pub since: Option<DateTime<Utc>>,
+    /// Entries before this instant
+    pub until: Option<DateTime<Utc>>,
+    /// Entries added after this id (`log tail --follow`)
+    pub after_id: Option<i64>,
+    /// Keep only the newest N entries
+    pub limit: Option<usize>,
+}
+
+fn sql_instant(dt: &DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+impl LogFilter {
+    /// `WHERE` clause and its parameters.
+    fn where_clause(&self) -> (String, Vec<SqlValue>) {
+        let mut clauses = Vec::new();
+        let mut params = Vec::new();
+
+        if let Some(op) = &self.operation {
+            clauses.push("UPPER(operation) GLOB ?");
+            params.push(SqlValue::Text(op.to_uppercase()));
+        }
+        if let Some(target) = &self.target {
+            clauses.push("UPPER(COALESCE(target, '')) GLOB ?");
+            params.push(SqlValue::Text(target.to_uppercase()));
+        }
+        if let Some(id) = self.book_id {
+            clauses.push("book_id = ?");
+            params.push(SqlValue::Integer(id));
+        }
+        if let Some(id) = self.after_id {
+            clauses.push("id > ?");
+            params.push(SqlValue::Integer(id));
+        }
+        if let Some(since) = &self.since {
+            clauses.push("julianday(date) >= julianday(?)");
+            params.push(SqlValue::Text(sql_instant(since)));
+        }
+        if let Some(until) = &self.until {
+            clauses.push("julianday(date) < julianday(?)");
+            params.push(SqlValue::Text(sql_instant(until)));
+        }
+
+        if clauses.is_empty() {
+            (String::new(), params)
+        } else {
+            (format!("WHERE {}", clauses.join(" AND ")), params)
+        }
+    }
+}
+
+/// Returns the entries matching `filter`, oldest first (the newest `limit`
+/// ones when a limit is set).
+pub fn list_log(conn: &Connection, filter: &LogFilter) -> Result<Vec<LogEntry>> {
+    create_log_table(conn)?;
+    let (where_sql, params) = filter.where_clause();
+    let limit = filter
+        .limit
+        .map(|n| format!("LIMIT {}", n))
+        .unwrap_or_default();
+    let sql = format!(
+        "SELECT {} FROM log {} ORDER BY id DESC {}",
+        LOG_COLUMNS, where_sql, limit
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut entries = stmt
+        .query_map(params_from_iter(params), LogEntry::from_row)?
+        .collect::<Result<Vec<_>>>()?;
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Counts the entries per operation, most frequent first.
+pub fn log_stats(conn: &Connection, filter: &LogFilter) -> Result<Vec<LogStat>> {
+    create_log_table(conn)?;
+    let (where_sql, params) = filter.where_clause();
+    let sql = format!(
+        "SELECT s.operation, s.count, first.date, last.date
+         FROM (
+            SELECT operation, COUNT(*) AS count, MIN(id) AS first_id, MAX(id) AS last_id
+            FROM log {}
+            GROUP BY operation
+         ) s
+         JOIN log first ON first.id = s.first_id
+         JOIN log last ON last.id = s.last_id
+         ORDER BY s.count DESC, s.operation",
+        where_sql
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    stmt.query_map(params_from_iter(params), |row| {
+        Ok(LogStat {
+            operation: row.get(0)?,
+            count: row.get(1)?,
+            first: row.get(2)?,
+            last: row.get(3)?,
+        })
+    })?
+    .collect()
+}
+
+/// Number of entries older than `cutoff`.
+pub fn count_log_before(conn: &Connection, cutoff: &DateTime<Utc>) -> Result<usize> {
+    create_log_table(conn)?;
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM log WHERE julianday(date) < julianday(?1)",
+        [sql_instant(cutoff)],
+        |row| row.get(0),
+    )?;
+    Ok(count as usize)
+}
+
+/// Deletes the entries older than `cutoff` and returns how many were removed.
+pub fn prune_log(conn: &Connection, cutoff: &DateTime<Utc>) -> Result<usize> {
+    create_log_table(conn)?;
+    conn.execute(
+        "DELETE FROM log WHERE julianday(date) < julianday(?1)",
+        [sql_instant(cutoff)],
+    )
+}
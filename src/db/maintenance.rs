@@ -0,0 +1,332 @@
+//! Database maintenance: consistency checks, safe repairs, statistics,
+//! `VACUUM` and `PRAGMA optimize` (used by `db check|repair|stats|vacuum|optimize`).
+
+use crate::db::books::find_book_by_id;
+use crate::db::history::record_history;
+use crate::db::migrations::{applied_migrations, current_version, latest_version};
+use crate::i18n::{tr_fmt, tr_with};
+use crate::models::HistoryOperation;
+use crate::utils::isbn::normalize_isbn;
+use chrono::{Datelike, Local};
+use rusqlite::{Connection, Result, params};
+use std::fmt;
+
+/// A problem found by [`check_database`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    /// Line reported by `PRAGMA integrity_check`
+    Integrity(String),
+    /// Row reported by `PRAGMA foreign_key_check`
+    ForeignKey {
+        table: String,
+        rowid: i64,
+        parent: String,
+    },
+    /// ISBN that `normalize_isbn` rejects (an empty ISBN means none)
+    InvalidIsbn {
+        id: i64,
+        isbn: String,
+    },
+    /// Valid ISBN not stored in plain form (hyphens or spaces)
+    NonCanonicalIsbn {
+        id: i64,
+        isbn: String,
+        canonical: String,
+    },
+    /// Negative or future publication year (0 means unknown and is accepted)
+    ImpossibleYear {
+        id: i64,
+        year: i64,
+    },
+    NegativePages {
+        id: i64,
+        pages: i64,
+    },
+    /// `book_history` rows of a book that no longer exists and whose removal
+    /// was never recorded
+    OrphanHistory {
+        book_id: i64,
+        rows: i64,
+    },
+}
+
+impl Issue {
+    /// Whether `repair` can fix this issue without losing information.
+    pub fn is_repairable(&self) -> bool {
+        matches!(
+            self,
+            Issue::NonCanonicalIsbn { .. }
+                | Issue::NegativePages { .. }
+                | Issue::OrphanHistory { .. }
+        )
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Issue::Integrity(detail) => tr_with("db.check.issue.integrity", &[("detail", detail)]),
+            Issue::ForeignKey {
+                table,
+                rowid,
+                parent,
+            } => tr_with(
+                "db.check.issue.foreign_key",
+                &[
+                    ("table", table),
+                    ("rowid", &rowid.to_string()),
+                    ("parent", parent),
+                ],
+            ),
+            Issue::InvalidIsbn { id, isbn } => tr_with(
+                "db.check.issue.invalid_isbn",
+                &[("id", &id.to_string()), ("isbn", isbn)],
+            ),
+            Issue::NonCanonicalIsbn {
+                id,
+                isbn,
+                canonical,
+            } => tr_with(
+                "db.check.issue.non_canonical_isbn",
+                &[
+                    ("id", &id.to_string()),
+                    ("isbn", isbn),
+                    ("canonical", canonical),
+                ],
+            ),
+            Issue::ImpossibleYear { id, year } => tr_with(
+                "db.check.issue.impossible_year",
+                &[("id", &id.to_string()), ("year", &year.to_string())],
+            ),
+            Issue::NegativePages { id, pages } => tr_with(
+                "db.check.issue.negative_pages",
+                &[("id", &id.to_string()), ("pages", &pages.to_string())],
+            ),
+            Issue::OrphanHistory { book_id, rows } => tr_fmt(
+                "db.check.issue.orphan_history",
+                &[("id", book_id.to_string().into()), ("rows", (*rows).into())],
+            ),
+        };
+        f.write_str(&msg)
+    }
+}
+
+/// Runs every check and returns the issues found (empty if the database is healthy).
+pub fn check_database(conn: &Connection) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+
+    // 1️⃣ Integrità strutturale di SQLite
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    for line in stmt.query_map([], |r| r.get::<_, String>(0))? {
+        let line = line?;
+        if line != "ok" {
+            issues.push(Issue::Integrity(line));
+        }
+    }
+
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let rows = stmt.query_map([], |r| {
+        Ok(Issue::ForeignKey {
+            table: r.get(0)?,
+            rowid: r.get::<_, Option<i64>>(1)?.unwrap_or_default(),
+            parent: r.get(2)?,
+        })
+    })?;
+    for issue in rows {
+        issues.push(issue?);
+    }
+
+    // 2️⃣ Dati dei libri
+    let max_year = i64::from(Local::now().year()) + 1;
+    let mut stmt = conn.prepare("SELECT id, isbn, year, pages FROM books ORDER BY id")?;
+    let rows = stmt.query_map([], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, String>(1)?,
+            r.get::<_, i64>(2)?,
+            r.get::<_, Option<i64>>(3)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, isbn, year, pages) = row?;
+
+        match normalize_isbn(&isbn, true) {
+            _ if isbn.is_empty() => {}
+            Ok(canonical) if canonical != isbn => issues.push(Issue::NonCanonicalIsbn {
+                id,
+                isbn,
+                canonical,
+            }),
+            Ok(_) => {}
+            Err(_) => issues.push(Issue::InvalidIsbn { id, isbn }),
+        }
+        if year < 0 || year > max_year {
+            issues.push(Issue::ImpossibleYear { id, year });
+        }
+        if let Some(pages) = pages
+            && pages < 0
+        {
+            issues.push(Issue::NegativePages { id, pages });
+        }
+    }
+
+    // 3️⃣ Cronologia orfana: il libro non esiste e nessuna voce ne spiega la rimozione
+    // (DELETE non annullata oppure INSERT annullata)
+    let mut stmt = conn.prepare(
+        "SELECT h.book_id, COUNT(*) FROM book_history h
+         WHERE NOT EXISTS (SELECT 1 FROM books b WHERE b.id = h.book_id)
+           AND NOT EXISTS (
+               SELECT 1 FROM book_history e
+               WHERE e.book_id = h.book_id
+                 AND ((e.operation = 'DELETE' AND e.undone_at IS NULL)
+                   OR (e.operation = 'INSERT' AND e.undone_at IS NOT NULL))
+           )
+         GROUP BY h.book_id
+         ORDER BY h.book_id",
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(Issue::OrphanHistory {
+            book_id: r.get(0)?,
+            rows: r.get(1)?,
+        })
+    })?;
+    for issue in rows {
+        issues.push(issue?);
+    }
+
+    Ok(issues)
+}
+
+/// Fixes the repairable issues in a single transaction and returns how many
+/// were fixed. An ISBN whose plain form already belongs to another book is
+/// left untouched.
+pub fn repair_issues(conn: &Connection, issues: &[Issue]) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut fixed = 0;
+
+    for issue in issues {
+        let changed = match issue {
+            Issue::NonCanonicalIsbn { id, canonical, .. } => {
+                let taken: bool = conn.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM books WHERE isbn = ?1 AND id != ?2)",
+                    params![canonical, id],
+                    |r| r.get(0),
+                )?;
+                if taken {
+                    0
+                } else {
+                    update_with_history(
+                        conn,
+                        *id,
+                        "UPDATE books SET isbn = ?1 WHERE id = ?2",
+                        params![canonical, id],
+                    )?
+                }
+            }
+            Issue::NegativePages { id, .. } => update_with_history(
+                conn,
+                *id,
+                "UPDATE books SET pages = NULL WHERE id = ?1",
+                params![id],
+            )?,
+            Issue::OrphanHistory { book_id, .. } => {
+                conn.execute("DELETE FROM book_history WHERE book_id = ?1", [book_id])?
+            }
+            _ => 0,
+        };
+        if changed > 0 {
+            fixed += 1;
+        }
+    }
+
+    tx.commit()?;
+    Ok(fixed)
+}
+
+/// Runs a single-book UPDATE and records it in `book_history`, so that
+/// repairs can be reverted with `undo` like any other edit.
+fn update_with_history(
+    conn: &Connection,
+    id: i64,
+    sql: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> Result<usize> {
+    let before = find_book_by_id(conn, id)?;
+    let changed = conn.execute(sql, params)?;
+    if changed > 0 {
+        let after = find_book_by_id(conn, id)?;
+        record_history(
+            conn,
+            HistoryOperation::Update,
+            id,
+            before.as_ref(),
+            after.as_ref(),
+        )?;
+    }
+    Ok(changed)
+}
+
+/// Size and content summary of the database for `db stats`.
+pub struct DbStats {
+    /// Size of the database file in bytes (`None` for in-memory databases)
+    pub file_size: Option<u64>,
+    /// Row count of every user table, by name
+    pub tables: Vec<(String, i64)>,
+    pub schema_version: u32,
+    pub latest_version: u32,
+    pub migrations: Vec<String>,
+    pub log_rows: i64,
+}
+
+pub fn collect_stats(conn: &Connection) -> Result<DbStats> {
+    let file_size = conn
+        .path()
+        .filter(|p| !p.is_empty())
+        .and_then(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len());
+
+    let names: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )?;
+        stmt.query_map([], |r| r.get(0))?.collect::<Result<_>>()?
+    };
+
+    let mut tables = Vec::new();
+    for name in names {
+        // I nomi arrivano da sqlite_master: quoting solo per sicurezza
+        let count: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")),
+            [],
+            |r| r.get(0),
+        )?;
+        tables.push((name, count));
+    }
+
+    let log_rows = tables
+        .iter()
+        .find(|(name, _)| name == "log")
+        .map(|(_, n)| *n)
+        .unwrap_or(0);
+
+    Ok(DbStats {
+        file_size,
+        tables,
+        schema_version: current_version(conn)?,
+        latest_version: latest_version(),
+        migrations: applied_migrations(conn)?,
+        log_rows,
+    })
+}
+
+/// Rebuilds the database file, reclaiming unused pages.
+pub fn vacuum(conn: &Connection) -> Result<()> {
+    conn.execute_batch("VACUUM;")
+}
+
+/// Refreshes the query planner statistics.
+pub fn optimize(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ANALYZE; PRAGMA optimize;")
+}
@@ -1,6 +1,104 @@
+//! Versioned schema migrations.
+//!
+//! Every migration has a numeric version, an `up` step and an optional `down`
+//! step. Applied versions are tracked in the `schema_migrations` table and each
+//! step runs in its own transaction together with its bookkeeping row, so a
+//! failing patch leaves the database at the previous version.
+
 use crate::i18n::{tr, tr_with};
-use crate::utils::{is_verbose, print_err, print_info, print_ok, write_log};
-use rusqlite::{Connection, Result};
+use crate::utils::backup::auto_snapshot;
+use crate::utils::log::create_log_table;
+use crate::utils::{is_verbose, now_str, print_err, print_info, print_ok, write_log};
+use rusqlite::{Connection, Result, params};
+use std::fmt;
+
+type Step = fn(&Connection) -> Result<()>;
+
+/// A single schema migration.
+pub struct Migration {
+    /// Schema version reached once this migration is applied (1-based, contiguous)
+    pub version: u32,
+    /// Historical patch name (`PATCH_00N`)
+    pub name: &'static str,
+    /// i18n key describing the change
+    pub description: &'static str,
+    pub up: Step,
+    /// Reverts `up`; `None` if the migration cannot be rolled back
+    pub down: Option<Step>,
+}
+
+/// All migrations, in version order. The last entry is the newest schema this
+/// binary supports.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "PATCH_001",
+        description: "db.migration.desc.001",
+        up: patch_001_create_books_table,
+        down: None,
+    },
+    Migration {
+        version: 2,
+        name: "PATCH_002",
+        description: "db.migration.desc.002",
+        up: patch_002_add_extra_fields,
+        down: Some(patch_002_down),
+    },
+    Migration {
+        version: 3,
+        name: "PATCH_003",
+        description: "db.migration.desc.003",
+        up: patch_003_add_unique_index_books_isbn,
+        down: Some(patch_003_down),
+    },
+    Migration {
+        version: 4,
+        name: "PATCH_004",
+        description: "db.migration.desc.004",
+        up: patch_004_create_book_history,
+        down: Some(patch_004_down),
+    },
+    Migration {
+        version: 5,
+        name: "PATCH_005",
+        description: "db.migration.desc.005",
+        up: patch_005_add_deleted_at,
+        down: Some(patch_005_down),
+    },
+    Migration {
+        version: 6,
+        name: "PATCH_006",
+        description: "db.migration.desc.006",
+        up: patch_006_structured_log,
+        down: Some(patch_006_down),
+    },
+    Migration {
+        version: 7,
+        name: "PATCH_007",
+        description: "db.migration.desc.007",
+        up: patch_007_reading_data,
+        down: Some(patch_007_down),
+    },
+    Migration {
+        version: 8,
+        name: "PATCH_008",
+        description: "db.migration.desc.008",
+        up: patch_008_add_format,
+        down: Some(patch_008_down),
+    },
+    Migration {
+        version: 9,
+        name: "PATCH_009",
+        description: "db.migration.desc.009",
+        up: patch_009_optional_isbn,
+        down: Some(patch_009_down),
+    },
+];
+
+/// Newest schema version supported by this binary.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
 
 /// Represents the outcome of a database migration run.
 pub enum MigrationResult {
@@ -10,74 +108,369 @@ pub enum MigrationResult {
     Applied(Vec<String>),
 }
 
-/// Runs all pending database migrations.
-pub fn run_migrations(conn: &Connection) -> Result<MigrationResult> {
-    let mut applied_patches = Vec::new();
+/// One step of a migration plan.
+#[derive(Clone, Copy)]
+pub enum MigrationStep {
+    Up(&'static Migration),
+    Down(&'static Migration),
+}
 
-    // Ensure log table exists
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS log (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            date TEXT NOT NULL,
-            operation TEXT NOT NULL,
-            target TEXT DEFAULT '',
-            message TEXT NOT NULL
+impl MigrationStep {
+    pub fn migration(&self) -> &'static Migration {
+        match self {
+            MigrationStep::Up(m) | MigrationStep::Down(m) => m,
+        }
+    }
+}
+
+/// Status of a migration for `db migrate --status`.
+pub struct MigrationStatus {
+    pub migration: &'static Migration,
+    /// `None` if the migration is pending
+    pub applied_at: Option<String>,
+}
+
+/// Errors raised while planning or applying migrations.
+#[derive(Debug)]
+pub enum MigrationError {
+    Sqlite(rusqlite::Error),
+    /// The database was written by a newer Librius
+    SchemaTooNew {
+        found: u32,
+        supported: u32,
+    },
+    /// `--to` outside the known versions
+    UnknownVersion {
+        target: u32,
+        latest: u32,
+    },
+    /// Rollback requested through a migration without a `down` step
+    Irreversible {
+        version: u32,
+        name: &'static str,
+    },
+    /// The database was rolled back on purpose with `db migrate --to`
+    RolledBack {
+        version: u32,
+        latest: u32,
+    },
+    /// The safety snapshot taken before a rollback could not be written
+    Snapshot(std::io::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Sqlite(e) => write!(f, "{}", e),
+            MigrationError::SchemaTooNew { found, supported } => f.write_str(&tr_with(
+                "db.migrate.schema_too_new",
+                &[
+                    ("found", &found.to_string()),
+                    ("supported", &supported.to_string()),
+                ],
+            )),
+            MigrationError::UnknownVersion { target, latest } => f.write_str(&tr_with(
+                "db.migrate.unknown_version",
+                &[
+                    ("target", &target.to_string()),
+                    ("latest", &latest.to_string()),
+                ],
+            )),
+            MigrationError::Irreversible { version, name } => f.write_str(&tr_with(
+                "db.migrate.irreversible",
+                &[("version", &version.to_string()), ("name", name)],
+            )),
+            MigrationError::RolledBack { version, latest } => f.write_str(&tr_with(
+                "db.migrate.rolled_back",
+                &[
+                    ("version", &version.to_string()),
+                    ("latest", &latest.to_string()),
+                ],
+            )),
+            MigrationError::Snapshot(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+/// Creates `schema_migrations` if missing. On first creation, adopts the
+/// patches recorded in the `log` table by older Librius versions, so existing
+/// databases are not migrated twice.
+fn ensure_migrations_table(conn: &Connection) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations')",
+        [],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Ok(());
+    }
+
+    conn.execute_batch(
+        "CREATE TABLE schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL
         );",
+    )?;
+
+    let has_log: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'log')",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_log {
+        for m in MIGRATIONS {
+            conn.execute(
+                "INSERT INTO schema_migrations (version, name, applied_at)
+                 SELECT ?1, ?2, MIN(date) FROM log WHERE operation = ?2 HAVING COUNT(*) > 0",
+                params![m.version, m.name],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Current schema version (highest applied migration, 0 for an empty database).
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    ensure_migrations_table(conn)?;
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Fails with `SchemaTooNew` if the database was migrated by a newer binary.
+pub fn check_schema_version(conn: &Connection) -> std::result::Result<(), MigrationError> {
+    let found = current_version(conn)?;
+    let supported = latest_version();
+    if found > supported {
+        return Err(MigrationError::SchemaTooNew { found, supported });
+    }
+    Ok(())
+}
+
+/// Version the database was deliberately rolled back to, if any.
+///
+/// Set by [`migrate_to`] after a rollback and cleared once the database is
+/// migrated back to the latest version.
+pub fn pinned_version(conn: &Connection) -> Result<Option<u32>> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_pin')",
         [],
+        |row| row.get(0),
     )?;
+    if !exists {
+        return Ok(None);
+    }
+    conn.query_row("SELECT MAX(version) FROM schema_pin", [], |row| row.get(0))
+}
 
-    let patches = vec![
-        (
-            "PATCH_001",
-            patch_001_create_books_table as fn(&Connection) -> Result<()>,
-        ),
-        (
-            "PATCH_002",
-            patch_002_add_extra_fields as fn(&Connection) -> Result<()>,
-        ),
-        (
-            "PATCH_003",
-            patch_003_add_unique_index_books_isbn as fn(&Connection) -> Result<()>,
-        ),
-    ];
+fn set_pinned_version(conn: &Connection, version: Option<u32>) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS schema_pin;")?;
+    if let Some(v) = version {
+        conn.execute_batch("CREATE TABLE schema_pin (version INTEGER NOT NULL);")?;
+        conn.execute("INSERT INTO schema_pin (version) VALUES (?1)", [v])?;
+    }
+    Ok(())
+}
+
+/// Fails with `RolledBack` if the database was rolled back with
+/// `db migrate --to`, so that opening it does not silently migrate it up again.
+pub fn check_not_rolled_back(conn: &Connection) -> std::result::Result<(), MigrationError> {
+    match pinned_version(conn)? {
+        Some(version) => Err(MigrationError::RolledBack {
+            version,
+            latest: latest_version(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Applied/pending state of every known migration.
+pub fn migration_status(conn: &Connection) -> Result<Vec<MigrationStatus>> {
+    ensure_migrations_table(conn)?;
+    let mut stmt = conn.prepare("SELECT applied_at FROM schema_migrations WHERE version = ?1")?;
 
-    for (name, patch_fn) in patches {
-        if !is_patch_applied(conn, name)? {
+    MIGRATIONS
+        .iter()
+        .map(|m| {
+            let mut rows = stmt.query_map([m.version], |row| row.get::<_, String>(0))?;
+            Ok(MigrationStatus {
+                migration: m,
+                applied_at: rows.next().transpose()?,
+            })
+        })
+        .collect()
+}
+
+/// Names of the applied migrations, in version order.
+pub fn applied_migrations(conn: &Connection) -> Result<Vec<String>> {
+    Ok(migration_status(conn)?
+        .into_iter()
+        .filter(|s| s.applied_at.is_some())
+        .map(|s| s.migration.name.to_string())
+        .collect())
+}
+
+/// Steps needed to bring the database from its current version to `target`:
+/// pending `up` steps in ascending order, or `down` steps in descending order.
+pub fn plan_migration(
+    conn: &Connection,
+    target: u32,
+) -> std::result::Result<Vec<MigrationStep>, MigrationError> {
+    check_schema_version(conn)?;
+    let latest = latest_version();
+    if target > latest {
+        return Err(MigrationError::UnknownVersion { target, latest });
+    }
+
+    let applied: Vec<bool> = migration_status(conn)?
+        .iter()
+        .map(|s| s.applied_at.is_some())
+        .collect();
+
+    let mut steps = Vec::new();
+    // Up: ogni migrazione non applicata fino al target (anche eventuali "buchi")
+    for (m, done) in MIGRATIONS.iter().zip(&applied) {
+        if m.version <= target && !done {
+            steps.push(MigrationStep::Up(m));
+        }
+    }
+    // Down: migrazioni applicate oltre il target, dalla più recente
+    for (m, done) in MIGRATIONS.iter().zip(&applied).rev() {
+        if m.version > target && *done {
+            if m.down.is_none() {
+                return Err(MigrationError::Irreversible {
+                    version: m.version,
+                    name: m.name,
+                });
+            }
+            steps.push(MigrationStep::Down(m));
+        }
+    }
+    Ok(steps)
+}
+
+/// Applies one step inside a transaction, together with its
+/// `schema_migrations` bookkeeping.
+pub fn apply_step(conn: &Connection, step: MigrationStep) -> Result<()> {
+    let m = step.migration();
+    let tx = conn.unchecked_transaction()?;
+
+    match step {
+        MigrationStep::Up(m) => {
+            print_info(
+                &tr_with("db.patch.applying", &[("patch", m.name)]),
+                is_verbose(),
+            );
+            (m.up)(conn)?;
+            conn.execute(
+                "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
+                params![m.version, m.name, now_str()],
+            )?;
+        }
+        MigrationStep::Down(m) => {
             print_info(
-                &tr_with("db.patch,applying", &[("patch", name)]),
+                &tr_with("db.patch.reverting", &[("patch", m.name)]),
                 is_verbose(),
             );
-            patch_fn(conn)?;
-            applied_patches.push(name.to_string());
-            record_patch(conn, name, "DB", "Migration applied successfully")?;
+            if let Some(down) = m.down {
+                down(conn)?;
+            }
+            conn.execute(
+                "DELETE FROM schema_migrations WHERE version = ?1",
+                [m.version],
+            )?;
         }
     }
 
-    if applied_patches.is_empty() {
-        Ok(MigrationResult::None)
-    } else {
-        write_log(
-            conn,
-            "MIGRATIONS_COMPLETED",
-            "DB",
-            &tr("db.patch.all_applied"),
-        )?;
-        print_ok(&tr("db.patch.all_applied"), is_verbose());
-        Ok(MigrationResult::Applied(applied_patches))
-    }
+    tx.commit()?;
+
+    let operation = match step {
+        MigrationStep::Up(_) => "DB_MIGRATION_UP",
+        MigrationStep::Down(_) => "DB_MIGRATION_DOWN",
+    };
+    write_log(conn, operation, "DB", m.name)?;
+    Ok(())
 }
 
-/// Checks if a migration patch was already applied.
-fn is_patch_applied(conn: &Connection, patch_name: &str) -> Result<bool> {
-    let mut stmt = conn.prepare("SELECT COUNT(*) FROM log WHERE operation = ?1")?;
-    let count: i64 = stmt.query_row([patch_name], |r| r.get(0))?;
-    Ok(count > 0)
+/// Migrates the database to `target`, returning the steps that were applied.
+///
+/// A rollback takes an automatic snapshot first, since `down` steps may drop
+/// data, and pins the database at `target` until it is migrated back to the
+/// latest version.
+pub fn migrate_to(
+    conn: &Connection,
+    target: u32,
+) -> std::result::Result<Vec<MigrationStep>, MigrationError> {
+    let steps = plan_migration(conn, target)?;
+    let rollback = steps.iter().any(|s| matches!(s, MigrationStep::Down(_)));
+    if rollback {
+        auto_snapshot(conn, "migrate").map_err(MigrationError::Snapshot)?;
+    }
+
+    for step in &steps {
+        if let Err(e) = apply_step(conn, *step) {
+            print_err(&tr_with(
+                "db.migrate.step_failed",
+                &[("patch", step.migration().name), ("error", &e.to_string())],
+            ));
+            return Err(e.into());
+        }
+    }
+
+    if target == latest_version() {
+        set_pinned_version(conn, None)?;
+    } else if rollback || pinned_version(conn)?.is_some() {
+        set_pinned_version(conn, Some(target))?;
+    }
+    Ok(steps)
 }
 
-/// Records a migration into the log table.
-fn record_patch(conn: &Connection, operation: &str, target: &str, message: &str) -> Result<()> {
-    write_log(conn, operation, target, message)?;
-    Ok(())
+/// Runs all pending database migrations.
+///
+/// Refuses with `RolledBack` if the database was deliberately rolled back.
+pub fn run_migrations(conn: &Connection) -> std::result::Result<MigrationResult, MigrationError> {
+    // Ensure log table exists
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            date TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            target TEXT DEFAULT '',
+            message TEXT NOT NULL
+        );",
+        [],
+    )?;
+
+    check_not_rolled_back(conn)?;
+    let applied = migrate_to(conn, latest_version())?;
+    if applied.is_empty() {
+        return Ok(MigrationResult::None);
+    }
+
+    write_log(
+        conn,
+        "MIGRATIONS_COMPLETED",
+        "DB",
+        &tr("db.patch.all_applied"),
+    )?;
+    print_ok(&tr("db.patch.all_applied"), is_verbose());
+    Ok(MigrationResult::Applied(
+        applied
+            .iter()
+            .map(|s| s.migration().name.to_string())
+            .collect(),
+    ))
 }
 
 /// First migration: initial books table.
@@ -148,6 +541,7 @@ fn patch_002_add_extra_fields(conn: &Connection) -> Result<()> {
                         "db.add.column_failed",
                         &[("column", col), ("error", &e.to_string())],
                     ));
+                    return Err(e);
                 }
             }
         }
@@ -199,3 +593,168 @@ fn patch_003_add_unique_index_books_isbn(conn: &Connection) -> Result<()> {
         }
     }
 }
+
+/// Migrazione: tabella `book_history` con snapshot JSON prima/dopo
+/// per ogni insert, update e delete (usata da `history` e `undo`).
+fn patch_004_create_book_history(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS book_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            book_id INTEGER NOT NULL,
+            operation TEXT NOT NULL,
+            before_json TEXT,
+            after_json TEXT,
+            created_at TEXT NOT NULL,
+            undone_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_book_history_book_id ON book_history(book_id);",
+    )?;
+    print_ok(&tr("db.migrate.history_created"), is_verbose());
+    Ok(())
+}
+
+/// Migrazione: colonna `deleted_at` per il cestino (soft delete).
+/// I libri con `deleted_at` valorizzato sono esclusi da list/search/export.
+fn patch_005_add_deleted_at(conn: &Connection) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM pragma_table_info('books') WHERE name = 'deleted_at')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    if !exists {
+        conn.execute("ALTER TABLE books ADD COLUMN deleted_at TEXT", [])?;
+        print_ok(
+            &tr_with("db.column.added", &[("column", "deleted_at")]),
+            is_verbose(),
+        );
+    }
+    Ok(())
+}
+
+/// Migrazione: log strutturato, con il libro coinvolto (`book_id`) e i
+/// dettagli dell'operazione in JSON (`details`).
+fn patch_006_structured_log(conn: &Connection) -> Result<()> {
+    // La tabella `log` può non esistere ancora (la crea `write_log`)
+    create_log_table(conn)?;
+    for (col, typ) in [("book_id", "INTEGER"), ("details", "TEXT")] {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info('log') WHERE name = ?1)",
+            [col],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute_batch(&format!("ALTER TABLE log ADD COLUMN {} {};", col, typ))?;
+            print_ok(
+                &tr_with("db.column.added", &[("column", col)]),
+                is_verbose(),
+            );
+        }
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_log_book_id ON log(book_id);")
+}
+
+/// Migrazione: dati di lettura dei libri (valutazione, stato, data di
+/// lettura, tag e recensione), importati ad esempio da Goodreads.
+fn patch_007_reading_data(conn: &Connection) -> Result<()> {
+    for (col, typ) in READING_COLUMNS {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info('books') WHERE name = ?1)",
+            [col],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute_batch(&format!("ALTER TABLE books ADD COLUMN {} {};", col, typ))?;
+            print_ok(
+                &tr_with("db.column.added", &[("column", col)]),
+                is_verbose(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Colonne aggiunte da `PATCH_007`.
+const READING_COLUMNS: [(&str, &str); 5] = [
+    ("rating", "INTEGER"),
+    ("read_status", "TEXT"),
+    ("read_at", "TEXT"),
+    ("tags", "TEXT"),
+    ("review", "TEXT"),
+];
+
+/// Migrazione: formato del libro (ad esempio `ebook` per i libri importati
+/// da Calibre); `NULL` indica un libro cartaceo.
+fn patch_008_add_format(conn: &Connection) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM pragma_table_info('books') WHERE name = 'format')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch("ALTER TABLE books ADD COLUMN format TEXT;")?;
+        print_ok(
+            &tr_with("db.column.added", &[("column", "format")]),
+            is_verbose(),
+        );
+    }
+    Ok(())
+}
+
+/// Migrazione: l'indice UNIQUE su ISBN ignora i libri senza ISBN, così
+/// un import può aggiungerne più d'uno.
+fn patch_009_optional_isbn(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_books_isbn;
+         CREATE UNIQUE INDEX idx_books_isbn ON books(isbn) WHERE isbn <> '';",
+    )
+}
+
+// --- down steps ---
+
+fn patch_002_down(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE books DROP COLUMN language;
+         ALTER TABLE books DROP COLUMN pages;
+         ALTER TABLE books DROP COLUMN genre;
+         ALTER TABLE books DROP COLUMN summary;",
+    )
+}
+
+fn patch_003_down(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP INDEX IF EXISTS idx_books_isbn;")
+}
+
+fn patch_004_down(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS book_history;")
+}
+
+fn patch_005_down(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE books DROP COLUMN deleted_at;")
+}
+
+fn patch_006_down(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_log_book_id;
+         ALTER TABLE log DROP COLUMN details;
+         ALTER TABLE log DROP COLUMN book_id;",
+    )
+}
+
+fn patch_007_down(conn: &Connection) -> Result<()> {
+    for (col, _) in READING_COLUMNS.iter().rev() {
+        conn.execute_batch(&format!("ALTER TABLE books DROP COLUMN {};", col))?;
+    }
+    Ok(())
+}
+
+fn patch_008_down(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE books DROP COLUMN format;")
+}
+
+fn patch_009_down(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_books_isbn;
+         CREATE UNIQUE INDEX idx_books_isbn ON books(isbn);",
+    )
+}
@@ -5,8 +5,22 @@
 
 pub mod books;
 pub mod connection;
+pub mod history;
+pub mod log;
+pub mod maintenance;
 pub mod migrations;
+pub mod trash;
 
-pub use books::{get_book_fields, search_books, update_book_by_id, update_book_by_isbn};
-pub use connection::{ensure_schema, get_db_path, init_db, start_db};
-pub use migrations::{MigrationResult, run_migrations};
+pub use books::{
+    BOOK_COLUMNS, find_book, find_book_by_id, find_book_without_isbn, get_book_fields, insert_book,
+    overwrite_book, search_books, update_book_by_id, update_book_by_isbn,
+};
+pub use connection::{ensure_schema, get_db_path, init_db, open_db, start_db};
+pub use history::{
+    get_history, last_undoable, record_delete, record_history, record_insert, undo_entry,
+};
+pub use log::{LogFilter, count_log_before, list_log, log_stats, prune_log};
+pub use migrations::{
+    MigrationError, MigrationResult, current_version, latest_version, migrate_to, run_migrations,
+};
+pub use trash::{list_trash, purge_book, restore_book, trash_book, trashed_id};
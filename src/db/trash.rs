@@ -0,0 +1,93 @@
+//! Trash bin (soft delete) for books.
+//!
+//! `del` sets `books.deleted_at` instead of removing the row; trashed books are
+//! hidden from `list`, `search` and `export` until restored or purged.
+
+use crate::db::books::{BOOK_COLUMNS, set_deleted_at};
+use crate::db::history::{record_delete, record_history};
+use crate::models::{Book, HistoryOperation, TrashedBook};
+use crate::utils::now_str;
+use rusqlite::{Connection, OptionalExtension, Result};
+
+/// Moves an active book to the trash and records the operation.
+/// Returns `false` if the book was already trashed.
+pub fn trash_book(conn: &Connection, book: &Book) -> Result<bool> {
+    let Some(id) = book.id.map(i64::from) else {
+        return Ok(false);
+    };
+    let affected = conn.execute(
+        "UPDATE books SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        rusqlite::params![now_str(), id],
+    )?;
+    if affected > 0 {
+        record_history(conn, HistoryOperation::Trash, id, Some(book), None)?;
+    }
+    Ok(affected > 0)
+}
+
+/// Takes a book out of the trash. Returns `false` if it is not in the trash.
+pub fn restore_book(conn: &Connection, id: i64) -> Result<bool> {
+    let Some(trashed) = find_trashed(conn, id)? else {
+        return Ok(false);
+    };
+    set_deleted_at(conn, id, None)?;
+    record_history(
+        conn,
+        HistoryOperation::Restore,
+        id,
+        None,
+        Some(&trashed.book),
+    )?;
+    Ok(true)
+}
+
+/// Permanently deletes a book (trashed or not) and records the operation,
+/// together with its trash date if it was in the trash.
+pub fn purge_book(conn: &Connection, book: &Book) -> Result<bool> {
+    let Some(id) = book.id.map(i64::from) else {
+        return Ok(false);
+    };
+    let deleted_at: Option<String> = conn
+        .query_row("SELECT deleted_at FROM books WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .optional()?
+        .flatten();
+    let affected = conn.execute("DELETE FROM books WHERE id = ?1", [id])?;
+    if affected > 0 {
+        record_delete(conn, id, book, deleted_at.as_deref())?;
+    }
+    Ok(affected > 0)
+}
+
+/// Returns every trashed book, most recently trashed first.
+pub fn list_trash(conn: &Connection) -> Result<Vec<TrashedBook>> {
+    let sql = format!(
+        "SELECT {}, deleted_at FROM books WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id",
+        BOOK_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([], TrashedBook::from_row)?;
+    rows.collect()
+}
+
+/// Fetches a book by id only if it is in the trash.
+pub fn find_trashed(conn: &Connection, id: i64) -> Result<Option<TrashedBook>> {
+    let sql = format!(
+        "SELECT {}, deleted_at FROM books WHERE id = ?1 AND deleted_at IS NOT NULL",
+        BOOK_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query_map([id], TrashedBook::from_row)?;
+    rows.next().transpose()
+}
+
+/// ID of the trashed book matching `key` (an ID, or an ISBN when `is_isbn`).
+pub fn trashed_id(conn: &Connection, key: &str, is_isbn: bool) -> Result<Option<i64>> {
+    let column = if is_isbn { "isbn" } else { "id" };
+    let sql = format!(
+        "SELECT id FROM books WHERE {} = ?1 AND deleted_at IS NOT NULL",
+        column
+    );
+    conn.query_row(&sql, [key], |row| row.get(0)).optional()
+}
@@ -0,0 +1,595 @@
+//! Consistency check between the source code and the locale files, used by
+//! `librius dev i18n-check` and by the test suite.
+//!
+//! The sources under `src/` are scanned for `tr`, `tr_with`, `tr_fmt` and
+//! `tr_s` calls with a literal key. Any other string literal equal to a key
+//! counts as a use too (keys kept in tables or struct fields), and a
+//! `format!("prefix.{}", ..)` key marks every key starting with `prefix.` as
+//! used.
+//!
+//! Errors:
+//! - a key used in the code is missing from an embedded locale;
+//! - a message is not valid (unbalanced braces, plural without `other`);
+//! - a translation does not use the same placeholders as English;
+//! - a call does not pass an argument the English message needs.
+//!
+//! Warnings: keys never used, keys only present in a translation and, for the
+//! extra locale files given to the check, keys left untranslated.
+
+use crate::i18n::format::message_arguments;
+use crate::i18n::loader::{FALLBACK_LANGUAGE, parse_json_to_map, tr_fmt, tr_with};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Functions taking a translation key as first argument.
+const TR_FUNCTIONS: [&str; 4] = ["tr", "tr_with", "tr_fmt", "tr_s"];
+
+/// A problem found by [`check_translations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum I18nIssue {
+    /// A locale file cannot be read or is not valid JSON
+    InvalidLocale { locale: String, error: String },
+    /// A key used in the code is missing from a locale
+    MissingKey {
+        key: String,
+        locale: String,
+        file: PathBuf,
+        line: usize,
+    },
+    /// A message cannot be parsed
+    InvalidMessage {
+        key: String,
+        locale: String,
+        error: String,
+    },
+    /// A translation uses other placeholders than the English message
+    PlaceholderMismatch {
+        key: String,
+        locale: String,
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    /// A call does not pass an argument used by the English message
+    MissingArgument {
+        key: String,
+        argument: String,
+        file: PathBuf,
+        line: usize,
+    },
+    /// An English key never used in the code
+    UnusedKey { key: String },
+    /// A key of a translation that English does not have
+    UnknownKey { key: String, locale: String },
+    /// English keys missing from an extra locale file (English is used)
+    Untranslated { locale: String, count: usize },
+}
+
+impl I18nIssue {
+    /// True for the problems that make the check fail.
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            I18nIssue::UnusedKey { .. }
+                | I18nIssue::UnknownKey { .. }
+                | I18nIssue::Untranslated { .. }
+        )
+    }
+}
+
+impl fmt::Display for I18nIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = |file: &Path, line: &usize| format!("{}:{}", file.display(), line);
+        let text = match self {
+            I18nIssue::InvalidLocale { locale, error } => tr_with(
+                "dev.i18n.invalid_locale",
+                &[("locale", locale), ("error", error)],
+            ),
+            I18nIssue::MissingKey {
+                key,
+                locale,
+                file,
+                line,
+            } => tr_with(
+                "dev.i18n.missing_key",
+                &[("key", key), ("locale", locale), ("at", &at(file, line))],
+            ),
+            I18nIssue::InvalidMessage { key, locale, error } => tr_with(
+                "dev.i18n.invalid_message",
+                &[("key", key), ("locale", locale), ("error", error)],
+            ),
+            I18nIssue::PlaceholderMismatch {
+                key,
+                locale,
+                expected,
+                found,
+            } => tr_with(
+                "dev.i18n.placeholder_mismatch",
+                &[
+                    ("key", key),
+                    ("locale", locale),
+                    ("expected", &expected.join(", ")),
+                    ("found", &found.join(", ")),
+                ],
+            ),
+            I18nIssue::MissingArgument {
+                key,
+                argument,
+                file,
+                line,
+            } => tr_with(
+                "dev.i18n.missing_argument",
+                &[
+                    ("key", key),
+                    ("argument", argument),
+                    ("at", &at(file, line)),
+                ],
+            ),
+            I18nIssue::UnusedKey { key } => tr_with("dev.i18n.unused_key", &[("key", key)]),
+            I18nIssue::UnknownKey { key, locale } => {
+                tr_with("dev.i18n.unknown_key", &[("key", key), ("locale", locale)])
+            }
+            I18nIssue::Untranslated { locale, count } => tr_fmt(
+                "dev.i18n.untranslated",
+                &[("locale", locale.into()), ("count", (*count).into())],
+            ),
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Result of [`check_translations`].
+#[derive(Debug, Clone, Default)]
+pub struct I18nReport {
+    /// Number of source files scanned
+    pub files: usize,
+    /// Distinct keys passed to the translation functions
+    pub keys_used: usize,
+    /// Locales checked, English first
+    pub locales: Vec<String>,
+    pub issues: Vec<I18nIssue>,
+}
+
+impl I18nReport {
+    pub fn errors(&self) -> impl Iterator<Item = &I18nIssue> {
+        self.issues.iter().filter(|i| i.is_error())
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &I18nIssue> {
+        self.issues.iter().filter(|i| !i.is_error())
+    }
+}
+
+/// A translation call with a literal key.
+struct KeyUse {
+    key: String,
+    file: PathBuf,
+    line: usize,
+    /// Argument names passed, `None` when they are not a literal array
+    args: Option<Vec<String>>,
+}
+
+/// Everything the sources say about the keys.
+#[derive(Default)]
+struct SourceScan {
+    files: usize,
+    uses: Vec<KeyUse>,
+    literals: BTreeSet<String>,
+    prefixes: BTreeSet<String>,
+}
+
+/// Checks the sources under `root/src` against the locales embedded from
+/// `root/src/i18n/locales`, plus the `extra` locale files (named after their
+/// language code, like the external locales).
+pub fn check_translations(root: &Path, extra: &[PathBuf]) -> io::Result<I18nReport> {
+    let locales_dir = root.join("src").join("i18n").join("locales");
+    let english = read_locale(&locales_dir.join(format!("{}.json", FALLBACK_LANGUAGE)))?;
+
+    let mut scan = SourceScan::default();
+    scan_dir(root, &root.join("src"), &mut scan)?;
+
+    let mut issues = Vec::new();
+    let mut locales = vec![(FALLBACK_LANGUAGE.to_string(), english.clone(), true)];
+    let mut embedded: Vec<PathBuf> = fs::read_dir(&locales_dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    embedded.sort();
+    let files = embedded
+        .into_iter()
+        .map(|p| (p, true))
+        .chain(extra.iter().map(|p| (p.clone(), false)));
+    for (path, strict) in files {
+        let code = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        if strict && code == FALLBACK_LANGUAGE {
+            continue;
+        }
+        match read_locale(&path) {
+            Ok(keys) => locales.push((code, keys, strict)),
+            Err(e) => issues.push(I18nIssue::InvalidLocale {
+                locale: path.display().to_string(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    // Chiavi usate nel codice: devono esistere, con gli argomenti richiesti
+    let mut reported = BTreeSet::new();
+    for usage in &scan.uses {
+        for (code, keys, strict) in &locales {
+            if *strict
+                && !keys.contains_key(&usage.key)
+                && reported.insert((usage.key.clone(), code.clone()))
+            {
+                issues.push(I18nIssue::MissingKey {
+                    key: usage.key.clone(),
+                    locale: code.clone(),
+                    file: usage.file.clone(),
+                    line: usage.line,
+                });
+            }
+        }
+        let (Some(passed), Some(message)) = (&usage.args, english.get(&usage.key)) else {
+            continue;
+        };
+        for argument in message_arguments(message).unwrap_or_default() {
+            if !passed.contains(&argument) {
+                issues.push(I18nIssue::MissingArgument {
+                    key: usage.key.clone(),
+                    argument,
+                    file: usage.file.clone(),
+                    line: usage.line,
+                });
+            }
+        }
+    }
+
+    // Messaggi validi e stessi segnaposto dell'inglese
+    let mut expected = BTreeMap::new();
+    for (code, keys, strict) in &locales {
+        for (key, message) in keys {
+            let found = match message_arguments(message) {
+                Ok(found) => found,
+                Err(error) => {
+                    issues.push(I18nIssue::InvalidMessage {
+                        key: key.clone(),
+                        locale: code.clone(),
+                        error,
+                    });
+                    continue;
+                }
+            };
+            if code == FALLBACK_LANGUAGE {
+                expected.insert(key.clone(), found);
+                continue;
+            }
+            match expected.get(key) {
+                Some(args) if *args != found => issues.push(I18nIssue::PlaceholderMismatch {
+                    key: key.clone(),
+                    locale: code.clone(),
+                    expected: args.clone(),
+                    found,
+                }),
+                Some(_) => {}
+                None if english.contains_key(key) => {}
+                None => issues.push(I18nIssue::UnknownKey {
+                    key: key.clone(),
+                    locale: code.clone(),
+                }),
+            }
+        }
+        let untranslated = english.keys().filter(|k| !keys.contains_key(*k)).count();
+        if !strict && untranslated > 0 {
+            issues.push(I18nIssue::Untranslated {
+                locale: code.clone(),
+                count: untranslated,
+            });
+        }
+    }
+
+    // Chiavi inglesi mai usate
+    let used: BTreeSet<&str> = scan.uses.iter().map(|u| u.key.as_str()).collect();
+    for key in english.keys() {
+        let is_used = used.contains(key.as_str())
+            || scan.literals.contains(key)
+            || scan.prefixes.iter().any(|p| key.starts_with(p.as_str()));
+        if !is_used {
+            issues.push(I18nIssue::UnusedKey { key: key.clone() });
+        }
+    }
+
+    Ok(I18nReport {
+        files: scan.files,
+        keys_used: used.len(),
+        locales: locales.into_iter().map(|(code, _, _)| code).collect(),
+        issues,
+    })
+}
+
+/// Keys of a locale file, sorted.
+fn read_locale(path: &Path) -> io::Result<BTreeMap<String, String>> {
+    let content = fs::read_to_string(path)?;
+    Ok(parse_json_to_map(&content)?.into_iter().collect())
+}
+
+/// Scans the `.rs` files under `dir`, recording paths relative to `root`.
+fn scan_dir(root: &Path, dir: &Path, scan: &mut SourceScan) -> io::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            scan_dir(root, &path, scan)?;
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            let source = fs::read_to_string(&path)?;
+            let file = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            scan_source(&file, &source, scan);
+            scan.files += 1;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+}
+
+fn scan_source(file: &Path, source: &str, scan: &mut SourceScan) {
+    let tokens = tokenize(source);
+    let token = |i: usize| tokens.get(i).map(|(t, _)| t);
+    let is_punct = |i: usize, c: char| token(i) == Some(&Token::Punct(c));
+
+    for (i, (tok, line)) in tokens.iter().enumerate() {
+        match tok {
+            Token::Str(s) => {
+                scan.literals.insert(s.clone());
+            }
+            Token::Ident(name) if TR_FUNCTIONS.contains(&name.as_str()) && is_punct(i + 1, '(') => {
+                let mut j = i + 2;
+                if is_punct(j, '&') {
+                    j += 1;
+                }
+                if let Some(Token::Str(key)) = token(j) {
+                    let args = match name.as_str() {
+                        "tr" | "tr_s" => Some(Vec::new()),
+                        _ => argument_names(&tokens, j + 1),
+                    };
+                    scan.uses.push(KeyUse {
+                        key: key.clone(),
+                        file: file.to_path_buf(),
+                        line: *line,
+                        args,
+                    });
+                } else if token(j) == Some(&Token::Ident("format".into()))
+                    && is_punct(j + 1, '!')
+                    && is_punct(j + 2, '(')
+                    && let Some(Token::Str(pattern)) = token(j + 3)
+                {
+                    let prefix = pattern.split('{').next().unwrap_or_default();
+                    if !prefix.is_empty() {
+                        scan.prefixes.insert(prefix.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Names in a literal `&[("name", value), ...]` array starting after the
+/// key at `start`; `None` if the arguments are built elsewhere.
+fn argument_names(tokens: &[(Token, usize)], start: usize) -> Option<Vec<String>> {
+    let mut i = start;
+    if tokens.get(i)?.0 != Token::Punct(',') {
+        return None;
+    }
+    i += 1;
+    if tokens.get(i)?.0 == Token::Punct('&') {
+        i += 1;
+    }
+    if tokens.get(i)?.0 != Token::Punct('[') {
+        return None;
+    }
+
+    let mut names = Vec::new();
+    let mut depth = 0usize;
+    while let Some((tok, _)) = tokens.get(i) {
+        match tok {
+            Token::Punct('(' | '[' | '{') => {
+                depth += 1;
+                if depth == 2
+                    && *tok == Token::Punct('(')
+                    && let Some((Token::Str(name), _)) = tokens.get(i + 1)
+                {
+                    names.push(name.clone());
+                }
+            }
+            Token::Punct(')' | ']' | '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(names);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Splits Rust source into identifiers, string literals and punctuation,
+/// with their line; comments, numbers and char literals are skipped.
+fn tokenize(source: &str) -> Vec<(Token, usize)> {
+    let chars: Vec<char> = source.chars().collect();
+    let at = |i: usize| chars.get(i).copied().unwrap_or('\0');
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            '/' if at(i + 1) == '/' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if at(i + 1) == '*' => {
+                let mut depth = 0;
+                while i < chars.len() {
+                    if chars[i] == '/' && at(i + 1) == '*' {
+                        depth += 1;
+                        i += 2;
+                    } else if chars[i] == '*' && at(i + 1) == '/' {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        if chars[i] == '\n' {
+                            line += 1;
+                        }
+                        i += 1;
+                    }
+                }
+            }
+            '"' => {
+                let start = line;
+                let (text, end) = string_literal(&chars, i + 1, &mut line);
+                tokens.push((Token::Str(text), start));
+                i = end;
+            }
+            '\'' => {
+                // Carattere ('x', '\n') oppure lifetime ('a)
+                if at(i + 1) == '\\' {
+                    i += 3;
+                    while i < chars.len() && chars[i] != '\'' {
+                        i += 1;
+                    }
+                    i += 1;
+                } else if at(i + 2) == '\'' {
+                    i += 3;
+                } else {
+                    i += 1;
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                match (ident.as_str(), at(i)) {
+                    ("r" | "br", '"' | '#') => {
+                        let hashes = chars[i..].iter().take_while(|c| **c == '#').count();
+                        if at(i + hashes) == '"' {
+                            let start = line;
+                            let (text, end) =
+                                raw_literal(&chars, i + hashes + 1, hashes, &mut line);
+                            tokens.push((Token::Str(text), start));
+                            i = end;
+                        } else {
+                            tokens.push((Token::Ident(ident), line));
+                        }
+                    }
+                    ("b", '"') => {
+                        let start = line;
+                        let (text, end) = string_literal(&chars, i + 1, &mut line);
+                        tokens.push((Token::Str(text), start));
+                        i = end;
+                    }
+                    _ => tokens.push((Token::Ident(ident), line)),
+                }
+            }
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+            }
+            c => {
+                tokens.push((Token::Punct(c), line));
+                i += 1;
+            }
+        }
+    }
+    tokens
+}
+
+/// Content of a `"..."` literal starting at `i` (after the quote) and the
+/// index after the closing quote.
+fn string_literal(chars: &[char], mut i: usize, line: &mut usize) -> (String, usize) {
+    let mut text = String::new();
+    while i < chars.len() {
+        match chars[i] {
+            '"' => return (text, i + 1),
+            '\\' => {
+                match chars.get(i + 1) {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('\n') => {
+                        // Continuazione di riga: spazi iniziali ignorati
+                        *line += 1;
+                        i += 2;
+                        while i < chars.len() && chars[i].is_whitespace() {
+                            if chars[i] == '\n' {
+                                *line += 1;
+                            }
+                            i += 1;
+                        }
+                        continue;
+                    }
+                    Some(c) => text.push(*c),
+                    None => {}
+                }
+                i += 2;
+            }
+            c => {
+                if c == '\n' {
+                    *line += 1;
+                }
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+    (text, i)
+}
+
+/// Content of a raw literal closed by `"` and `hashes` `#` characters.
+fn raw_literal(chars: &[char], mut i: usize, hashes: usize, line: &mut usize) -> (String, usize) {
+    let start = i;
+    while i < chars.len() {
+        if chars[i] == '"'
+            && chars[i + 1..]
+                .iter()
+                .take(hashes)
+                .filter(|c| **c == '#')
+                .count()
+                == hashes
+        {
+            let text = chars[start..i].iter().collect();
+            return (text, i + 1 + hashes);
+        }
+        if chars[i] == '\n' {
+            *line += 1;
+        }
+        i += 1;
+    }
+    (chars[start..].iter().collect(), i)
+}
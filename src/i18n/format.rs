@@ -0,0 +1,449 @@
+//! Locale-aware message formatting.
+//!
+//! Messages use an ICU-style syntax on top of the plain `{name}` placeholders:
+//!
+//! - `{count, plural, one {# book} other {# books}}` picks a branch by the
+//!   plural category of `count` in the active language (`=0 {...}` matches an
+//!   exact value); `#` inside a branch is the formatted number.
+//! - `{count, number}`, `{when, date}`, `{when, time}` and `{when, datetime}`
+//!   force a formatter; without a style, numbers and dates use their default
+//!   one.
+//!
+//! Separators and date patterns come from the `format.*` keys of the locale,
+//! so external locale files can change them too.
+
+use crate::i18n::loader::{current_language, normalize_locale_code, tr};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::fmt::Write;
+
+/// CLDR plural categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    /// Name of the category as written in the messages.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Plural category of the integer `n` in `lang` (CLDR cardinal rules).
+///
+/// Languages without a specific rule use the English one (`one` for 1,
+/// `other` for everything else).
+pub fn plural_category(lang: &str, n: i64) -> PluralCategory {
+    use PluralCategory::*;
+
+    let lang = normalize_locale_code(lang);
+    let n = n.unsigned_abs();
+    let (mod10, mod100) = (n % 10, n % 100);
+    let slavic_few = (2..=4).contains(&mod10) && !(12..=14).contains(&mod100);
+
+    match lang.split('-').next().unwrap_or_default() {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" => Other,
+        "pt" if lang == "pt-PT" => {
+            if n == 1 {
+                One
+            } else {
+                Other
+            }
+        }
+        "fr" | "pt" | "hy" => {
+            if n <= 1 {
+                One
+            } else {
+                Other
+            }
+        }
+        "ru" | "uk" | "be" => match () {
+            _ if mod10 == 1 && mod100 != 11 => One,
+            _ if slavic_few => Few,
+            _ => Many,
+        },
+        "pl" => match () {
+            _ if n == 1 => One,
+            _ if slavic_few => Few,
+            _ => Many,
+        },
+        "cs" | "sk" => match n {
+            1 => One,
+            2..=4 => Few,
+            _ => Other,
+        },
+        "ar" => match (n, mod100) {
+            (0, _) => Zero,
+            (1, _) => One,
+            (2, _) => Two,
+            (_, 3..=10) => Few,
+            (_, 11..=99) => Many,
+            _ => Other,
+        },
+        _ => {
+            if n == 1 {
+                One
+            } else {
+                Other
+            }
+        }
+    }
+}
+
+/// A named argument of a message.
+#[derive(Debug, Clone)]
+pub enum FmtArg {
+    Text(String),
+    Int(i64),
+    Float(f64),
+    DateTime(DateTime<Local>),
+    Date(NaiveDate),
+}
+
+impl From<&str> for FmtArg {
+    fn from(s: &str) -> Self {
+        FmtArg::Text(s.to_string())
+    }
+}
+
+impl From<String> for FmtArg {
+    fn from(s: String) -> Self {
+        FmtArg::Text(s)
+    }
+}
+
+impl From<&String> for FmtArg {
+    fn from(s: &String) -> Self {
+        FmtArg::Text(s.clone())
+    }
+}
+
+macro_rules! int_arg {
+    ($($t:ty),*) => {
+        $(impl From<$t> for FmtArg {
+            fn from(n: $t) -> Self {
+                FmtArg::Int(i64::try_from(n).unwrap_or(i64::MAX))
+            }
+        })*
+    };
+}
+
+int_arg!(i32, i64, u32, u64, usize);
+
+impl From<f64> for FmtArg {
+    fn from(x: f64) -> Self {
+        FmtArg::Float(x)
+    }
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for FmtArg {
+    fn from(dt: DateTime<Tz>) -> Self {
+        FmtArg::DateTime(dt.with_timezone(&Local))
+    }
+}
+
+impl From<NaiveDateTime> for FmtArg {
+    /// A naive date and time is taken as local time.
+    fn from(dt: NaiveDateTime) -> Self {
+        Local
+            .from_local_datetime(&dt)
+            .earliest()
+            .map(FmtArg::DateTime)
+            .unwrap_or_else(|| FmtArg::Text(dt.to_string()))
+    }
+}
+
+impl From<NaiveDate> for FmtArg {
+    fn from(d: NaiveDate) -> Self {
+        FmtArg::Date(d)
+    }
+}
+
+impl FmtArg {
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            FmtArg::Int(n) => Some(*n),
+            FmtArg::Float(x) if x.fract() == 0.0 => Some(*x as i64),
+            FmtArg::Text(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn format(&self, style: Option<&str>) -> String {
+        match (self, style) {
+            (FmtArg::DateTime(dt), Some("date")) => format_date(dt.date_naive()),
+            (FmtArg::DateTime(dt), Some("time")) => format_time(dt),
+            (FmtArg::DateTime(dt), _) => format_datetime(dt),
+            (FmtArg::Date(d), _) => format_date(*d),
+            (FmtArg::Int(n), _) => format_number(*n),
+            (FmtArg::Float(x), _) => format_decimal(*x, 1),
+            (FmtArg::Text(s), Some("number")) => s
+                .trim()
+                .parse()
+                .map(format_number)
+                .unwrap_or_else(|_| s.clone()),
+            (FmtArg::Text(s), _) => s.clone(),
+        }
+    }
+}
+
+/// Value of a `format.*` key of the active locale, or `default`.
+fn locale_setting(key: &str, default: &str) -> String {
+    let value = tr(key);
+    if value == key {
+        default.to_string()
+    } else {
+        value
+    }
+}
+
+fn group_digits(digits: &str, separator: &str) -> String {
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(separator);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Integer with the digit grouping of the active locale (`1,234` / `1.234`).
+pub fn format_number(n: i64) -> String {
+    let grouped = group_digits(
+        &n.unsigned_abs().to_string(),
+        &locale_setting("format.number.group", ","),
+    );
+    if n < 0 {
+        format!("-{}", grouped)
+    } else {
+        grouped
+    }
+}
+
+/// Decimal number with `digits` fractional digits and the separators of the
+/// active locale (`1,234.5` / `1.234,5`).
+pub fn format_decimal(x: f64, digits: usize) -> String {
+    let text = format!("{:.*}", digits, x.abs());
+    let (int, frac) = text.split_once('.').unwrap_or((&text, ""));
+    let mut out = String::new();
+    if x < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        out.push('-');
+    }
+    out.push_str(&group_digits(
+        int,
+        &locale_setting("format.number.group", ","),
+    ));
+    if !frac.is_empty() {
+        out.push_str(&locale_setting("format.number.decimal", "."));
+        out.push_str(frac);
+    }
+    out
+}
+
+/// Formats with the `pattern` of the locale, or `default` when the pattern is
+/// not a valid strftime string.
+fn format_with<T>(pattern_key: &str, default: &str, render: T) -> String
+where
+    T: Fn(&str, &mut String) -> std::fmt::Result,
+{
+    let pattern = locale_setting(pattern_key, default);
+    let mut out = String::new();
+    if render(&pattern, &mut out).is_ok() {
+        return out;
+    }
+    out.clear();
+    let _ = render(default, &mut out);
+    out
+}
+
+/// Date in the format of the active locale (`format.date`).
+pub fn format_date(date: NaiveDate) -> String {
+    format_with("format.date", "%Y-%m-%d", |p, out| {
+        write!(out, "{}", date.format(p))
+    })
+}
+
+/// Time of day in the format of the active locale (`format.time`).
+pub fn format_time<Tz: TimeZone>(dt: &DateTime<Tz>) -> String {
+    let local = dt.with_timezone(&Local);
+    format_with("format.time", "%H:%M:%S", |p, out| {
+        write!(out, "{}", local.format(p))
+    })
+}
+
+/// Date and time, in local time, in the format of the active locale
+/// (`format.datetime`).
+pub fn format_datetime<Tz: TimeZone>(dt: &DateTime<Tz>) -> String {
+    let local = dt.with_timezone(&Local);
+    format_with("format.datetime", "%Y-%m-%d %H:%M:%S", |p, out| {
+        write!(out, "{}", local.format(p))
+    })
+}
+
+/// Date and time stored as text (RFC 3339 or SQLite `CURRENT_TIMESTAMP`),
+/// formatted for the active locale; unknown formats are returned unchanged.
+pub fn format_timestamp(text: &str) -> String {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return format_datetime(&dt);
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+        .map(|dt| format_datetime(&dt.and_utc()))
+        .unwrap_or_else(|| text.to_string())
+}
+
+/// Index of the `}` closing the `{` at `open`.
+fn matching_brace(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Formats `pattern` with the named `args` (see the module documentation).
+///
+/// Placeholders without a matching argument are left as they are.
+pub fn format_message(pattern: &str, args: &[(&str, FmtArg)]) -> String {
+    render(pattern, args, None)
+}
+
+fn render(pattern: &str, args: &[(&str, FmtArg)], pound: Option<&str>) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    let mut i = 0;
+    while let Some(c) = pattern[i..].chars().next() {
+        if c == '{'
+            && let Some(end) = matching_brace(pattern, i)
+        {
+            match render_argument(&pattern[i + 1..end], args) {
+                Some(text) => out.push_str(&text),
+                None => out.push_str(&pattern[i..=end]),
+            }
+            i = end + 1;
+            continue;
+        }
+        match (c, pound) {
+            ('#', Some(number)) => out.push_str(number),
+            _ => out.push(c),
+        }
+        i += c.len_utf8();
+    }
+    out
+}
+
+fn render_argument(inner: &str, args: &[(&str, FmtArg)]) -> Option<String> {
+    let mut parts = inner.splitn(3, ',');
+    let name = parts.next()?.trim();
+    let (_, arg) = args.iter().find(|(n, _)| *n == name)?;
+
+    match parts.next().map(str::trim) {
+        None => Some(arg.format(None)),
+        Some("plural") => render_plural(parts.next().unwrap_or_default(), arg, args),
+        Some(style) => Some(arg.format(Some(style))),
+    }
+}
+
+/// Branches of a plural argument as `(selector, body)` pairs; `None` if the
+/// braces are not balanced.
+fn plural_branches(spec: &str) -> Option<Vec<(&str, &str)>> {
+    let mut branches = Vec::new();
+    let mut rest = spec.trim_start();
+    while !rest.is_empty() {
+        let open = rest.find('{')?;
+        let selector = rest[..open].trim();
+        let close = matching_brace(rest, open)?;
+        branches.push((selector, &rest[open + 1..close]));
+        rest = rest[close + 1..].trim_start();
+    }
+    Some(branches)
+}
+
+/// Picks the branch of a plural argument: an exact `=N` match first, then the
+/// plural category of the active language, then `other`.
+fn render_plural(spec: &str, arg: &FmtArg, args: &[(&str, FmtArg)]) -> Option<String> {
+    let n = arg.as_int();
+    let category = n.map(|n| plural_category(&current_language(), n));
+    let branches = plural_branches(spec)?;
+
+    let exact = n.map(|n| format!("={}", n));
+    let body = branches
+        .iter()
+        .find(|(s, _)| exact.as_deref() == Some(*s))
+        .or_else(|| category.and_then(|c| branches.iter().find(|(s, _)| *s == c.as_str())))
+        .or_else(|| branches.iter().find(|(s, _)| *s == "other"))?
+        .1;
+
+    let number = arg.format(Some("number"));
+    Some(render(body, args, Some(&number)))
+}
+
+/// Names of the arguments used by `pattern`, plural branches included, sorted
+/// and without duplicates.
+///
+/// Fails on unbalanced braces, empty placeholders and plural arguments without
+/// an `other` branch.
+pub fn message_arguments(pattern: &str) -> Result<Vec<String>, String> {
+    let mut names = Vec::new();
+    collect_arguments(pattern, &mut names)?;
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+fn collect_arguments(pattern: &str, names: &mut Vec<String>) -> Result<(), String> {
+    let mut i = 0;
+    while let Some(c) = pattern[i..].chars().next() {
+        match c {
+            '{' => {
+                let end =
+                    matching_brace(pattern, i).ok_or_else(|| format!("unclosed '{{' at {}", i))?;
+                let inner = &pattern[i + 1..end];
+                let mut parts = inner.splitn(3, ',');
+                let name = parts.next().unwrap_or_default().trim();
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return Err(format!("invalid placeholder '{{{}}}'", inner));
+                }
+                names.push(name.to_string());
+                if parts.next().map(str::trim) == Some("plural") {
+                    let branches = plural_branches(parts.next().unwrap_or_default())
+                        .ok_or_else(|| format!("invalid plural '{{{}}}'", inner))?;
+                    if !branches.iter().any(|(s, _)| *s == "other") {
+                        return Err(format!("plural '{}' without an 'other' branch", name));
+                    }
+                    for (_, body) in branches {
+                        collect_arguments(body, names)?;
+                    }
+                }
+                i = end + 1;
+                continue;
+            }
+            '}' => return Err(format!("unexpected '}}' at {}", i)),
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    Ok(())
+}
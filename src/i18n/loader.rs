@@ -1,22 +1,16 @@
+use crate::i18n::format::{FmtArg, format_message};
 use once_cell::sync::Lazy;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
 use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 /// Parses a JSON string and returns a HashMap of key → text pairs.
-/// Used for both embedded and (future) external locale loading.
+/// Used for both embedded and external locale loading.
 pub fn parse_json_to_map(content: &str) -> io::Result<HashMap<String, String>> {
-    // NOTE: For now Librius embeds all locales via `include_str!()`.
-    // If in the future external JSON files are reintroduced,
-    // implement a `load_from_file()` here using `fs::read_to_string()`.
-    //
-    // Example:
-    // pub fn load_from_file(path: &str) -> io::Result<HashMap<String, String>> {
-    //     let data = fs::read_to_string(path)?;
-    //     parse_json_to_map(&data)
-    // }
-
     let json: Value =
         serde_json::from_str(content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
@@ -33,24 +27,291 @@ pub fn parse_json_to_map(content: &str) -> io::Result<HashMap<String, String>> {
     Ok(map)
 }
 
+/// Loads a locale file from disk (see [`parse_json_to_map`]).
+pub fn load_from_file(path: &Path) -> io::Result<HashMap<String, String>> {
+    let data = fs::read_to_string(path)?;
+    parse_json_to_map(&data)
+}
+
 /// Global translation map
 static TRANSLATIONS: Lazy<RwLock<HashMap<String, String>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
-/// Loads translations for the selected language (embedded JSON)
-pub fn load_language(lang_code: &str) {
-    let mut map = TRANSLATIONS.write().unwrap();
-    map.clear();
+/// Language loaded by [`load_language`], used for plural rules.
+static CURRENT_LANGUAGE: Lazy<RwLock<String>> =
+    Lazy::new(|| RwLock::new(FALLBACK_LANGUAGE.to_string()));
+
+/// Directory with user-provided locale files (`<config dir>/locales`).
+static LOCALES_DIR: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+/// Language codes embedded in the binary.
+pub const EMBEDDED_LANGUAGES: [&str; 2] = ["en", "it"];
+
+/// Language used when nothing else provides a key.
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+fn embedded_locale(code: &str) -> Option<&'static str> {
+    match code {
+        "it" => Some(include_str!("locales/it.json")),
+        "en" => Some(include_str!("locales/en.json")),
+        _ => None,
+    }
+}
+
+/// Sets the directory searched for external locale files.
+///
+/// Every `<code>.json` file found there adds a language, or overrides keys of
+/// an embedded one. `None` (the default) uses the embedded locales only.
+pub fn set_locales_dir(dir: Option<PathBuf>) {
+    *LOCALES_DIR.write().unwrap() = dir;
+}
+
+/// Directory searched for external locale files, if any.
+pub fn locales_dir() -> Option<PathBuf> {
+    LOCALES_DIR.read().unwrap().clone()
+}
+
+/// Canonical form of a language tag: lowercase language, title-case script,
+/// uppercase region, `-` as separator (`PT_br` → `pt-BR`, `zh-hant` → `zh-Hant`).
+pub fn normalize_locale_code(code: &str) -> String {
+    code.trim()
+        .split(['-', '_'])
+        .filter(|part| !part.is_empty())
+        .enumerate()
+        .map(|(i, part)| match (i, part.len()) {
+            (0, _) => part.to_lowercase(),
+            (_, 4) => {
+                let lower = part.to_lowercase();
+                let mut chars = lower.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            (_, 2) | (_, 3) => part.to_uppercase(),
+            _ => part.to_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// `code` and its parent tags, most specific first (`pt-BR` → `pt-BR`, `pt`).
+fn own_chain(code: &str) -> Vec<String> {
+    let code = normalize_locale_code(code);
+    let parts: Vec<&str> = code.split('-').filter(|p| !p.is_empty()).collect();
+    (1..=parts.len())
+        .rev()
+        .map(|n| parts[..n].join("-"))
+        .collect()
+}
+
+/// Locales looked up for `code`, most specific first and always ending with
+/// English: `pt-BR` → `pt-BR`, `pt`, `en`.
+pub fn fallback_chain(code: &str) -> Vec<String> {
+    let mut chain = own_chain(code);
+    if !chain.iter().any(|c| c == FALLBACK_LANGUAGE) {
+        chain.push(FALLBACK_LANGUAGE.to_string());
+    }
+    chain
+}
+
+/// An external locale file and its keys.
+struct ExternalLocale {
+    path: PathBuf,
+    keys: HashMap<String, String>,
+}
 
-    // Select embedded JSON string
-    let content = match lang_code {
-        "it" => include_str!("locales/it.json"),
-        "en" => include_str!("locales/en.json"),
-        _ => include_str!("locales/en.json"),
+/// Valid external locale files, keyed by their normalized code; the files that
+/// cannot be read are reported in `warnings` and skipped.
+fn external_locales(warnings: &mut Vec<LocaleWarning>) -> BTreeMap<String, ExternalLocale> {
+    let mut files = BTreeMap::new();
+    let Some(dir) = locales_dir() else {
+        return files;
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
     };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_file()
+            && path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+            && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+        {
+            let code = normalize_locale_code(stem);
+            if code.is_empty() {
+                continue;
+            }
+            match load_from_file(&path) {
+                Ok(keys) => {
+                    files.insert(code, ExternalLocale { path, keys });
+                }
+                Err(e) => warnings.push(LocaleWarning::Invalid {
+                    path,
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+    files
+}
+
+/// Returns true if `lang_code`, or one of its parent tags, is embedded or has
+/// an external locale file (`pt-BR` is available when `pt.json` exists).
+pub fn is_language_available(lang_code: &str) -> bool {
+    let external = external_locales(&mut Vec::new());
+    own_chain(lang_code)
+        .iter()
+        .any(|c| embedded_locale(c).is_some() || external.contains_key(c))
+}
+
+/// Problems found while loading the locales; the language is still loaded,
+/// without the faulty file.
+#[derive(Debug)]
+pub enum LocaleWarning {
+    /// An external locale file could not be read or is not valid JSON
+    Invalid { path: PathBuf, error: String },
+    /// Neither the language nor its parents exist: English is used
+    NotFound { code: String },
+}
+
+impl fmt::Display for LocaleWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocaleWarning::Invalid { path, error } => write!(
+                f,
+                "{}",
+                tr_with(
+                    "i18n.locale.invalid",
+                    &[("path", &path.display().to_string()), ("error", error)]
+                )
+            ),
+            LocaleWarning::NotFound { code } => {
+                write!(f, "{}", tr_with("i18n.locale.not_found", &[("code", code)]))
+            }
+        }
+    }
+}
+
+/// Keys of the locales in `chain`, the most specific winning; for each
+/// locale the external file overrides the embedded keys.
+fn merge_chain(
+    chain: &[String],
+    external: &BTreeMap<String, ExternalLocale>,
+) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for code in chain.iter().rev() {
+        if let Some(content) = embedded_locale(code) {
+            map.extend(parse_json_to_map(content).expect("Invalid embedded locale JSON"));
+        }
+        if let Some(locale) = external.get(code) {
+            map.extend(locale.keys.clone());
+        }
+    }
+    map
+}
+
+/// Loads translations for the selected language along its fallback chain
+/// (`pt-BR` → `pt` → `en`), embedded locales first and external files on top.
+///
+/// The returned warnings are meant to be printed once the language is loaded.
+pub fn load_language(lang_code: &str) -> Vec<LocaleWarning> {
+    let chain = fallback_chain(lang_code);
+    let mut warnings = Vec::new();
+    let external = external_locales(&mut warnings);
+    let map = merge_chain(&chain, &external);
+
+    // Solo i file difettosi della catena riguardano questa lingua
+    warnings.retain(|w| match w {
+        LocaleWarning::Invalid { path, .. } => path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .is_some_and(|stem| chain.contains(&normalize_locale_code(stem))),
+        LocaleWarning::NotFound { .. } => true,
+    });
+
+    *TRANSLATIONS.write().unwrap() = map;
+    *CURRENT_LANGUAGE.write().unwrap() = normalize_locale_code(lang_code);
+
+    if !is_language_available(lang_code) {
+        warnings.push(LocaleWarning::NotFound {
+            code: lang_code.to_string(),
+        });
+    }
+    warnings
+}
+
+/// Where the keys of a language come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocaleSource {
+    /// Embedded in the binary
+    Embedded,
+    /// Only an external file
+    External,
+    /// Embedded, with an external file overriding some keys
+    Extended,
+}
+
+/// A language listed by `lang list`.
+#[derive(Debug, Clone)]
+pub struct LocaleInfo {
+    pub code: String,
+    /// Native name, from the `lang.name` key of the locale
+    pub name: String,
+    pub source: LocaleSource,
+    /// External file, if any
+    pub path: Option<PathBuf>,
+    /// English keys translated by the locale or its parents (`pt` for `pt-BR`)
+    pub translated: usize,
+    /// Number of English keys
+    pub total: usize,
+}
+
+/// Every language available: embedded ones and valid external files, sorted
+/// by code. Invalid locale files are reported with [`check_locale_files`].
+pub fn available_languages() -> Vec<LocaleInfo> {
+    let external = external_locales(&mut Vec::new());
+    let english = merge_chain(&[FALLBACK_LANGUAGE.to_string()], &external);
+
+    let mut codes: Vec<String> = EMBEDDED_LANGUAGES.iter().map(|c| c.to_string()).collect();
+    codes.extend(external.keys().cloned());
+    codes.sort();
+    codes.dedup();
 
-    // Parse JSON using shared helper
-    *map = parse_json_to_map(content).expect("Invalid embedded locale JSON");
+    codes
+        .into_iter()
+        .map(|code| {
+            let keys = merge_chain(&own_chain(&code), &external);
+            let source = match (
+                embedded_locale(&code).is_some(),
+                external.contains_key(&code),
+            ) {
+                (true, true) => LocaleSource::Extended,
+                (true, false) => LocaleSource::Embedded,
+                _ => LocaleSource::External,
+            };
+            LocaleInfo {
+                name: keys.get("lang.name").cloned().unwrap_or_default(),
+                source,
+                path: external.get(&code).map(|l| l.path.clone()),
+                translated: english.keys().filter(|k| keys.contains_key(*k)).count(),
+                total: english.len(),
+                code,
+            }
+        })
+        .collect()
+}
+
+/// External locale files that cannot be used (unreadable or invalid JSON).
+pub fn check_locale_files() -> Vec<LocaleWarning> {
+    let mut warnings = Vec::new();
+    external_locales(&mut warnings);
+    warnings
+}
+
+/// Language code loaded by the last [`load_language`] call.
+pub fn current_language() -> String {
+    CURRENT_LANGUAGE.read().unwrap().clone()
 }
 
 /// Returns the translation for the given key.
@@ -64,13 +325,24 @@ pub fn tr(key: &str) -> String {
 }
 
 /// Same as `tr`, but with runtime placeholder substitution.
+///
+/// Text arguments only; use [`tr_fmt`] for numbers and dates.
 pub fn tr_with(key: &str, vars: &[(&str, &str)]) -> String {
-    let mut s = tr(key);
-    for (k, v) in vars {
-        let placeholder = format!("{{{}}}", k);
-        s = s.replace(&placeholder, v);
-    }
-    s
+    let args: Vec<(&str, FmtArg)> = vars.iter().map(|(k, v)| (*k, FmtArg::from(*v))).collect();
+    format_message(&tr(key), &args)
+}
+
+/// Translation of `key` formatted with typed arguments: plural branches,
+/// numbers and dates follow the active locale.
+///
+/// # Example
+/// ```
+/// use librius::i18n::{FmtArg, tr_fmt};
+/// let msg = tr_fmt("log.prune.done", &[("count", FmtArg::from(1_200))]);
+/// assert!(!msg.is_empty());
+/// ```
+pub fn tr_fmt(key: &str, args: &[(&str, FmtArg)]) -> String {
+    format_message(&tr(key), args)
 }
 
 pub fn tr_s(key: &str) -> &'static str {
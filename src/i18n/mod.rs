@@ -1,4 +1,18 @@
-//! i18n - Embedded Internationalization module for Librius
+//! i18n - Internationalization module for Librius: embedded locales, plus
+//! user-provided locale files that add languages or override keys, and
+//! locale-aware formatting of plurals, numbers and dates.
+mod check;
+mod format;
 mod loader;
 
-pub use loader::{load_language, parse_json_to_map, tr, tr_s, tr_with};
+pub use check::{I18nIssue, I18nReport, check_translations};
+pub use format::{
+    FmtArg, PluralCategory, format_date, format_datetime, format_decimal, format_message,
+    format_number, format_time, format_timestamp, message_arguments, plural_category,
+};
+pub use loader::{
+    EMBEDDED_LANGUAGES, FALLBACK_LANGUAGE, LocaleInfo, LocaleSource, LocaleWarning,
+    available_languages, check_locale_files, current_language, fallback_chain,
+    is_language_available, load_from_file, load_language, locales_dir, normalize_locale_code,
+    parse_json_to_map, set_locales_dir, tr, tr_fmt, tr_s, tr_with,
+};
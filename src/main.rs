@@ -1,31 +1,46 @@
 use librius::cli::{build_cli, run_cli};
 use librius::config;
 use librius::db;
-use librius::i18n::{load_language, tr, tr_with};
-use librius::utils::icons::ERR;
-use librius::utils::{is_verbose, print_err, print_info, print_ok, set_verbose};
+use librius::i18n::{fallback_chain, load_language, set_locales_dir, tr, tr_with};
+use librius::utils::{is_verbose, print_err, print_info, print_warn, set_verbose};
+use std::path::PathBuf;
 
 fn main() {
     // ------------------------------------------------------------
-    // 1️⃣ Legge eventuali flag --lang e --verbose da CLI minimale
+    // 1️⃣ Legge eventuali flag --lang, --library, --config, --db e
+    //    --verbose da CLI minimale
     // ------------------------------------------------------------
-    let mut lang_arg: Option<String> = None;
+    let mut overrides = config::CliOverrides::default();
     let mut verbose = false;
+    let mut command: Option<String> = None;
 
     {
-        let mut args = std::env::args().peekable();
+        let mut args = std::env::args().skip(1).peekable();
         while let Some(arg) = args.next() {
-            match arg.as_str() {
-                "-l" | "--lang" => {
-                    if let Some(val) = args.peek() {
-                        lang_arg = Some(val.clone());
-                        args.next(); // consuma valore
+            let target = match arg.as_str() {
+                "-l" | "--lang" => &mut overrides.language,
+                "--library" => &mut overrides.library,
+                "--db" => &mut overrides.database,
+                "--config" => {
+                    if let Some(val) = args.next() {
+                        overrides.config_file = Some(PathBuf::from(val));
                     }
+                    continue;
                 }
                 "-v" | "--verbose" => {
                     verbose = true;
+                    continue;
                 }
-                _ => {}
+                other => {
+                    if command.is_none() && !other.starts_with('-') {
+                        command = Some(other.to_string());
+                    }
+                    continue;
+                }
+            };
+            if let Some(val) = args.peek() {
+                *target = Some(val.clone());
+                args.next(); // consuma valore
             }
         }
     }
@@ -33,52 +48,115 @@ fn main() {
     set_verbose(verbose);
 
     // ------------------------------------------------------------
-    // 2️⃣ Carica o inizializza il file di configurazione
+    // 2️⃣ Migrazioni del file di configurazione (prima di leggerlo)
+    // ------------------------------------------------------------
+    config::set_config_file(overrides.config_file.clone());
+    set_locales_dir(Some(config::locales_dir()));
+    let migration = config::migrate_config_file(&config::config_file_path());
+
+    // ------------------------------------------------------------
+    // 3️⃣ Configurazione a livelli: predefiniti, file (e libreria con
+    //    nome), variabili LIBRIUS_*, opzioni da riga di comando
     // ------------------------------------------------------------
     print_info("Loading configuration...", is_verbose());
-    let config = config::load_or_init().unwrap_or_else(|_| panic!("{}Unable to load config", ERR));
+    let effective = match config::read_layers(overrides.clone()).and_then(|l| config::resolve(&l)) {
+        Ok(effective) => effective,
+        Err(e) => {
+            let lang = overrides
+                .language
+                .clone()
+                .or_else(config::load_config::load_language_from_conf)
+                .unwrap_or_else(|| "en".to_string());
+            load_language(&lang);
+            print_err(&e.to_string());
+            // Con un file non valido resta disponibile solo `config`, per correggerlo
+            let fallback = config::resolve(&config::Layers {
+                cli: overrides.clone(),
+                ..Default::default()
+            });
+            match fallback {
+                Ok(fallback) if command.as_deref() == Some("config") => {
+                    print_warn(&tr("config.file.recovery"));
+                    fallback
+                }
+                _ => {
+                    print_info(&tr("config.file.fix_hint"), true);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+    let config = effective.config;
 
     // ------------------------------------------------------------
-    // 3️⃣ Determina la lingua effettiva
+    // 4️⃣ Carica la lingua effettiva
     // ------------------------------------------------------------
-    let lang_code = lang_arg
-        .or_else(|| {
-            // tenta di leggere dal file di configurazione
-            Some(config.language.clone())
-        })
-        .unwrap_or_else(|| "en".to_string());
+    let lang_code = config.language.clone();
+    let locale_warnings = load_language(&lang_code);
+    print_info(
+        &tr_with(
+            "app.language.loaded",
+            &[("lang", &fallback_chain(&lang_code).join(" → "))],
+        ),
+        is_verbose(),
+    );
+    for warning in &locale_warnings {
+        print_warn(&warning.to_string());
+    }
 
-    // Carica la lingua selezionata
-    load_language(&lang_code);
+    // Override ignorati (valori non validi, libreria attiva inesistente)
+    for warning in &effective.warnings {
+        print_warn(&warning.to_string());
+    }
     print_info(
-        &tr_with("app.language.loaded", &[("lang", &lang_code)]),
+        &tr_with(
+            "library.active",
+            &[
+                ("name", config.library_name()),
+                ("db_path", &config.database),
+            ],
+        ),
         is_verbose(),
     );
 
     // ------------------------------------------------------------
-    // 4️⃣ Inizializza o apre il database
+    // 5️⃣ CLI localizzata (analizzata prima del database: `db migrate`
+    //    gestisce le migrazioni in modo esplicito)
+    // ------------------------------------------------------------
+    let matches = build_cli().get_matches();
+    let explicit_migrate = matches!(
+        matches.subcommand(),
+        Some(("db", sub_m)) if sub_m.subcommand_matches("migrate").is_some()
+    );
+
+    // ------------------------------------------------------------
+    // 6️⃣ Inizializza o apre il database
     // ------------------------------------------------------------
-    let mut conn = db::start_db(&config)
-        .unwrap_or_else(|_| panic!("{}", &tr_with("db.open.failed", &[("icon-err", ERR)])));
+    let mut conn = match db::open_db(&config, !explicit_migrate) {
+        Ok(conn) => conn,
+        Err(e) => {
+            print_err(&tr_with("db.open.failed", &[("error", &e.to_string())]));
+            std::process::exit(1);
+        }
+    };
 
     // ------------------------------------------------------------
-    // 5️⃣ Esegue migrazioni config
+    // 7️⃣ Esito delle migrazioni config (registrate nel log)
     // ------------------------------------------------------------
-    // Note: db migrations are already run by start_db() above.
-    if let Err(e) = config::migrate_config(&conn, &config::config_file_path()) {
-        print_err(&tr_with(
+    match &migration {
+        Ok(result) => config::log_config_migration(&conn, result),
+        Err(config::ConfigMigrationError::Parse(_)) => {} // già segnalato al caricamento
+        Err(e) => print_err(&tr_with(
             "config.migrate.failed",
             &[("error", &e.to_string())],
-        ));
-    } else {
-        print_ok(&tr("config.schema.verified"), is_verbose());
+        )),
     }
 
     // ------------------------------------------------------------
-    // 6️⃣ CLI localizzata ed esecuzione comandi
+    // 8️⃣ Esecuzione comandi
     // ------------------------------------------------------------
-    let matches = build_cli().get_matches();
     if let Err(e) = run_cli(&config, &matches, &mut conn) {
-        print_err(&format!("{} {}", ERR, e));
+        print_err(&e.to_string());
+        std::process::exit(1);
     }
 }
@@ -6,11 +6,12 @@
 // serializzazione e costruzione da riga SQLite.
 // =====================================================
 
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use rusqlite::Row;
+use rusqlite::types::FromSql;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Book {
     pub id: Option<i32>,
     pub title: String,
@@ -27,8 +28,30 @@ pub struct Book {
     pub row: Option<String>,
     pub position: Option<String>,
     pub added_at: Option<DateTime<Utc>>,
+    /// Valutazione personale, da 1 a 5
+    #[serde(default)]
+    pub rating: Option<i32>,
+    /// Stato di lettura (`read`, `reading`, `to_read`)
+    #[serde(default)]
+    pub read_status: Option<String>,
+    #[serde(default)]
+    pub read_at: Option<NaiveDate>,
+    /// Tag separati da virgola
+    #[serde(default)]
+    pub tags: Option<String>,
+    #[serde(default)]
+    pub review: Option<String>,
+    /// Formato (`ebook`); `None` per i libri cartacei
+    #[serde(default)]
+    pub format: Option<String>,
 }
 
+/// Stati di lettura ammessi in `read_status`.
+pub const READ_STATUSES: [&str; 3] = ["read", "reading", "to_read"];
+
+/// Valore di `format` per i libri elettronici.
+pub const EBOOK_FORMAT: &str = "ebook";
+
 impl Book {
     pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
         // `added_at` is stored by SQLite as either:
@@ -56,10 +79,44 @@ impl Book {
             row: row.get("row")?,
             position: row.get("position")?,
             added_at,
+            rating: optional_column(row, "rating")?,
+            read_status: optional_column(row, "read_status")?,
+            read_at: optional_column::<String>(row, "read_at")?
+                .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()),
+            tags: optional_column(row, "tags")?,
+            review: optional_column(row, "review")?,
+            format: optional_column(row, "format")?,
         })
     }
 }
 
+/// A book currently in the trash, with the time it was moved there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashedBook {
+    pub book: Book,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl TrashedBook {
+    /// Builds from a row containing the `Book` columns plus `deleted_at`.
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let deleted_at: String = row.get("deleted_at")?;
+        Ok(Self {
+            book: Book::from_row(row)?,
+            deleted_at: parse_sqlite_datetime(&deleted_at).unwrap_or_default(),
+        })
+    }
+}
+
+/// Reads a column added by a later migration (`PATCH_007`): `None` when the
+/// query does not select it.
+fn optional_column<T: FromSql>(row: &Row, name: &str) -> rusqlite::Result<Option<T>> {
+    match row.get(name) {
+        Err(rusqlite::Error::InvalidColumnName(_)) => Ok(None),
+        other => other,
+    }
+}
+
 /// Parse a SQLite timestamp string into `DateTime<Utc>`.
 ///
 /// Tries the following formats in order, returning `None` if none match:
@@ -6,8 +6,10 @@
 // dal modello dati puro definito in book.rs.
 // =====================================================
 
-use crate::i18n::tr;
-use crate::models::book::Book;
+use crate::i18n::{LocaleInfo, LocaleSource, format_datetime, format_timestamp, tr};
+use crate::models::book::{Book, TrashedBook};
+use crate::models::history::{HistoryEntry, HistoryOperation};
+use crate::models::log::{LogEntry, LogStat};
 use std::borrow::Cow;
 use tabled::Tabled;
 
@@ -17,6 +19,39 @@ pub struct BookFull<'a>(pub &'a Book);
 /// Vista ridotta del libro (6 colonne) per `list --short`.
 pub struct BookShort<'a>(pub &'a Book);
 
+/// Riga della cronologia di un libro per il comando `history`.
+pub struct HistoryRow<'a>(pub &'a HistoryEntry);
+
+/// Libro nel cestino per il comando `trash list`.
+pub struct TrashRow<'a>(pub &'a TrashedBook);
+
+/// Chiave di configurazione con valore e origine per `config show`.
+pub struct ConfigRow<'a> {
+    pub key: &'a str,
+    pub value: String,
+    pub source: String,
+}
+
+/// Libreria configurata per il comando `library list`.
+pub struct LibraryRow<'a> {
+    pub name: &'a str,
+    pub database: &'a str,
+    pub language: &'a str,
+    pub active: bool,
+}
+
+/// Lingua disponibile per `lang list`.
+pub struct LanguageRow<'a> {
+    pub info: &'a LocaleInfo,
+    pub active: bool,
+}
+
+/// Voce del log per `log list` e `log tail`.
+pub struct LogRow<'a>(pub &'a LogEntry);
+
+/// Conteggio per operazione per `log stats`.
+pub struct LogStatRow<'a>(pub &'a LogStat);
+
 impl<'a> Tabled for BookFull<'a> {
     const LENGTH: usize = 10;
 
@@ -78,3 +113,211 @@ impl<'a> Tabled for BookShort<'a> {
         ]
     }
 }
+
+impl<'a> Tabled for HistoryRow<'a> {
+    const LENGTH: usize = 5;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let e = self.0;
+        let changes = match e.operation {
+            HistoryOperation::Update => e
+                .changed_fields()
+                .iter()
+                .map(|(field, old, new)| format!("{}: {} → {}", field, old, new))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            HistoryOperation::Insert
+            | HistoryOperation::Delete
+            | HistoryOperation::Trash
+            | HistoryOperation::Restore => e
+                .after
+                .as_ref()
+                .or(e.before.as_ref())
+                .map(|b| format!("{} — {}", b.title, b.author))
+                .unwrap_or_default(),
+        };
+        let status = if e.undone_at.is_some() {
+            tr("history.status.undone")
+        } else {
+            String::new()
+        };
+
+        vec![
+            Cow::from(e.id.to_string()),
+            Cow::from(format_timestamp(&e.created_at)),
+            Cow::from(tr(&format!(
+                "history.op.{}",
+                e.operation.as_str().to_lowercase()
+            ))),
+            Cow::from(changes),
+            Cow::from(status),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Cow::from(tr("history.header.id")),
+            Cow::from(tr("history.header.date")),
+            Cow::from(tr("history.header.operation")),
+            Cow::from(tr("history.header.changes")),
+            Cow::from(tr("history.header.status")),
+        ]
+    }
+}
+
+impl<'a> Tabled for TrashRow<'a> {
+    const LENGTH: usize = 5;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let t = self.0;
+        vec![
+            Cow::from(t.book.id.map(|v| v.to_string()).unwrap_or_default()),
+            Cow::from(&t.book.title),
+            Cow::from(&t.book.author),
+            Cow::from(&t.book.isbn),
+            Cow::from(format_datetime(&t.deleted_at)),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Cow::from(tr("list.header.id")),
+            Cow::from(tr("list.header.title")),
+            Cow::from(tr("list.header.author")),
+            Cow::from(tr("list.header.ISBN")),
+            Cow::from(tr("trash.header.deleted_at")),
+        ]
+    }
+}
+
+impl<'a> Tabled for LibraryRow<'a> {
+    const LENGTH: usize = 4;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            Cow::from(if self.active { "*" } else { "" }),
+            Cow::from(self.name),
+            Cow::from(self.database),
+            Cow::from(self.language),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Cow::from(tr("library.header.active")),
+            Cow::from(tr("library.header.name")),
+            Cow::from(tr("library.header.database")),
+            Cow::from(tr("library.header.language")),
+        ]
+    }
+}
+
+impl<'a> Tabled for LanguageRow<'a> {
+    const LENGTH: usize = 6;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let info = self.info;
+        let source = match info.source {
+            LocaleSource::Embedded => tr("lang.source.embedded"),
+            LocaleSource::External => tr("lang.source.external"),
+            LocaleSource::Extended => tr("lang.source.extended"),
+        };
+        let coverage = (info.translated * 100)
+            .checked_div(info.total)
+            .unwrap_or_default();
+        vec![
+            Cow::from(if self.active { "*" } else { "" }),
+            Cow::from(info.code.as_str()),
+            Cow::from(info.name.as_str()),
+            Cow::from(source),
+            Cow::from(format!("{}%", coverage)),
+            Cow::from(
+                info.path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default(),
+            ),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Cow::from(tr("lang.header.active")),
+            Cow::from(tr("lang.header.code")),
+            Cow::from(tr("lang.header.name")),
+            Cow::from(tr("lang.header.source")),
+            Cow::from(tr("lang.header.coverage")),
+            Cow::from(tr("lang.header.file")),
+        ]
+    }
+}
+
+impl<'a> Tabled for ConfigRow<'a> {
+    const LENGTH: usize = 3;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            Cow::from(self.key),
+            Cow::from(&self.value),
+            Cow::from(&self.source),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Cow::from(tr("config.header.key")),
+            Cow::from(tr("config.header.value")),
+            Cow::from(tr("config.header.source")),
+        ]
+    }
+}
+
+impl<'a> Tabled for LogRow<'a> {
+    const LENGTH: usize = 6;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let e = self.0;
+        vec![
+            Cow::from(e.id.to_string()),
+            Cow::from(format_timestamp(&e.date)),
+            Cow::from(&e.operation),
+            Cow::from(&e.target),
+            Cow::from(e.book_id.map(|id| id.to_string()).unwrap_or_default()),
+            Cow::from(&e.message),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Cow::from(tr("log.header.id")),
+            Cow::from(tr("log.header.date")),
+            Cow::from(tr("log.header.operation")),
+            Cow::from(tr("log.header.target")),
+            Cow::from(tr("log.header.book")),
+            Cow::from(tr("log.header.message")),
+        ]
+    }
+}
+
+impl<'a> Tabled for LogStatRow<'a> {
+    const LENGTH: usize = 4;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let s = self.0;
+        vec![
+            Cow::from(&s.operation),
+            Cow::from(s.count.to_string()),
+            Cow::from(format_timestamp(&s.first)),
+            Cow::from(format_timestamp(&s.last)),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            Cow::from(tr("log.header.operation")),
+            Cow::from(tr("log.header.count")),
+            Cow::from(tr("log.header.first")),
+            Cow::from(tr("log.header.last")),
+        ]
+    }
+}
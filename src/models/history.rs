@@ -0,0 +1,147 @@
+// =====================================================
+// Librius - models/history.rs
+// -----------------------------------------------------
+// Voce della cronologia di un libro: operazione e
+// snapshot completi prima/dopo, salvati come JSON
+// nella tabella `book_history`.
+// =====================================================
+
+use crate::models::book::Book;
+use rusqlite::Row;
+use rusqlite::types::Type;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::str::FromStr;
+
+/// Kind of change recorded in `book_history`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryOperation {
+    Insert,
+    Update,
+    Delete,
+    /// Moved to the trash (`deleted_at` set)
+    Trash,
+    /// Restored from the trash
+    Restore,
+}
+
+impl HistoryOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryOperation::Insert => "INSERT",
+            HistoryOperation::Update => "UPDATE",
+            HistoryOperation::Delete => "DELETE",
+            HistoryOperation::Trash => "TRASH",
+            HistoryOperation::Restore => "RESTORE",
+        }
+    }
+}
+
+impl fmt::Display for HistoryOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HistoryOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "INSERT" => Ok(HistoryOperation::Insert),
+            "UPDATE" => Ok(HistoryOperation::Update),
+            "DELETE" => Ok(HistoryOperation::Delete),
+            "TRASH" => Ok(HistoryOperation::Trash),
+            "RESTORE" => Ok(HistoryOperation::Restore),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+/// One row of `book_history`.
+///
+/// `before` is `None` for inserts and restores, `after` is `None` for deletes
+/// and trash moves.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub book_id: i64,
+    pub operation: HistoryOperation,
+    pub before: Option<Book>,
+    pub after: Option<Book>,
+    pub created_at: String,
+    /// Set when the operation has been reverted by `undo`
+    pub undone_at: Option<String>,
+    /// Trash date of a book deleted while in the trash (stored as
+    /// `deleted_at` in the `before` snapshot), so `undo` puts it back there
+    pub deleted_at: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let op: String = row.get("operation")?;
+        let operation = op
+            .parse::<HistoryOperation>()
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, e.into()))?;
+
+        let before_json: Option<String> = row.get("before_json")?;
+        let deleted_at = before_json
+            .as_deref()
+            .and_then(|s| serde_json::from_str::<Value>(s).ok())
+            .and_then(|v| v.get("deleted_at")?.as_str().map(str::to_string));
+
+        Ok(Self {
+            id: row.get("id")?,
+            book_id: row.get("book_id")?,
+            operation,
+            before: parse_snapshot(before_json)?,
+            after: parse_snapshot(row.get("after_json")?)?,
+            created_at: row.get("created_at")?,
+            undone_at: row.get("undone_at")?,
+            deleted_at,
+        })
+    }
+
+    /// Fields that differ between the `before` and `after` snapshots, as
+    /// `(field, old, new)` with values rendered as plain text.
+    pub fn changed_fields(&self) -> Vec<(String, String, String)> {
+        let to_map = |b: &Option<Book>| match b.as_ref().map(serde_json::to_value) {
+            Some(Ok(Value::Object(m))) => m,
+            _ => Map::new(),
+        };
+        let before = to_map(&self.before);
+        let after = to_map(&self.after);
+
+        let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        keys.into_iter()
+            .filter(|k| before.get(*k) != after.get(*k))
+            .map(|k| {
+                (
+                    k.clone(),
+                    value_to_text(before.get(k)),
+                    value_to_text(after.get(k)),
+                )
+            })
+            .collect()
+    }
+}
+
+fn value_to_text(v: Option<&Value>) -> String {
+    match v {
+        None | Some(Value::Null) => "—".to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn parse_snapshot(json: Option<String>) -> rusqlite::Result<Option<Book>> {
+    match json {
+        Some(s) => serde_json::from_str(&s)
+            .map(Some)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e))),
+        None => Ok(None),
+    }
+}
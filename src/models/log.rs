@@ -0,0 +1,53 @@
+// =====================================================
+// Librius - models/log.rs
+// -----------------------------------------------------
+// Voce della tabella `log`: operazione, destinazione,
+// messaggio e, per le operazioni sui libri, l'id del
+// libro e i dettagli in JSON (PATCH_006).
+// =====================================================
+
+use rusqlite::Row;
+use serde::Serialize;
+use serde_json::Value;
+
+/// One row of the `log` table.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub id: i64,
+    /// ISO 8601 timestamp with offset, as written by `now_str()`
+    pub date: String,
+    pub operation: String,
+    pub target: String,
+    pub message: String,
+    /// Book the operation refers to, if any
+    pub book_id: Option<i64>,
+    /// Structured details (JSON object), if any
+    pub details: Option<Value>,
+}
+
+impl LogEntry {
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let details: Option<String> = row.get("details")?;
+        Ok(Self {
+            id: row.get("id")?,
+            date: row.get("date")?,
+            operation: row.get("operation")?,
+            target: row.get::<_, Option<String>>("target")?.unwrap_or_default(),
+            message: row.get("message")?,
+            book_id: row.get("book_id")?,
+            // Dettagli non validi: conservati come stringa
+            details: details.map(|d| serde_json::from_str(&d).unwrap_or(Value::String(d))),
+        })
+    }
+}
+
+/// Number of log entries of one operation, for `log stats`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogStat {
+    pub operation: String,
+    pub count: i64,
+    /// Date of the oldest entry
+    pub first: String,
+    /// Date of the newest entry
+    pub last: String,
+}
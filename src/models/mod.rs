@@ -1,5 +1,12 @@
 pub mod book;
 pub mod display;
+pub mod history;
+pub mod log;
 
-pub use book::Book;
-pub use display::{BookFull, BookShort};
+pub use book::{Book, TrashedBook};
+pub use display::{
+    BookFull, BookShort, ConfigRow, HistoryRow, LanguageRow, LibraryRow, LogRow, LogStatRow,
+    TrashRow,
+};
+pub use history::{HistoryEntry, HistoryOperation};
+pub use log::{LogEntry, LogStat};
@@ -0,0 +1,47 @@
+// =====================================================
+// Librius - utils/age.rs
+// -----------------------------------------------------
+// Parsing di durate relative come "30d", "2w", "12h"
+// (usate da `trash empty --older-than` e `log prune`) e
+// di istanti per `log list --since/--until`.
+// =====================================================
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+
+/// Parses an age such as `30d`, `2w` or `12h`; a bare number means days.
+/// Returns `None` for empty, negative or malformed input.
+pub fn parse_age(input: &str) -> Option<Duration> {
+    let s = input.trim().to_lowercase();
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => s.split_at(pos),
+        None => (s.as_str(), "d"),
+    };
+    let n: i64 = digits.parse().ok()?;
+
+    match unit {
+        "h" => Duration::try_hours(n),
+        "d" => Duration::try_days(n),
+        "w" => Duration::try_weeks(n),
+        _ => None,
+    }
+}
+
+/// Parses a point in time: an age relative to now (`7d`, `12h`), a local
+/// date (`2025-10-13`) or an RFC 3339 timestamp.
+///
+/// With `end_of_day` a plain date means the end of that day, so that
+/// `--until 2025-10-13` includes the whole day.
+pub fn parse_instant(input: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+    let s = input.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let day = if end_of_day { date.succ_opt()? } else { date };
+        let midnight = Local
+            .from_local_datetime(&day.and_hms_opt(0, 0, 0)?)
+            .earliest()?;
+        return Some(midnight.with_timezone(&Utc));
+    }
+    parse_age(s).map(|age| Utc::now() - age)
+}
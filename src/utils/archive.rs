@@ -0,0 +1,177 @@
+// =====================================================
+// Librius - utils/archive.rs
+// -----------------------------------------------------
+// Scrittura e lettura degli archivi di backup
+// (zip, tar.gz, tar.zst) con il manifest JSON incluso
+// in ogni archivio. Identico su tutte le piattaforme.
+// =====================================================
+
+use crate::config::BackupFormat;
+use crate::db::migrations::applied_migrations;
+use crate::utils::log::now_str;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{self, Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Name of the manifest entry stored in every backup archive.
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Metadata describing the database contained in a backup archive.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupManifest {
+    pub librius_version: String,
+    pub created_at: String,
+    /// File name of the database inside the archive
+    pub db_file: String,
+    /// Schema patches applied to the database, in order
+    pub schema_patches: Vec<String>,
+    pub book_count: i64,
+    /// Hex-encoded SHA-256 of the database file
+    pub sha256: String,
+}
+
+impl BackupManifest {
+    /// Builds the manifest for `db_bytes`, reading patches and book count
+    /// from the live connection.
+    pub fn build(conn: &Connection, db_file: &str, db_bytes: &[u8]) -> Self {
+        Self {
+            librius_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: now_str(),
+            db_file: db_file.to_string(),
+            schema_patches: applied_migrations(conn).unwrap_or_default(),
+            book_count: conn
+                .query_row("SELECT COUNT(*) FROM books", [], |r| r.get(0))
+                .unwrap_or(0),
+            sha256: sha256_hex(db_bytes),
+        }
+    }
+}
+
+/// Hex-encoded SHA-256 digest.
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Builds a backup in `format` containing the database and its manifest.
+/// For `BackupFormat::Sqlite` the database bytes are returned unchanged.
+pub fn write_archive(
+    format: BackupFormat,
+    db_bytes: &[u8],
+    manifest: &BackupManifest,
+) -> io::Result<Vec<u8>> {
+    let manifest_json = serde_json::to_vec_pretty(manifest).map_err(io::Error::other)?;
+
+    match format {
+        BackupFormat::Sqlite => Ok(db_bytes.to_vec()),
+        BackupFormat::Zip => {
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+            zip.start_file(MANIFEST_NAME, options)
+                .map_err(io::Error::other)?;
+            zip.write_all(&manifest_json)?;
+            zip.start_file(manifest.db_file.as_str(), options)
+                .map_err(io::Error::other)?;
+            zip.write_all(db_bytes)?;
+            Ok(zip.finish().map_err(io::Error::other)?.into_inner())
+        }
+        BackupFormat::TarGz => {
+            let encoder = GzEncoder::new(Vec::new(), Compression::default());
+            let encoder = write_tar(encoder, db_bytes, &manifest.db_file, &manifest_json)?;
+            encoder.finish()
+        }
+        BackupFormat::TarZst => {
+            let encoder = zstd::Encoder::new(Vec::new(), 0)?;
+            let encoder = write_tar(encoder, db_bytes, &manifest.db_file, &manifest_json)?;
+            encoder.finish()
+        }
+    }
+}
+
+fn write_tar<W: Write>(
+    writer: W,
+    db_bytes: &[u8],
+    db_file: &str,
+    manifest_json: &[u8],
+) -> io::Result<W> {
+    let mut tar = tar::Builder::new(writer);
+    for (name, data) in [(MANIFEST_NAME, manifest_json), (db_file, db_bytes)] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+        tar.append_data(&mut header, name, data)?;
+    }
+    tar.into_inner()
+}
+
+/// Extracts the database (and manifest, when present) from a decrypted
+/// backup. Returns `Ok(None)` if the format is not recognized or the archive
+/// contains no SQLite database.
+pub fn read_archive(data: &[u8]) -> io::Result<Option<(Vec<u8>, Option<BackupManifest>)>> {
+    if data.starts_with(SQLITE_MAGIC) {
+        return Ok(Some((data.to_vec(), None)));
+    }
+
+    let entries = if data.starts_with(GZIP_MAGIC) {
+        read_tar(GzDecoder::new(data))?
+    } else if data.starts_with(ZSTD_MAGIC) {
+        read_tar(zstd::Decoder::new(data)?)?
+    } else if data.starts_with(ZIP_MAGIC) {
+        read_zip(data)?
+    } else {
+        return Ok(None);
+    };
+
+    let mut manifest = None;
+    let mut db = None;
+    for (name, bytes) in entries {
+        if name == MANIFEST_NAME {
+            manifest = serde_json::from_slice(&bytes).ok();
+        } else if db.is_none() && bytes.starts_with(SQLITE_MAGIC) {
+            db = Some(bytes);
+        }
+    }
+
+    Ok(db.map(|d| (d, manifest)))
+}
+
+fn read_tar<R: Read>(reader: R) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut out = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        let mut buf = Vec::new();
+        entry.read_to_end(&mut buf)?;
+        out.push((name, buf));
+    }
+    Ok(out)
+}
+
+fn read_zip(data: &[u8]) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = ZipArchive::new(Cursor::new(data)).map_err(io::Error::other)?;
+    let mut out = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(io::Error::other)?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        out.push((file.name().to_string(), buf));
+    }
+    Ok(out)
+}
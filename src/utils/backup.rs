@@ -0,0 +1,170 @@
+// =====================================================
+// Librius - utils/backup.rs
+// -----------------------------------------------------
+// Helper per i backup del database: directory dei
+// backup, snapshot automatici prima delle operazioni
+// distruttive e selezione dei file da eliminare
+// secondo la politica di conservazione.
+// =====================================================
+
+use crate::config::BackupConfig;
+use crate::i18n::tr_with;
+use crate::utils::log::write_log;
+use crate::utils::print::{print_err, print_info};
+use chrono::{Datelike, Local, NaiveDateTime};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Common prefix of every backup file created by Librius.
+pub const BACKUP_PREFIX: &str = "librius_backup_";
+
+/// Timestamp format embedded in backup file names.
+pub const BACKUP_TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+/// A backup file found in the backups directory.
+#[derive(Debug, Clone)]
+pub struct BackupEntry {
+    pub path: PathBuf,
+    pub created: NaiveDateTime,
+}
+
+/// Returns the `backups` directory placed next to the database file.
+pub fn backup_dir_for(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("backups")
+}
+
+/// Returns the current local time formatted for backup file names.
+pub fn backup_timestamp() -> String {
+    Local::now().format(BACKUP_TIMESTAMP_FORMAT).to_string()
+}
+
+/// Extracts the creation timestamp from a backup file name.
+///
+/// Accepts both manual (`librius_backup_2025-10-13_21-32-07.sqlite`) and
+/// automatic (`librius_backup_2025-10-13_21-32-07_pre-reset.sqlite`) names.
+pub fn parse_backup_timestamp(file_name: &str) -> Option<NaiveDateTime> {
+    let rest = file_name.strip_prefix(BACKUP_PREFIX)?;
+    let stamp = rest.get(0..19)?;
+    NaiveDateTime::parse_from_str(stamp, BACKUP_TIMESTAMP_FORMAT).ok()
+}
+
+/// Lists the backups in `dir`, newest first.
+/// Files that do not follow the Librius naming scheme are ignored.
+pub fn list_backups(dir: &Path) -> io::Result<Vec<BackupEntry>> {
+    let mut entries = Vec::new();
+    if !dir.exists() {
+        return Ok(entries);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(created) = parse_backup_timestamp(&name) {
+            entries.push(BackupEntry {
+                path: entry.path(),
+                created,
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| b.created.cmp(&a.created).then(b.path.cmp(&a.path)));
+    Ok(entries)
+}
+
+/// Returns the backups that the retention policy does not keep.
+///
+/// `entries` must be sorted newest first (as returned by [`list_backups`]).
+/// An empty policy keeps nothing, so callers should check
+/// [`BackupConfig::is_empty`] before deleting anything.
+pub fn select_prunable(entries: &[BackupEntry], policy: &BackupConfig) -> Vec<PathBuf> {
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    let mut prunable = Vec::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        let mut keep = i < policy.keep_last;
+
+        let day = entry.created.date();
+        if days.len() < policy.keep_daily && days.insert(day) {
+            keep = true;
+        }
+
+        let week = day.iso_week();
+        if weeks.len() < policy.keep_weekly && weeks.insert((week.year(), week.week())) {
+            keep = true;
+        }
+
+        if !keep {
+            prunable.push(entry.path.clone());
+        }
+    }
+
+    prunable
+}
+
+/// Copies the database file into the backups directory, tagging the file
+/// name with the operation that triggered the snapshot.
+pub fn snapshot_file(db_path: &Path, reason: &str) -> io::Result<PathBuf> {
+    let backup_dir = backup_dir_for(db_path);
+    fs::create_dir_all(&backup_dir)?;
+
+    let name = format!(
+        "{}{}_pre-{}.sqlite",
+        BACKUP_PREFIX,
+        backup_timestamp(),
+        reason
+    );
+    let dest = backup_dir.join(name);
+    fs::copy(db_path, &dest)?;
+    Ok(dest)
+}
+
+/// Creates an automatic snapshot of the database behind `conn` before a
+/// destructive operation.
+///
+/// Returns `Ok(None)` for in-memory databases. On failure a localized error
+/// is printed and the caller is expected to abort the operation.
+pub fn auto_snapshot(conn: &Connection, reason: &str) -> io::Result<Option<PathBuf>> {
+    let db_path = match conn.path() {
+        Some(p) if !p.is_empty() => PathBuf::from(p),
+        _ => return Ok(None),
+    };
+
+    if !db_path.exists() {
+        return Ok(None);
+    }
+
+    match snapshot_file(&db_path, reason) {
+        Ok(dest) => {
+            let dest_str = dest.display().to_string();
+            print_info(
+                &tr_with("backup.auto.created", &[("path", &dest_str)]),
+                true,
+            );
+            let _ = write_log(
+                conn,
+                "AUTO_BACKUP",
+                "DB",
+                &tr_with(
+                    "log.backup.auto",
+                    &[("reason", reason), ("path", &dest_str)],
+                ),
+            );
+            Ok(Some(dest))
+        }
+        Err(e) => {
+            print_err(&tr_with("backup.auto.failed", &[("error", &e.to_string())]));
+            Err(e)
+        }
+    }
+}
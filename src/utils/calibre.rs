@@ -0,0 +1,186 @@
+// =====================================================
+// Librius - utils/calibre.rs
+// -----------------------------------------------------
+// Lettura del database `metadata.db` di una libreria
+// Calibre e conversione dei libri nel modello `Book`
+// (marcati come ebook).
+// =====================================================
+
+use crate::models::Book;
+use crate::models::book::EBOOK_FORMAT;
+use crate::utils::import_helpers::{MappedImport, RejectReason, RejectedRow, import_isbn};
+use crate::utils::lang_code_to_name;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OpenFlags, Result};
+use std::path::{Path, PathBuf};
+
+/// Name of the Calibre database inside a library folder.
+pub const CALIBRE_DB: &str = "metadata.db";
+
+/// Year Calibre stores when the publication date is unknown (`0101-01-01`).
+const UNDEFINED_YEAR: i32 = 101;
+
+/// One row per Calibre book, with the linked tables folded into columns.
+const CALIBRE_QUERY: &str = "
+    SELECT b.id, b.title, b.timestamp, b.pubdate, b.isbn,
+           (SELECT group_concat(name, ', ') FROM (
+                SELECT a.name FROM books_authors_link l JOIN authors a ON a.id = l.author
+                WHERE l.book = b.id ORDER BY l.id)) AS authors,
+           (SELECT p.name FROM books_publishers_link l JOIN publishers p ON p.id = l.publisher
+            WHERE l.book = b.id) AS publisher,
+           (SELECT group_concat(name, ', ') FROM (
+                SELECT t.name FROM books_tags_link l JOIN tags t ON t.id = l.tag
+                WHERE l.book = b.id ORDER BY t.name)) AS tags,
+           (SELECT i.val FROM identifiers i
+            WHERE i.book = b.id AND i.type = 'isbn') AS identifier_isbn,
+           (SELECT lg.lang_code FROM books_languages_link l JOIN languages lg ON lg.id = l.lang_code
+            WHERE l.book = b.id ORDER BY l.item_order LIMIT 1) AS lang_code,
+           (SELECT c.text FROM comments c WHERE c.book = b.id) AS comments,
+           (SELECT r.rating FROM books_ratings_link l JOIN ratings r ON r.id = l.rating
+            WHERE l.book = b.id) AS rating
+    FROM books b
+    ORDER BY b.id";
+
+/// Accepts either a Calibre library folder or the `metadata.db` file itself.
+pub fn calibre_db_path(library: &Path) -> PathBuf {
+    if library.is_dir() {
+        library.join(CALIBRE_DB)
+    } else {
+        library.to_path_buf()
+    }
+}
+
+/// Reads every book of a Calibre `metadata.db` (opened read-only). Books
+/// without an `isbn` identifier are mapped with an empty ISBN; those with a
+/// malformed one end up in `rejected`, with the Calibre book id as `line`.
+/// Series are not mapped: librius has no series model.
+pub fn read_calibre(db_path: &Path) -> Result<MappedImport> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare(CALIBRE_QUERY)?;
+    let mut rows = stmt.query([])?;
+
+    let mut import = MappedImport::default();
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get("id")?;
+        let title: String = row.get::<_, Option<String>>("title")?.unwrap_or_default();
+        let title = title.trim().to_string();
+        if title.is_empty() {
+            import.rejected.push(RejectedRow {
+                line: id as u64,
+                title,
+                reason: RejectReason::MissingTitle,
+            });
+            continue;
+        }
+
+        // L'ISBN sta negli identificatori; `books.isbn` è la colonna storica
+        let raw_isbn = [
+            row.get::<_, Option<String>>("identifier_isbn")?,
+            row.get::<_, Option<String>>("isbn")?,
+        ]
+        .into_iter()
+        .flatten()
+        .find(|s| !s.trim().is_empty())
+        .unwrap_or_default();
+        let isbn = match import_isbn(&raw_isbn) {
+            Ok(isbn) => isbn,
+            Err(reason) => {
+                import.rejected.push(RejectedRow {
+                    line: id as u64,
+                    title,
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let pubdate: Option<String> = row.get("pubdate")?;
+        let timestamp: Option<String> = row.get("timestamp")?;
+        let lang_code: Option<String> = row.get("lang_code")?;
+        let comments: Option<String> = row.get("comments")?;
+        // Calibre salva le stelle da 0 a 10 (mezze stelle comprese)
+        let rating: Option<i32> = row.get("rating")?;
+
+        import.books.push((
+            id as u64,
+            Book {
+                id: None,
+                title,
+                author: row.get::<_, Option<String>>("authors")?.unwrap_or_default(),
+                editor: row
+                    .get::<_, Option<String>>("publisher")?
+                    .unwrap_or_default(),
+                year: pubdate
+                    .as_deref()
+                    .and_then(|d| d.get(..4))
+                    .and_then(|y| y.parse().ok())
+                    .filter(|y| *y > UNDEFINED_YEAR)
+                    .unwrap_or_default(),
+                isbn,
+                language: lang_code
+                    .as_deref()
+                    .map(|c| lang_code_to_name(c).to_string()),
+                pages: None,
+                genre: None,
+                summary: comments
+                    .as_deref()
+                    .map(strip_html)
+                    .filter(|s| !s.is_empty()),
+                room: None,
+                shelf: None,
+                row: None,
+                position: None,
+                added_at: timestamp.as_deref().and_then(parse_calibre_timestamp),
+                rating: rating.map(|r| (r + 1) / 2).filter(|r| (1..=5).contains(r)),
+                read_status: None,
+                read_at: None,
+                tags: row.get("tags")?,
+                review: None,
+                format: Some(EBOOK_FORMAT.to_string()),
+            },
+        ));
+    }
+    Ok(import)
+}
+
+/// Calibre timestamps: `2023-05-14 10:20:30.123456+00:00` (or RFC 3339).
+fn parse_calibre_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%:z")
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+/// Plain text of a Calibre comment (HTML): paragraphs and line breaks become
+/// newlines, other tags are dropped and common entities decoded.
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        if tag.starts_with("br") || tag == "/p" || tag == "/div" || tag == "/li" {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,374 @@
+// =====================================================
+// Librius - utils/citation.rs
+// -----------------------------------------------------
+// Citazioni bibliografiche: chiavi stabili costruite da
+// autore, anno e titolo e voci in formato BibTeX, RIS e
+// CSL-JSON, pronte per i gestori di bibliografie.
+// =====================================================
+
+use crate::models::Book;
+use crate::utils::lang::lang_name_to_code;
+use serde_json::{Map, Value, json};
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Citation format of `export` and `cite`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CitationFormat {
+    /// BibTeX `@book` entries (default)
+    #[default]
+    Bibtex,
+    /// RIS tagged records
+    Ris,
+    /// CSL-JSON items, as read by Pandoc and Zotero
+    CslJson,
+}
+
+impl CitationFormat {
+    /// Every accepted value, as written on the CLI.
+    pub const NAMES: [&'static str; 3] = ["bibtex", "ris", "csl-json"];
+
+    /// Extension of the exported file.
+    pub fn extension(self) -> &'static str {
+        match self {
+            CitationFormat::Bibtex => "bib",
+            CitationFormat::Ris => "ris",
+            CitationFormat::CslJson => "json",
+        }
+    }
+}
+
+impl FromStr for CitationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bibtex" => Ok(CitationFormat::Bibtex),
+            "ris" => Ok(CitationFormat::Ris),
+            "csl-json" => Ok(CitationFormat::CslJson),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+/// Articoli iniziali saltati nella scelta della parola del titolo.
+const LEADING_ARTICLES: [&str; 20] = [
+    "the", "a", "an", "il", "lo", "la", "i", "gli", "le", "l", "un", "uno", "una", "der", "die",
+    "das", "el", "los", "las", "les",
+];
+
+/// Particelle che appartengono al cognome ("Ludwig van Beethoven").
+const NAME_PARTICLES: [&str; 10] = [
+    "van", "von", "der", "de", "di", "da", "del", "della", "du", "la",
+];
+
+/// Authors of a book, in the order they are stored.
+fn authors(book: &Book) -> Vec<&str> {
+    book.author
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+/// Splits a name in direct order into family and given names: the family
+/// name is the last word, with any lowercase particle before it
+/// ("J. R. R. Tolkien" → `Tolkien`, `J. R. R.`).
+pub fn split_name(name: &str) -> (&str, &str) {
+    let name = name.trim();
+    let Some((given, last)) = name.rsplit_once(' ') else {
+        return (name, "");
+    };
+    let mut start = name.len() - last.len();
+    let mut given = given.trim_end();
+    while let Some((rest, word)) = given.rsplit_once(' ')
+        && NAME_PARTICLES.contains(&word)
+    {
+        start = rest.len() + 1;
+        given = rest.trim_end();
+    }
+    (&name[start..], given)
+}
+
+/// Lowercase ASCII words of a text: accented Latin letters lose their
+/// accents, everything else separates words.
+fn slug_words(text: &str) -> Vec<String> {
+    let mut folded = String::new();
+    for c in text.to_lowercase().chars() {
+        match c {
+            'a'..='z' | '0'..='9' => folded.push(c),
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ą' => folded.push('a'),
+            'æ' => folded.push_str("ae"),
+            'ç' | 'ć' | 'č' => folded.push('c'),
+            'ď' | 'đ' => folded.push('d'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => folded.push('e'),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' => folded.push('i'),
+            'ł' => folded.push('l'),
+            'ñ' | 'ń' | 'ň' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => folded.push('o'),
+            'œ' => folded.push_str("oe"),
+            'ř' => folded.push('r'),
+            'ß' => folded.push_str("ss"),
+            'ś' | 'š' | 'ş' => folded.push('s'),
+            'ť' | 'ţ' => folded.push('t'),
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'ź' | 'ż' | 'ž' => folded.push('z'),
+            _ => folded.push(' '),
+        }
+    }
+    folded.split_whitespace().map(str::to_string).collect()
+}
+
+/// Citation key of a book before deduplication: family name of the first
+/// author, year and first significant word of the title
+/// (`herbert1965dune`). Unknown parts become `anon` and `nd`.
+pub fn citation_key_base(book: &Book) -> String {
+    let family = authors(book)
+        .first()
+        .map(|a| slug_words(split_name(a).0).concat())
+        .filter(|f| !f.is_empty())
+        .unwrap_or_else(|| "anon".to_string());
+    let year = if book.year > 0 {
+        book.year.to_string()
+    } else {
+        "nd".to_string()
+    };
+    let word = slug_words(&book.title)
+        .into_iter()
+        .find(|w| !LEADING_ARTICLES.contains(&w.as_str()))
+        .unwrap_or_default();
+    format!("{}{}{}", family, year, word)
+}
+
+/// Suffisso alfabetico dei duplicati: 0 → `a`, 25 → `z`, 26 → `aa`.
+fn letter_suffix(mut n: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'a' + (n % 26) as u8);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+    letters.iter().rev().map(|&b| b as char).collect()
+}
+
+/// Pairs every book with its citation key. Books are expected in catalog
+/// order (by ID): the first book with a given key keeps it, later ones get
+/// a letter suffix (`herbert1965dunea`), so adding books never changes the
+/// keys already in use. Pass the trashed books too, or trashing and restoring
+/// a book would shift the suffixes of the ones after it.
+pub fn with_citation_keys(books: &[Book]) -> Vec<(String, &Book)> {
+    let mut used = HashSet::new();
+    books
+        .iter()
+        .map(|book| {
+            let base = citation_key_base(book);
+            let mut key = base.clone();
+            let mut n = 0;
+            while !used.insert(key.clone()) {
+                key = format!("{}{}", base, letter_suffix(n));
+                n += 1;
+            }
+            (key, book)
+        })
+        .collect()
+}
+
+/// Writes the entries in `format`: BibTeX and RIS records are separated by a
+/// blank line, CSL-JSON is a single array.
+pub fn write_citations<W: Write>(
+    mut out: W,
+    format: CitationFormat,
+    entries: &[(String, &Book)],
+) -> io::Result<()> {
+    match format {
+        CitationFormat::Bibtex | CitationFormat::Ris => {
+            for (i, (key, book)) in entries.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                if format == CitationFormat::Bibtex {
+                    write_bibtex(&mut out, key, book)?;
+                } else {
+                    write_ris(&mut out, key, book)?;
+                }
+            }
+        }
+        CitationFormat::CslJson => {
+            let items: Vec<Value> = entries
+                .iter()
+                .map(|(key, book)| csl_item(key, book))
+                .collect();
+            serde_json::to_writer_pretty(&mut out, &items).map_err(io::Error::other)?;
+            writeln!(out)?;
+        }
+    }
+    out.flush()
+}
+
+/// Testo opzionale non vuoto.
+fn text(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Tag separati da virgola.
+fn keywords(book: &Book) -> Vec<&str> {
+    text(&book.tags)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Un solo rigo: gli a capo diventano spazi.
+fn one_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+//
+// 🧩 BibTeX
+//
+
+/// Escapes the characters BibTeX treats as commands or groups.
+fn bibtex_escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in one_line(value).chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Nome in forma "Cognome, Nome", come lo vuole BibTeX.
+fn inverted_name(name: &str) -> String {
+    match split_name(name) {
+        (family, "") => family.to_string(),
+        (family, given) => format!("{}, {}", family, given),
+    }
+}
+
+fn write_bibtex<W: Write>(out: &mut W, key: &str, book: &Book) -> io::Result<()> {
+    let names: Vec<String> = authors(book).into_iter().map(inverted_name).collect();
+    let mut fields = vec![
+        ("author", bibtex_escape(&names.join(" and "))),
+        // doppie graffe: il titolo mantiene le maiuscole in ogni stile
+        ("title", format!("{{{}}}", bibtex_escape(book.title.trim()))),
+        ("publisher", bibtex_escape(book.editor.trim())),
+    ];
+    if book.year > 0 {
+        fields.push(("year", book.year.to_string()));
+    }
+    fields.push(("isbn", book.isbn.clone()));
+    if let Some(pages) = book.pages {
+        fields.push(("pagetotal", pages.to_string()));
+    }
+    if let Some(language) = text(&book.language) {
+        fields.push(("language", bibtex_escape(language)));
+    }
+    if let Some(summary) = text(&book.summary) {
+        fields.push(("abstract", bibtex_escape(summary)));
+    }
+    fields.push(("keywords", bibtex_escape(&keywords(book).join(", "))));
+
+    writeln!(out, "@book{{{},", key)?;
+    for (name, value) in fields.iter().filter(|(_, v)| !v.is_empty() && v != "{}") {
+        writeln!(out, "  {} = {{{}}},", name, value)?;
+    }
+    writeln!(out, "}}")
+}
+
+//
+// 🧩 RIS
+//
+
+fn write_ris<W: Write>(out: &mut W, key: &str, book: &Book) -> io::Result<()> {
+    let mut fields = vec![("TY", "BOOK".to_string()), ("ID", key.to_string())];
+    fields.extend(authors(book).into_iter().map(|a| ("AU", inverted_name(a))));
+    fields.push(("TI", book.title.clone()));
+    if book.year > 0 {
+        fields.push(("PY", book.year.to_string()));
+    }
+    fields.push(("PB", book.editor.clone()));
+    fields.push(("SN", book.isbn.clone()));
+    if let Some(pages) = book.pages {
+        fields.push(("SP", pages.to_string()));
+    }
+    if let Some(language) = text(&book.language) {
+        fields.push(("LA", language.to_string()));
+    }
+    if let Some(summary) = text(&book.summary) {
+        fields.push(("AB", summary.to_string()));
+    }
+    fields.extend(keywords(book).into_iter().map(|k| ("KW", k.to_string())));
+
+    for (tag, value) in fields {
+        let value = one_line(&value);
+        if !value.is_empty() {
+            writeln!(out, "{}  - {}", tag, value)?;
+        }
+    }
+    writeln!(out, "ER  - ")
+}
+
+//
+// 🧩 CSL-JSON
+//
+
+/// CSL-JSON item of a book; names without a given name are kept literal.
+pub fn csl_item(key: &str, book: &Book) -> Value {
+    let mut item = Map::new();
+    let mut set = |name: &str, value: Value| {
+        item.insert(name.to_string(), value);
+    };
+    set("id", json!(key));
+    set("type", json!("book"));
+    set("title", json!(book.title.trim()));
+    let names: Vec<Value> = authors(book)
+        .into_iter()
+        .map(|a| match split_name(a) {
+            (family, "") => json!({ "literal": family }),
+            (family, given) => json!({ "family": family, "given": given }),
+        })
+        .collect();
+    if !names.is_empty() {
+        set("author", Value::Array(names));
+    }
+    if !book.editor.trim().is_empty() {
+        set("publisher", json!(book.editor.trim()));
+    }
+    if book.year > 0 {
+        set("issued", json!({ "date-parts": [[book.year]] }));
+    }
+    if !book.isbn.trim().is_empty() {
+        set("ISBN", json!(book.isbn.trim()));
+    }
+    if let Some(pages) = book.pages {
+        set("number-of-pages", json!(pages));
+    }
+    if let Some(language) = text(&book.language) {
+        set(
+            "language",
+            json!(lang_name_to_code(language).unwrap_or(language)),
+        );
+    }
+    if let Some(summary) = text(&book.summary) {
+        set("abstract", json!(summary));
+    }
+    let keywords = keywords(book);
+    if !keywords.is_empty() {
+        set("keyword", json!(keywords.join(", ")));
+    }
+    Value::Object(item)
+}
@@ -0,0 +1,658 @@
+// =====================================================
+// Librius - utils/column_map.rs
+// -----------------------------------------------------
+// Mappatura configurabile delle colonne per l'import di
+// tabelle generiche (CSV, XLSX): file YAML, regole
+// `--map`, valori predefiniti, trasformazioni e
+// riconoscimento delle intestazioni in inglese/italiano.
+// =====================================================
+
+use crate::i18n::tr_with;
+use crate::models::Book;
+use crate::models::book::READ_STATUSES;
+use crate::utils::import_helpers::RejectReason;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Book fields a column can be mapped to.
+pub const IMPORT_FIELDS: [&str; 20] = [
+    "title",
+    "author",
+    "editor",
+    "year",
+    "isbn",
+    "language",
+    "pages",
+    "genre",
+    "summary",
+    "room",
+    "shelf",
+    "row",
+    "position",
+    "added_at",
+    "rating",
+    "read_status",
+    "read_at",
+    "tags",
+    "review",
+    "format",
+];
+
+/// Header names recognized by `--infer` for each field, in English and
+/// Italian (compared after `normalize_header`).
+const HEADER_SYNONYMS: [(&str, &[&str]); 20] = [
+    ("title", &["title", "book title", "name", "titolo", "nome"]),
+    (
+        "author",
+        &[
+            "author",
+            "authors",
+            "writer",
+            "autore",
+            "autori",
+            "scrittore",
+        ],
+    ),
+    (
+        "editor",
+        &[
+            "editor",
+            "publisher",
+            "editore",
+            "casa editrice",
+            "edizioni",
+        ],
+    ),
+    (
+        "year",
+        &[
+            "year",
+            "year published",
+            "publication year",
+            "publication date",
+            "published",
+            "anno",
+            "anno di pubblicazione",
+            "anno pubblicazione",
+            "data di pubblicazione",
+        ],
+    ),
+    (
+        "isbn",
+        &[
+            "isbn",
+            "isbn13",
+            "isbn 13",
+            "isbn10",
+            "isbn 10",
+            "ean",
+            "codice isbn",
+        ],
+    ),
+    ("language", &["language", "lang", "lingua"]),
+    (
+        "pages",
+        &[
+            "pages",
+            "page count",
+            "number of pages",
+            "pagine",
+            "numero di pagine",
+            "n pagine",
+        ],
+    ),
+    ("genre", &["genre", "category", "genere", "categoria"]),
+    (
+        "summary",
+        &[
+            "summary",
+            "description",
+            "synopsis",
+            "sommario",
+            "descrizione",
+            "trama",
+            "riassunto",
+        ],
+    ),
+    ("room", &["room", "stanza"]),
+    ("shelf", &["shelf", "bookcase", "scaffale"]),
+    ("row", &["row", "ripiano", "fila"]),
+    ("position", &["position", "posizione"]),
+    (
+        "added_at",
+        &[
+            "added at",
+            "added",
+            "date added",
+            "aggiunto",
+            "aggiunto il",
+            "data aggiunta",
+            "data di inserimento",
+        ],
+    ),
+    ("rating", &["rating", "my rating", "valutazione", "voto"]),
+    (
+        "read_status",
+        &["read status", "status", "stato", "stato di lettura"],
+    ),
+    (
+        "read_at",
+        &[
+            "read at",
+            "date read",
+            "letto il",
+            "data lettura",
+            "data di lettura",
+        ],
+    ),
+    (
+        "tags",
+        &["tags", "tag", "keywords", "etichette", "parole chiave"],
+    ),
+    ("review", &["review", "my review", "recensione"]),
+    ("format", &["format", "formato"]),
+];
+
+/// Transformation applied to a column value before it is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    /// Removes leading and trailing spaces
+    Trim,
+    /// `il NOME della rosa` → `Il Nome Della Rosa`
+    TitleCase,
+    /// `A; B & C` → `A, B, C` (also `/`, ` and `, ` e `)
+    SplitAuthors,
+    /// Keeps the four-digit year of a date (`14/05/2023` → `2023`)
+    Year,
+}
+
+impl FromStr for Transform {
+    type Err = MapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "trim" => Ok(Transform::Trim),
+            "title_case" => Ok(Transform::TitleCase),
+            "split_authors" => Ok(Transform::SplitAuthors),
+            "year" => Ok(Transform::Year),
+            other => Err(MapError::UnknownTransform(other.to_string())),
+        }
+    }
+}
+
+impl Transform {
+    pub fn apply(self, value: &str) -> String {
+        match self {
+            Transform::Trim => value.trim().to_string(),
+            Transform::TitleCase => value
+                .split(' ')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first
+                            .to_uppercase()
+                            .chain(chars.flat_map(char::to_lowercase))
+                            .collect(),
+                        None => String::new(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+            Transform::SplitAuthors => {
+                let mut value = format!(" {} ", value);
+                for separator in [";", "/", " & ", " and ", " e "] {
+                    value = value.replace(separator, ",");
+                }
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|a| !a.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+            Transform::Year => four_digit_year(value).unwrap_or(value).to_string(),
+        }
+    }
+}
+
+/// First run of exactly four digits in `value`.
+fn four_digit_year(value: &str) -> Option<&str> {
+    let bytes = value.as_bytes();
+    let mut start = 0;
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_digit() {
+            start += 1;
+            continue;
+        }
+        let end = start
+            + bytes[start..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+        if end - start == 4 {
+            return Some(&value[start..end]);
+        }
+        start = end;
+    }
+    None
+}
+
+/// Source column → book field, with the transforms applied in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnRule {
+    pub column: String,
+    pub field: String,
+    pub transforms: Vec<Transform>,
+}
+
+/// Mapping options of `import` (`--map-file`, `--map`, `--default`, `--infer`).
+#[derive(Debug, Clone, Default)]
+pub struct MappingOptions {
+    pub map_file: Option<PathBuf>,
+    /// `Column=field[:transform...]` rules, comma-separated
+    pub rules: Vec<String>,
+    /// `field=value` pairs
+    pub defaults: Vec<String>,
+    pub infer: bool,
+}
+
+/// Errors in a column mapping.
+#[derive(Debug)]
+pub enum MapError {
+    File { path: String, error: String },
+    InvalidRule(String),
+    UnknownField(String),
+    UnknownTransform(String),
+    UnknownColumn(String),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            MapError::File { path, error } => tr_with(
+                "import.map.file_invalid",
+                &[("path", path), ("error", error)],
+            ),
+            MapError::InvalidRule(rule) => tr_with("import.map.invalid_rule", &[("rule", rule)]),
+            MapError::UnknownField(field) => {
+                tr_with("import.map.unknown_field", &[("field", field)])
+            }
+            MapError::UnknownTransform(name) => {
+                tr_with("import.map.unknown_transform", &[("name", name)])
+            }
+            MapError::UnknownColumn(column) => {
+                tr_with("import.map.unknown_column", &[("column", column)])
+            }
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl std::error::Error for MapError {}
+
+/// Column of a YAML mapping file: a field name (`field[:transform...]`) or
+/// `{ field, transform: [...] }`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FileColumn {
+    Field(String),
+    Rule {
+        field: String,
+        #[serde(default)]
+        transform: Vec<Transform>,
+    },
+}
+
+/// YAML mapping file (`--map-file`).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MapFile {
+    columns: serde_yaml::Mapping,
+    defaults: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// How the columns of a table become book fields.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnMap {
+    pub rules: Vec<ColumnRule>,
+    /// Values used when a field is missing or empty
+    pub defaults: BTreeMap<String, String>,
+}
+
+impl ColumnMap {
+    /// Builds the mapping for a table with the given headers: the mapping
+    /// file first, then `--map` rules and `--default` values. Columns left
+    /// unmapped are matched by field name, or guessed with `--infer` (which
+    /// also extracts the year from dates and splits author lists).
+    pub fn resolve(headers: &[String], options: &MappingOptions) -> Result<Self, MapError> {
+        let mut map = match &options.map_file {
+            Some(path) => load_map_file(path)?,
+            None => ColumnMap::default(),
+        };
+        for spec in &options.rules {
+            for rule in spec.split(',').filter(|r| !r.trim().is_empty()) {
+                let (column, target) = rule
+                    .split_once('=')
+                    .ok_or_else(|| MapError::InvalidRule(rule.trim().to_string()))?;
+                map.rules.push(parse_rule(column, target)?);
+            }
+        }
+        for spec in &options.defaults {
+            let (field, value) = spec
+                .split_once('=')
+                .ok_or_else(|| MapError::InvalidRule(spec.clone()))?;
+            let field = check_field(field.trim())?;
+            map.defaults.insert(field, value.trim().to_string());
+        }
+
+        if let Some(rule) = map
+            .rules
+            .iter()
+            .find(|r| !headers.iter().any(|h| h.trim() == r.column))
+        {
+            return Err(MapError::UnknownColumn(rule.column.clone()));
+        }
+
+        for header in headers {
+            let header = header.trim();
+            if map.rules.iter().any(|r| r.column == header) {
+                continue;
+            }
+            let guess = if options.infer {
+                infer_field(header)
+            } else {
+                IMPORT_FIELDS
+                    .iter()
+                    .find(|f| f.eq_ignore_ascii_case(header))
+                    .copied()
+            };
+            if let Some(field) = guess
+                && !map.rules.iter().any(|r| r.field == field)
+            {
+                let mut transforms = vec![Transform::Trim];
+                match field {
+                    "year" if options.infer => transforms.push(Transform::Year),
+                    "author" if options.infer => transforms.push(Transform::SplitAuthors),
+                    _ => {}
+                }
+                map.rules.push(ColumnRule {
+                    column: header.to_string(),
+                    field: field.to_string(),
+                    transforms,
+                });
+            }
+        }
+        Ok(map)
+    }
+
+    /// Headers not used by any rule.
+    pub fn unmapped<'a>(&self, headers: &'a [String]) -> Vec<&'a str> {
+        headers
+            .iter()
+            .map(|h| h.trim())
+            .filter(|h| !h.is_empty() && !self.rules.iter().any(|r| r.column == *h))
+            .collect()
+    }
+
+    /// Raw value of the first column mapped to `field` (used in reports).
+    pub fn raw_value(&self, field: &str, headers: &[String], values: &[String]) -> Option<String> {
+        self.rules
+            .iter()
+            .filter(|r| r.field == field)
+            .find_map(|r| headers.iter().position(|h| h.trim() == r.column))
+            .and_then(|i| values.get(i).cloned())
+    }
+
+    /// Maps one row onto a `Book`. Several columns mapped to the same field
+    /// are joined with `, `; empty fields take their default value.
+    pub fn map_row(&self, headers: &[String], values: &[String]) -> Result<Book, RejectReason> {
+        let mut fields: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for rule in &self.rules {
+            let Some(index) = headers.iter().position(|h| h.trim() == rule.column) else {
+                continue;
+            };
+            let raw = values.get(index).map(String::as_str).unwrap_or_default();
+            let value = rule
+                .transforms
+                .iter()
+                .fold(raw.to_string(), |v, t| t.apply(&v));
+            if !value.trim().is_empty() {
+                fields.entry(rule.field.as_str()).or_default().push(value);
+            }
+        }
+
+        let mut values: BTreeMap<&str, String> = fields
+            .into_iter()
+            .map(|(field, parts)| (field, parts.join(", ")))
+            .collect();
+        for (field, value) in &self.defaults {
+            values
+                .entry(field.as_str())
+                .or_insert_with(|| value.clone());
+        }
+        book_from_fields(&values)
+    }
+}
+
+/// Reads a YAML mapping file:
+///
+/// ```yaml
+/// columns:
+///   Titolo: title
+///   Autori: author:split_authors
+///   Anno:
+///     field: year
+///     transform: [trim, year]
+/// defaults:
+///   editor: Sconosciuto
+/// ```
+pub fn load_map_file(path: &Path) -> Result<ColumnMap, MapError> {
+    let file_error = |error: String| MapError::File {
+        path: path.display().to_string(),
+        error,
+    };
+    let text = std::fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+    let file: MapFile = serde_yaml::from_str(&text).map_err(|e| file_error(e.to_string()))?;
+
+    let mut map = ColumnMap::default();
+    for (column, value) in file.columns {
+        let column = yaml_scalar(&column).ok_or_else(|| file_error(format!("{:?}", column)))?;
+        let entry: FileColumn =
+            serde_yaml::from_value(value).map_err(|e| file_error(e.to_string()))?;
+        map.rules.push(match entry {
+            FileColumn::Field(target) => parse_rule(&column, &target)?,
+            FileColumn::Rule { field, transform } => ColumnRule {
+                column: column.trim().to_string(),
+                field: check_field(field.trim())?,
+                transforms: transform,
+            },
+        });
+    }
+    for (field, value) in file.defaults {
+        let value = yaml_scalar(&value).ok_or_else(|| file_error(format!("{:?}", value)))?;
+        map.defaults.insert(check_field(field.trim())?, value);
+    }
+    Ok(map)
+}
+
+/// `Column` + `field[:transform...]`
+fn parse_rule(column: &str, target: &str) -> Result<ColumnRule, MapError> {
+    let mut parts = target.split(':');
+    let field = check_field(parts.next().unwrap_or_default().trim())?;
+    let column = column.trim();
+    if column.is_empty() {
+        return Err(MapError::InvalidRule(format!("={}", target)));
+    }
+    Ok(ColumnRule {
+        column: column.to_string(),
+        field,
+        transforms: parts.map(str::parse).collect::<Result<_, _>>()?,
+    })
+}
+
+fn check_field(field: &str) -> Result<String, MapError> {
+    if IMPORT_FIELDS.contains(&field) {
+        Ok(field.to_string())
+    } else {
+        Err(MapError::UnknownField(field.to_string()))
+    }
+}
+
+fn yaml_scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Field guessed from a header name (`--infer`).
+pub fn infer_field(header: &str) -> Option<&'static str> {
+    let header = normalize_header(header);
+    HEADER_SYNONYMS
+        .iter()
+        .find(|(field, names)| {
+            names.contains(&header.as_str()) || header == field.replace('_', " ")
+        })
+        .map(|(field, _)| *field)
+}
+
+/// `  Numero_di-Pagine ` → `numero di pagine`; accented vowels are
+/// replaced by plain ones.
+fn normalize_header(header: &str) -> String {
+    header
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            '_' | '-' | '.' | '\'' => ' ',
+            'à' | 'á' => 'a',
+            'è' | 'é' => 'e',
+            'ì' | 'í' => 'i',
+            'ò' | 'ó' => 'o',
+            'ù' | 'ú' => 'u',
+            c => c,
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Builds a `Book` from field values; only `title` is required.
+fn book_from_fields(fields: &BTreeMap<&str, String>) -> Result<Book, RejectReason> {
+    let text = |field: &str| fields.get(field).map(|v| v.trim().to_string());
+    let invalid = |field: &str, value: &str| RejectReason::InvalidField {
+        field: field.to_string(),
+        value: value.to_string(),
+    };
+    let parsed = |field: &str| -> Result<Option<i32>, RejectReason> {
+        text(field)
+            .map(|v| v.parse().map_err(|_| invalid(field, &v)))
+            .transpose()
+    };
+
+    let title = text("title").unwrap_or_default();
+    if title.is_empty() {
+        return Err(RejectReason::MissingTitle);
+    }
+    let rating = parsed("rating")?;
+    if let Some(r) = rating
+        && !(1..=5).contains(&r)
+    {
+        return Err(invalid("rating", &r.to_string()));
+    }
+    let read_status = text("read_status");
+    if let Some(status) = &read_status
+        && !READ_STATUSES.contains(&status.as_str())
+    {
+        return Err(invalid("read_status", status));
+    }
+    let read_at = text("read_at")
+        .map(|v| parse_date(&v).ok_or_else(|| invalid("read_at", &v)))
+        .transpose()?;
+    let added_at = text("added_at")
+        .map(|v| parse_datetime(&v).ok_or_else(|| invalid("added_at", &v)))
+        .transpose()?;
+
+    Ok(Book {
+        id: None,
+        title,
+        author: text("author").unwrap_or_default(),
+        editor: text("editor").unwrap_or_default(),
+        year: parsed("year")?.unwrap_or_default(),
+        isbn: text("isbn").unwrap_or_default(),
+        language: text("language"),
+        pages: parsed("pages")?,
+        genre: text("genre"),
+        summary: text("summary"),
+        room: text("room"),
+        shelf: text("shelf"),
+        row: text("row"),
+        position: text("position"),
+        added_at,
+        rating,
+        read_status,
+        read_at,
+        tags: text("tags"),
+        review: text("review"),
+        format: text("format"),
+    })
+}
+
+/// Text of a book field as written by the exports, in a form `map_row` reads
+/// back (empty when the value is missing).
+pub fn field_value(book: &Book, field: &str) -> String {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let number = |value: Option<i32>| value.map(|n| n.to_string()).unwrap_or_default();
+    match field {
+        "id" => number(book.id),
+        "title" => book.title.clone(),
+        "author" => book.author.clone(),
+        "editor" => book.editor.clone(),
+        "year" => book.year.to_string(),
+        "isbn" => book.isbn.clone(),
+        "language" => text(&book.language),
+        "pages" => number(book.pages),
+        "genre" => text(&book.genre),
+        "summary" => text(&book.summary),
+        "room" => text(&book.room),
+        "shelf" => text(&book.shelf),
+        "row" => text(&book.row),
+        "position" => text(&book.position),
+        "added_at" => book.added_at.map(|d| d.to_rfc3339()).unwrap_or_default(),
+        "rating" => number(book.rating),
+        "read_status" => text(&book.read_status),
+        "read_at" => book.read_at.map(|d| d.to_string()).unwrap_or_default(),
+        "tags" => text(&book.tags),
+        "review" => text(&book.review),
+        "format" => text(&book.format),
+        _ => String::new(),
+    }
+}
+
+/// `2023-05-14`, `2023/05/14` or `14/05/2023`.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%Y/%m/%d", "%d/%m/%Y"]
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+}
+
+/// RFC 3339, `2023-05-14 10:20:30` or a plain date (midnight UTC).
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|d| d.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .or_else(|| parse_date(value).and_then(|d| d.and_hms_opt(0, 0, 0)))
+                .map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc))
+        })
+}
@@ -0,0 +1,230 @@
+// =====================================================
+// Librius - utils/crypto.rs
+// -----------------------------------------------------
+// Cifratura autenticata dei backup (XChaCha20-Poly1305)
+// con chiave derivata dalla passphrase tramite Argon2id.
+// =====================================================
+
+use crate::i18n::{tr, tr_with};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::fmt;
+
+/// Magic bytes at the start of every encrypted Librius archive.
+pub const ENCRYPTED_MAGIC: &[u8; 8] = b"LBRSENC\0";
+
+/// File extension appended to encrypted backups.
+pub const ENCRYPTED_EXT: &str = "enc";
+
+const FORMAT_VERSION: u8 = 1;
+const KDF_ARGON2ID: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+// magic + version + kdf id + m_cost + t_cost + p_cost + salt + nonce
+const HEADER_LEN: usize = 8 + 1 + 1 + 4 + 4 + 4 + SALT_LEN + NONCE_LEN;
+
+/// Argon2id cost parameters, stored in clear in the archive header so that
+/// restoring never depends on the defaults of the running binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub m_cost: u32,
+    /// Number of iterations
+    pub t_cost: u32,
+    /// Degree of parallelism
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Highest costs accepted from an archive header: four times the
+    /// defaults. The header is only authenticated after the key has been
+    /// derived, so larger values are rejected before running Argon2.
+    pub const MAX: KdfParams = KdfParams {
+        m_cost: 4 * 19 * 1024,
+        t_cost: 4 * 2,
+        p_cost: 4,
+    };
+
+    fn within_limits(&self) -> bool {
+        self.m_cost <= Self::MAX.m_cost
+            && self.t_cost <= Self::MAX.t_cost
+            && self.p_cost <= Self::MAX.p_cost
+    }
+}
+
+/// Errors raised while encrypting or decrypting a backup archive.
+#[derive(Debug)]
+pub enum CryptoError {
+    /// The data does not start with a valid Librius header
+    InvalidHeader,
+    /// The header was written by a newer, unsupported format version
+    UnsupportedVersion(u8),
+    /// Authentication failed: wrong passphrase or tampered archive
+    WrongPassphrase,
+    /// Key derivation or cipher setup failed
+    Kdf(String),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::InvalidHeader => write!(f, "{}", tr("backup.crypto.invalid_header")),
+            CryptoError::UnsupportedVersion(v) => write!(
+                f,
+                "{}",
+                tr_with(
+                    "backup.crypto.unsupported_version",
+                    &[("version", &v.to_string())]
+                )
+            ),
+            CryptoError::WrongPassphrase => write!(f, "{}", tr("backup.crypto.wrong_passphrase")),
+            CryptoError::Kdf(e) => {
+                write!(
+                    f,
+                    "{}",
+                    tr_with("backup.crypto.kdf_failed", &[("error", e)])
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+/// Returns true if `data` starts with the encrypted archive magic bytes.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENCRYPTED_MAGIC)
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: &KdfParams,
+) -> Result<[u8; KEY_LEN], CryptoError> {
+    let argon_params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(KEY_LEN))
+        .map_err(|e| CryptoError::Kdf(e.to_string()))?;
+    let argon = Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params);
+
+    let mut key = [0u8; KEY_LEN];
+    argon
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| CryptoError::Kdf(e.to_string()))?;
+    Ok(key)
+}
+
+/// Encrypts `data` with a key derived from `passphrase` using the default
+/// Argon2id parameters.
+pub fn encrypt_bytes(data: &[u8], passphrase: &str) -> Result<Vec<u8>, CryptoError> {
+    encrypt_bytes_with(data, passphrase, &KdfParams::default())
+}
+
+/// Encrypts `data` with explicit KDF parameters.
+///
+/// Layout: `magic | version | kdf | m_cost | t_cost | p_cost | salt | nonce | ciphertext`.
+/// The whole header is authenticated as associated data, so tampering with
+/// the KDF parameters is detected on decryption.
+pub fn encrypt_bytes_with(
+    data: &[u8],
+    passphrase: &str,
+    params: &KdfParams,
+) -> Result<Vec<u8>, CryptoError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(ENCRYPTED_MAGIC);
+    header.push(FORMAT_VERSION);
+    header.push(KDF_ARGON2ID);
+    header.extend_from_slice(&params.m_cost.to_le_bytes());
+    header.extend_from_slice(&params.t_cost.to_le_bytes());
+    header.extend_from_slice(&params.p_cost.to_le_bytes());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let key = derive_key(passphrase, &salt, params)?;
+    let cipher =
+        XChaCha20Poly1305::new_from_slice(&key).map_err(|e| CryptoError::Kdf(e.to_string()))?;
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: data,
+                aad: &header,
+            },
+        )
+        .map_err(|e| CryptoError::Kdf(e.to_string()))?;
+
+    let mut out = header;
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Reads the KDF parameters recorded in an encrypted archive header.
+///
+/// Costs above [`KdfParams::MAX`] are reported as
+/// [`CryptoError::InvalidHeader`].
+pub fn read_kdf_params(data: &[u8]) -> Result<KdfParams, CryptoError> {
+    if data.len() < HEADER_LEN || !is_encrypted(data) {
+        return Err(CryptoError::InvalidHeader);
+    }
+    if data[8] != FORMAT_VERSION {
+        return Err(CryptoError::UnsupportedVersion(data[8]));
+    }
+    if data[9] != KDF_ARGON2ID {
+        return Err(CryptoError::InvalidHeader);
+    }
+
+    let u32_at = |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    let params = KdfParams {
+        m_cost: u32_at(10),
+        t_cost: u32_at(14),
+        p_cost: u32_at(18),
+    };
+    // L'header non è ancora autenticato: costi eccessivi bloccherebbero Argon2
+    if !params.within_limits() {
+        return Err(CryptoError::InvalidHeader);
+    }
+    Ok(params)
+}
+
+/// Decrypts an archive produced by [`encrypt_bytes`].
+///
+/// Returns [`CryptoError::WrongPassphrase`] when authentication fails; no
+/// partially decrypted data is ever returned.
+pub fn decrypt_bytes(data: &[u8], passphrase: &str) -> Result<Vec<u8>, CryptoError> {
+    let params = read_kdf_params(data)?;
+
+    let salt_start = 22;
+    let nonce_start = salt_start + SALT_LEN;
+    let salt = &data[salt_start..nonce_start];
+    let mut nonce = XNonce::default();
+    nonce.copy_from_slice(&data[nonce_start..HEADER_LEN]);
+
+    let key = derive_key(passphrase, salt, &params)?;
+    let cipher =
+        XChaCha20Poly1305::new_from_slice(&key).map_err(|e| CryptoError::Kdf(e.to_string()))?;
+    cipher
+        .decrypt(
+            &nonce,
+            Payload {
+                msg: &data[HEADER_LEN..],
+                aad: &data[..HEADER_LEN],
+            },
+        )
+        .map_err(|_| CryptoError::WrongPassphrase)
+}
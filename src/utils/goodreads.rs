@@ -0,0 +1,204 @@
+// =====================================================
+// Librius - utils/goodreads.rs
+// -----------------------------------------------------
+// Lettura dell'export CSV di Goodreads e conversione
+// delle righe nel modello `Book` (dati di lettura,
+// valutazione e scaffali compresi).
+// =====================================================
+
+use crate::i18n::tr_with;
+use crate::models::Book;
+use crate::utils::import_helpers::{MappedImport, RejectReason, RejectedRow, import_isbn};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use std::io;
+
+/// Columns that identify a Goodreads export.
+const REQUIRED_COLUMNS: [&str; 3] = ["Title", "Author", "ISBN13"];
+
+/// Built-in Goodreads shelves, mapped to `Book::read_status`.
+const EXCLUSIVE_SHELVES: [(&str, &str); 3] = [
+    ("read", "read"),
+    ("currently-reading", "reading"),
+    ("to-read", "to_read"),
+];
+
+/// A row of the Goodreads "Export Library" CSV; missing columns are empty.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GoodreadsRecord {
+    #[serde(rename = "Title")]
+    pub title: String,
+    #[serde(rename = "Author")]
+    pub author: String,
+    #[serde(rename = "Additional Authors")]
+    pub additional_authors: String,
+    #[serde(rename = "ISBN")]
+    pub isbn: String,
+    #[serde(rename = "ISBN13")]
+    pub isbn13: String,
+    #[serde(rename = "My Rating")]
+    pub my_rating: String,
+    #[serde(rename = "Publisher")]
+    pub publisher: String,
+    #[serde(rename = "Number of Pages")]
+    pub pages: String,
+    #[serde(rename = "Year Published")]
+    pub year_published: String,
+    #[serde(rename = "Original Publication Year")]
+    pub original_year: String,
+    #[serde(rename = "Date Read")]
+    pub date_read: String,
+    #[serde(rename = "Date Added")]
+    pub date_added: String,
+    #[serde(rename = "Bookshelves")]
+    pub bookshelves: String,
+    #[serde(rename = "Exclusive Shelf")]
+    pub exclusive_shelf: String,
+    #[serde(rename = "My Review")]
+    pub my_review: String,
+}
+
+/// Reads a Goodreads export. Fails only if the file is not a Goodreads CSV
+/// (missing columns); unusable rows end up in `rejected`.
+pub fn read_goodreads<R: io::Read>(reader: R) -> io::Result<MappedImport> {
+    let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv.headers()?.clone();
+    if let Some(missing) = REQUIRED_COLUMNS
+        .iter()
+        .find(|c| !headers.iter().any(|h| h.trim() == **c))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            tr_with("import.goodreads.not_export", &[("column", missing)]),
+        ));
+    }
+
+    let mut import = MappedImport::default();
+    for result in csv.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                import.rejected.push(RejectedRow {
+                    line: e.position().map(|p| p.line()).unwrap_or_default(),
+                    title: String::new(),
+                    reason: RejectReason::Unreadable(e.to_string()),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        let mapped = record
+            .deserialize::<GoodreadsRecord>(Some(&headers))
+            .map_err(|e| (String::new(), RejectReason::Unreadable(e.to_string())))
+            .and_then(|r| map_record(&r).map_err(|reason| (r.title.trim().to_string(), reason)));
+        match mapped {
+            Ok(book) => import.books.push((line, book)),
+            Err((title, reason)) => import.rejected.push(RejectedRow {
+                line,
+                title,
+                reason,
+            }),
+        }
+    }
+    Ok(import)
+}
+
+/// Maps a Goodreads row onto a `Book`, normalizing the ISBN.
+pub fn map_record(record: &GoodreadsRecord) -> Result<Book, RejectReason> {
+    let title = record.title.trim();
+    if title.is_empty() {
+        return Err(RejectReason::MissingTitle);
+    }
+
+    // Goodreads scrive gli ISBN come formule Excel: ="9780441013593"
+    let raw_isbn = [&record.isbn13, &record.isbn]
+        .into_iter()
+        .map(|s| unquote_excel(s))
+        .find(|s| !s.is_empty())
+        .unwrap_or_default();
+    let isbn = import_isbn(&raw_isbn)?;
+
+    let authors: Vec<&str> = std::iter::once(record.author.as_str())
+        .chain(record.additional_authors.split(','))
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .collect();
+
+    let shelf = record.exclusive_shelf.trim();
+    let read_status = EXCLUSIVE_SHELVES
+        .iter()
+        .find(|(name, _)| *name == shelf)
+        .map(|(_, status)| status.to_string());
+
+    // Scaffali personali come tag; uno scaffale esclusivo personalizzato
+    // (non `read`/`to-read`/...) diventa anch'esso un tag
+    let mut tags: Vec<&str> = record
+        .bookshelves
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && !EXCLUSIVE_SHELVES.iter().any(|(name, _)| name == s))
+        .collect();
+    if read_status.is_none() && !shelf.is_empty() && !tags.contains(&shelf) {
+        tags.push(shelf);
+    }
+
+    let review = record
+        .my_review
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .replace("<br>", "\n");
+
+    Ok(Book {
+        id: None,
+        title: title.to_string(),
+        author: authors.join(", "),
+        editor: record.publisher.trim().to_string(),
+        year: [&record.year_published, &record.original_year]
+            .into_iter()
+            .find_map(|y| y.trim().parse().ok())
+            .unwrap_or_default(),
+        isbn,
+        language: None,
+        pages: record.pages.trim().parse().ok(),
+        genre: None,
+        summary: None,
+        room: None,
+        shelf: None,
+        row: None,
+        position: None,
+        added_at: parse_date(&record.date_added)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)),
+        rating: record
+            .my_rating
+            .trim()
+            .parse()
+            .ok()
+            .filter(|r| (1..=5).contains(r)),
+        read_status,
+        read_at: parse_date(&record.date_read),
+        tags: Some(tags.join(", ")).filter(|t| !t.is_empty()),
+        review: Some(review.trim().to_string()).filter(|r| !r.is_empty()),
+        format: None,
+    })
+}
+
+/// `="0441013593"` → `0441013593`
+fn unquote_excel(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix("=\"")
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .trim()
+        .to_string()
+}
+
+/// Goodreads dates: `2023/05/14` (or ISO `2023-05-14`).
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    ["%Y/%m/%d", "%Y-%m-%d"]
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+}
@@ -0,0 +1,515 @@
+// =====================================================
+// Librius - utils/html.rs
+// -----------------------------------------------------
+// Catalogo statico in HTML: indice dei libri ordinabile
+// e filtrabile, una pagina per libro, indici per autore
+// e per genere e copertine. Nessun server né risorsa
+// esterna: il sito si apre anche da file://.
+// =====================================================
+
+use crate::i18n::{current_language, format_datetime, tr, tr_with};
+use crate::models::Book;
+use crate::utils::citation::split_name;
+use crate::utils::table::detail_rows;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const STYLE: &str = include_str!("html/style.css");
+const SCRIPT: &str = include_str!("html/catalog.js");
+
+/// Extensions looked up for cover images, in order of preference.
+pub const COVER_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "webp", "gif"];
+
+/// Campi mostrati nel riquadro della collocazione.
+const LOCATION_FIELDS: [&str; 4] = ["room", "shelf", "row", "position"];
+
+/// Returns the `covers` directory placed next to the database file, where
+/// cover images are looked up as `<ISBN>.jpg` (or `.png`, `.webp`, …).
+pub fn covers_dir_for(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("covers")
+}
+
+/// Cover image of a book in `covers_dir`, if there is one.
+pub fn find_cover(covers_dir: &Path, isbn: &str) -> Option<PathBuf> {
+    let isbn = isbn.trim();
+    if isbn.is_empty() {
+        return None;
+    }
+    COVER_EXTENSIONS
+        .iter()
+        .map(|ext| covers_dir.join(format!("{}.{}", isbn, ext)))
+        .find(|path| path.is_file())
+}
+
+/// What `write_site` has written.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SiteReport {
+    pub books: usize,
+    pub covers: usize,
+}
+
+/// Escapes text for HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Identificatore di ancora: minuscole, trattini al posto del resto.
+fn anchor(name: &str) -> String {
+    let words: Vec<String> = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect();
+    words.join("-")
+}
+
+fn authors(book: &Book) -> Vec<&str> {
+    book.author
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+fn genre(book: &Book) -> Option<&str> {
+    book.genre
+        .as_deref()
+        .map(str::trim)
+        .filter(|g| !g.is_empty())
+}
+
+fn book_page(book: &Book) -> String {
+    format!("books/{}.html", book.id.unwrap_or_default())
+}
+
+/// Collocazione compatta per l'indice ("Studio · B2 · 3").
+fn location(book: &Book) -> String {
+    [&book.room, &book.shelf, &book.row, &book.position]
+        .into_iter()
+        .filter_map(|v| v.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// Link agli autori di un libro, relativi a `root`.
+fn author_links(book: &Book, root: &str) -> String {
+    authors(book)
+        .into_iter()
+        .map(|a| {
+            format!(
+                "<a href=\"{}authors.html#{}\">{}</a>",
+                root,
+                anchor(a),
+                escape(a)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Books,
+    Authors,
+    Genres,
+}
+
+/// Common frame of every page; `root` is the relative path to the site root.
+fn page(site: &Site, title: &str, root: &str, current: Option<Section>, body: &str) -> String {
+    let nav = [
+        (Section::Books, "index.html", tr("html.nav.books")),
+        (Section::Authors, "authors.html", tr("html.nav.authors")),
+        (Section::Genres, "genres.html", tr("html.nav.genres")),
+    ]
+    .into_iter()
+    .map(|(section, href, label)| {
+        let current = if current == Some(section) {
+            " aria-current=\"page\""
+        } else {
+            ""
+        };
+        format!(
+            "<a href=\"{}{}\"{}>{}</a>",
+            root,
+            href,
+            current,
+            escape(&label)
+        )
+    })
+    .collect::<Vec<_>>()
+    .join("\n      ");
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <meta name="generator" content="Librius {version}">
+  <title>{title}</title>
+  <link rel="stylesheet" href="{root}assets/style.css">
+</head>
+<body>
+  <header>
+    <p class="site"><a href="{root}index.html">{site}</a></p>
+    <nav>
+      {nav}
+    </nav>
+  </header>
+  <main>
+{body}
+  </main>
+  <footer>{generated}</footer>
+</body>
+</html>
+"#,
+        lang = escape(&site.lang),
+        version = env!("CARGO_PKG_VERSION"),
+        title = escape(title),
+        root = root,
+        site = escape(&site.title),
+        nav = nav,
+        body = body,
+        generated = escape(&site.generated),
+    )
+}
+
+/// Dati comuni a tutte le pagine.
+struct Site {
+    lang: String,
+    title: String,
+    generated: String,
+}
+
+/// Writes the static site into `dir`: `index.html`, `authors.html`,
+/// `genres.html`, one `books/<ID>.html` per book, the assets and the covers
+/// found in `covers_dir`. `library` is the name of the active named library,
+/// shown in the title. Pages of books no longer in the catalog are removed.
+pub fn write_site(
+    dir: &Path,
+    books: &[Book],
+    covers_dir: Option<&Path>,
+    library: Option<&str>,
+) -> io::Result<SiteReport> {
+    let site = Site {
+        lang: current_language(),
+        title: match library {
+            Some(name) => tr_with("html.title_named", &[("name", name)]),
+            None => tr("html.title"),
+        },
+        generated: tr_with("html.generated", &[("date", &format_datetime(&Utc::now()))]),
+    };
+
+    for sub in ["assets", "books", "covers"] {
+        fs::create_dir_all(dir.join(sub))?;
+    }
+    fs::write(dir.join("assets/style.css"), STYLE)?;
+    fs::write(dir.join("assets/catalog.js"), SCRIPT)?;
+
+    let mut report = SiteReport::default();
+    let mut pages = HashSet::new();
+    for book in books {
+        let cover = match covers_dir.and_then(|d| find_cover(d, &book.isbn)) {
+            Some(source) => {
+                let name = source.file_name().unwrap_or_default().to_owned();
+                fs::copy(&source, dir.join("covers").join(&name))?;
+                report.covers += 1;
+                Some(name.to_string_lossy().to_string())
+            }
+            None => None,
+        };
+        let path = book_page(book);
+        fs::write(dir.join(&path), detail_page(&site, book, cover.as_deref()))?;
+        pages.insert(path);
+        report.books += 1;
+    }
+
+    // Pagine generate in precedenza per libri non più in catalogo
+    for entry in fs::read_dir(dir.join("books"))? {
+        let path = entry?.path();
+        let stale = path.extension().is_some_and(|e| e == "html")
+            && path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.chars().all(|c| c.is_ascii_digit()))
+            && !pages.contains(&format!(
+                "books/{}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ));
+        if stale {
+            fs::remove_file(path)?;
+        }
+    }
+
+    fs::write(dir.join("index.html"), index_page(&site, books))?;
+    fs::write(dir.join("authors.html"), authors_page(&site, books))?;
+    fs::write(dir.join("genres.html"), genres_page(&site, books))?;
+
+    Ok(report)
+}
+
+//
+// 🧩 Indice dei libri
+//
+
+fn index_page(site: &Site, books: &[Book]) -> String {
+    let columns = [
+        (tr("list.header.title"), "text"),
+        (tr("list.header.author"), "text"),
+        (tr("list.header.editor"), "text"),
+        (tr("list.header.year"), "number"),
+        (tr("list.header.genre"), "text"),
+        (tr("list.header.language"), "text"),
+        (tr("html.location"), "text"),
+    ];
+
+    let mut body = String::new();
+    let total = books.len().to_string();
+    let _ = write!(
+        body,
+        r#"    <h1>{title}</h1>
+    <div class="toolbar">
+      <input id="filter" type="search" placeholder="{placeholder}" aria-label="{placeholder}" hidden>
+      <span id="count" class="count" data-template="{template}">{count}</span>
+    </div>
+    <table id="books">
+      <thead>
+        <tr>
+"#,
+        title = escape(&tr("html.nav.books")),
+        placeholder = escape(&tr("html.filter")),
+        // il modello conserva i segnaposto: li sostituisce catalog.js
+        template = escape(&tr_with(
+            "html.shown",
+            &[("shown", "{shown}"), ("total", "{total}")]
+        )),
+        count = escape(&tr_with(
+            "html.shown",
+            &[("shown", &total), ("total", &total)]
+        )),
+    );
+    for (label, kind) in &columns {
+        let _ = writeln!(
+            body,
+            "          <th data-type=\"{}\"><button type=\"button\">{}</button></th>",
+            kind,
+            escape(label)
+        );
+    }
+    body.push_str("        </tr>\n      </thead>\n      <tbody>\n");
+
+    for book in books {
+        let year = if book.year > 0 {
+            book.year.to_string()
+        } else {
+            String::new()
+        };
+        let genre = genre(book)
+            .map(|g| format!("<a href=\"genres.html#{}\">{}</a>", anchor(g), escape(g)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            body,
+            "        <tr class=\"book-row\"><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td>\
+             <td class=\"number\">{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            book_page(book),
+            escape(&book.title),
+            author_links(book, ""),
+            escape(&book.editor),
+            year,
+            genre,
+            escape(book.language.as_deref().unwrap_or_default()),
+            escape(&location(book)),
+        );
+    }
+    let _ = write!(
+        body,
+        "        <tr class=\"empty\" hidden><td colspan=\"{}\">{}</td></tr>\n      </tbody>\n    </table>\n    <script src=\"assets/catalog.js\"></script>",
+        columns.len(),
+        escape(&tr("html.no_match"))
+    );
+
+    page(site, &site.title, "", Some(Section::Books), &body)
+}
+
+//
+// 🧩 Pagina del libro
+//
+
+/// Coppie `<dt>`/`<dd>` dei campi valorizzati.
+fn definitions<'a>(
+    rows: impl Iterator<Item = &'a (&'static str, String, Option<String>)>,
+) -> String {
+    rows.filter_map(|(_, label, value)| {
+        let value = value.as_deref().filter(|v| !v.trim().is_empty())?;
+        Some(format!(
+            "        <dt>{}</dt>\n        <dd>{}</dd>\n",
+            escape(label),
+            escape(value)
+        ))
+    })
+    .collect()
+}
+
+fn detail_page(site: &Site, book: &Book, cover: Option<&str>) -> String {
+    let rows = detail_rows(book).unwrap_or_default();
+    let value = |field: &str| {
+        rows.iter()
+            .find(|(key, _, _)| *key == field)
+            .and_then(|(_, _, v)| v.as_deref())
+            .filter(|v| !v.trim().is_empty())
+    };
+
+    let mut body = String::from("    <article class=\"book\">\n");
+    if let Some(cover) = cover {
+        let _ = writeln!(
+            body,
+            "      <div class=\"cover\"><img src=\"../covers/{}\" alt=\"{}\"></div>",
+            escape(cover),
+            escape(&tr_with("html.cover_alt", &[("title", &book.title)]))
+        );
+    }
+    let _ = write!(
+        body,
+        "      <div class=\"text\">\n      <h1>{}</h1>\n      <p class=\"authors\">{}</p>\n",
+        escape(&book.title),
+        author_links(book, "../")
+    );
+    if let Some(summary) = value("summary") {
+        let _ = write!(
+            body,
+            "      <h2>{}</h2>\n      <p class=\"summary\">{}</p>\n",
+            escape(&tr("list.header.summary")),
+            escape(summary)
+        );
+    }
+    let location = definitions(
+        rows.iter()
+            .filter(|(key, _, _)| LOCATION_FIELDS.contains(key)),
+    );
+    if !location.is_empty() {
+        let _ = write!(
+            body,
+            "      <h2>{}</h2>\n      <dl class=\"location\">\n{}      </dl>\n",
+            escape(&tr("html.location")),
+            location
+        );
+    }
+    let details = definitions(rows.iter().filter(|(key, _, _)| {
+        !["title", "summary"].contains(key) && !LOCATION_FIELDS.contains(key)
+    }));
+    let _ = write!(
+        body,
+        "      <h2>{}</h2>\n      <dl>\n{}      </dl>\n      </div>\n    </article>",
+        escape(&tr("html.details")),
+        details
+    );
+
+    page(site, &book.title, "../", None, &body)
+}
+
+//
+// 🧩 Indici per autore e per genere
+//
+
+/// Una sezione per gruppo, con ancora e libri in ordine di titolo.
+fn groups_body(title: &str, groups: &BTreeMap<String, (String, Vec<&Book>)>) -> String {
+    let mut body = format!("    <h1>{}</h1>\n", escape(title));
+    for (name, books) in groups.values() {
+        let _ = write!(
+            body,
+            "    <section class=\"group\" id=\"{}\">\n      <h2>{} <small>({})</small></h2>\n      <ul>\n",
+            anchor(name),
+            escape(name),
+            books.len()
+        );
+        let mut books = books.clone();
+        books.sort_by_key(|b| (b.title.to_lowercase(), b.year));
+        for book in books {
+            let year = if book.year > 0 {
+                format!(" ({})", book.year)
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                body,
+                "        <li><a href=\"{}\">{}</a>{}</li>",
+                book_page(book),
+                escape(&book.title),
+                year
+            );
+        }
+        body.push_str("      </ul>\n    </section>\n");
+    }
+    body.trim_end().to_string()
+}
+
+fn authors_page(site: &Site, books: &[Book]) -> String {
+    // In ordine di cognome, poi di nome
+    let mut groups: BTreeMap<String, (String, Vec<&Book>)> = BTreeMap::new();
+    for book in books {
+        for author in authors(book) {
+            // le particelle non contano ("di Lampedusa" sotto la L)
+            let (family, given) = split_name(author);
+            let family = family
+                .split(' ')
+                .skip_while(|w| w.starts_with(char::is_lowercase))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let key = format!("{}\u{0}{}", family.to_lowercase(), given.to_lowercase());
+            groups
+                .entry(key)
+                .or_insert_with(|| (author.to_string(), Vec::new()))
+                .1
+                .push(book);
+        }
+    }
+    let title = tr("html.nav.authors");
+    page(
+        site,
+        &title,
+        "",
+        Some(Section::Authors),
+        &groups_body(&title, &groups),
+    )
+}
+
+fn genres_page(site: &Site, books: &[Book]) -> String {
+    let mut groups: BTreeMap<String, (String, Vec<&Book>)> = BTreeMap::new();
+    for book in books {
+        if let Some(genre) = genre(book) {
+            groups
+                .entry(genre.to_lowercase())
+                .or_insert_with(|| (genre.to_string(), Vec::new()))
+                .1
+                .push(book);
+        }
+    }
+    let title = tr("html.nav.genres");
+    page(
+        site,
+        &title,
+        "",
+        Some(Section::Genres),
+        &groups_body(&title, &groups),
+    )
+}
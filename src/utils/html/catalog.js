@@ -0,0 +1,86 @@
+// Librius — catalogo statico: ordinamento e filtro della tabella dei libri.
+// Nessuna dipendenza e nessuna richiesta di rete: funziona anche da file://.
+(function () {
+  "use strict";
+
+  var table = document.getElementById("books");
+  if (!table) {
+    return;
+  }
+  var body = table.tBodies[0];
+  var rows = Array.prototype.slice.call(body.querySelectorAll("tr.book-row"));
+  var empty = body.querySelector("tr.empty");
+  var filter = document.getElementById("filter");
+  var count = document.getElementById("count");
+  var collator = new Intl.Collator(document.documentElement.lang || undefined, {
+    numeric: true,
+    sensitivity: "base"
+  });
+
+  // Testo normalizzato: minuscole e senza accenti
+  function fold(text) {
+    return text.normalize("NFD").replace(/[\u0300-\u036f]/g, "").toLowerCase();
+  }
+
+  rows.forEach(function (row) {
+    row.dataset.search = fold(row.textContent);
+  });
+
+  function applyFilter() {
+    var terms = fold(filter.value).split(/\s+/).filter(Boolean);
+    var shown = 0;
+    rows.forEach(function (row) {
+      var match = terms.every(function (term) {
+        return row.dataset.search.indexOf(term) !== -1;
+      });
+      row.hidden = !match;
+      if (match) {
+        shown += 1;
+      }
+    });
+    empty.hidden = shown !== 0;
+    count.textContent = count.dataset.template
+      .replace("{shown}", shown)
+      .replace("{total}", rows.length);
+  }
+
+  function sortBy(header) {
+    var column = header.cellIndex;
+    var numeric = header.dataset.type === "number";
+    var ascending = header.getAttribute("aria-sort") !== "ascending";
+    Array.prototype.forEach.call(table.tHead.rows[0].cells, function (th) {
+      th.removeAttribute("aria-sort");
+    });
+    header.setAttribute("aria-sort", ascending ? "ascending" : "descending");
+
+    var value = function (row) {
+      var cell = row.cells[column];
+      return cell.dataset.sort !== undefined ? cell.dataset.sort : cell.textContent.trim();
+    };
+    rows.sort(function (a, b) {
+      var x = value(a);
+      var y = value(b);
+      // Celle vuote sempre in fondo
+      if (x === "" || y === "") {
+        return (x === "") - (y === "");
+      }
+      var order = numeric ? Number(x) - Number(y) : collator.compare(x, y);
+      return ascending ? order : -order;
+    });
+    rows.forEach(function (row) {
+      body.insertBefore(row, empty);
+    });
+  }
+
+  Array.prototype.forEach.call(table.tHead.rows[0].cells, function (th) {
+    var button = th.querySelector("button");
+    if (button) {
+      button.addEventListener("click", function () {
+        sortBy(th);
+      });
+    }
+  });
+  filter.addEventListener("input", applyFilter);
+  filter.hidden = false;
+  applyFilter();
+})();
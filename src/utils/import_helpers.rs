@@ -5,11 +5,17 @@
 // delle operazioni di import nel database.
 // =====================================================
 
-use crate::i18n::tr_with;
+use crate::i18n::{tr, tr_with};
+use crate::models::Book;
+use crate::models::book::READ_STATUSES;
+use crate::utils::isbn::normalize_isbn;
 use crate::utils::print::print_err;
-use rusqlite::Result as SqlResult;
+use chrono::{Datelike, Local};
+use std::fmt;
 use std::fs::File;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 /// Opens a file for import operations and prints a localized error message on failure.
 pub fn open_import_file(file: &str) -> Result<File, io::Error> {
@@ -23,24 +29,324 @@ pub fn open_import_file(file: &str) -> Result<File, io::Error> {
     })
 }
 
-/// Handles the result of a database insert operation for book import.
-/// Increments counters and prints localized error messages if necessary.
-pub fn handle_import_result(
-    result: &SqlResult<usize>,
-    imported: &mut u32,
-    failed: &mut u32,
-    title: &str,
-) {
-    match result {
-        Ok(_) => {
-            *imported += 1;
+/// Why an imported record could not become a book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The record itself cannot be read
+    Unreadable(String),
+    MissingTitle,
+    MissingAuthor,
+    InvalidIsbn(String),
+    /// A value that does not fit its field (e.g. a non-numeric year)
+    InvalidField {
+        field: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            RejectReason::Unreadable(error) => {
+                tr_with("import.reason.unreadable", &[("error", error)])
+            }
+            RejectReason::MissingTitle => tr("import.reason.missing_title"),
+            RejectReason::MissingAuthor => tr("import.reason.missing_author"),
+            RejectReason::InvalidIsbn(isbn) => {
+                tr_with("import.reason.invalid_isbn", &[("isbn", isbn)])
+            }
+            RejectReason::InvalidField { field, value } => tr_with(
+                "import.reason.invalid_field",
+                &[("field", field), ("value", value)],
+            ),
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// A record left out of an import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedRow {
+    /// Line in the source file
+    pub line: u64,
+    pub title: String,
+    pub reason: RejectReason,
+}
+
+impl fmt::Display for RejectedRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr_with(
+                "import.rejected",
+                &[
+                    ("line", &self.line.to_string()),
+                    ("title", &self.title),
+                    ("reason", &self.reason.to_string()),
+                ],
+            )
+        )
+    }
+}
+
+/// Plain ISBN of an imported record, normalized with `normalize_isbn`.
+/// An empty ISBN means the record has none and is kept empty; only a
+/// malformed one is rejected.
+pub fn import_isbn(raw: &str) -> Result<String, RejectReason> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(String::new());
+    }
+    normalize_isbn(raw, true).map_err(|_| RejectReason::InvalidIsbn(raw.to_string()))
+}
+
+/// Books mapped from an import source, and the records that could not be
+/// mapped.
+#[derive(Debug, Default)]
+pub struct MappedImport {
+    /// Books with the line (or record number) they come from
+    pub books: Vec<(u64, Book)>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+/// What to do with an imported book whose ISBN is already in the catalog.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnConflict {
+    /// Keep the existing book
+    #[default]
+    Skip,
+    /// Fill the existing book with the imported values
+    Update,
+    /// Cancel the whole import
+    Fail,
+}
+
+impl OnConflict {
+    /// Every accepted value, as written on the CLI.
+    pub const NAMES: [&'static str; 3] = ["skip", "update", "fail"];
+}
+
+impl FromStr for OnConflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(OnConflict::Skip),
+            "update" => Ok(OnConflict::Update),
+            "fail" => Ok(OnConflict::Fail),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+/// Options shared by every import (`--dry-run`, `--on-conflict`, `--partial`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions {
+    /// Validate and report without writing anything
+    pub dry_run: bool,
+    pub on_conflict: OnConflict,
+    /// Import the valid records even if some are rejected
+    pub partial: bool,
+}
+
+/// Checks a book before it is written: title and author are required, the
+/// ISBN may be empty but must otherwise be valid (it is normalized to its
+/// plain form), the year must be 0
+/// (unknown) or not in the future, and pages, rating and reading status
+/// must be plausible.
+pub fn validate_book(book: &mut Book) -> Result<(), RejectReason> {
+    if book.title.trim().is_empty() {
+        return Err(RejectReason::MissingTitle);
+    }
+    if book.author.trim().is_empty() {
+        return Err(RejectReason::MissingAuthor);
+    }
+    book.isbn = import_isbn(&book.isbn)?;
+
+    let invalid = |field: &str, value: String| RejectReason::InvalidField {
+        field: field.to_string(),
+        value,
+    };
+    let max_year = Local::now().year() + 1;
+    if !(0..=max_year).contains(&book.year) {
+        return Err(invalid("year", book.year.to_string()));
+    }
+    if let Some(pages) = book.pages
+        && pages < 0
+    {
+        return Err(invalid("pages", pages.to_string()));
+    }
+    if let Some(rating) = book.rating
+        && !(1..=5).contains(&rating)
+    {
+        return Err(invalid("rating", rating.to_string()));
+    }
+    if let Some(status) = &book.read_status
+        && !READ_STATUSES.contains(&status.as_str())
+    {
+        return Err(invalid("read_status", status.clone()));
+    }
+    Ok(())
+}
+
+/// Moves the books that fail `validate_book` to `rejected`, which is then
+/// sorted by line.
+pub fn validate_import(import: &mut MappedImport) {
+    let books = std::mem::take(&mut import.books);
+    for (line, mut book) in books {
+        match validate_book(&mut book) {
+            Ok(()) => import.books.push((line, book)),
+            Err(reason) => import.rejected.push(RejectedRow {
+                line,
+                title: book.title.trim().to_string(),
+                reason,
+            }),
         }
-        Err(e) => {
-            *failed += 1;
-            print_err(&tr_with(
-                "import.error.insert_failed",
-                &[("title", title), ("error", &e.to_string())],
-            ));
+    }
+    import.rejected.sort_by_key(|r| r.line);
+}
+
+/// The existing book filled with the imported values; fields the import
+/// leaves empty keep their current value, and `id`/`added_at` never change.
+pub fn merge_book(existing: &Book, imported: &Book) -> Book {
+    let text = |new: &String, old: &String| {
+        if new.trim().is_empty() {
+            old.clone()
+        } else {
+            new.clone()
         }
+    };
+    Book {
+        id: existing.id,
+        title: text(&imported.title, &existing.title),
+        author: text(&imported.author, &existing.author),
+        editor: text(&imported.editor, &existing.editor),
+        year: if imported.year != 0 {
+            imported.year
+        } else {
+            existing.year
+        },
+        isbn: existing.isbn.clone(),
+        language: imported.language.clone().or(existing.language.clone()),
+        pages: imported.pages.or(existing.pages),
+        genre: imported.genre.clone().or(existing.genre.clone()),
+        summary: imported.summary.clone().or(existing.summary.clone()),
+        room: imported.room.clone().or(existing.room.clone()),
+        shelf: imported.shelf.clone().or(existing.shelf.clone()),
+        row: imported.row.clone().or(existing.row.clone()),
+        position: imported.position.clone().or(existing.position.clone()),
+        added_at: existing.added_at,
+        rating: imported.rating.or(existing.rating),
+        read_status: imported
+            .read_status
+            .clone()
+            .or(existing.read_status.clone()),
+        read_at: imported.read_at.or(existing.read_at),
+        tags: imported.tags.clone().or(existing.tags.clone()),
+        review: imported.review.clone().or(existing.review.clone()),
+        format: imported.format.clone().or(existing.format.clone()),
     }
 }
+
+/// Rows of a table (CSV, XLSX): line number and cells, or the read error.
+pub type TableRows = Vec<(u64, Result<Vec<String>, String>)>;
+
+/// Headers and rows of a CSV file.
+pub fn read_csv_table<R: io::Read>(
+    reader: R,
+    delimiter: u8,
+) -> csv::Result<(Vec<String>, TableRows)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(reader);
+    let headers = reader.headers()?.iter().map(String::from).collect();
+    let rows = reader
+        .records()
+        .map(|record| match record {
+            Ok(record) => (
+                record.position().map(|p| p.line()).unwrap_or_default(),
+                Ok(record.iter().map(String::from).collect()),
+            ),
+            Err(e) => (
+                e.position().map(|p| p.line()).unwrap_or_default(),
+                Err(e.to_string()),
+            ),
+        })
+        .collect();
+    Ok((headers, rows))
+}
+
+/// `dir/catalogo.csv` → `dir/catalogo.rejected.<ext>`
+pub fn rejected_file_path(source: &Path, ext: &str) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    source.with_file_name(format!("{}.rejected.{}", stem, ext))
+}
+
+/// Writes the rejected rows of a table as CSV: the original cells, then
+/// `rejected_line` and `rejected_reason`. The file can be fixed and imported
+/// again with the same options.
+pub fn write_rejected_table(
+    path: &Path,
+    delimiter: u8,
+    headers: &[String],
+    rows: &TableRows,
+    rejected: &[RejectedRow],
+) -> io::Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(path)?;
+    let mut header = headers.to_vec();
+    header.extend(["rejected_line".to_string(), "rejected_reason".to_string()]);
+    writer.write_record(&header)?;
+    for row in rejected {
+        let mut cells = rows
+            .iter()
+            .find(|(line, _)| *line == row.line)
+            .and_then(|(_, cells)| cells.as_ref().ok())
+            .cloned()
+            .unwrap_or_default();
+        cells.resize(headers.len(), String::new());
+        cells.extend([row.line.to_string(), row.reason.to_string()]);
+        writer.write_record(&cells)?;
+    }
+    writer.flush()
+}
+
+/// Writes the rejected records of a JSON import (record `n` is `values[n - 1]`)
+/// as a JSON array that can be fixed and imported again.
+pub fn write_rejected_json(
+    path: &Path,
+    values: &[serde_json::Value],
+    rejected: &[RejectedRow],
+) -> io::Result<()> {
+    let records: Vec<&serde_json::Value> = rejected
+        .iter()
+        .filter_map(|r| values.get((r.line as usize).checked_sub(1)?))
+        .collect();
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(file, &records).map_err(io::Error::other)
+}
+
+/// Cell values (as displayed) of the first worksheet of an XLSX file, row
+/// by row; the first row holds the headers.
+pub fn read_xlsx_rows(path: &Path) -> io::Result<Vec<Vec<String>>> {
+    let workbook =
+        umya_spreadsheet::reader::xlsx::read(path).map_err(|e| io::Error::other(e.to_string()))?;
+    let sheet = workbook
+        .get_sheet(&0)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr("import.xlsx.no_sheet")))?;
+    let (columns, rows) = sheet.get_highest_column_and_row();
+    Ok((1..=rows)
+        .map(|row| {
+            (1..=columns)
+                .map(|column| sheet.get_formatted_value((column, row)))
+                .collect()
+        })
+        .collect())
+}
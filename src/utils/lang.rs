@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
-/// Converts a Google Books language code (ISO 639-1) into a readable name.
+/// Converts a language code into a readable name: ISO 639-1 as used by
+/// Google Books, or ISO 639-2 as stored by Calibre.
 pub fn lang_code_to_name(code: &str) -> &str {
     let map = HashMap::from([
         ("en", "English"),
@@ -15,6 +16,66 @@ pub fn lang_code_to_name(code: &str) -> &str {
         ("ar", "Arabic"),
         ("el", "Greek"),
         ("la", "Latin"),
+        ("eng", "English"),
+        ("ita", "Italian"),
+        ("fra", "French"),
+        ("fre", "French"),
+        ("deu", "German"),
+        ("ger", "German"),
+        ("spa", "Spanish"),
+        ("por", "Portuguese"),
+        ("rus", "Russian"),
+        ("zho", "Chinese"),
+        ("chi", "Chinese"),
+        ("jpn", "Japanese"),
+        ("ara", "Arabic"),
+        ("ell", "Greek"),
+        ("gre", "Greek"),
+        ("lat", "Latin"),
     ]);
     map.get(code).copied().unwrap_or(code)
 }
+
+/// MARC language code (ISO 639-2/B) of a name returned by `lang_code_to_name`;
+/// a three-letter lowercase code is kept as it is.
+pub fn lang_name_to_marc(name: &str) -> Option<String> {
+    let code = match name.trim() {
+        "English" => "eng",
+        "Italian" => "ita",
+        "French" => "fre",
+        "German" => "ger",
+        "Spanish" => "spa",
+        "Portuguese" => "por",
+        "Russian" => "rus",
+        "Chinese" => "chi",
+        "Japanese" => "jpn",
+        "Arabic" => "ara",
+        "Greek" => "gre",
+        "Latin" => "lat",
+        other if other.len() == 3 && other.chars().all(|c| c.is_ascii_lowercase()) => other,
+        _ => return None,
+    };
+    Some(code.to_string())
+}
+
+/// ISO 639-1 code of a name returned by `lang_code_to_name`, as expected by
+/// citation processors; a two-letter lowercase code is kept as it is.
+pub fn lang_name_to_code(name: &str) -> Option<&str> {
+    let code = match name.trim() {
+        "English" => "en",
+        "Italian" => "it",
+        "French" => "fr",
+        "German" => "de",
+        "Spanish" => "es",
+        "Portuguese" => "pt",
+        "Russian" => "ru",
+        "Chinese" => "zh",
+        "Japanese" => "ja",
+        "Arabic" => "ar",
+        "Greek" => "el",
+        "Latin" => "la",
+        other if other.len() == 2 && other.chars().all(|c| c.is_ascii_lowercase()) => other,
+        _ => return None,
+    };
+    Some(code)
+}
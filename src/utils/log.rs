@@ -7,6 +7,7 @@
 
 use chrono::Local;
 use rusqlite::{Connection, Result};
+use serde_json::Value;
 
 /// Returns the current local date-time in full ISO 8601 format.
 ///
@@ -18,6 +19,23 @@ pub fn now_str() -> String {
     Local::now().format("%+").to_string()
 }
 
+/// Creates the `log` table if it does not exist yet.
+pub fn create_log_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            date TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            target TEXT DEFAULT '',
+            message TEXT NOT NULL,
+            book_id INTEGER,
+            details TEXT
+        );",
+        [],
+    )?;
+    Ok(())
+}
+
 /// Writes an entry into the 'log' table.
 /// If the table does not exist, it will be created automatically.
 ///
@@ -37,17 +55,9 @@ pub fn now_str() -> String {
 /// ```
 pub fn write_log(conn: &Connection, operation: &str, target: &str, message: &str) -> Result<()> {
     // Ensure log table exists
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS log (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            date TEXT NOT NULL,
-            operation TEXT NOT NULL,
-            target TEXT DEFAULT '',
-            message TEXT NOT NULL
-        );",
-        [],
-    )?;
+    create_log_table(conn)?;
 
+    // Solo le colonne originali: funziona anche prima di PATCH_006
     let now = now_str();
     conn.execute(
         "INSERT INTO log (date, operation, target, message) VALUES (?1, ?2, ?3, ?4)",
@@ -56,3 +66,36 @@ pub fn write_log(conn: &Connection, operation: &str, target: &str, message: &str
 
     Ok(())
 }
+
+/// Writes a structured entry: like [`write_log`], plus the book involved and
+/// a JSON object with the details of the operation (requires `PATCH_006`).
+///
+/// # Example
+/// ```
+/// use librius::utils::write_log_with;
+/// use rusqlite::Connection;
+/// use serde_json::json;
+/// let conn = Connection::open_in_memory().unwrap();
+/// let details = json!({ "title": "Dune", "isbn": "9780441013593" });
+/// write_log_with(&conn, "ADD_BOOK", "BOOKS", "Inserted 'Dune'", Some(1), Some(&details)).unwrap();
+/// ```
+pub fn write_log_with(
+    conn: &Connection,
+    operation: &str,
+    target: &str,
+    message: &str,
+    book_id: Option<i64>,
+    details: Option<&Value>,
+) -> Result<()> {
+    create_log_table(conn)?;
+
+    let now = now_str();
+    let details = details.map(Value::to_string);
+    conn.execute(
+        "INSERT INTO log (date, operation, target, message, book_id, details)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (&now, &operation, &target, &message, &book_id, &details),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,823 @@
+// =====================================================
+// Librius - utils/marc.rs
+// -----------------------------------------------------
+// Record MARC 21 bibliografici: lettura e scrittura in
+// formato binario ISO 2709 e MARCXML, conversione da e
+// verso il modello `Book` e resoconto dei campi che non
+// hanno un equivalente.
+// =====================================================
+
+use crate::models::Book;
+use crate::models::book::EBOOK_FORMAT;
+use crate::utils::import_helpers::{MappedImport, RejectReason, RejectedRow, import_isbn};
+use crate::utils::lang::{lang_code_to_name, lang_name_to_marc};
+use quick_xml::Reader;
+use quick_xml::Writer;
+use quick_xml::events::{BytesDecl, BytesStart, BytesText, Event};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+/// Namespace of MARCXML documents.
+pub const MARCXML_NS: &str = "http://www.loc.gov/MARC21/slim";
+
+const RECORD_TERMINATOR: u8 = 0x1D;
+const FIELD_TERMINATOR: u8 = 0x1E;
+const SUBFIELD_DELIMITER: u8 = 0x1F;
+const LEADER_LEN: usize = 24;
+const DIRECTORY_ENTRY_LEN: usize = 12;
+
+/// Data fields read by `record_to_book`; the others are reported as lost.
+/// Control fields (`00X`) only describe the record and are never reported.
+const MAPPED_TAGS: [&str; 14] = [
+    "020", "041", "100", "245", "260", "264", "300", "338", "520", "546", "650", "655", "700",
+    "852",
+];
+
+/// Book fields with no MARC equivalent (personal reading data and the
+/// catalog date).
+const UNMAPPED_FIELDS: [&str; 5] = ["added_at", "rating", "read_status", "read_at", "review"];
+
+/// A MARC 21 bibliographic record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarcRecord {
+    pub leader: String,
+    pub fields: Vec<MarcField>,
+}
+
+/// A control field (`001`–`009`) or a data field with indicators and subfields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarcField {
+    Control {
+        tag: String,
+        value: String,
+    },
+    Data {
+        tag: String,
+        ind1: char,
+        ind2: char,
+        subfields: Vec<(char, String)>,
+    },
+}
+
+impl MarcField {
+    pub fn tag(&self) -> &str {
+        match self {
+            MarcField::Control { tag, .. } | MarcField::Data { tag, .. } => tag,
+        }
+    }
+
+    fn data(tag: &str, ind1: char, ind2: char, subfields: Vec<(char, String)>) -> Self {
+        MarcField::Data {
+            tag: tag.to_string(),
+            ind1,
+            ind2,
+            subfields,
+        }
+    }
+}
+
+impl MarcRecord {
+    /// Data fields with the given tag.
+    fn data_fields<'a>(
+        &'a self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = (char, char, &'a [(char, String)])> + 'a {
+        self.fields.iter().filter_map(move |f| match f {
+            MarcField::Data {
+                tag: t,
+                ind1,
+                ind2,
+                subfields,
+            } if t == tag => Some((*ind1, *ind2, subfields.as_slice())),
+            _ => None,
+        })
+    }
+
+    /// First non-empty subfield `code` of the first field `tag` that has it.
+    fn subfield(&self, tag: &str, code: char) -> Option<String> {
+        self.data_fields(tag)
+            .flat_map(|(_, _, subfields)| subfields.iter())
+            .find(|(c, v)| *c == code && !v.trim().is_empty())
+            .map(|(_, v)| v.trim().to_string())
+    }
+
+    fn control(&self, tag: &str) -> Option<&str> {
+        self.fields.iter().find_map(|f| match f {
+            MarcField::Control { tag: t, value } if t == tag => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Data fields Librius does not read, e.g. `082` (Dewey) or `490` (series).
+    pub fn unmapped_tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self
+            .fields
+            .iter()
+            .map(MarcField::tag)
+            .filter(|t| !t.starts_with("00") && !MAPPED_TAGS.contains(t))
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ISO 2709
+// ---------------------------------------------------------------------------
+
+/// Splits an ISO 2709 file into records (UTF-8 expected, leader position 9
+/// `a`). A damaged record becomes an error without affecting the others.
+pub fn parse_iso2709(data: &[u8]) -> Vec<Result<MarcRecord, String>> {
+    data.split(|b| *b == RECORD_TERMINATOR)
+        .map(|chunk| chunk.trim_ascii())
+        .filter(|chunk| !chunk.is_empty())
+        .map(parse_iso2709_record)
+        .collect()
+}
+
+fn parse_iso2709_record(raw: &[u8]) -> Result<MarcRecord, String> {
+    if raw.len() < LEADER_LEN {
+        return Err("leader shorter than 24 bytes".to_string());
+    }
+    let leader = String::from_utf8_lossy(&raw[..LEADER_LEN]).into_owned();
+    let base_field = String::from_utf8_lossy(&raw[12..17]);
+    let base: usize = base_field
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid base address '{}'", base_field))?;
+    if base <= LEADER_LEN || base > raw.len() {
+        return Err(format!("base address {} out of range", base));
+    }
+
+    // La directory termina con un separatore di campo subito prima della base
+    let directory = &raw[LEADER_LEN..base - 1];
+    let mut fields = Vec::new();
+    for entry in directory.chunks(DIRECTORY_ENTRY_LEN) {
+        // Solo ASCII: le posizioni della voce sono in byte, non in caratteri
+        let entry = std::str::from_utf8(entry)
+            .ok()
+            .filter(|e| e.len() == DIRECTORY_ENTRY_LEN && e.is_ascii())
+            .ok_or_else(|| "invalid directory entry".to_string())?;
+        let tag = &entry[..3];
+        let length: usize = entry[3..7]
+            .parse()
+            .map_err(|_| format!("invalid length for field {}", tag))?;
+        let start: usize = entry[7..12]
+            .parse()
+            .map_err(|_| format!("invalid offset for field {}", tag))?;
+        let data = raw
+            .get(base + start..base + start + length)
+            .ok_or_else(|| format!("field {} past the end of the record", tag))?;
+        let data = data.strip_suffix(&[FIELD_TERMINATOR]).unwrap_or(data);
+        fields.push(parse_iso2709_field(tag, data));
+    }
+    Ok(MarcRecord { leader, fields })
+}
+
+fn parse_iso2709_field(tag: &str, data: &[u8]) -> MarcField {
+    if tag.starts_with("00") {
+        return MarcField::Control {
+            tag: tag.to_string(),
+            value: String::from_utf8_lossy(data).into_owned(),
+        };
+    }
+    let mut parts = data.split(|b| *b == SUBFIELD_DELIMITER);
+    let indicators: Vec<char> = String::from_utf8_lossy(parts.next().unwrap_or_default())
+        .chars()
+        .collect();
+    let subfields = parts
+        .filter_map(|part| {
+            let text = String::from_utf8_lossy(part);
+            let mut chars = text.chars();
+            let code = chars.next()?;
+            Some((code, chars.as_str().to_string()))
+        })
+        .collect();
+    MarcField::data(
+        tag,
+        indicators.first().copied().unwrap_or(' '),
+        indicators.get(1).copied().unwrap_or(' '),
+        subfields,
+    )
+}
+
+/// Writes records in ISO 2709, computing record length, base address and
+/// directory.
+///
+/// Records that do not fit the format (a field over 9999 bytes, a record over
+/// 99999 bytes or a tag that is not three ASCII characters) are left out;
+/// their positions in `records` are returned alongside the data.
+pub fn write_iso2709(records: &[MarcRecord]) -> (Vec<u8>, Vec<usize>) {
+    let mut out = Vec::new();
+    let mut skipped = Vec::new();
+    for (i, record) in records.iter().enumerate() {
+        match encode_iso2709_record(record) {
+            Some(bytes) => out.extend(bytes),
+            None => skipped.push(i),
+        }
+    }
+    (out, skipped)
+}
+
+/// A single ISO 2709 record, or `None` if a length overflows its directory
+/// or leader digits.
+fn encode_iso2709_record(record: &MarcRecord) -> Option<Vec<u8>> {
+    let mut directory = Vec::new();
+    let mut data = Vec::new();
+    for field in &record.fields {
+        let tag = field.tag();
+        if tag.len() != 3 || !tag.is_ascii() {
+            return None;
+        }
+        let start = data.len();
+        match field {
+            MarcField::Control { value, .. } => data.extend(value.as_bytes()),
+            MarcField::Data {
+                ind1,
+                ind2,
+                subfields,
+                ..
+            } => {
+                data.extend(format!("{}{}", ind1, ind2).as_bytes());
+                for (code, value) in subfields {
+                    data.push(SUBFIELD_DELIMITER);
+                    data.extend(code.to_string().as_bytes());
+                    data.extend(value.as_bytes());
+                }
+            }
+        }
+        data.push(FIELD_TERMINATOR);
+        let length = data.len() - start;
+        if length > 9999 || start > 99999 {
+            return None;
+        }
+        directory.extend(format!("{}{:04}{:05}", tag, length, start).as_bytes());
+    }
+    directory.push(FIELD_TERMINATOR);
+
+    let base = LEADER_LEN + directory.len();
+    let length = base + data.len() + 1;
+    if length > 99999 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(length);
+    out.extend(leader_with(&record.leader, length, base));
+    out.extend(directory);
+    out.extend(data);
+    out.push(RECORD_TERMINATOR);
+    Some(out)
+}
+
+/// The record's leader with length and base address filled in (and UTF-8
+/// declared at position 9). Works on bytes so that the result is always 24
+/// bytes long; anything that is not ASCII becomes a blank.
+fn leader_with(leader: &str, length: usize, base: usize) -> [u8; LEADER_LEN] {
+    let mut out = [b' '; LEADER_LEN];
+    for (slot, byte) in out.iter_mut().zip(leader.bytes()) {
+        if byte.is_ascii() && !byte.is_ascii_control() {
+            *slot = byte;
+        }
+    }
+    out[..5].copy_from_slice(format!("{:05}", length).as_bytes());
+    out[9..12].copy_from_slice(b"a22");
+    out[12..17].copy_from_slice(format!("{:05}", base).as_bytes());
+    out
+}
+
+// ---------------------------------------------------------------------------
+// MARCXML
+// ---------------------------------------------------------------------------
+
+/// Reads the `<record>` elements of a MARCXML document (a `<collection>` or
+/// a single record, with or without namespace prefix).
+pub fn parse_marcxml(text: &str) -> Result<Vec<MarcRecord>, String> {
+    let mut reader = Reader::from_str(text);
+    let mut state = XmlState::default();
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("{} (byte {})", e, reader.buffer_position()))?;
+        match event {
+            Event::Start(e) => state.open(&e),
+            Event::Empty(e) => {
+                state.open(&e);
+                state.close(e.local_name().as_ref());
+            }
+            Event::Text(t) if state.target.is_some() => {
+                let text = t.unescape().map_err(|e| e.to_string())?;
+                state.buffer.push_str(&text);
+            }
+            Event::CData(t) if state.target.is_some() => {
+                state.buffer.push_str(&String::from_utf8_lossy(&t));
+            }
+            Event::End(e) => state.close(e.local_name().as_ref()),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(state.records)
+}
+
+/// Stato della lettura MARCXML.
+#[derive(Default)]
+struct XmlState {
+    records: Vec<MarcRecord>,
+    record: Option<MarcRecord>,
+    /// Campo dati in costruzione
+    current: Option<MarcField>,
+    /// Elemento che riceve il testo: tag del leader/controlfield o codice del subfield
+    target: Option<(String, char)>,
+    buffer: String,
+}
+
+impl XmlState {
+    fn open(&mut self, e: &BytesStart) {
+        let attr = |name: &str| -> Option<String> {
+            e.try_get_attribute(name)
+                .ok()
+                .flatten()
+                .and_then(|a| a.unescape_value().ok())
+                .map(|v| v.into_owned())
+        };
+        let first_char = |name: &str| attr(name).and_then(|v| v.chars().next()).unwrap_or(' ');
+        self.buffer.clear();
+        match e.local_name().as_ref() {
+            b"record" => {
+                self.record = Some(MarcRecord {
+                    leader: String::new(),
+                    fields: Vec::new(),
+                })
+            }
+            b"leader" => self.target = Some(("leader".to_string(), ' ')),
+            b"controlfield" => self.target = Some((attr("tag").unwrap_or_default(), ' ')),
+            b"datafield" => {
+                self.current = Some(MarcField::data(
+                    &attr("tag").unwrap_or_default(),
+                    first_char("ind1"),
+                    first_char("ind2"),
+                    Vec::new(),
+                ))
+            }
+            b"subfield" => self.target = Some(("subfield".to_string(), first_char("code"))),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &[u8]) {
+        let text = std::mem::take(&mut self.buffer);
+        match name {
+            b"record" => self.records.extend(self.record.take()),
+            b"leader" => {
+                if let Some(record) = self.record.as_mut() {
+                    record.leader = text;
+                }
+                self.target = None;
+            }
+            b"controlfield" => {
+                if let (Some(record), Some((tag, _))) = (self.record.as_mut(), self.target.take()) {
+                    record.fields.push(MarcField::Control { tag, value: text });
+                }
+            }
+            b"datafield" => {
+                if let (Some(record), Some(field)) = (self.record.as_mut(), self.current.take()) {
+                    record.fields.push(field);
+                }
+            }
+            b"subfield" => {
+                if let (Some(MarcField::Data { subfields, .. }), Some((_, code))) =
+                    (self.current.as_mut(), self.target.take())
+                {
+                    subfields.push((code, text));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Writes records as an indented MARCXML `<collection>`.
+pub fn write_marcxml(records: &[MarcRecord]) -> io::Result<Vec<u8>> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("collection")
+        .with_attribute(("xmlns", MARCXML_NS))
+        .write_inner_content(|w| {
+            for record in records {
+                w.create_element("record").write_inner_content(|w| {
+                    w.create_element("leader")
+                        .write_text_content(BytesText::new(&record.leader))?;
+                    for field in &record.fields {
+                        write_xml_field(w, field)?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        })?;
+    let mut out = writer.into_inner();
+    out.push(b'\n');
+    Ok(out)
+}
+
+fn write_xml_field(w: &mut Writer<Vec<u8>>, field: &MarcField) -> io::Result<()> {
+    match field {
+        MarcField::Control { tag, value } => {
+            w.create_element("controlfield")
+                .with_attribute(("tag", tag.as_str()))
+                .write_text_content(BytesText::new(value))?;
+        }
+        MarcField::Data {
+            tag,
+            ind1,
+            ind2,
+            subfields,
+        } => {
+            w.create_element("datafield")
+                .with_attributes([
+                    ("tag", tag.as_str()),
+                    ("ind1", ind1.to_string().as_str()),
+                    ("ind2", ind2.to_string().as_str()),
+                ])
+                .write_inner_content(|w| {
+                    for (code, value) in subfields {
+                        w.create_element("subfield")
+                            .with_attribute(("code", code.to_string().as_str()))
+                            .write_text_content(BytesText::new(value))?;
+                    }
+                    Ok(())
+                })?;
+        }
+    }
+    Ok(())
+}
+
+/// MARCXML if the extension is `.xml` or the content starts with `<`.
+pub fn is_marcxml(path: &Path, data: &[u8]) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("xml"))
+        || data.trim_ascii_start().first() == Some(&b'<')
+}
+
+// ---------------------------------------------------------------------------
+// Book <-> MARC
+// ---------------------------------------------------------------------------
+
+/// Leader of the records written by Librius (new record, language material,
+/// monograph, UTF-8, ISBD); length and base address are computed on write.
+const LEADER_TEMPLATE: &str = "00000nam a2200000 i 4500";
+
+/// Builds the MARC record of a book: 020 ISBN, 041/546 language, 100/700
+/// authors, 245 title, 264 publisher and year, 300 pages, 338 carrier
+/// (ebooks), 520 summary, 650 tags, 655 genre, 852 location.
+pub fn book_to_record(book: &Book) -> MarcRecord {
+    let mut fields = Vec::new();
+    let field = |tag: &str, ind1: char, ind2: char, subfields: Vec<(char, &str)>| {
+        let subfields: Vec<(char, String)> = subfields
+            .into_iter()
+            .filter(|(_, v)| !v.trim().is_empty())
+            .map(|(c, v)| (c, v.trim().to_string()))
+            .collect();
+        (!subfields.is_empty()).then(|| MarcField::data(tag, ind1, ind2, subfields))
+    };
+    let language = book.language.as_deref().unwrap_or_default();
+    let language_code = lang_name_to_marc(language);
+
+    if let Some(id) = book.id {
+        fields.push(MarcField::Control {
+            tag: "001".to_string(),
+            value: id.to_string(),
+        });
+    }
+    fields.push(MarcField::Control {
+        tag: "008".to_string(),
+        value: fixed_data(book, language_code.as_deref()),
+    });
+    fields.extend(field("020", ' ', ' ', vec![('a', &book.isbn)]));
+    if let Some(code) = &language_code {
+        fields.extend(field("041", '0', ' ', vec![('a', code)]));
+    }
+
+    let authors: Vec<&str> = book
+        .author
+        .split(", ")
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .collect();
+    // Nomi in ordine diretto (indicatore 0), come li conserva Librius
+    if let Some((first, others)) = authors.split_first() {
+        fields.extend(field("100", '0', ' ', vec![('a', first)]));
+        fields.extend(
+            others
+                .iter()
+                .filter_map(|a| field("700", '0', ' ', vec![('a', a)])),
+        );
+    }
+    let ind1 = if authors.is_empty() { '0' } else { '1' };
+    fields.extend(field("245", ind1, '0', vec![('a', &book.title)]));
+    let year = if book.year > 0 {
+        book.year.to_string()
+    } else {
+        String::new()
+    };
+    fields.extend(field(
+        "264",
+        ' ',
+        '1',
+        vec![('b', &book.editor), ('c', &year)],
+    ));
+    if let Some(pages) = book.pages {
+        fields.extend(field(
+            "300",
+            ' ',
+            ' ',
+            vec![('a', &format!("{} p.", pages))],
+        ));
+    }
+    if book.format.as_deref() == Some(EBOOK_FORMAT) {
+        fields.extend(field(
+            "338",
+            ' ',
+            ' ',
+            vec![('a', "online resource"), ('b', "cr"), ('2', "rdacarrier")],
+        ));
+    }
+    if let Some(summary) = &book.summary {
+        fields.extend(field("520", ' ', ' ', vec![('a', summary)]));
+    }
+    if language_code.is_none() {
+        fields.extend(field("546", ' ', ' ', vec![('a', language)]));
+    }
+    for tag in book.tags.as_deref().unwrap_or_default().split(',') {
+        fields.extend(field("650", ' ', '4', vec![('a', tag)]));
+    }
+    if let Some(genre) = &book.genre {
+        fields.extend(field("655", ' ', '4', vec![('a', genre)]));
+    }
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    fields.extend(field(
+        "852",
+        ' ',
+        ' ',
+        vec![
+            ('b', &text(&book.room)),
+            ('c', &text(&book.shelf)),
+            ('h', &text(&book.row)),
+            ('i', &text(&book.position)),
+        ],
+    ));
+
+    MarcRecord {
+        leader: LEADER_TEMPLATE.to_string(),
+        fields,
+    }
+}
+
+/// Field 008 (40 characters): date entered, publication year and language.
+fn fixed_data(book: &Book, language_code: Option<&str>) -> String {
+    let entered = book
+        .added_at
+        .map(|d| d.format("%y%m%d").to_string())
+        .unwrap_or_else(|| chrono::Local::now().format("%y%m%d").to_string());
+    let (date_type, year) = if book.year > 0 {
+        ('s', format!("{:04}", book.year))
+    } else {
+        ('n', "uuuu".to_string())
+    };
+    format!(
+        "{}{}{}    xx {:17}{} d",
+        entered,
+        date_type,
+        year,
+        "",
+        language_code.unwrap_or("und")
+    )
+}
+
+/// Book fields with a value that MARC cannot carry.
+pub fn lost_on_export(book: &Book) -> Vec<&'static str> {
+    let mut lost: Vec<&'static str> = UNMAPPED_FIELDS
+        .into_iter()
+        .filter(|field| match *field {
+            "added_at" => book.added_at.is_some(),
+            "rating" => book.rating.is_some(),
+            "read_status" => book.read_status.is_some(),
+            "read_at" => book.read_at.is_some(),
+            _ => book.review.is_some(),
+        })
+        .collect();
+    if book.format.as_deref().is_some_and(|f| f != EBOOK_FORMAT) {
+        lost.push("format");
+    }
+    lost
+}
+
+/// Maps a MARC record onto a book. 1XX/7XX names in inverted form
+/// (`Herbert, Frank`) are turned around, ISBD punctuation is dropped, and
+/// 008 supplies year and language when 264/260 and 041 are missing.
+pub fn record_to_book(record: &MarcRecord) -> Result<Book, RejectReason> {
+    let mut title = record
+        .subfield("245", 'a')
+        .map(|t| clean(&t))
+        .unwrap_or_default();
+    if title.is_empty() {
+        return Err(RejectReason::MissingTitle);
+    }
+    if let Some(subtitle) = record.subfield("245", 'b') {
+        title = format!("{}: {}", title, clean(&subtitle));
+    }
+
+    let authors: Vec<String> = ["100", "700"]
+        .iter()
+        .flat_map(|tag| record.data_fields(tag))
+        .filter_map(|(ind1, _, subfields)| {
+            let name = subfields.iter().find(|(c, _)| *c == 'a')?;
+            Some(person_name(ind1, &name.1))
+        })
+        .filter(|n| !n.is_empty())
+        .collect();
+
+    // Il primo ISBN valido tra i campi 020 (`9780441013593 (pbk.)`)
+    let isbns: Vec<String> = record
+        .data_fields("020")
+        .flat_map(|(_, _, subfields)| subfields.iter())
+        .filter(|(c, _)| *c == 'a')
+        .filter_map(|(_, v)| v.split_whitespace().next().map(String::from))
+        .collect();
+    let isbn = isbns
+        .iter()
+        .find(|i| import_isbn(i).is_ok())
+        .or(isbns.first())
+        .cloned()
+        .unwrap_or_default();
+
+    let publication = record
+        .data_fields("264")
+        .find(|(_, ind2, _)| *ind2 == '1')
+        .or_else(|| record.data_fields("260").next())
+        .map(|(_, _, subfields)| subfields);
+    let publication_value = |code: char| {
+        publication
+            .and_then(|s| s.iter().find(|(c, _)| *c == code))
+            .map(|(_, v)| v.as_str())
+    };
+    let fixed = record.control("008").unwrap_or_default();
+    let year = publication_value('c')
+        .and_then(first_year)
+        .or_else(|| fixed.get(7..11).and_then(first_year))
+        .unwrap_or_default();
+
+    let language = record
+        .subfield("041", 'a')
+        .and_then(|codes| codes.get(..3).map(String::from))
+        .or_else(|| {
+            fixed
+                .get(35..38)
+                .filter(|c| c.chars().all(|ch| ch.is_ascii_lowercase()))
+                .filter(|c| !["und", "zxx", "mul"].contains(c))
+                .map(String::from)
+        })
+        .map(|code| lang_code_to_name(&code).to_string())
+        .or_else(|| record.subfield("546", 'a').map(|l| clean(&l)));
+
+    let tags: Vec<String> = record
+        .data_fields("650")
+        .filter_map(|(_, _, subfields)| subfields.iter().find(|(c, _)| *c == 'a'))
+        .map(|(_, v)| clean(v))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let carrier_online = record
+        .data_fields("338")
+        .flat_map(|(_, _, subfields)| subfields.iter())
+        .any(|(c, v)| *c == 'b' && v.trim() == "cr");
+
+    Ok(Book {
+        title,
+        author: authors.join(", "),
+        editor: publication_value('b').map(clean).unwrap_or_default(),
+        year,
+        isbn,
+        language,
+        pages: record.subfield("300", 'a').and_then(|a| page_count(&a)),
+        genre: record.subfield("655", 'a').map(|g| clean(&g)),
+        summary: record.subfield("520", 'a'),
+        room: record.subfield("852", 'b'),
+        shelf: record.subfield("852", 'c'),
+        row: record.subfield("852", 'h'),
+        position: record.subfield("852", 'i'),
+        tags: (!tags.is_empty()).then(|| tags.join(", ")),
+        format: carrier_online.then(|| EBOOK_FORMAT.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Books and rejected records of a MARC file, plus how many records had each
+/// data field Librius does not read.
+#[derive(Debug, Default)]
+pub struct MarcImport {
+    pub import: MappedImport,
+    pub lost_tags: BTreeMap<String, u32>,
+}
+
+/// Maps parsed records (numbered from 1) onto books.
+pub fn map_marc_records(records: &[Result<MarcRecord, String>]) -> MarcImport {
+    let mut result = MarcImport::default();
+    for (index, record) in records.iter().enumerate() {
+        let line = index as u64 + 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                result.import.rejected.push(RejectedRow {
+                    line,
+                    title: String::new(),
+                    reason: RejectReason::Unreadable(e.clone()),
+                });
+                continue;
+            }
+        };
+        for tag in record.unmapped_tags() {
+            *result.lost_tags.entry(tag.to_string()).or_default() += 1;
+        }
+        match record_to_book(record) {
+            Ok(book) => result.import.books.push((line, book)),
+            Err(reason) => result.import.rejected.push(RejectedRow {
+                line,
+                title: record
+                    .subfield("245", 'a')
+                    .map(|t| clean(&t))
+                    .unwrap_or_default(),
+                reason,
+            }),
+        }
+    }
+    result
+}
+
+/// Parses the content of a MARC file, ISO 2709 or MARCXML (see `is_marcxml`).
+pub fn parse_marc(path: &Path, data: &[u8]) -> Result<Vec<Result<MarcRecord, String>>, String> {
+    if is_marcxml(path, data) {
+        let records = parse_marcxml(&String::from_utf8_lossy(data))?;
+        Ok(records.into_iter().map(Ok).collect())
+    } else {
+        Ok(parse_iso2709(data))
+    }
+}
+
+/// Drops ISBD punctuation at the end of a value (` /`, ` :`, `,`, a final
+/// period after a word, not after an initial).
+fn clean(value: &str) -> String {
+    let mut value = value
+        .trim()
+        .trim_end_matches([' ', '/', ':', ';', ',', '=']);
+    if let Some(stripped) = value.strip_suffix('.')
+        && stripped
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_lowercase() || c.is_ascii_digit())
+    {
+        value = stripped;
+    }
+    value.trim().to_string()
+}
+
+/// `Herbert, Frank,` (first indicator 1, inverted) → `Frank Herbert`.
+fn person_name(ind1: char, value: &str) -> String {
+    let name = clean(value);
+    match name.split_once(", ") {
+        Some((surname, forename)) if ind1 == '1' => format!("{} {}", forename.trim(), surname),
+        _ => name,
+    }
+}
+
+/// First four-digit year in a value (`c1965.`, `[2005]`).
+fn first_year(value: &str) -> Option<i32> {
+    let digits: Vec<char> = value.chars().collect();
+    digits
+        .windows(4)
+        .find(|w| w.iter().all(char::is_ascii_digit))
+        .and_then(|w| w.iter().collect::<String>().parse().ok())
+        .filter(|y| *y > 0)
+}
+
+/// Page count of a 300 $a (`412 p.`, `xii, 412 pages`, `412 p. ; 24 cm`): the
+/// number followed by `p`, or the first number.
+fn page_count(extent: &str) -> Option<i32> {
+    let mut numbers = Vec::new();
+    let mut rest = extent;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        let tail = &rest[start..];
+        let end = tail
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let after = tail[end..].trim_start();
+        numbers.push((&tail[..end], after.starts_with(['p', 'P'])));
+        rest = &tail[end..];
+    }
+    numbers
+        .iter()
+        .find(|(_, pages)| *pages)
+        .or(numbers.first())
+        .and_then(|(n, _)| n.parse().ok())
+}
@@ -5,10 +5,23 @@
 // ri-esporta i simboli pubblici in modo esplicito.
 // =====================================================
 
+pub mod age;
+pub mod archive;
+pub mod backup;
+pub mod calibre;
+pub mod citation;
+pub mod column_map;
+pub mod crypto;
+pub mod goodreads;
+pub mod html;
 pub mod import_helpers;
 pub mod isbn;
 pub mod lang;
 pub mod log;
+pub mod marc;
+pub mod notes;
+pub mod ods;
+pub mod output;
 pub mod print;
 pub mod table;
 pub mod verbose;
@@ -22,13 +35,53 @@ pub use verbose::{is_verbose, set_verbose};
 pub use print::{icons, print_err, print_info, print_ok, print_warn};
 
 // log
-pub use log::{now_str, write_log};
+pub use log::{now_str, write_log, write_log_with};
+
+// age
+pub use age::{parse_age, parse_instant};
+
+// backup
+pub use backup::{auto_snapshot, backup_dir_for, list_backups, select_prunable};
+
+// calibre
+pub use calibre::{calibre_db_path, read_calibre};
+
+// citation
+pub use citation::{CitationFormat, with_citation_keys, write_citations};
+
+// column map
+pub use column_map::{ColumnMap, MappingOptions};
+
+// goodreads
+pub use goodreads::read_goodreads;
+
+// html
+pub use html::{SiteReport, covers_dir_for, write_site};
 
 // import helpers
-pub use import_helpers::{handle_import_result, open_import_file};
+pub use import_helpers::{
+    ImportOptions, MappedImport, OnConflict, RejectReason, RejectedRow, TableRows, import_isbn,
+    merge_book, open_import_file, read_csv_table, read_xlsx_rows, rejected_file_path,
+    validate_book, validate_import, write_rejected_json, write_rejected_table,
+};
 
 // lang
 pub use lang::lang_code_to_name;
 
+// marc
+pub use marc::{
+    MarcImport, MarcRecord, book_to_record, is_marcxml, lost_on_export, map_marc_records,
+    parse_marc, write_iso2709, write_marcxml,
+};
+
+// notes
+pub use notes::{NotesReport, OrgDocument, org_document, write_markdown_notes};
+
+// ods
+pub use ods::{OdsCell, read_ods_rows, write_ods};
+
+// output
+pub use output::{OutputFormat, write_records};
+
 // table
-pub use table::{build_table, build_vertical_table};
+pub use table::{build_table, build_vertical_table, detail_rows};
@@ -0,0 +1,565 @@
+// =====================================================
+// Librius - utils/notes.rs
+// -----------------------------------------------------
+// Esportazione per le app di note: una nota Markdown per
+// libro con front matter YAML (Obsidian, Logseq, …) e una
+// nota indice, oppure un unico file Org con i campi nei
+// cassetti delle proprietà. Il testo scritto dall'utente
+// sotto il segnaposto sopravvive alle nuove esportazioni.
+// =====================================================
+
+use crate::i18n::{current_language, tr, tr_with};
+use crate::models::Book;
+use crate::utils::column_map::{IMPORT_FIELDS, field_value};
+use chrono::Local;
+use serde_yaml::{Mapping, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Line of a Markdown note below which the text belongs to the user and is
+/// kept as it is when the export runs again.
+pub const MARKDOWN_MARKER: &str = "<!-- librius: your notes go below this line -->";
+
+/// Same as [`MARKDOWN_MARKER`], for the sections of the Org export.
+pub const ORG_MARKER: &str = "# librius: your notes go below this line";
+
+/// File name of the generated index note.
+pub const INDEX_NOTE: &str = "index.md";
+
+/// Caratteri non ammessi nei nomi dei file (o nei link di Obsidian).
+const FORBIDDEN: [char; 13] = [
+    '/', '\\', ':', '*', '?', '"', '<', '>', '|', '#', '^', '[', ']',
+];
+
+/// Lunghezza massima del nome di una nota, estensione esclusa.
+const MAX_STEM: usize = 120;
+
+/// What `write_markdown_notes` has done.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NotesReport {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Notes of books no longer in the catalogue, left untouched.
+    pub orphaned: usize,
+    /// Notes whose marker was removed, left untouched.
+    pub skipped: Vec<String>,
+}
+
+/// Text after the marker line, if the content has one.
+pub fn after_marker<'a>(content: &'a str, marker: &str) -> Option<&'a str> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == marker {
+            return Some(&content[offset..]);
+        }
+    }
+    None
+}
+
+/// YAML front matter of a Markdown note, if it starts with one.
+pub fn front_matter_of(content: &str) -> Option<Mapping> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let end = rest.find("\n---")?;
+    serde_yaml::from_str(&rest[..end]).ok()
+}
+
+fn tags(book: &Book) -> Vec<&str> {
+    book.tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Front matter of a book note: every field, in export order, with numbers
+/// as numbers, tags as a list and `null` for missing values. Properties added
+/// by the user to an earlier note (`extra`) follow unchanged.
+pub fn front_matter(book: &Book, extra: &Mapping) -> Mapping {
+    let number = |n: Option<i32>| n.map(Value::from).unwrap_or(Value::Null);
+    let mut map = Mapping::new();
+    for field in std::iter::once("id").chain(IMPORT_FIELDS) {
+        let value = match field {
+            "id" => number(book.id),
+            "year" => number(Some(book.year).filter(|y| *y != 0)),
+            "pages" => number(book.pages),
+            "rating" => number(book.rating),
+            "tags" => Value::Sequence(tags(book).into_iter().map(Value::from).collect()),
+            _ => match field_value(book, field) {
+                text if text.is_empty() => Value::Null,
+                text => Value::String(text),
+            },
+        };
+        map.insert(field.into(), value);
+    }
+    for (key, value) in extra {
+        if !map.contains_key(key) {
+            map.insert(key.clone(), value.clone());
+        }
+    }
+    map
+}
+
+fn yaml_block(map: &Mapping) -> String {
+    let yaml = serde_yaml::to_string(map).unwrap_or_default();
+    format!("---\n{}---\n", yaml)
+}
+
+/// Full text of a book note: front matter, the generated body, the marker and
+/// the user text that follows it.
+pub fn markdown_note(book: &Book, extra: &Mapping, user_text: &str) -> String {
+    let mut note = yaml_block(&front_matter(book, extra));
+    let _ = write!(note, "\n# {}\n\n", book.title);
+
+    let byline: Vec<String> = [
+        book.author.trim().to_string(),
+        book.editor.trim().to_string(),
+        if book.year > 0 {
+            book.year.to_string()
+        } else {
+            String::new()
+        },
+    ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect();
+    if !byline.is_empty() {
+        let _ = write!(note, "*{}*\n\n", byline.join(" · "));
+    }
+    if let Some(summary) = book.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        let _ = write!(note, "{}\n\n", summary.trim());
+    }
+    if let Some(review) = book.review.as_deref().filter(|s| !s.trim().is_empty()) {
+        let _ = write!(
+            note,
+            "## {}\n\n{}\n\n",
+            tr("list.header.review"),
+            review.trim()
+        );
+    }
+
+    note.push_str(MARKDOWN_MARKER);
+    note.push('\n');
+    note.push_str(user_text);
+    note
+}
+
+/// Nome del file di una nota: il titolo senza i caratteri vietati, con l'ID
+/// tra parentesi se il nome è già usato da un'altra nota.
+fn note_name(book: &Book, taken: &HashSet<String>, own: Option<&str>) -> String {
+    let cleaned: String = book
+        .title
+        .chars()
+        .map(|c| {
+            if FORBIDDEN.contains(&c) || c.is_control() {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect();
+    let mut stem: String = cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_STEM)
+        .collect();
+    // niente file nascosti
+    stem = stem.trim_matches('.').trim().to_string();
+    let id = book.id.unwrap_or_default();
+    if stem.is_empty() {
+        stem = format!("book-{}", id);
+    }
+
+    let name = format!("{}.md", stem);
+    let lower = name.to_lowercase();
+    if own.is_some_and(|o| o.to_lowercase() == lower) || !taken.contains(&lower) {
+        name
+    } else {
+        format!("{} ({}).md", stem, id)
+    }
+}
+
+/// Scrive il file solo se il contenuto è cambiato.
+fn write_if_changed(path: &Path, content: &str) -> io::Result<()> {
+    if fs::read_to_string(path).is_ok_and(|old| old == content) {
+        return Ok(());
+    }
+    fs::write(path, content)
+}
+
+/// Writes one Markdown note per book into `dir`, plus the index note.
+///
+/// Existing notes are matched by the `id` in their front matter: only notes
+/// whose content changed are rewritten (and renamed if the title changed),
+/// keeping the text below [`MARKDOWN_MARKER`] and any property added by the
+/// user. Notes of books no longer in the catalogue, notes without the marker
+/// and files not written by Librius are left untouched.
+pub fn write_markdown_notes(
+    dir: &Path,
+    books: &[Book],
+    library: Option<&str>,
+) -> io::Result<NotesReport> {
+    fs::create_dir_all(dir)?;
+
+    // Note già presenti, per ID del libro; tutti i nomi restano riservati
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "md"))
+        .collect();
+    paths.sort();
+
+    let mut existing: HashMap<i64, (String, String)> = HashMap::new();
+    let mut taken: HashSet<String> = HashSet::from([INDEX_NOTE.to_string()]);
+    for path in paths {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let content = fs::read_to_string(&path).ok();
+        let id = content
+            .as_deref()
+            .and_then(front_matter_of)
+            .and_then(|m| m.get("id").and_then(Value::as_i64));
+        taken.insert(name.to_lowercase());
+        if let (Some(id), Some(content)) = (id, content)
+            && name != INDEX_NOTE
+            && !existing.contains_key(&id)
+        {
+            existing.insert(id, (name.to_string(), content));
+        }
+    }
+
+    let mut report = NotesReport::default();
+    let mut index: Vec<(&Book, String)> = Vec::new();
+    for book in books {
+        let old = existing.remove(&book.id.map(i64::from).unwrap_or_default());
+        let (extra, user_text) = match &old {
+            Some((name, content)) => match after_marker(content, MARKDOWN_MARKER) {
+                Some(text) => (front_matter_of(content).unwrap_or_default(), text),
+                None => {
+                    report.skipped.push(name.clone());
+                    index.push((book, name.clone()));
+                    continue;
+                }
+            },
+            None => (Mapping::new(), ""),
+        };
+
+        let name = note_name(book, &taken, old.as_ref().map(|(n, _)| n.as_str()));
+        taken.insert(name.to_lowercase());
+        let note = markdown_note(book, &extra, user_text);
+
+        match &old {
+            Some((old_name, content)) if *old_name == name && *content == note => {
+                report.unchanged += 1;
+            }
+            Some((old_name, _)) => {
+                fs::write(dir.join(&name), &note)?;
+                if *old_name != name {
+                    fs::remove_file(dir.join(old_name))?;
+                }
+                report.updated += 1;
+            }
+            None => {
+                fs::write(dir.join(&name), &note)?;
+                report.created += 1;
+            }
+        }
+        index.push((book, name));
+    }
+    // restano le note dei libri non più in catalogo
+    report.orphaned = existing.len();
+
+    let index_path = dir.join(INDEX_NOTE);
+    let previous = fs::read_to_string(&index_path).ok();
+    match previous.as_deref() {
+        Some(content) if after_marker(content, MARKDOWN_MARKER).is_none() => {
+            report.skipped.push(INDEX_NOTE.to_string());
+        }
+        _ => {
+            let user_text = previous
+                .as_deref()
+                .and_then(|c| after_marker(c, MARKDOWN_MARKER))
+                .unwrap_or_default();
+            write_if_changed(&index_path, &index_note(&mut index, library, user_text))?;
+        }
+    }
+
+    Ok(report)
+}
+
+fn catalogue_title(library: Option<&str>) -> String {
+    match library {
+        Some(name) => tr_with("notes.title_named", &[("name", name)]),
+        None => tr("notes.title"),
+    }
+}
+
+/// Destinazione di un link Markdown: tutto percent-encoded tranne i caratteri
+/// non riservati, così i nomi con spazi o parentesi restano validi.
+fn link_target(name: &str) -> String {
+    let mut target = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            target.push(byte as char);
+        } else {
+            let _ = write!(target, "%{:02X}", byte);
+        }
+    }
+    target
+}
+
+fn table_cell(text: &str) -> String {
+    text.trim().replace('|', "\\|")
+}
+
+/// Nota indice: tabella dei libri in ordine di titolo, con link alle note.
+fn index_note(index: &mut [(&Book, String)], library: Option<&str>, user_text: &str) -> String {
+    index.sort_by(|(a, _), (b, _)| {
+        a.title
+            .to_lowercase()
+            .cmp(&b.title.to_lowercase())
+            .then(a.id.cmp(&b.id))
+    });
+
+    let mut note = String::from("---\nlibrius: index\n---\n");
+    let _ = write!(note, "\n# {}\n\n", catalogue_title(library));
+    let _ = writeln!(
+        note,
+        "| {} | {} | {} | {} |\n| --- | --- | --- | --- |",
+        tr("list.header.title"),
+        tr("list.header.author"),
+        tr("list.header.year"),
+        tr("list.header.read_status"),
+    );
+    for (book, name) in index.iter() {
+        let year = if book.year > 0 {
+            book.year.to_string()
+        } else {
+            String::new()
+        };
+        let status = book
+            .read_status
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .map(|s| tr(&format!("book.read_status.{}", s)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            note,
+            "| [{}]({}) | {} | {} | {} |",
+            table_cell(&book.title)
+                .replace('[', "\\[")
+                .replace(']', "\\]"),
+            link_target(name),
+            table_cell(&book.author),
+            year,
+            status
+        );
+    }
+    note.push('\n');
+    note.push_str(MARKDOWN_MARKER);
+    note.push('\n');
+    note.push_str(user_text);
+    note
+}
+
+//
+// 🧩 Org
+//
+
+/// Proprietà Org di un libro; titolo, trama e recensione vanno nel testo.
+const ORG_SKIPPED: [&str; 3] = ["title", "summary", "review"];
+
+/// Tag Org validi: lettere, cifre e `_@#%`, il resto diventa `_`.
+fn org_tag(tag: &str) -> String {
+    tag.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "_@#%".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Testo libero: una riga che inizia con `*` diventerebbe un titolo.
+fn org_text(text: &str) -> String {
+    text.trim()
+        .lines()
+        .map(|line| {
+            if line.starts_with('*') || line.starts_with('#') {
+                format!(" {}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Generated part of the Org section of a book, up to and including the
+/// marker line.
+pub fn org_section(book: &Book) -> String {
+    let mut section = format!("* {}", book.title.trim());
+    let tags: Vec<String> = tags(book).into_iter().map(org_tag).collect();
+    if !tags.is_empty() {
+        let _ = write!(section, " :{}:", tags.join(":"));
+    }
+    section.push_str("\n:PROPERTIES:\n");
+    let _ = writeln!(section, ":LIBRIUS_ID: {}", book.id.unwrap_or_default());
+    for field in IMPORT_FIELDS.iter().filter(|f| !ORG_SKIPPED.contains(f)) {
+        // date come marcatori di tempo inattivi di Org
+        let value = match *field {
+            "year" if book.year == 0 => String::new(),
+            "added_at" => book
+                .added_at
+                .map(|d| {
+                    d.with_timezone(&Local)
+                        .format("[%Y-%m-%d %a %H:%M]")
+                        .to_string()
+                })
+                .unwrap_or_default(),
+            "read_at" => book
+                .read_at
+                .map(|d| d.format("[%Y-%m-%d %a]").to_string())
+                .unwrap_or_default(),
+            _ => field_value(book, field),
+        };
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !value.is_empty() {
+            let _ = writeln!(section, ":{}: {}", field.to_uppercase(), value);
+        }
+    }
+    section.push_str(":END:\n\n");
+
+    if let Some(summary) = book.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        let _ = write!(section, "{}\n\n", org_text(summary));
+    }
+    if let Some(review) = book.review.as_deref().filter(|s| !s.trim().is_empty()) {
+        let _ = write!(
+            section,
+            "** {}\n{}\n\n",
+            tr("list.header.review"),
+            org_text(review)
+        );
+    }
+    section.push_str(ORG_MARKER);
+    section.push('\n');
+    section
+}
+
+/// Sezioni di primo livello di un file Org, con il testo che precede la prima.
+fn org_sections(content: &str) -> Vec<&str> {
+    let mut starts: Vec<usize> = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if line.starts_with("* ") {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, start)| &content[*start..starts.get(i + 1).copied().unwrap_or(content.len())])
+        .collect()
+}
+
+fn org_id(section: &str) -> Option<i64> {
+    section
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(":LIBRIUS_ID:"))
+        .and_then(|id| id.trim().parse().ok())
+}
+
+/// What [`org_document`] has produced.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct OrgDocument {
+    pub text: String,
+    /// Titles of the book sections whose marker was removed, kept as they were.
+    pub skipped: Vec<String>,
+}
+
+/// Sezione di un libro nell'esportazione precedente, trovata tramite `LIBRIUS_ID`.
+enum OrgPrevious<'a> {
+    /// Testo dell'utente sotto il segnaposto
+    Notes(&'a str),
+    /// Sezione senza segnaposto: resta com'è al posto di quella generata
+    Untouched(&'a str),
+}
+
+/// Single Org document with one top-level heading per book.
+///
+/// When `previous` holds an earlier export, book sections are matched by
+/// their `LIBRIUS_ID`: the text below [`ORG_MARKER`] is carried over, and a
+/// section whose marker was removed is kept as it is in place of the
+/// generated one (listed in [`OrgDocument::skipped`]). Sections that do not
+/// belong to a book in the catalogue (headings added by hand, books since
+/// deleted) are kept verbatim at the end.
+pub fn org_document(books: &[Book], previous: Option<&str>, library: Option<&str>) -> OrgDocument {
+    let mut sections: HashMap<i64, OrgPrevious> = HashMap::new();
+    let mut foreign: Vec<&str> = Vec::new();
+    let ids: HashSet<i64> = books.iter().filter_map(|b| b.id.map(i64::from)).collect();
+    for section in org_sections(previous.unwrap_or_default()) {
+        match org_id(section) {
+            Some(id) if ids.contains(&id) && !sections.contains_key(&id) => {
+                let kept = match after_marker(section, ORG_MARKER) {
+                    Some(text) => OrgPrevious::Notes(text),
+                    None => OrgPrevious::Untouched(section),
+                };
+                sections.insert(id, kept);
+            }
+            _ => foreign.push(section),
+        }
+    }
+
+    let mut document = format!(
+        "#+TITLE: {}\n#+LANGUAGE: {}\n\n",
+        catalogue_title(library),
+        current_language()
+    );
+    let mut skipped = Vec::new();
+    for book in books {
+        let id = book.id.map(i64::from).unwrap_or_default();
+        match sections.get(&id) {
+            Some(OrgPrevious::Untouched(section)) => {
+                push_section(&mut document, section);
+                skipped.push(book.title.clone());
+            }
+            Some(OrgPrevious::Notes(text)) => {
+                document.push_str(&org_section(book));
+                push_section(&mut document, text);
+            }
+            None => {
+                document.push_str(&org_section(book));
+                document.push('\n');
+            }
+        }
+    }
+    for section in foreign {
+        push_section(&mut document, section);
+    }
+    OrgDocument {
+        text: document,
+        skipped,
+    }
+}
+
+/// Appends text taken from the previous export, so that the next heading
+/// still starts on a line of its own.
+fn push_section(document: &mut String, text: &str) {
+    document.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        document.push('\n');
+    }
+}
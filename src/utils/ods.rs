@@ -0,0 +1,523 @@
+// =====================================================
+// Librius - utils/ods.rs
+// -----------------------------------------------------
+// Fogli di calcolo OpenDocument (.ods): scrittura di un
+// foglio con intestazione in grassetto e celle tipizzate
+// (numeri e date), lettura del primo foglio come righe
+// di testo per l'import.
+// =====================================================
+
+use chrono::{NaiveDate, NaiveDateTime};
+use quick_xml::events::{BytesDecl, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use std::fs::File;
+use std::io::{self, Cursor, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Media type of an OpenDocument spreadsheet.
+pub const ODS_MIMETYPE: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+const ODF_VERSION: &str = "1.3";
+
+/// Namespace del documento: (prefisso, URI).
+const NAMESPACES: [(&str, &str); 6] = [
+    (
+        "xmlns:office",
+        "urn:oasis:names:tc:opendocument:xmlns:office:1.0",
+    ),
+    (
+        "xmlns:style",
+        "urn:oasis:names:tc:opendocument:xmlns:style:1.0",
+    ),
+    (
+        "xmlns:text",
+        "urn:oasis:names:tc:opendocument:xmlns:text:1.0",
+    ),
+    (
+        "xmlns:table",
+        "urn:oasis:names:tc:opendocument:xmlns:table:1.0",
+    ),
+    (
+        "xmlns:fo",
+        "urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0",
+    ),
+    (
+        "xmlns:number",
+        "urn:oasis:names:tc:opendocument:xmlns:datastyle:1.0",
+    ),
+];
+
+/// Stili automatici delle celle.
+const HEADER_STYLE: &str = "header";
+const DATE_STYLE: &str = "date";
+const DATETIME_STYLE: &str = "datetime";
+
+/// A typed spreadsheet cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OdsCell {
+    Empty,
+    Text(String),
+    Number(f64),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+/// Writes a single-sheet ODS file: a bold header row, then one row per entry
+/// of `rows`.
+pub fn write_ods(
+    path: &Path,
+    sheet: &str,
+    headers: &[&str],
+    rows: &[Vec<OdsCell>],
+) -> io::Result<()> {
+    let content = content_xml(sheet, headers, rows)?;
+
+    let mut zip = ZipWriter::new(File::create(path)?);
+    // il mimetype va per primo e non compresso
+    zip.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )
+    .map_err(io::Error::other)?;
+    zip.write_all(ODS_MIMETYPE.as_bytes())?;
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("META-INF/manifest.xml", options)
+        .map_err(io::Error::other)?;
+    zip.write_all(&manifest_xml()?)?;
+    zip.start_file("content.xml", options)
+        .map_err(io::Error::other)?;
+    zip.write_all(&content)?;
+    zip.finish().map_err(io::Error::other)?;
+    Ok(())
+}
+
+fn manifest_xml() -> io::Result<Vec<u8>> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 1);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("manifest:manifest")
+        .with_attributes([
+            (
+                "xmlns:manifest",
+                "urn:oasis:names:tc:opendocument:xmlns:manifest:1.0",
+            ),
+            ("manifest:version", ODF_VERSION),
+        ])
+        .write_inner_content(|w| {
+            w.create_element("manifest:file-entry")
+                .with_attributes([
+                    ("manifest:full-path", "/"),
+                    ("manifest:version", ODF_VERSION),
+                    ("manifest:media-type", ODS_MIMETYPE),
+                ])
+                .write_empty()?;
+            w.create_element("manifest:file-entry")
+                .with_attributes([
+                    ("manifest:full-path", "content.xml"),
+                    ("manifest:media-type", "text/xml"),
+                ])
+                .write_empty()?;
+            Ok(())
+        })?;
+    Ok(writer.into_inner())
+}
+
+// Niente indentazione: gli spazi dentro <text:p> fanno parte del testo
+fn content_xml(sheet: &str, headers: &[&str], rows: &[Vec<OdsCell>]) -> io::Result<Vec<u8>> {
+    let mut writer = Writer::new(Vec::new());
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    writer
+        .create_element("office:document-content")
+        .with_attributes(NAMESPACES)
+        .with_attribute(("office:version", ODF_VERSION))
+        .write_inner_content(|w| {
+            w.create_element("office:automatic-styles")
+                .write_inner_content(write_styles)?;
+            w.create_element("office:body").write_inner_content(|w| {
+                w.create_element("office:spreadsheet")
+                    .write_inner_content(|w| {
+                        w.create_element("table:table")
+                            .with_attribute(("table:name", sheet))
+                            .write_inner_content(|w| write_table(w, headers, rows))?;
+                        Ok(())
+                    })?;
+                Ok(())
+            })?;
+            Ok(())
+        })?;
+    Ok(writer.into_inner())
+}
+
+/// Formati delle date (`2024-03-01`, `2024-03-01 10:20`) e stili delle celle.
+fn write_styles(w: &mut Writer<Vec<u8>>) -> io::Result<()> {
+    for (name, with_time) in [("N1", false), ("N2", true)] {
+        w.create_element("number:date-style")
+            .with_attribute(("style:name", name))
+            .write_inner_content(|w| {
+                let part = |w: &mut Writer<Vec<u8>>, element: &str| {
+                    w.create_element(element)
+                        .with_attribute(("number:style", "long"))
+                        .write_empty()
+                        .map(|_| ())
+                };
+                let separator = |w: &mut Writer<Vec<u8>>, text: &str| {
+                    w.create_element("number:text")
+                        .write_text_content(BytesText::new(text))
+                        .map(|_| ())
+                };
+                part(w, "number:year")?;
+                separator(w, "-")?;
+                part(w, "number:month")?;
+                separator(w, "-")?;
+                part(w, "number:day")?;
+                if with_time {
+                    separator(w, " ")?;
+                    part(w, "number:hours")?;
+                    separator(w, ":")?;
+                    part(w, "number:minutes")?;
+                }
+                Ok(())
+            })?;
+    }
+
+    w.create_element("style:style")
+        .with_attributes([("style:name", HEADER_STYLE), ("style:family", "table-cell")])
+        .write_inner_content(|w| {
+            w.create_element("style:text-properties")
+                .with_attributes([
+                    ("fo:font-weight", "bold"),
+                    ("style:font-weight-asian", "bold"),
+                    ("style:font-weight-complex", "bold"),
+                ])
+                .write_empty()?;
+            Ok(())
+        })?;
+    for (name, data_style) in [(DATE_STYLE, "N1"), (DATETIME_STYLE, "N2")] {
+        w.create_element("style:style")
+            .with_attributes([
+                ("style:name", name),
+                ("style:family", "table-cell"),
+                ("style:data-style-name", data_style),
+            ])
+            .write_empty()?;
+    }
+    Ok(())
+}
+
+fn write_table(w: &mut Writer<Vec<u8>>, headers: &[&str], rows: &[Vec<OdsCell>]) -> io::Result<()> {
+    w.create_element("table:table-column")
+        .with_attribute((
+            "table:number-columns-repeated",
+            headers.len().to_string().as_str(),
+        ))
+        .write_empty()?;
+
+    w.create_element("table:table-row")
+        .write_inner_content(|w| {
+            for header in headers {
+                w.create_element("table:table-cell")
+                    .with_attributes([
+                        ("table:style-name", HEADER_STYLE),
+                        ("office:value-type", "string"),
+                    ])
+                    .write_inner_content(|w| write_paragraphs(w, header))?;
+            }
+            Ok(())
+        })?;
+
+    for row in rows {
+        w.create_element("table:table-row")
+            .write_inner_content(|w| {
+                for cell in row {
+                    write_cell(w, cell)?;
+                }
+                Ok(())
+            })?;
+    }
+    Ok(())
+}
+
+fn write_cell(w: &mut Writer<Vec<u8>>, cell: &OdsCell) -> io::Result<()> {
+    let element = w.create_element("table:table-cell");
+    match cell {
+        OdsCell::Empty => {
+            element.write_empty()?;
+        }
+        OdsCell::Text(text) => {
+            element
+                .with_attribute(("office:value-type", "string"))
+                .write_inner_content(|w| write_paragraphs(w, text))?;
+        }
+        OdsCell::Number(value) => {
+            let value = value.to_string();
+            element
+                .with_attributes([
+                    ("office:value-type", "float"),
+                    ("office:value", value.as_str()),
+                ])
+                .write_inner_content(|w| write_paragraphs(w, &value))?;
+        }
+        OdsCell::Date(date) => {
+            let value = date.format("%Y-%m-%d").to_string();
+            element
+                .with_attributes([
+                    ("table:style-name", DATE_STYLE),
+                    ("office:value-type", "date"),
+                    ("office:date-value", value.as_str()),
+                ])
+                .write_inner_content(|w| write_paragraphs(w, &value))?;
+        }
+        OdsCell::DateTime(datetime) => {
+            let value = datetime.format("%Y-%m-%dT%H:%M:%S").to_string();
+            let shown = datetime.format("%Y-%m-%d %H:%M").to_string();
+            element
+                .with_attributes([
+                    ("table:style-name", DATETIME_STYLE),
+                    ("office:value-type", "date"),
+                    ("office:date-value", value.as_str()),
+                ])
+                .write_inner_content(|w| write_paragraphs(w, &shown))?;
+        }
+    }
+    Ok(())
+}
+
+/// Un paragrafo per riga; spazi ripetuti e tabulazioni diventano
+/// `<text:s>` e `<text:tab>`, altrimenti chi legge li comprimerebbe.
+fn write_paragraphs(w: &mut Writer<Vec<u8>>, text: &str) -> io::Result<()> {
+    for line in text.split('\n') {
+        w.create_element("text:p").write_inner_content(|w| {
+            let mut plain = String::new();
+            let mut spaces = 0usize;
+            let flush = |w: &mut Writer<Vec<u8>>, plain: &mut String, spaces: &mut usize| {
+                if !plain.is_empty() {
+                    w.write_event(Event::Text(BytesText::new(plain)))?;
+                    plain.clear();
+                }
+                if *spaces > 0 {
+                    w.create_element("text:s")
+                        .with_attribute(("text:c", spaces.to_string().as_str()))
+                        .write_empty()?;
+                    *spaces = 0;
+                }
+                io::Result::Ok(())
+            };
+            let mut previous: Option<char> = None;
+            for c in line.chars() {
+                match c {
+                    // il primo spazio di una serie resta testo, tranne a inizio riga
+                    ' ' if previous.is_some_and(|p| p != ' ' && p != '\t') => plain.push(' '),
+                    ' ' => spaces += 1,
+                    '\t' => {
+                        flush(w, &mut plain, &mut spaces)?;
+                        w.create_element("text:tab").write_empty()?;
+                    }
+                    _ => {
+                        if spaces > 0 {
+                            flush(w, &mut plain, &mut spaces)?;
+                        }
+                        plain.push(c);
+                    }
+                }
+                previous = Some(c);
+            }
+            flush(w, &mut plain, &mut spaces)
+        })?;
+    }
+    Ok(())
+}
+
+//
+// 🧩 Lettura
+//
+
+/// Cell values of the first sheet of an ODS file, row by row; the first row
+/// holds the headers.
+///
+/// Numbers and dates are read from their typed value rather than from the
+/// displayed text: `1965`, `2024-03-01`, `2024-03-01 10:20:30`. Trailing empty
+/// rows and cells are dropped and every row is padded to the same width.
+pub fn read_ods_rows(path: &Path) -> io::Result<Vec<Vec<String>>> {
+    let data = std::fs::read(path)?;
+    let mut archive = ZipArchive::new(Cursor::new(data)).map_err(io::Error::other)?;
+    let mut content = String::new();
+    archive
+        .by_name("content.xml")
+        .map_err(io::Error::other)?
+        .read_to_string(&mut content)?;
+    parse_content(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Stato della lettura di `content.xml`.
+#[derive(Default)]
+struct SheetState {
+    rows: Vec<Vec<String>>,
+    /// Righe vuote in attesa: contano solo se segue una riga piena
+    empty_rows: usize,
+    row: Vec<String>,
+    row_repeat: usize,
+    empty_cells: usize,
+    /// Valore tipizzato e ripetizioni della cella corrente
+    cell: Option<(Option<String>, usize)>,
+    text: String,
+    paragraphs: usize,
+    in_paragraph: bool,
+    /// Profondità dentro un commento (office:annotation), da ignorare
+    annotation: usize,
+}
+
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+fn repeat(e: &BytesStart, name: &[u8]) -> usize {
+    attr(e, name)
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Valore di una cella tipizzata, nella forma che l'import riconosce.
+fn typed_value(e: &BytesStart) -> Option<String> {
+    match attr(e, b"value-type")?.as_str() {
+        "float" | "percentage" | "currency" => {
+            let value = attr(e, b"value")?;
+            match value.parse::<f64>() {
+                Ok(n) if n.fract() == 0.0 && n.abs() < 1e15 => Some((n as i64).to_string()),
+                _ => Some(value),
+            }
+        }
+        "date" => {
+            // 2024-03-01T10:20:30.123 → 2024-03-01 10:20:30
+            let value = attr(e, b"date-value")?;
+            let value = value
+                .split('.')
+                .next()
+                .unwrap_or_default()
+                .replace('T', " ");
+            Some(value)
+        }
+        "boolean" => attr(e, b"boolean-value"),
+        _ => None,
+    }
+}
+
+impl SheetState {
+    fn open_cell(&mut self, e: &BytesStart) {
+        self.cell = Some((typed_value(e), repeat(e, b"number-columns-repeated")));
+        self.text.clear();
+        self.paragraphs = 0;
+    }
+
+    fn close_cell(&mut self) {
+        let Some((typed, repeat)) = self.cell.take() else {
+            return;
+        };
+        let value = typed.unwrap_or_else(|| std::mem::take(&mut self.text));
+        if value.is_empty() {
+            self.empty_cells += repeat;
+        } else {
+            let pending = std::mem::take(&mut self.empty_cells);
+            self.row.extend(std::iter::repeat_n(String::new(), pending));
+            self.row.extend(std::iter::repeat_n(value, repeat));
+        }
+    }
+
+    fn close_row(&mut self) {
+        let row = std::mem::take(&mut self.row);
+        self.empty_cells = 0;
+        if row.is_empty() {
+            self.empty_rows += self.row_repeat;
+        } else {
+            let pending = std::mem::take(&mut self.empty_rows);
+            self.rows.extend(std::iter::repeat_n(Vec::new(), pending));
+            self.rows.extend(std::iter::repeat_n(row, self.row_repeat));
+        }
+    }
+
+    /// Elemento vuoto o di apertura dentro una cella.
+    fn inline(&mut self, e: &BytesStart) {
+        if self.cell.is_none() || self.annotation > 0 {
+            return;
+        }
+        match e.local_name().as_ref() {
+            b"s" => {
+                let count = repeat(e, b"c");
+                self.text.extend(std::iter::repeat_n(' ', count));
+            }
+            b"tab" => self.text.push('\t'),
+            b"line-break" => self.text.push('\n'),
+            _ => {}
+        }
+    }
+}
+
+fn parse_content(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut reader = Reader::from_str(content);
+    let mut state = SheetState::default();
+    let mut in_table = false;
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("{} (byte {})", e, reader.buffer_position()))?;
+        match event {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"table" if !in_table => in_table = true,
+                _ if !in_table => {}
+                b"table-row" => state.row_repeat = repeat(&e, b"number-rows-repeated"),
+                b"table-cell" | b"covered-table-cell" => state.open_cell(&e),
+                b"annotation" => state.annotation += 1,
+                b"p" if state.cell.is_some() && state.annotation == 0 => {
+                    if state.paragraphs > 0 {
+                        state.text.push('\n');
+                    }
+                    state.paragraphs += 1;
+                    state.in_paragraph = true;
+                }
+                _ => state.inline(&e),
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                _ if !in_table => {}
+                b"table-row" => {
+                    state.row_repeat = repeat(&e, b"number-rows-repeated");
+                    state.close_row();
+                }
+                b"table-cell" | b"covered-table-cell" => {
+                    state.open_cell(&e);
+                    state.close_cell();
+                }
+                _ => state.inline(&e),
+            },
+            Event::Text(t) if state.in_paragraph && state.annotation == 0 => {
+                let text = t.unescape().map_err(|e| e.to_string())?;
+                state.text.push_str(&text);
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                _ if !in_table => {}
+                // solo il primo foglio
+                b"table" => break,
+                b"table-row" => state.close_row(),
+                b"table-cell" | b"covered-table-cell" => state.close_cell(),
+                b"annotation" => state.annotation = state.annotation.saturating_sub(1),
+                b"p" => state.in_paragraph = false,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let mut rows = state.rows;
+    let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+    for row in &mut rows {
+        row.resize(width, String::new());
+    }
+    Ok(rows)
+}
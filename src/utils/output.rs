@@ -0,0 +1,95 @@
+// =====================================================
+// Librius - utils/output.rs
+// -----------------------------------------------------
+// Formati di output leggibili dalle macchine (JSON,
+// JSON Lines, CSV) per i comandi che stampano elenchi,
+// in alternativa alla tabella predefinita.
+// =====================================================
+
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Output format of a listing command (`--format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Localized table (default)
+    #[default]
+    Table,
+    /// A single JSON array
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// CSV with a header row
+    Csv,
+}
+
+impl OutputFormat {
+    /// Every accepted value, as written on the CLI.
+    pub const NAMES: [&'static str; 4] = ["table", "json", "jsonl", "csv"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            "csv" => Ok(OutputFormat::Csv),
+            other => Err(other.to_string()),
+        }
+    }
+}
+
+/// Writes `records` to `out` in a machine-readable `format`.
+///
+/// In CSV the columns are the fields of the records, in declaration order;
+/// nested values (such as JSON details) are written as JSON text. `Table` is
+/// rendered by the caller and writes nothing here.
+pub fn write_records<T: Serialize, W: Write>(
+    out: W,
+    format: OutputFormat,
+    records: &[T],
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => Ok(()),
+        OutputFormat::Json => {
+            let mut out = out;
+            serde_json::to_writer_pretty(&mut out, records)?;
+            writeln!(out)
+        }
+        OutputFormat::Jsonl => {
+            let mut out = out;
+            for record in records {
+                serde_json::to_writer(&mut out, record)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => write_csv(out, records),
+    }
+}
+
+fn write_csv<T: Serialize, W: Write>(out: W, records: &[T]) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    let mut header_written = false;
+
+    for record in records {
+        let Value::Object(map) = serde_json::to_value(record)? else {
+            continue;
+        };
+        if !header_written {
+            writer.write_record(map.keys())?;
+            header_written = true;
+        }
+        writer.write_record(map.values().map(|v| match v {
+            Value::Null => String::new(),
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }))?;
+    }
+    writer.flush()
+}
@@ -3,8 +3,10 @@
 //! Provides a unified interface for rendering tabular data using the `tabled` crate,
 //! ensuring consistent visual style and alignment across commands.
 
-use crate::i18n::tr;
+use crate::i18n::{format_date, format_timestamp, tr};
+use crate::models::book::{EBOOK_FORMAT, READ_STATUSES};
 use crate::utils::print::print_warn;
+use chrono::NaiveDate;
 use serde::Serialize;
 use serde_json::Value;
 use tabled::settings::{Alignment, Modify, Style, object::Rows};
@@ -48,59 +50,113 @@ struct VerticalRow {
     value: String,
 }
 
-pub fn build_vertical_table<T: Serialize>(record: &T, compact: bool) {
+/// Localized label and display value of every book field, in detail-view
+/// order (`None` for empty fields). Shared by `list --details` and the HTML
+/// export.
+pub fn detail_rows<T: Serialize>(
+    record: &T,
+) -> Option<Vec<(&'static str, String, Option<String>)>> {
     // Serializza in mappa dinamica
-    let value = serde_json::to_value(record).expect("Failed to serialize record");
+    let Ok(Value::Object(map)) = serde_json::to_value(record) else {
+        return None;
+    };
+
+    // ✅ Ordine corretto dei campi
+    let field_order = [
+        "id",
+        "title",
+        "author",
+        "editor",
+        "year",
+        "isbn",
+        "language",
+        "pages",
+        "genre",
+        "summary",
+        "room",
+        "shelf",
+        "row",
+        "position",
+        "added_at",
+        "read_status",
+        "read_at",
+        "rating",
+        "tags",
+        "review",
+        "format",
+    ];
 
-    if let Value::Object(map) = value {
-        // ✅ Ordine corretto dei campi
-        let field_order = [
-            "id", "title", "author", "editor", "year", "isbn", "language", "pages", "genre",
-            "summary", "room", "shelf", "row", "position", "added_at",
-        ];
+    // ✅ Etichette localizzate
+    let field_labels = [
+        tr("list.header.id"),
+        tr("list.header.title"),
+        tr("list.header.author"),
+        tr("list.header.editor"),
+        tr("list.header.year"),
+        tr("list.header.ISBN"),
+        tr("list.header.language"),
+        tr("list.header.pages"),
+        tr("list.header.genre"),
+        tr("list.header.summary"),
+        tr("list.header.room"),
+        tr("list.header.shelf"),
+        tr("list.header.row"),
+        tr("list.header.position"),
+        tr("list.header.added_at"),
+        tr("list.header.read_status"),
+        tr("list.header.read_at"),
+        tr("list.header.rating"),
+        tr("list.header.tags"),
+        tr("list.header.review"),
+        tr("list.header.format"),
+    ];
 
-        // ✅ Etichette localizzate
-        let field_labels = [
-            tr("list.header.id"),
-            tr("list.header.title"),
-            tr("list.header.author"),
-            tr("list.header.editor"),
-            tr("list.header.year"),
-            tr("list.header.ISBN"),
-            tr("list.header.language"),
-            tr("list.header.pages"),
-            tr("list.header.genre"),
-            tr("list.header.summary"),
-            tr("list.header.room"),
-            tr("list.header.shelf"),
-            tr("list.header.row"),
-            tr("list.header.position"),
-            tr("list.header.added_at"),
-        ];
+    let rows = field_order
+        .iter()
+        .zip(field_labels)
+        .map(|(key, label)| {
+            let value = map.get(*key).and_then(|v| match v {
+                Value::Null => None,
+                // Date in formato locale
+                Value::String(s) if *key == "added_at" => Some(format_timestamp(s)),
+                Value::String(s) if *key == "read_at" => Some(
+                    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                        .map(format_date)
+                        .unwrap_or_else(|_| s.clone()),
+                ),
+                Value::String(s)
+                    if *key == "read_status" && READ_STATUSES.contains(&s.as_str()) =>
+                {
+                    Some(tr(&format!("book.read_status.{}", s)))
+                }
+                Value::String(s) if *key == "format" && s == EBOOK_FORMAT => {
+                    Some(tr("book.format.ebook"))
+                }
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                Value::Bool(b) => Some(b.to_string()),
+                other => Some(other.to_string()),
+            });
+            (*key, label, value)
+        })
+        .collect();
+    Some(rows)
+}
 
+pub fn build_vertical_table<T: Serialize>(record: &T, compact: bool) {
+    if let Some(fields) = detail_rows(record) {
         // ✅ Costruisci righe ordinate
         let mut rows: Vec<VerticalRow> = Vec::new();
 
-        for (i, key) in field_order.iter().enumerate() {
-            let value_str = map
-                .get(*key)
-                .map(|v| match v {
-                    Value::Null => "—".to_string(),
-                    Value::String(s) => s.clone(),
-                    Value::Number(n) => n.to_string(),
-                    Value::Bool(b) => b.to_string(),
-                    other => other.to_string(),
-                })
-                .unwrap_or_else(|| "—".to_string());
-
+        for (_, label, value) in fields {
             // ✅ In modalità compatta, salta i campi vuoti
-            if compact && value_str == "—" {
+            if compact && value.is_none() {
                 continue;
             }
 
             rows.push(VerticalRow {
-                field: field_labels[i].clone(),
-                value: value_str,
+                field: label,
+                value: value.unwrap_or_else(|| "—".to_string()),
             });
         }
 
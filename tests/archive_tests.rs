@@ -0,0 +1,74 @@
+mod common;
+use common::{setup_temp_db, temp_db_path};
+use librius::config::BackupFormat;
+use librius::db::migrations::MIGRATIONS;
+use librius::db::run_migrations;
+use librius::utils::archive::{BackupManifest, read_archive, sha256_hex, write_archive};
+use std::fs;
+
+fn sample_db(name: &str) -> (rusqlite::Connection, Vec<u8>) {
+    let conn = setup_temp_db(name);
+    conn.execute(
+        "INSERT INTO books (title, author, editor, year, isbn) VALUES ('Dune', 'Frank Herbert', 'Chilton', 1965, '9780441013593')",
+        [],
+    )
+    .unwrap();
+    run_migrations(&conn).unwrap();
+    let bytes = fs::read(temp_db_path(name)).unwrap();
+    (conn, bytes)
+}
+
+#[test]
+fn test_manifest_contents() {
+    let (conn, bytes) = sample_db("manifest");
+    let manifest = BackupManifest::build(&conn, "librius.sqlite", &bytes);
+
+    assert_eq!(manifest.librius_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(manifest.book_count, 1);
+    let all: Vec<String> = MIGRATIONS.iter().map(|m| m.name.to_string()).collect();
+    assert_eq!(manifest.schema_patches, all);
+    assert_eq!(manifest.sha256, sha256_hex(&bytes));
+}
+
+#[test]
+fn test_archive_roundtrip_all_formats() {
+    let (conn, bytes) = sample_db("archive_roundtrip");
+    let manifest = BackupManifest::build(&conn, "librius.sqlite", &bytes);
+
+    for format in [BackupFormat::Zip, BackupFormat::TarGz, BackupFormat::TarZst] {
+        let archive = write_archive(format, &bytes, &manifest).unwrap();
+        let (db, read_manifest) = read_archive(&archive)
+            .unwrap()
+            .unwrap_or_else(|| panic!("{} archive not recognized", format.ext()));
+
+        assert_eq!(db, bytes, "{} database mismatch", format.ext());
+        assert_eq!(read_manifest.as_ref(), Some(&manifest));
+    }
+}
+
+#[test]
+fn test_plain_sqlite_backup_has_no_manifest() {
+    let (conn, bytes) = sample_db("archive_plain");
+    let manifest = BackupManifest::build(&conn, "librius.sqlite", &bytes);
+
+    let plain = write_archive(BackupFormat::Sqlite, &bytes, &manifest).unwrap();
+    assert_eq!(plain, bytes);
+
+    let (db, read_manifest) = read_archive(&plain).unwrap().unwrap();
+    assert_eq!(db, bytes);
+    assert!(read_manifest.is_none());
+}
+
+#[test]
+fn test_unknown_format_is_rejected() {
+    assert!(read_archive(b"not a backup").unwrap().is_none());
+}
+
+#[test]
+fn test_backup_format_names() {
+    for name in BackupFormat::NAMES {
+        let format: BackupFormat = name.parse().unwrap();
+        assert_eq!(format.ext(), name);
+    }
+    assert!("rar".parse::<BackupFormat>().is_err());
+}
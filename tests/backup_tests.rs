@@ -0,0 +1,120 @@
+mod common;
+use chrono::NaiveDateTime;
+use common::setup_temp_db;
+use librius::config::BackupConfig;
+use librius::utils::backup::{BackupEntry, auto_snapshot, parse_backup_timestamp, select_prunable};
+use std::fs;
+use std::path::PathBuf;
+
+fn entry(stamp: &str) -> BackupEntry {
+    BackupEntry {
+        path: PathBuf::from(format!("librius_backup_{}.sqlite", stamp)),
+        created: NaiveDateTime::parse_from_str(stamp, "%Y-%m-%d_%H-%M-%S").unwrap(),
+    }
+}
+
+#[test]
+fn test_parse_backup_timestamp() {
+    assert!(parse_backup_timestamp("librius_backup_2025-10-13_21-32-07.sqlite").is_some());
+    assert!(
+        parse_backup_timestamp("librius_backup_2025-10-13_21-32-07_pre-reset.sqlite").is_some()
+    );
+    assert!(parse_backup_timestamp("librius_backup_2025-10-13_21-32-07.tar.gz").is_some());
+    assert!(parse_backup_timestamp("notes.txt").is_none());
+    assert!(parse_backup_timestamp("librius_backup_garbage.sqlite").is_none());
+}
+
+#[test]
+fn test_keep_last_only() {
+    let entries = vec![
+        entry("2025-10-13_12-00-00"),
+        entry("2025-10-12_12-00-00"),
+        entry("2025-10-11_12-00-00"),
+    ];
+    let policy = BackupConfig {
+        keep_last: 2,
+        keep_daily: 0,
+        keep_weekly: 0,
+        ..Default::default()
+    };
+
+    let pruned = select_prunable(&entries, &policy);
+    assert_eq!(pruned, vec![entries[2].path.clone()]);
+}
+
+#[test]
+fn test_keep_daily_keeps_newest_per_day() {
+    let entries = vec![
+        entry("2025-10-13_18-00-00"),
+        entry("2025-10-13_09-00-00"),
+        entry("2025-10-12_18-00-00"),
+        entry("2025-10-12_09-00-00"),
+        entry("2025-10-11_18-00-00"),
+    ];
+    let policy = BackupConfig {
+        keep_last: 0,
+        keep_daily: 2,
+        keep_weekly: 0,
+        ..Default::default()
+    };
+
+    let pruned = select_prunable(&entries, &policy);
+    assert_eq!(
+        pruned,
+        vec![
+            entries[1].path.clone(),
+            entries[3].path.clone(),
+            entries[4].path.clone()
+        ]
+    );
+}
+
+#[test]
+fn test_keep_weekly_keeps_newest_per_iso_week() {
+    // 2025-10-13 is a Monday: the 12th and 11th belong to the previous ISO week.
+    let entries = vec![
+        entry("2025-10-13_12-00-00"),
+        entry("2025-10-12_12-00-00"),
+        entry("2025-10-11_12-00-00"),
+        entry("2025-09-30_12-00-00"),
+    ];
+    let policy = BackupConfig {
+        keep_last: 0,
+        keep_daily: 0,
+        keep_weekly: 2,
+        ..Default::default()
+    };
+
+    let pruned = select_prunable(&entries, &policy);
+    assert_eq!(
+        pruned,
+        vec![entries[2].path.clone(), entries[3].path.clone()]
+    );
+}
+
+#[test]
+fn test_auto_snapshot_copies_database() {
+    let conn = setup_temp_db("auto_snapshot");
+    conn.execute(
+        "INSERT INTO books (title, author, editor, year, isbn) VALUES ('Dune', 'Frank Herbert', 'Chilton', 1965, '9780441013593')",
+        [],
+    )
+    .unwrap();
+
+    let snapshot = auto_snapshot(&conn, "test")
+        .unwrap()
+        .expect("file-backed DB must be snapshotted");
+
+    assert!(snapshot.exists());
+    let name = snapshot.file_name().unwrap().to_string_lossy().to_string();
+    assert!(name.ends_with("_pre-test.sqlite"));
+    assert!(parse_backup_timestamp(&name).is_some());
+
+    fs::remove_file(snapshot).unwrap();
+}
+
+#[test]
+fn test_auto_snapshot_skips_in_memory_db() {
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    assert!(auto_snapshot(&conn, "test").unwrap().is_none());
+}
@@ -0,0 +1,135 @@
+mod common;
+use chrono::NaiveDate;
+use common::setup_temp_db;
+use librius::commands::handle_import_calibre;
+use librius::db::{find_book, insert_book};
+use librius::models::Book;
+use librius::utils::{ImportOptions, RejectReason, calibre_db_path, read_calibre};
+use rusqlite::Connection;
+use std::fs;
+use std::path::PathBuf;
+
+/// Crea una libreria Calibre di prova (cartella con `metadata.db`).
+fn calibre_library(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("librius_test_calibre_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let conn = Connection::open(dir.join("metadata.db")).unwrap();
+    conn.execute_batch(include_str!("fixtures/calibre/metadata.sql"))
+        .unwrap();
+    dir
+}
+
+#[test]
+fn test_calibre_books_map_onto_ebooks() {
+    let library = calibre_library("mapping");
+    let import = read_calibre(&calibre_db_path(&library)).unwrap();
+    assert_eq!(import.books.len(), 4);
+    assert!(
+        import
+            .books
+            .iter()
+            .all(|(_, b)| b.format.as_deref() == Some("ebook"))
+    );
+
+    let dune = &import.books[0].1;
+    assert_eq!(dune.title, "Dune");
+    assert_eq!(dune.author, "Frank Herbert");
+    assert_eq!(dune.editor, "Ace");
+    assert_eq!(dune.isbn, "9780441013593");
+    assert_eq!(dune.year, 2005);
+    assert_eq!(dune.language.as_deref(), Some("English"));
+    assert_eq!(dune.rating, Some(5));
+    assert_eq!(dune.tags.as_deref(), Some("classics, sci-fi"));
+    assert_eq!(
+        dune.summary.as_deref(),
+        Some("Spice & sand.\nSet on Arrakis.")
+    );
+    assert_eq!(
+        dune.added_at.unwrap().date_naive(),
+        NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()
+    );
+
+    // Autori nell'ordine di Calibre, ISBN dalla colonna storica, data ignota
+    let omens = &import.books[1].1;
+    assert_eq!(omens.author, "Terry Pratchett, Neil Gaiman");
+    assert_eq!(omens.isbn, "0060853980");
+    assert_eq!(omens.year, 0);
+    assert_eq!(omens.language.as_deref(), Some("Italian"));
+    assert_eq!(omens.rating, Some(4));
+    assert_eq!(omens.summary, None);
+
+    assert_eq!(import.books[3].1.isbn, "9780547928227");
+
+    let _ = fs::remove_dir_all(&library);
+}
+
+#[test]
+fn test_calibre_books_without_isbn_are_kept() {
+    let library = calibre_library("no_isbn");
+    let import = read_calibre(&library.join("metadata.db")).unwrap();
+
+    assert!(import.rejected.is_empty());
+    let (line, notes) = &import.books[2];
+    assert_eq!(*line, 3);
+    assert_eq!(notes.title, "Notes");
+    assert_eq!(notes.isbn, "");
+
+    let _ = fs::remove_dir_all(&library);
+}
+
+#[test]
+fn test_calibre_books_with_malformed_isbn_are_rejected() {
+    let library = calibre_library("bad_isbn");
+    Connection::open(library.join("metadata.db"))
+        .unwrap()
+        .execute("UPDATE books SET isbn = '9780441013590' WHERE id = 3", [])
+        .unwrap();
+    let import = read_calibre(&library.join("metadata.db")).unwrap();
+
+    assert_eq!(import.books.len(), 3);
+    assert_eq!(import.rejected.len(), 1);
+    assert_eq!(import.rejected[0].line, 3);
+    assert_eq!(
+        import.rejected[0].reason,
+        RejectReason::InvalidIsbn("9780441013590".to_string())
+    );
+
+    let _ = fs::remove_dir_all(&library);
+}
+
+#[test]
+fn test_calibre_import_skips_existing_isbns() {
+    let library = calibre_library("dedupe");
+    let mut conn = setup_temp_db("calibre_import");
+    let hobbit = Book {
+        title: "The Hobbit".to_string(),
+        author: "J. R. R. Tolkien".to_string(),
+        editor: "Mariner".to_string(),
+        year: 2012,
+        isbn: "9780547928227".to_string(),
+        ..Default::default()
+    };
+    let hobbit_id = insert_book(&conn, &hobbit).unwrap();
+    let count = |conn: &Connection| -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM books", [], |r| r.get(0))
+            .unwrap()
+    };
+
+    let options = ImportOptions::default();
+    handle_import_calibre(&mut conn, library.to_str().unwrap(), &options).unwrap();
+    assert_eq!(count(&conn), 4);
+
+    // Il libro cartaceo già presente non viene toccato
+    let existing = find_book(&conn, "9780547928227", true).unwrap().unwrap();
+    assert_eq!(existing.id.map(i64::from), Some(hobbit_id));
+    assert_eq!(existing.format, None);
+    let dune = find_book(&conn, "9780441013593", true).unwrap().unwrap();
+    assert_eq!(dune.format.as_deref(), Some("ebook"));
+
+    // Una seconda importazione non aggiunge nulla, nemmeno il libro senza ISBN
+    handle_import_calibre(&mut conn, library.to_str().unwrap(), &options).unwrap();
+    assert_eq!(count(&conn), 4);
+
+    let _ = fs::remove_dir_all(&library);
+}
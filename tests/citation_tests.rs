@@ -0,0 +1,231 @@
+mod common;
+use common::setup_temp_db;
+use librius::commands::handle_export_citations;
+use librius::db::trash::{restore_book, trash_book};
+use librius::db::{find_book_by_id, insert_book};
+use librius::models::Book;
+use librius::utils::citation::{citation_key_base, csl_item, split_name};
+use librius::utils::{CitationFormat, with_citation_keys, write_citations};
+use serde_json::json;
+use std::fs;
+
+fn book(title: &str, author: &str, year: i32) -> Book {
+    Book {
+        title: title.to_string(),
+        author: author.to_string(),
+        year,
+        ..Default::default()
+    }
+}
+
+fn dune() -> Book {
+    Book {
+        editor: "Chilton".to_string(),
+        isbn: "9780441013593".to_string(),
+        language: Some("English".to_string()),
+        pages: Some(412),
+        summary: Some("Spice & {sand},\n100% worms.".to_string()),
+        tags: Some("classics, sci-fi".to_string()),
+        ..book("Dune", "Frank Herbert, Brian Herbert", 1965)
+    }
+}
+
+fn render(format: CitationFormat, books: &[Book]) -> String {
+    let mut out = Vec::new();
+    write_citations(&mut out, format, &with_citation_keys(books)).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_split_name_keeps_particles_with_the_family_name() {
+    assert_eq!(split_name("J. R. R. Tolkien"), ("Tolkien", "J. R. R."));
+    assert_eq!(
+        split_name("Giuseppe Tomasi di Lampedusa"),
+        ("di Lampedusa", "Giuseppe Tomasi")
+    );
+    assert_eq!(split_name("Homer"), ("Homer", ""));
+}
+
+#[test]
+fn test_citation_key_base() {
+    assert_eq!(citation_key_base(&dune()), "herbert1965dune");
+    // Articoli iniziali saltati, accenti rimossi
+    assert_eq!(
+        citation_key_base(&book("L'amica geniale", "Elena Ferrante", 2011)),
+        "ferrante2011amica"
+    );
+    assert_eq!(
+        citation_key_base(&book("Les Misérables", "Émile Zola", 1862)),
+        "zola1862miserables"
+    );
+    assert_eq!(citation_key_base(&book("Beowulf", "", 0)), "anonndbeowulf");
+}
+
+#[test]
+fn test_duplicate_keys_get_a_letter_suffix_in_catalog_order() {
+    let books = vec![
+        dune(),
+        book("Dune Messiah", "Frank Herbert", 1969),
+        book("Dune: the graphic novel", "Frank Herbert", 1965),
+        book("Dune", "Frank Herbert", 1965),
+    ];
+    let keys: Vec<String> = with_citation_keys(&books)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            "herbert1965dune",
+            "herbert1969dune",
+            "herbert1965dunea",
+            "herbert1965duneb"
+        ]
+    );
+
+    // Un libro aggiunto in fondo non cambia le chiavi esistenti
+    let fewer = with_citation_keys(&books[..3]);
+    assert_eq!(fewer[2].0, "herbert1965dunea");
+}
+
+#[test]
+fn test_bibtex_entry_escapes_special_characters() {
+    let bibtex = render(CitationFormat::Bibtex, &[dune()]);
+    assert_eq!(
+        bibtex,
+        "@book{herbert1965dune,\n\
+         \x20 author = {Herbert, Frank and Herbert, Brian},\n\
+         \x20 title = {{Dune}},\n\
+         \x20 publisher = {Chilton},\n\
+         \x20 year = {1965},\n\
+         \x20 isbn = {9780441013593},\n\
+         \x20 pagetotal = {412},\n\
+         \x20 language = {English},\n\
+         \x20 abstract = {Spice \\& \\{sand\\}, 100\\% worms.},\n\
+         \x20 keywords = {classics, sci-fi},\n\
+         }\n"
+    );
+}
+
+#[test]
+fn test_ris_and_csl_json_entries() {
+    let minimal = book("The Hobbit", "J. R. R. Tolkien", 0);
+    let ris = render(CitationFormat::Ris, &[dune(), minimal.clone()]);
+    let records: Vec<&str> = ris.split("\n\n").collect();
+    assert_eq!(records.len(), 2);
+    assert!(records[0].starts_with("TY  - BOOK\nID  - herbert1965dune\n"));
+    assert!(records[0].contains("AU  - Herbert, Brian\n"));
+    assert!(records[0].contains("AB  - Spice & {sand}, 100% worms.\n"));
+    assert!(records[0].contains("KW  - classics\nKW  - sci-fi\n"));
+    assert_eq!(
+        records[1],
+        "TY  - BOOK\nID  - tolkienndhobbit\nAU  - Tolkien, J. R. R.\nTI  - The Hobbit\nER  - \n"
+    );
+
+    assert_eq!(
+        csl_item("herbert1965dune", &dune()),
+        json!({
+            "id": "herbert1965dune",
+            "type": "book",
+            "title": "Dune",
+            "author": [
+                { "family": "Herbert", "given": "Frank" },
+                { "family": "Herbert", "given": "Brian" }
+            ],
+            "publisher": "Chilton",
+            "issued": { "date-parts": [[1965]] },
+            "ISBN": "9780441013593",
+            "number-of-pages": 412,
+            "language": "en",
+            "abstract": "Spice & {sand},\n100% worms.",
+            "keyword": "classics, sci-fi"
+        })
+    );
+}
+
+#[test]
+fn test_export_citations_writes_every_book() {
+    let conn = setup_temp_db("export_citations");
+    insert_book(&conn, &dune()).unwrap();
+    insert_book(
+        &conn,
+        &Book {
+            isbn: "9780547928227".to_string(),
+            ..book("The Hobbit", "J. R. R. Tolkien", 2012)
+        },
+    )
+    .unwrap();
+
+    let path = std::env::temp_dir().join("librius_test_citations.json");
+    handle_export_citations(
+        &conn,
+        Some(path.to_str().unwrap().to_string()),
+        CitationFormat::CslJson,
+    )
+    .unwrap();
+
+    let items: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let ids: Vec<&str> = items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["herbert1965dune", "tolkien2012hobbit"]);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_citation_keys_survive_trash_and_restore() {
+    let conn = setup_temp_db("export_citations_trash");
+    let ids: Vec<i64> = ["9780441013593", "9780441172719", "9780593098233"]
+        .iter()
+        .map(|isbn| {
+            insert_book(
+                &conn,
+                &Book {
+                    isbn: isbn.to_string(),
+                    ..book("Dune", "Frank Herbert", 1965)
+                },
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let path = std::env::temp_dir().join("librius_test_citations_trash.json");
+    let export = || {
+        handle_export_citations(
+            &conn,
+            Some(path.to_str().unwrap().to_string()),
+            CitationFormat::CslJson,
+        )
+        .unwrap();
+        let items: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        export(),
+        vec!["herbert1965dune", "herbert1965dunea", "herbert1965duneb"]
+    );
+
+    // Il primo libro nel cestino non libera la sua chiave
+    let first = find_book_by_id(&conn, ids[0]).unwrap().unwrap();
+    trash_book(&conn, &first).unwrap();
+    assert_eq!(export(), vec!["herbert1965dunea", "herbert1965duneb"]);
+
+    restore_book(&conn, ids[0]).unwrap();
+    assert_eq!(
+        export(),
+        vec!["herbert1965dune", "herbert1965dunea", "herbert1965duneb"]
+    );
+
+    let _ = fs::remove_file(&path);
+}
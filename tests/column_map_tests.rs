@@ -0,0 +1,206 @@
+use librius::utils::column_map::{MapError, Transform, infer_field, load_map_file};
+use librius::utils::{ColumnMap, MappingOptions, RejectReason, read_xlsx_rows};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/import")
+        .join(name)
+}
+
+/// Intestazioni e righe di `catalogo.csv` (delimitatore `;`).
+fn catalogo() -> (Vec<String>, Vec<Vec<String>>) {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .from_path(fixture("catalogo.csv"))
+        .unwrap();
+    let headers = reader.headers().unwrap().iter().map(String::from).collect();
+    let rows = reader
+        .records()
+        .map(|r| r.unwrap().iter().map(String::from).collect())
+        .collect();
+    (headers, rows)
+}
+
+#[test]
+fn test_transforms() {
+    assert_eq!(Transform::Trim.apply("  Dune "), "Dune");
+    assert_eq!(
+        Transform::TitleCase.apply("il NOME della rosa"),
+        "Il Nome Della Rosa"
+    );
+    assert_eq!(
+        Transform::SplitAuthors.apply("Terry Pratchett & Neil Gaiman; Ada e Bea"),
+        "Terry Pratchett, Neil Gaiman, Ada, Bea"
+    );
+    assert_eq!(Transform::Year.apply("14/05/1980"), "1980");
+    assert_eq!(Transform::Year.apply("1965-08-01"), "1965");
+    // Un seriale di Excel non è un anno: il valore resta invariato
+    assert_eq!(Transform::Year.apply("45123"), "45123");
+}
+
+#[test]
+fn test_infer_recognizes_english_and_italian_headers() {
+    assert_eq!(infer_field("Titolo"), Some("title"));
+    assert_eq!(infer_field("Casa Editrice"), Some("editor"));
+    assert_eq!(infer_field("numero_di_pagine"), Some("pages"));
+    assert_eq!(infer_field("Year Published"), Some("year"));
+    assert_eq!(infer_field("ISBN-13"), Some("isbn"));
+    assert_eq!(infer_field("Publisher"), Some("editor"));
+    assert_eq!(infer_field("Read Status"), Some("read_status"));
+    assert_eq!(infer_field("Note"), None);
+
+    let (headers, rows) = catalogo();
+    let options = MappingOptions {
+        infer: true,
+        ..Default::default()
+    };
+    let map = ColumnMap::resolve(&headers, &options).unwrap();
+    assert_eq!(map.unmapped(&headers), vec!["Note"]);
+
+    let rosa = map.map_row(&headers, &rows[0]).unwrap();
+    assert_eq!(rosa.title, "il nome della rosa");
+    assert_eq!(rosa.editor, "Bompiani");
+    assert_eq!(rosa.year, 1980);
+    assert_eq!(rosa.pages, Some(503));
+    let omens = map.map_row(&headers, &rows[1]).unwrap();
+    assert_eq!(omens.author, "Terry Pratchett, Neil Gaiman");
+}
+
+#[test]
+fn test_rows_without_title_or_with_bad_values_are_rejected() {
+    let (headers, rows) = catalogo();
+    let options = MappingOptions {
+        infer: true,
+        ..Default::default()
+    };
+    let map = ColumnMap::resolve(&headers, &options).unwrap();
+
+    assert_eq!(
+        map.map_row(&headers, &rows[2]),
+        Err(RejectReason::MissingTitle)
+    );
+    assert_eq!(
+        map.map_row(&headers, &rows[3]),
+        Err(RejectReason::InvalidField {
+            field: "year".to_string(),
+            value: "boh".to_string()
+        })
+    );
+    assert_eq!(
+        map.raw_value("title", &headers, &rows[3]).as_deref(),
+        Some("Libro")
+    );
+}
+
+#[test]
+fn test_map_rules_and_defaults() {
+    let (headers, rows) = catalogo();
+    let options = MappingOptions {
+        rules: vec![
+            "Titolo=title:title_case, Autori=author".to_string(),
+            "Data di pubblicazione=year:year".to_string(),
+        ],
+        defaults: vec!["editor=Ignoto".to_string(), "year=1900".to_string()],
+        ..Default::default()
+    };
+    let map = ColumnMap::resolve(&headers, &options).unwrap();
+
+    let rosa = map.map_row(&headers, &rows[0]).unwrap();
+    assert_eq!(rosa.title, "Il Nome Della Rosa");
+    assert_eq!(rosa.year, 1980);
+    // Senza --infer si associano solo le colonne col nome di un campo
+    // (ISBN); gli altri campi vuoti prendono il valore predefinito
+    assert_eq!(rosa.isbn, "9788845292613");
+    assert_eq!(rosa.editor, "Ignoto");
+    assert_eq!(rosa.pages, None);
+
+    let omens = map.map_row(&headers, &rows[1]).unwrap();
+    assert_eq!(omens.author, "Terry Pratchett & Neil Gaiman");
+
+    let resolve = |rule: &str| {
+        ColumnMap::resolve(
+            &headers,
+            &MappingOptions {
+                rules: vec![rule.to_string()],
+                ..Default::default()
+            },
+        )
+    };
+    assert!(matches!(
+        resolve("Titolo=name"),
+        Err(MapError::UnknownField(f)) if f == "name"
+    ));
+    assert!(matches!(
+        resolve("Titolo=title:upper"),
+        Err(MapError::UnknownTransform(t)) if t == "upper"
+    ));
+    assert!(matches!(resolve("Titolo"), Err(MapError::InvalidRule(_))));
+    assert!(matches!(
+        resolve("Title=title"),
+        Err(MapError::UnknownColumn(c)) if c == "Title"
+    ));
+}
+
+#[test]
+fn test_map_file() {
+    let map = load_map_file(&fixture("catalogo.yaml")).unwrap();
+    assert_eq!(map.rules.len(), 5);
+    assert_eq!(map.rules[1].transforms, vec![Transform::SplitAuthors]);
+    assert_eq!(map.defaults["genre"], "Narrativa");
+
+    let (headers, rows) = catalogo();
+    let options = MappingOptions {
+        map_file: Some(fixture("catalogo.yaml")),
+        ..Default::default()
+    };
+    let map = ColumnMap::resolve(&headers, &options).unwrap();
+    let omens = map.map_row(&headers, &rows[1]).unwrap();
+    assert_eq!(omens.title, "Good Omens");
+    assert_eq!(omens.author, "Terry Pratchett, Neil Gaiman");
+    assert_eq!(omens.editor, "Sconosciuto");
+    assert_eq!(omens.genre.as_deref(), Some("Narrativa"));
+    assert_eq!(omens.isbn, "0060853980");
+}
+
+#[test]
+fn test_headers_matching_field_names_need_no_mapping() {
+    let headers: Vec<String> = ["Title", "author", "isbn", "shelf"]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    let row: Vec<String> = ["Dune", "Frank Herbert", "9780441013593", "B2"]
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    let map = ColumnMap::resolve(&headers, &MappingOptions::default()).unwrap();
+    let dune = map.map_row(&headers, &row).unwrap();
+    assert_eq!(dune.title, "Dune");
+    assert_eq!(dune.year, 0);
+    assert_eq!(dune.shelf.as_deref(), Some("B2"));
+}
+
+#[test]
+fn test_read_xlsx_rows() {
+    let path = std::env::temp_dir().join("librius_test_import.xlsx");
+    let mut workbook = umya_spreadsheet::new_file();
+    let sheet = workbook.get_sheet_by_name_mut("Sheet1").unwrap();
+    sheet.get_cell_mut("A1").set_value("Titolo");
+    sheet.get_cell_mut("B1").set_value("Anno");
+    sheet.get_cell_mut("A2").set_value("Dune");
+    sheet.get_cell_mut("B2").set_value_number(1965);
+    umya_spreadsheet::writer::xlsx::write(&workbook, &path).unwrap();
+
+    let rows = read_xlsx_rows(&path).unwrap();
+    assert_eq!(rows, vec![vec!["Titolo", "Anno"], vec!["Dune", "1965"]]);
+
+    let options = MappingOptions {
+        infer: true,
+        ..Default::default()
+    };
+    let map = ColumnMap::resolve(&rows[0], &options).unwrap();
+    let dune = map.map_row(&rows[0], &rows[1]).unwrap();
+    assert_eq!(dune.year, 1965);
+
+    let _ = std::fs::remove_file(&path);
+}
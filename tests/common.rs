@@ -40,7 +40,23 @@ pub fn setup_temp_db(name: &str) -> Connection {
             shelf TEXT,
             row TEXT,
             position TEXT,
-            added_at TEXT
+            added_at TEXT,
+            deleted_at TEXT,
+            rating INTEGER,
+            read_status TEXT,
+            read_at TEXT,
+            tags TEXT,
+            review TEXT,
+            format TEXT
+        );
+        CREATE TABLE IF NOT EXISTS book_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            book_id INTEGER NOT NULL,
+            operation TEXT NOT NULL,
+            before_json TEXT,
+            after_json TEXT,
+            created_at TEXT NOT NULL,
+            undone_at TEXT
         );
         "#,
     )
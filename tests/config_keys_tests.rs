@@ -0,0 +1,113 @@
+use librius::config::ConfigError;
+use librius::config::keys::{find_key, get_value, parse_config, set_value, unset_value};
+use serde_yaml::Value;
+
+const CONF: &str = "\
+# Librius configuration
+database: /data/librius.sqlite
+language: en   # interface language
+backup:
+  keep_last: 5     # most recent backups
+  format: sqlite
+";
+
+#[test]
+fn test_set_keeps_comments_and_order() {
+    let updated = set_value(CONF, "backup.keep_last", "3").unwrap();
+    assert_eq!(
+        updated,
+        CONF.replace("keep_last: 5     #", "keep_last: 3     #")
+    );
+
+    let updated = set_value(&updated, "language", "it").unwrap();
+    assert!(updated.contains("language: it   # interface language"));
+    assert!(updated.starts_with("# Librius configuration\n"));
+}
+
+#[test]
+fn test_set_adds_missing_keys_inside_their_section() {
+    let updated = set_value(CONF, "backup.keep_weekly", "2").unwrap();
+    assert!(updated.contains("  format: sqlite\n  keep_weekly: 2\n"));
+    assert_eq!(parse_config(&updated).unwrap().backup.keep_weekly, 2);
+}
+
+#[test]
+fn test_set_validates_values_against_schema() {
+    assert!(matches!(
+        set_value(CONF, "language", "xx"),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        set_value(CONF, "backup.keep_last", "-1"),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        set_value(CONF, "backup.format", "rar"),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+    // active_library deve indicare una libreria esistente
+    assert!(matches!(
+        set_value(CONF, "active_library", "office"),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+    assert_eq!(
+        set_value(CONF, "backup.nope", "1"),
+        Err(ConfigError::UnknownKey("backup.nope".to_string()))
+    );
+}
+
+#[test]
+fn test_set_rejects_changes_that_break_the_config() {
+    // Un profilo senza `database` non è valido
+    assert!(matches!(
+        set_value(CONF, "libraries.office.language", "it"),
+        Err(ConfigError::Invalid(_))
+    ));
+
+    let with_library = set_value(CONF, "libraries.office.database", "/data/office.sqlite").unwrap();
+    let updated = set_value(&with_library, "libraries.office.language", "it").unwrap();
+    let cfg = parse_config(&updated).unwrap();
+    assert_eq!(cfg.libraries["office"].language.as_deref(), Some("it"));
+    assert!(set_value(&updated, "active_library", "office").is_ok());
+}
+
+#[test]
+fn test_unset_removes_optional_keys_and_empty_sections() {
+    let updated = unset_value(CONF, "backup.keep_last").unwrap();
+    assert!(!updated.contains("keep_last"));
+    assert!(updated.contains("language: en   # interface language"));
+    // Torna a valere il predefinito
+    assert_eq!(parse_config(&updated).unwrap().backup.keep_last, 5);
+
+    let updated = unset_value(&updated, "backup.format").unwrap();
+    assert!(!updated.contains("backup"));
+    assert!(parse_config(&updated).is_ok());
+
+    assert_eq!(
+        unset_value(CONF, "database"),
+        Err(ConfigError::RequiredKey("database".to_string()))
+    );
+}
+
+#[test]
+fn test_get_applies_defaults_and_accepts_sections() {
+    let cfg = parse_config(CONF).unwrap();
+    assert_eq!(
+        get_value(&cfg, "backup.keep_daily").unwrap(),
+        Some(Value::Number(7.into()))
+    );
+    assert!(get_value(&cfg, "backup").unwrap().unwrap().is_mapping());
+    assert_eq!(get_value(&cfg, "active_library").unwrap(), None);
+    assert!(get_value(&cfg, "unknown").is_err());
+    assert!(find_key("libraries.home.backup.format").is_some());
+    assert!(find_key("libraries..database").is_none());
+}
+
+#[test]
+fn test_unsupported_layout_falls_back_to_rewrite() {
+    let flow = "{database: /data/librius.sqlite, language: en}\n";
+    let updated = set_value(flow, "language", "it").unwrap();
+    let cfg = parse_config(&updated).unwrap();
+    assert_eq!(cfg.language, "it");
+    assert_eq!(cfg.database, "/data/librius.sqlite");
+}
@@ -0,0 +1,191 @@
+use librius::config::keys::parse_config;
+use librius::config::migrate_config::{
+    backup_path, migrate_text, move_key, rename_key, transform_key,
+};
+use librius::config::{
+    BackupFormat, ConfigMigrationError, ConfigMigrationResult, latest_config_version,
+    migrate_config_file,
+};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::PathBuf;
+
+/// Config files written by older Librius releases (or by hand).
+fn fixtures() -> Vec<(String, String)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/config");
+    let mut files: Vec<(String, String)> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "conf"))
+        .map(|p| {
+            let name = p.file_name().unwrap().to_string_lossy().to_string();
+            (name, fs::read_to_string(&p).unwrap())
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn fixture(name: &str) -> String {
+    fixtures()
+        .into_iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, text)| text)
+        .unwrap()
+}
+
+#[test]
+fn test_historical_config_files_migrate_cleanly() {
+    let files = fixtures();
+    assert!(files.len() >= 5);
+
+    for (name, text) in files {
+        let migrated = migrate_text(&text)
+            .unwrap_or_else(|e| panic!("{}: {:?}", name, e))
+            .unwrap_or_else(|| panic!("{}: nothing migrated", name));
+        assert_eq!(migrated.from, 0, "{}", name);
+
+        let cfg = parse_config(&migrated.text).unwrap_or_else(|e| panic!("{}: {:?}", name, e));
+        assert_eq!(cfg.config_version, latest_config_version(), "{}", name);
+        assert!(!cfg.language.is_empty(), "{}", name);
+
+        // Una seconda esecuzione non cambia nulla
+        assert!(migrate_text(&migrated.text).unwrap().is_none(), "{}", name);
+    }
+}
+
+#[test]
+fn test_migration_keeps_comments_and_existing_values() {
+    let migrated = migrate_text(&fixture("v0.6.0.conf")).unwrap().unwrap();
+    assert!(migrated.text.starts_with("# Librius configuration\n"));
+    assert!(
+        migrated
+            .text
+            .contains("language: it   # interface language")
+    );
+
+    let cfg = parse_config(&migrated.text).unwrap();
+    assert_eq!(cfg.database, "/home/reader/.librius/librius.sqlite");
+    assert_eq!(cfg.backup.keep_last, 5);
+    assert_eq!(cfg.backup.format, BackupFormat::Sqlite);
+
+    let migrated = migrate_text(&fixture("unversioned_backup_retention.conf"))
+        .unwrap()
+        .unwrap();
+    assert!(
+        migrated
+            .text
+            .contains("keep_weekly: 0   # no weekly backups")
+    );
+    let cfg = parse_config(&migrated.text).unwrap();
+    assert_eq!(cfg.backup.keep_last, 3);
+    assert_eq!(cfg.backup.keep_weekly, 0);
+
+    let migrated = migrate_text(&fixture("unversioned_libraries.conf"))
+        .unwrap()
+        .unwrap();
+    let cfg = parse_config(&migrated.text).unwrap();
+    assert_eq!(cfg.active_library.as_deref(), Some("office"));
+    assert_eq!(cfg.libraries["office"].language.as_deref(), Some("it"));
+}
+
+#[test]
+fn test_migration_normalizes_hand_edited_values() {
+    let migrated = migrate_text(&fixture("hand_edited.conf")).unwrap().unwrap();
+    let cfg = parse_config(&migrated.text).unwrap();
+    assert_eq!(cfg.language, "it-IT");
+    assert_eq!(cfg.backup.format, BackupFormat::TarGz);
+    assert_eq!(cfg.backup.keep_last, 10);
+    assert_eq!(cfg.database, "/home/reader/My Books/librius.sqlite");
+    assert!(migrated.text.contains("# written by hand"));
+}
+
+#[test]
+fn test_migration_of_empty_file_and_invalid_yaml() {
+    let migrated = migrate_text("").unwrap().unwrap();
+    let root: Value = serde_yaml::from_str(&migrated.text).unwrap();
+    assert_eq!(root["language"], Value::String("en".to_string()));
+    assert!(root["backup"].is_mapping());
+
+    assert!(matches!(
+        migrate_text("database: [unclosed\n"),
+        Err(ConfigMigrationError::Parse(_))
+    ));
+    assert!(matches!(
+        migrate_text("just a string\n"),
+        Err(ConfigMigrationError::NotAMapping)
+    ));
+}
+
+#[test]
+fn test_newer_config_version_is_left_untouched() {
+    let text = format!(
+        "config_version: {}\ndatabase: /data/librius.sqlite\nlanguage: en\n",
+        latest_config_version() + 1
+    );
+    assert!(matches!(
+        migrate_text(&text),
+        Err(ConfigMigrationError::TooNew { .. })
+    ));
+
+    let current = format!(
+        "config_version: {}\ndatabase: /data/librius.sqlite\nlanguage: en\n",
+        latest_config_version()
+    );
+    assert!(migrate_text(&current).unwrap().is_none());
+}
+
+#[test]
+fn test_migrate_config_file_keeps_a_backup() {
+    let path = std::env::temp_dir().join("librius_test_migrate.conf");
+    let original = fixture("v0.2.0.conf");
+    fs::write(&path, &original).unwrap();
+
+    match migrate_config_file(&path).unwrap() {
+        ConfigMigrationResult::Applied {
+            from,
+            to,
+            applied,
+            backup,
+        } => {
+            assert_eq!(from, 0);
+            assert_eq!(to, latest_config_version());
+            assert_eq!(applied.len(), to as usize);
+            assert_eq!(backup, backup_path(&path));
+            assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+        }
+        ConfigMigrationResult::UpToDate => panic!("expected a migration"),
+    }
+    assert!(matches!(
+        migrate_config_file(&path).unwrap(),
+        ConfigMigrationResult::UpToDate
+    ));
+
+    let _ = fs::remove_file(backup_path(&path));
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_key_operations_for_migration_steps() {
+    let mut map: Mapping =
+        serde_yaml::from_str("db_path: /data/a.sqlite\nkeep: 3\nold:\n  format: zip\n").unwrap();
+
+    assert!(rename_key(&mut map, "db_path", "database"));
+    assert!(!rename_key(&mut map, "missing", "other"));
+    assert_eq!(map["database"], Value::String("/data/a.sqlite".to_string()));
+
+    assert!(move_key(&mut map, "keep", "backup.keep_last"));
+    assert!(move_key(&mut map, "old.format", "backup.format"));
+    // La sezione rimasta vuota viene rimossa
+    assert!(!map.contains_key("old"));
+    assert_eq!(map["backup"]["keep_last"], Value::Number(3.into()));
+    // Un valore già presente nella destinazione non viene sovrascritto
+    map.insert("format".into(), "tar.gz".into());
+    assert!(!move_key(&mut map, "format", "backup.format"));
+
+    assert!(transform_key(&mut map, "backup.keep_last", |v| {
+        Value::Number((v.as_u64().unwrap() * 2).into())
+    }));
+    assert_eq!(map["backup"]["keep_last"], Value::Number(6.into()));
+    assert!(!transform_key(&mut map, "backup.missing", |v| v.clone()));
+}
@@ -0,0 +1,160 @@
+mod common;
+use common::{setup_temp_db, temp_db_path};
+use librius::AppConfig;
+use librius::commands::handle_backup_restore;
+use librius::db::{current_version, latest_version, run_migrations};
+use librius::utils::crypto::{
+    CryptoError, KdfParams, decrypt_bytes, encrypt_bytes_with, is_encrypted, read_kdf_params,
+};
+use std::fs;
+
+// Cheap parameters: the tests check the format, not the KDF strength.
+const FAST: KdfParams = KdfParams {
+    m_cost: 64,
+    t_cost: 1,
+    p_cost: 1,
+};
+
+#[test]
+fn test_encrypt_decrypt_roundtrip() {
+    let data = b"SQLite format 3\0 some database bytes";
+    let enc = encrypt_bytes_with(data, "correct horse", &FAST).unwrap();
+
+    assert!(is_encrypted(&enc));
+    assert_ne!(&enc[..], &data[..]);
+    assert_eq!(read_kdf_params(&enc).unwrap(), FAST);
+    assert_eq!(decrypt_bytes(&enc, "correct horse").unwrap(), data);
+}
+
+#[test]
+fn test_wrong_passphrase_is_reported() {
+    let enc = encrypt_bytes_with(b"secret", "right", &FAST).unwrap();
+    assert!(matches!(
+        decrypt_bytes(&enc, "wrong"),
+        Err(CryptoError::WrongPassphrase)
+    ));
+}
+
+#[test]
+fn test_tampered_header_is_rejected() {
+    let mut enc = encrypt_bytes_with(b"secret", "right", &FAST).unwrap();
+    // Flip a byte of the salt: authentication must fail
+    enc[25] ^= 0xff;
+    assert!(decrypt_bytes(&enc, "right").is_err());
+}
+
+#[test]
+fn test_excessive_kdf_costs_are_rejected_before_derivation() {
+    let enc = encrypt_bytes_with(b"secret", "right", &FAST).unwrap();
+    // m_cost, t_cost and p_cost start at bytes 10, 14 and 18
+    for offset in [13, 17, 21] {
+        let mut tampered = enc.clone();
+        tampered[offset] = 0xff;
+        assert!(matches!(
+            read_kdf_params(&tampered),
+            Err(CryptoError::InvalidHeader)
+        ));
+        assert!(matches!(
+            decrypt_bytes(&tampered, "right"),
+            Err(CryptoError::InvalidHeader)
+        ));
+    }
+
+    // The ceiling itself is still accepted
+    let mut max = enc.clone();
+    max[10..14].copy_from_slice(&KdfParams::MAX.m_cost.to_le_bytes());
+    max[14..18].copy_from_slice(&KdfParams::MAX.t_cost.to_le_bytes());
+    max[18..22].copy_from_slice(&KdfParams::MAX.p_cost.to_le_bytes());
+    assert_eq!(read_kdf_params(&max).unwrap(), KdfParams::MAX);
+}
+
+#[test]
+fn test_plain_data_is_not_encrypted() {
+    assert!(!is_encrypted(b"SQLite format 3\0"));
+    assert!(matches!(
+        read_kdf_params(b"SQLite format 3\0"),
+        Err(CryptoError::InvalidHeader)
+    ));
+}
+
+#[test]
+fn test_restore_encrypted_backup() {
+    // Source database with one book, encrypted as a backup archive
+    let src = setup_temp_db("restore_src");
+    src.execute(
+        "INSERT INTO books (title, author, editor, year, isbn) VALUES ('Dune', 'Frank Herbert', 'Chilton', 1965, '9780441013593')",
+        [],
+    )
+    .unwrap();
+    drop(src);
+
+    let archive = temp_db_path("restore_archive").with_extension("sqlite.enc");
+    let plain = fs::read(temp_db_path("restore_src")).unwrap();
+    fs::write(&archive, encrypt_bytes_with(&plain, "pw", &FAST).unwrap()).unwrap();
+
+    // Empty target database, kept open during the restore as `main` does
+    let target = temp_db_path("restore_target");
+    let mut conn = setup_temp_db("restore_target");
+
+    let config = AppConfig {
+        database: target.display().to_string(),
+        ..Default::default()
+    };
+
+    // SAFETY: only this test reads or writes the variable.
+    unsafe { std::env::set_var("LIBRIUS_BACKUP_PASSPHRASE", "pw") };
+    handle_backup_restore(&mut conn, &config, &archive.display().to_string(), true).unwrap();
+
+    // The open connection sees the restored data
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM books", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 1);
+    assert!(!target.with_extension("restore.tmp").exists());
+    drop(conn);
+
+    let reopened = rusqlite::Connection::open(&target).unwrap();
+    let count: i64 = reopened
+        .query_row("SELECT COUNT(*) FROM books", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let _ = fs::remove_file(archive);
+}
+
+#[test]
+fn test_restore_refuses_newer_schema() {
+    // Backup taken by a newer Librius, with a migration this binary does not know
+    let src = setup_temp_db("restore_newer_src");
+    run_migrations(&src).unwrap();
+    src.execute(
+        "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, 'future', '2099-01-01T00:00:00Z')",
+        [latest_version() + 1],
+    )
+    .unwrap();
+    drop(src);
+    let backup = temp_db_path("restore_newer_src");
+
+    let target = temp_db_path("restore_newer_target");
+    let mut conn = setup_temp_db("restore_newer_target");
+    run_migrations(&conn).unwrap();
+    conn.execute(
+        "INSERT INTO books (title, author, editor, year, isbn) VALUES ('Dune', 'Frank Herbert', 'Chilton', 1965, '9780441013593')",
+        [],
+    )
+    .unwrap();
+
+    let config = AppConfig {
+        database: target.display().to_string(),
+        ..Default::default()
+    };
+    handle_backup_restore(&mut conn, &config, &backup.display().to_string(), true).unwrap();
+
+    // The live database is untouched and the temporary copy is gone
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM books", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(current_version(&conn).unwrap(), latest_version());
+    assert!(!target.with_extension("restore.tmp").exists());
+}
@@ -0,0 +1,215 @@
+mod common;
+use chrono::{NaiveDate, TimeZone, Utc};
+use common::setup_temp_db;
+use librius::commands::{
+    handle_export_csv, handle_export_json, handle_export_ods, handle_export_xlsx,
+    handle_import_csv, handle_import_json, handle_import_ods, handle_import_xlsx,
+};
+use librius::db::{find_book, insert_book};
+use librius::models::Book;
+use librius::utils::{ImportOptions, MappingOptions, read_ods_rows, read_xlsx_rows};
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cartella temporanea dedicata a un test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("librius_test_roundtrip_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn output(path: &Path) -> Option<String> {
+    Some(path.to_str().unwrap().to_string())
+}
+
+/// Un libro con ogni campo valorizzato (testo con separatori, virgolette e
+/// a capo compresi) e uno con i soli campi obbligatori.
+fn catalog(name: &str) -> Connection {
+    let conn = setup_temp_db(name);
+    let dune = Book {
+        title: "Dune".to_string(),
+        author: "Frank Herbert".to_string(),
+        editor: "Chilton; \"prima edizione\"".to_string(),
+        year: 1965,
+        isbn: "9780441013593".to_string(),
+        language: Some("English".to_string()),
+        pages: Some(412),
+        genre: Some("Science fiction".to_string()),
+        summary: Some("Spice, sand\nand worms.".to_string()),
+        room: Some("Studio".to_string()),
+        shelf: Some("B2".to_string()),
+        row: Some("3".to_string()),
+        position: Some("12".to_string()),
+        added_at: Some(Utc.with_ymd_and_hms(2021, 3, 4, 10, 20, 30).unwrap()),
+        rating: Some(5),
+        read_status: Some("read".to_string()),
+        read_at: NaiveDate::from_ymd_opt(2022, 1, 9),
+        tags: Some("classics, sci-fi".to_string()),
+        review: Some("A masterpiece.".to_string()),
+        format: Some("ebook".to_string()),
+        ..Default::default()
+    };
+    let hobbit = Book {
+        title: "The Hobbit".to_string(),
+        author: "J. R. R. Tolkien".to_string(),
+        editor: "Mariner".to_string(),
+        year: 2012,
+        isbn: "9780547928227".to_string(),
+        ..Default::default()
+    };
+    insert_book(&conn, &dune).unwrap();
+    insert_book(&conn, &hobbit).unwrap();
+    conn
+}
+
+fn books(conn: &Connection) -> Vec<Book> {
+    ["9780441013593", "9780547928227"]
+        .iter()
+        .map(|isbn| find_book(conn, isbn, true).unwrap().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_csv_round_trip_is_lossless() {
+    let dir = temp_dir("csv");
+    let source = catalog("roundtrip_csv_source");
+    handle_export_csv(&source, output(&dir.join("first.csv"))).unwrap();
+
+    let mut copy = setup_temp_db("roundtrip_csv_copy");
+    handle_import_csv(
+        &mut copy,
+        dir.join("first.csv").to_str().unwrap(),
+        ';',
+        &MappingOptions::default(),
+        &ImportOptions::default(),
+    )
+    .unwrap();
+    handle_export_csv(&copy, output(&dir.join("second.csv"))).unwrap();
+
+    assert_eq!(books(&copy), books(&source));
+    assert_eq!(
+        fs::read_to_string(dir.join("first.csv")).unwrap(),
+        fs::read_to_string(dir.join("second.csv")).unwrap()
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_json_round_trip_is_lossless() {
+    let dir = temp_dir("json");
+    let source = catalog("roundtrip_json_source");
+    handle_export_json(&source, output(&dir.join("first.json"))).unwrap();
+
+    let mut copy = setup_temp_db("roundtrip_json_copy");
+    handle_import_json(
+        &mut copy,
+        dir.join("first.json").to_str().unwrap(),
+        &ImportOptions::default(),
+    )
+    .unwrap();
+    handle_export_json(&copy, output(&dir.join("second.json"))).unwrap();
+
+    let dune = &books(&copy)[0];
+    assert_eq!(dune.shelf.as_deref(), Some("B2"));
+    assert_eq!(
+        dune.added_at,
+        Some(Utc.with_ymd_and_hms(2021, 3, 4, 10, 20, 30).unwrap())
+    );
+    assert_eq!(books(&copy), books(&source));
+    assert_eq!(
+        fs::read_to_string(dir.join("first.json")).unwrap(),
+        fs::read_to_string(dir.join("second.json")).unwrap()
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_xlsx_round_trip_is_lossless() {
+    let dir = temp_dir("xlsx");
+    let source = catalog("roundtrip_xlsx_source");
+    handle_export_xlsx(&source, output(&dir.join("first.xlsx"))).unwrap();
+
+    let mut copy = setup_temp_db("roundtrip_xlsx_copy");
+    handle_import_xlsx(
+        &mut copy,
+        dir.join("first.xlsx").to_str().unwrap(),
+        &MappingOptions::default(),
+        &ImportOptions::default(),
+    )
+    .unwrap();
+    handle_export_xlsx(&copy, output(&dir.join("second.xlsx"))).unwrap();
+
+    assert_eq!(books(&copy), books(&source));
+    let first = read_xlsx_rows(&dir.join("first.xlsx")).unwrap();
+    assert_eq!(first[1][5], "9780441013593");
+    assert_eq!(first, read_xlsx_rows(&dir.join("second.xlsx")).unwrap());
+
+    // intestazioni in grassetto, come nell'ODS
+    let workbook = umya_spreadsheet::reader::xlsx::read(dir.join("first.xlsx")).unwrap();
+    let header = workbook.get_sheet(&0).unwrap().get_cell((1, 1)).unwrap();
+    assert!(*header.get_style().get_font().unwrap().get_bold());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_ods_round_trip_is_lossless() {
+    let dir = temp_dir("ods");
+    let source = catalog("roundtrip_ods_source");
+    handle_export_ods(&source, output(&dir.join("first.ods"))).unwrap();
+
+    let mut copy = setup_temp_db("roundtrip_ods_copy");
+    handle_import_ods(
+        &mut copy,
+        dir.join("first.ods").to_str().unwrap(),
+        &MappingOptions::default(),
+        &ImportOptions::default(),
+    )
+    .unwrap();
+    handle_export_ods(&copy, output(&dir.join("second.ods"))).unwrap();
+
+    assert_eq!(books(&copy), books(&source));
+    let first = read_ods_rows(&dir.join("first.ods")).unwrap();
+    assert_eq!(first[0].len(), 21);
+    assert_eq!(first[1][5], "9780441013593");
+    // date lette dal valore tipizzato
+    assert_eq!(first[1][14], "2021-03-04 10:20:30");
+    assert_eq!(first[1][17], "2022-01-09");
+    assert_eq!(first, read_ods_rows(&dir.join("second.ods")).unwrap());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_import_without_added_at_uses_now() {
+    let dir = temp_dir("added_at");
+    let path = dir.join("libri.csv");
+    fs::write(
+        &path,
+        "title,author,editor,year,isbn,room,shelf,row,position\n\
+         Dune,Frank Herbert,Chilton,1965,9780441013593,Studio,B2,3,12\n",
+    )
+    .unwrap();
+
+    let mut conn = setup_temp_db("roundtrip_added_at");
+    let before = Utc::now() - chrono::Duration::seconds(1);
+    handle_import_csv(
+        &mut conn,
+        path.to_str().unwrap(),
+        ',',
+        &MappingOptions::default(),
+        &ImportOptions::default(),
+    )
+    .unwrap();
+
+    let dune = find_book(&conn, "9780441013593", true).unwrap().unwrap();
+    assert_eq!(dune.room.as_deref(), Some("Studio"));
+    assert_eq!(dune.position.as_deref(), Some("12"));
+    assert!(dune.added_at.unwrap() >= before);
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,72 @@
+-- Sottoinsieme dello schema di `metadata.db` di Calibre, con quattro libri:
+-- 1. Dune: identificatore ISBN, serie, tag, commento HTML, 5 stelle
+-- 2. Good Omens: due autori, ISBN solo nella colonna storica, data ignota
+-- 3. Notes: nessun ISBN, autore `Unknown` come in Calibre
+-- 4. The Hobbit: ISBN già presente nel catalogo nei test di deduplica
+CREATE TABLE books (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL DEFAULT 'Unknown' COLLATE NOCASE,
+    sort TEXT COLLATE NOCASE,
+    timestamp TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    pubdate TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    series_index REAL NOT NULL DEFAULT 1.0,
+    author_sort TEXT COLLATE NOCASE,
+    isbn TEXT DEFAULT '' COLLATE NOCASE,
+    lccn TEXT DEFAULT '' COLLATE NOCASE,
+    path TEXT NOT NULL DEFAULT '',
+    flags INTEGER NOT NULL DEFAULT 1,
+    uuid TEXT,
+    has_cover BOOL DEFAULT 0,
+    last_modified TIMESTAMP NOT NULL DEFAULT '2000-01-01 00:00:00+00:00'
+);
+CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT NOT NULL COLLATE NOCASE, sort TEXT COLLATE NOCASE, link TEXT NOT NULL DEFAULT '');
+CREATE TABLE books_authors_link (id INTEGER PRIMARY KEY, book INTEGER NOT NULL, author INTEGER NOT NULL, UNIQUE(book, author));
+CREATE TABLE publishers (id INTEGER PRIMARY KEY, name TEXT NOT NULL COLLATE NOCASE, sort TEXT COLLATE NOCASE);
+CREATE TABLE books_publishers_link (id INTEGER PRIMARY KEY, book INTEGER NOT NULL, publisher INTEGER NOT NULL, UNIQUE(book));
+CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT NOT NULL COLLATE NOCASE);
+CREATE TABLE books_tags_link (id INTEGER PRIMARY KEY, book INTEGER NOT NULL, tag INTEGER NOT NULL, UNIQUE(book, tag));
+CREATE TABLE series (id INTEGER PRIMARY KEY, name TEXT NOT NULL COLLATE NOCASE, sort TEXT COLLATE NOCASE);
+CREATE TABLE books_series_link (id INTEGER PRIMARY KEY, book INTEGER NOT NULL, series INTEGER NOT NULL, UNIQUE(book));
+CREATE TABLE identifiers (id INTEGER PRIMARY KEY, book INTEGER NOT NULL, type TEXT NOT NULL DEFAULT 'isbn' COLLATE NOCASE, val TEXT NOT NULL COLLATE NOCASE, UNIQUE(book, type));
+CREATE TABLE languages (id INTEGER PRIMARY KEY, lang_code TEXT NOT NULL COLLATE NOCASE);
+CREATE TABLE books_languages_link (id INTEGER PRIMARY KEY, book INTEGER NOT NULL, lang_code INTEGER NOT NULL, item_order INTEGER NOT NULL DEFAULT 0, UNIQUE(book, lang_code));
+CREATE TABLE comments (id INTEGER PRIMARY KEY, book INTEGER NOT NULL, text TEXT NOT NULL COLLATE NOCASE, UNIQUE(book));
+CREATE TABLE ratings (id INTEGER PRIMARY KEY, rating INTEGER CHECK(rating > -1 AND rating < 11), UNIQUE (rating));
+CREATE TABLE books_ratings_link (id INTEGER PRIMARY KEY, book INTEGER NOT NULL, rating INTEGER NOT NULL, UNIQUE(book, rating));
+
+INSERT INTO books (id, title, sort, timestamp, pubdate, author_sort, isbn, path) VALUES
+    (1, 'Dune', 'Dune', '2023-01-02 10:20:30.123456+00:00', '2005-08-02 00:00:00+00:00', 'Herbert, Frank', '', 'Frank Herbert/Dune (1)'),
+    (2, 'Good Omens', 'Good Omens', '2022-11-20 08:00:00+00:00', '0101-01-01 00:00:00+00:00', 'Pratchett, Terry', '0-06-085398-0', 'Terry Pratchett/Good Omens (2)'),
+    (3, 'Notes', 'Notes', '2024-03-01 12:00:00+00:00', '2024-03-01 00:00:00+00:00', 'Unknown', '', 'Unknown/Notes (3)'),
+    (4, 'The Hobbit', 'Hobbit, The', '2021-06-10 09:30:00+00:00', '2012-09-18 00:00:00+00:00', 'Tolkien, J. R. R.', '', 'J. R. R. Tolkien/The Hobbit (4)');
+
+INSERT INTO authors (id, name, sort) VALUES
+    (1, 'Frank Herbert', 'Herbert, Frank'),
+    (2, 'Neil Gaiman', 'Gaiman, Neil'),
+    (3, 'Terry Pratchett', 'Pratchett, Terry'),
+    (4, 'J. R. R. Tolkien', 'Tolkien, J. R. R.'),
+    (5, 'Unknown', 'Unknown');
+INSERT INTO books_authors_link (id, book, author) VALUES (1, 1, 1), (2, 2, 3), (3, 2, 2), (4, 4, 4), (5, 3, 5);
+
+INSERT INTO publishers (id, name) VALUES (1, 'Ace'), (2, 'HarperTorch');
+INSERT INTO books_publishers_link (id, book, publisher) VALUES (1, 1, 1), (2, 2, 2);
+
+INSERT INTO tags (id, name) VALUES (1, 'sci-fi'), (2, 'classics'), (3, 'humour');
+INSERT INTO books_tags_link (id, book, tag) VALUES (1, 1, 1), (2, 1, 2), (3, 2, 3);
+
+INSERT INTO series (id, name) VALUES (1, 'Dune Chronicles');
+INSERT INTO books_series_link (id, book, series) VALUES (1, 1, 1);
+
+INSERT INTO identifiers (id, book, type, val) VALUES
+    (1, 1, 'isbn', '9780441013593'),
+    (2, 1, 'goodreads', '234225'),
+    (3, 4, 'isbn', '978-0-547-92822-7');
+
+INSERT INTO languages (id, lang_code) VALUES (1, 'eng'), (2, 'ita');
+INSERT INTO books_languages_link (id, book, lang_code, item_order) VALUES (1, 1, 1, 0), (2, 2, 2, 0), (3, 4, 1, 0);
+
+INSERT INTO comments (id, book, text) VALUES
+    (1, 1, '<div><p>Spice &amp; sand.</p><p>Set on <i>Arrakis</i>.</p></div>');
+
+INSERT INTO ratings (id, rating) VALUES (1, 10), (2, 7);
+INSERT INTO books_ratings_link (id, book, rating) VALUES (1, 1, 1), (2, 2, 2);
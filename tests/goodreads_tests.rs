@@ -0,0 +1,98 @@
+mod common;
+use chrono::NaiveDate;
+use common::setup_temp_db;
+use librius::db::{find_book, insert_book};
+use librius::utils::{RejectReason, read_goodreads};
+use std::fs::File;
+
+fn fixture() -> File {
+    File::open(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/goodreads/goodreads_library_export.csv"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_goodreads_rows_map_onto_books() {
+    let import = read_goodreads(fixture()).unwrap();
+    assert_eq!(import.books.len(), 4);
+
+    let dune = &import.books[0].1;
+    assert_eq!(dune.title, "Dune");
+    assert_eq!(dune.author, "Frank Herbert, Brian Herbert");
+    assert_eq!(dune.editor, "Ace");
+    assert_eq!(dune.isbn, "9780441013593");
+    assert_eq!(dune.year, 2005);
+    assert_eq!(dune.pages, Some(688));
+    assert_eq!(dune.rating, Some(5));
+    assert_eq!(dune.read_status.as_deref(), Some("read"));
+    assert_eq!(dune.read_at, NaiveDate::from_ymd_opt(2023, 5, 14));
+    assert_eq!(dune.tags.as_deref(), Some("favorites, sci-fi"));
+    assert_eq!(
+        dune.review.as_deref(),
+        Some("Spice must flow.\nStill the best.")
+    );
+    assert_eq!(
+        dune.added_at.unwrap().date_naive(),
+        NaiveDate::from_ymd_opt(2023, 1, 2).unwrap()
+    );
+
+    // Solo ISBN-10, nessuna valutazione (0), scaffale esclusivo standard
+    let hobbit = &import.books[1].1;
+    assert_eq!(hobbit.isbn, "0547928211");
+    assert_eq!(hobbit.rating, None);
+    assert_eq!(hobbit.read_status.as_deref(), Some("reading"));
+    assert_eq!(hobbit.tags, None);
+
+    // Anno originale in mancanza dell'anno di edizione; scaffale esclusivo
+    // personalizzato come tag
+    let emma = &import.books[2].1;
+    assert_eq!(emma.year, 1815);
+    assert_eq!(emma.read_status, None);
+    assert_eq!(emma.tags.as_deref(), Some("did-not-finish, classics"));
+}
+
+#[test]
+fn test_goodreads_rows_without_isbn_are_kept() {
+    // Goodreads lascia spesso vuoti ISBN e ISBN13 (="")
+    let import = read_goodreads(fixture()).unwrap();
+    let (line, kindle) = &import.books[3];
+    assert_eq!(*line, 5);
+    assert_eq!(kindle.title, "A Kindle Only Book");
+    assert_eq!(kindle.isbn, "");
+}
+
+#[test]
+fn test_goodreads_reports_unmapped_rows() {
+    let import = read_goodreads(fixture()).unwrap();
+    assert_eq!(import.rejected.len(), 1);
+
+    assert_eq!(import.rejected[0].line, 6);
+    assert_eq!(
+        import.rejected[0].reason,
+        RejectReason::InvalidIsbn("9780441013590".to_string())
+    );
+}
+
+#[test]
+fn test_goodreads_rejects_other_csv_files() {
+    let csv = "title,author,editor,year,isbn\nDune,Frank Herbert,Ace,1965,9780441013593\n";
+    assert!(read_goodreads(csv.as_bytes()).is_err());
+}
+
+#[test]
+fn test_imported_reading_data_is_stored() {
+    let conn = setup_temp_db("goodreads_import");
+    let import = read_goodreads(fixture()).unwrap();
+    for (_, book) in &import.books {
+        insert_book(&conn, book).unwrap();
+    }
+
+    let dune = find_book(&conn, "9780441013593", true).unwrap().unwrap();
+    assert_eq!(dune.rating, Some(5));
+    assert_eq!(dune.read_status.as_deref(), Some("read"));
+    assert_eq!(dune.read_at, NaiveDate::from_ymd_opt(2023, 5, 14));
+    assert_eq!(dune.tags.as_deref(), Some("favorites, sci-fi"));
+    assert_eq!(dune.added_at, import.books[0].1.added_at);
+}
@@ -0,0 +1,136 @@
+mod common;
+use common::setup_temp_db;
+use librius::commands::handle_undo;
+use librius::db::find_book_by_id;
+use librius::db::history::{get_history, last_undoable, record_history, record_insert, undo_entry};
+use librius::models::HistoryOperation;
+use rusqlite::Connection;
+
+fn insert_dune(conn: &Connection) -> i64 {
+    conn.execute(
+        "INSERT INTO books (title, author, editor, year, isbn) VALUES ('Dune', 'Frank Herbert', 'Chilton', 1965, '9780441013593')",
+        [],
+    )
+    .unwrap();
+    let id = conn.last_insert_rowid();
+    record_insert(conn, id).unwrap();
+    id
+}
+
+#[test]
+fn test_history_records_insert_update_delete() {
+    let conn = setup_temp_db("history_timeline");
+    let id = insert_dune(&conn);
+
+    let before = find_book_by_id(&conn, id).unwrap().unwrap();
+    conn.execute("UPDATE books SET year = 1966 WHERE id = ?1", [id])
+        .unwrap();
+    let after = find_book_by_id(&conn, id).unwrap().unwrap();
+    record_history(
+        &conn,
+        HistoryOperation::Update,
+        id,
+        Some(&before),
+        Some(&after),
+    )
+    .unwrap();
+
+    conn.execute("DELETE FROM books WHERE id = ?1", [id])
+        .unwrap();
+    record_history(&conn, HistoryOperation::Delete, id, Some(&after), None).unwrap();
+
+    let history = get_history(&conn, id).unwrap();
+    let ops: Vec<HistoryOperation> = history.iter().map(|h| h.operation).collect();
+    assert_eq!(
+        ops,
+        vec![
+            HistoryOperation::Insert,
+            HistoryOperation::Update,
+            HistoryOperation::Delete
+        ]
+    );
+
+    let changes = history[1].changed_fields();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0], ("year".into(), "1965".into(), "1966".into()));
+}
+
+#[test]
+fn test_undo_delete_restores_original_id() {
+    let conn = setup_temp_db("history_undo_delete");
+    let id = insert_dune(&conn);
+    let book = find_book_by_id(&conn, id).unwrap().unwrap();
+
+    conn.execute("DELETE FROM books WHERE id = ?1", [id])
+        .unwrap();
+    record_history(&conn, HistoryOperation::Delete, id, Some(&book), None).unwrap();
+
+    let entries = last_undoable(&conn, 1).unwrap();
+    undo_entry(&conn, &entries[0]).unwrap();
+
+    let restored = find_book_by_id(&conn, id).unwrap();
+    assert_eq!(restored, Some(book));
+    assert!(get_history(&conn, id).unwrap()[1].undone_at.is_some());
+}
+
+#[test]
+fn test_undo_update_and_insert() {
+    let conn = setup_temp_db("history_undo_update");
+    let id = insert_dune(&conn);
+    let before = find_book_by_id(&conn, id).unwrap().unwrap();
+
+    conn.execute(
+        "UPDATE books SET title = 'Dune Messiah' WHERE id = ?1",
+        [id],
+    )
+    .unwrap();
+    let after = find_book_by_id(&conn, id).unwrap().unwrap();
+    record_history(
+        &conn,
+        HistoryOperation::Update,
+        id,
+        Some(&before),
+        Some(&after),
+    )
+    .unwrap();
+
+    // Annulla le ultime due operazioni: prima la modifica, poi l'inserimento
+    let entries = last_undoable(&conn, 2).unwrap();
+    assert_eq!(entries[0].operation, HistoryOperation::Update);
+
+    undo_entry(&conn, &entries[0]).unwrap();
+    assert_eq!(find_book_by_id(&conn, id).unwrap(), Some(before));
+
+    undo_entry(&conn, &entries[1]).unwrap();
+    assert!(find_book_by_id(&conn, id).unwrap().is_none());
+    assert!(last_undoable(&conn, 5).unwrap().is_empty());
+}
+
+#[test]
+fn test_failed_undo_rolls_back_and_returns_error() {
+    let conn = setup_temp_db("history_undo_failed");
+    let id = insert_dune(&conn);
+    let book = find_book_by_id(&conn, id).unwrap().unwrap();
+    conn.execute("DELETE FROM books WHERE id = ?1", [id])
+        .unwrap();
+    record_history(&conn, HistoryOperation::Delete, id, Some(&book), None).unwrap();
+
+    // L'id del libro eliminato viene occupato senza passare dalla cronologia
+    conn.execute(
+        "INSERT INTO books (id, title, author, editor, year, isbn) VALUES (?1, 'Zeno', 'Italo Svevo', 'Cappelli', 1923, '9788807900112')",
+        [id],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO books (title, author, editor, year, isbn) VALUES ('Emma', 'Jane Austen', 'Murray', 1815, '9780141439587')",
+        [],
+    )
+    .unwrap();
+    let emma = conn.last_insert_rowid();
+    record_insert(&conn, emma).unwrap();
+
+    // La prima voce (inserimento di Emma) riesce, la seconda no: tutto annullato
+    assert!(handle_undo(&conn, 2).is_err());
+    assert!(find_book_by_id(&conn, emma).unwrap().is_some());
+    assert_eq!(last_undoable(&conn, 5).unwrap().len(), 3);
+}
@@ -0,0 +1,162 @@
+use librius::i18n::load_language;
+use librius::models::Book;
+use librius::utils::html::{escape, find_cover};
+use librius::utils::{SiteReport, write_site};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cartella temporanea dedicata a un test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("librius_test_html_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn catalog() -> Vec<Book> {
+    vec![
+        Book {
+            id: Some(1),
+            title: "Dune".to_string(),
+            author: "Frank Herbert, Brian Herbert".to_string(),
+            editor: "Chilton".to_string(),
+            year: 1965,
+            isbn: "9780441013593".to_string(),
+            genre: Some("Science fiction".to_string()),
+            summary: Some("Spice & <sand>".to_string()),
+            room: Some("Studio".to_string()),
+            shelf: Some("B2".to_string()),
+            ..Default::default()
+        },
+        Book {
+            id: Some(4),
+            title: "Il Gattopardo".to_string(),
+            author: "Giuseppe Tomasi di Lampedusa".to_string(),
+            editor: "Feltrinelli".to_string(),
+            year: 1958,
+            isbn: "9788807881817".to_string(),
+            ..Default::default()
+        },
+    ]
+}
+
+fn read(dir: &Path, page: &str) -> String {
+    fs::read_to_string(dir.join(page)).unwrap()
+}
+
+#[test]
+fn test_escape() {
+    assert_eq!(
+        escape(r#"<a href="x">Tom & 'Jerry'</a>"#),
+        "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+    );
+}
+
+#[test]
+fn test_site_pages_and_links() {
+    let dir = temp_dir("pages");
+    let report = write_site(&dir, &catalog(), None, None).unwrap();
+    assert_eq!(
+        report,
+        SiteReport {
+            books: 2,
+            covers: 0
+        }
+    );
+
+    for page in [
+        "index.html",
+        "authors.html",
+        "genres.html",
+        "books/1.html",
+        "books/4.html",
+        "assets/style.css",
+        "assets/catalog.js",
+    ] {
+        assert!(dir.join(page).is_file(), "{} missing", page);
+    }
+
+    // Link relativi: il sito funziona da file://
+    let index = read(&dir, "index.html");
+    assert!(index.contains("<a href=\"books/1.html\">Dune</a>"));
+    assert!(index.contains("<a href=\"authors.html#brian-herbert\">Brian Herbert</a>"));
+    assert!(index.contains("<a href=\"genres.html#science-fiction\">Science fiction</a>"));
+    assert!(index.contains("<td>Studio · B2</td>"));
+    assert!(index.contains("<script src=\"assets/catalog.js\"></script>"));
+    assert!(!index.contains("http"));
+
+    let dune = read(&dir, "books/1.html");
+    assert!(dune.contains("href=\"../assets/style.css\""));
+    assert!(dune.contains("<p class=\"summary\">Spice &amp; &lt;sand&gt;</p>"));
+    assert!(dune.contains("<dd>Studio</dd>"));
+
+    // Autori per cognome, senza contare le particelle
+    let authors = read(&dir, "authors.html");
+    let position = |id: &str| authors.find(&format!("id=\"{}\"", id)).unwrap();
+    assert!(position("brian-herbert") < position("frank-herbert"));
+    assert!(position("frank-herbert") < position("giuseppe-tomasi-di-lampedusa"));
+    assert!(authors.contains("<a href=\"books/4.html\">Il Gattopardo</a> (1958)"));
+
+    let genres = read(&dir, "genres.html");
+    assert!(genres.contains("id=\"science-fiction\""));
+    assert!(!genres.contains("Il Gattopardo"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_covers_are_copied_when_available() {
+    let dir = temp_dir("covers");
+    let covers = dir.join("library_covers");
+    fs::create_dir_all(&covers).unwrap();
+    fs::write(covers.join("9780441013593.png"), b"png").unwrap();
+    assert_eq!(
+        find_cover(&covers, "9780441013593"),
+        Some(covers.join("9780441013593.png"))
+    );
+    assert_eq!(find_cover(&covers, "9788807881817"), None);
+
+    let site = dir.join("site");
+    let report = write_site(&site, &catalog(), Some(&covers), None).unwrap();
+    assert_eq!(report.covers, 1);
+    assert_eq!(
+        fs::read(site.join("covers/9780441013593.png")).unwrap(),
+        b"png"
+    );
+    assert!(read(&site, "books/1.html").contains("<img src=\"../covers/9780441013593.png\""));
+    assert!(!read(&site, "books/4.html").contains("<img"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_rerun_removes_pages_of_books_no_longer_listed() {
+    let dir = temp_dir("rerun");
+    write_site(&dir, &catalog(), None, None).unwrap();
+    fs::write(dir.join("books/notes.html"), "mine").unwrap();
+
+    write_site(&dir, &catalog()[..1], None, None).unwrap();
+    assert!(dir.join("books/1.html").exists());
+    assert!(!dir.join("books/4.html").exists());
+    // File non generati da Librius restano dove sono
+    assert!(dir.join("books/notes.html").exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_site_uses_the_active_language() {
+    let dir = temp_dir("language");
+    load_language("it");
+    write_site(&dir, &catalog(), None, Some("ufficio")).unwrap();
+    load_language("en");
+
+    let index = read(&dir, "index.html");
+    assert!(index.contains("<html lang=\"it\">"));
+    assert!(index.contains("Catalogo della biblioteca — ufficio"));
+    assert!(index.contains("data-template=\"{shown} di {total} libri\""));
+    assert!(index.contains(">2 di 2 libri<"));
+    assert!(read(&dir, "books/1.html").contains("<h2>Collocazione</h2>"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
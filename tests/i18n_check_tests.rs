@@ -0,0 +1,143 @@
+use librius::i18n::{I18nIssue, check_translations, message_arguments};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sorgenti e traduzioni di prova con un problema per ogni tipo di controllo.
+fn fixture_tree() -> PathBuf {
+    let root = std::env::temp_dir().join("librius_test_i18n_check");
+    let _ = fs::remove_dir_all(&root);
+    let locales = root.join("src").join("i18n").join("locales");
+    fs::create_dir_all(&locales).unwrap();
+
+    fs::write(
+        root.join("src").join("main.rs"),
+        r##"
+fn main() {
+    println!("{}", tr("greet.hello"));
+    println!("{}", tr_with("greet.name", &[("name", "Ada")]));
+    println!("{}", tr_fmt("greet.count", &[("total", 3.into())]));
+    println!("{}", tr("greet.missing"));
+    println!("{}", tr(&format!("greet.op.{}", "add")));
+    let quote = '"';
+    let raw = r#"tr("greet.raw") "#;
+    // tr("greet.commented")
+    let table = ["greet.table"];
+}
+"##,
+    )
+    .unwrap();
+    fs::write(
+        locales.join("en.json"),
+        r#"{
+          "greet.hello": "Hello",
+          "greet.name": "Hello {name}",
+          "greet.count": "{count, plural, one {# item} other {# items}}",
+          "greet.op.add": "Added",
+          "greet.table": "Table",
+          "greet.broken": "{oops",
+          "greet.unused": "Unused"
+        }"#,
+    )
+    .unwrap();
+    fs::write(
+        locales.join("it.json"),
+        r#"{
+          "greet.hello": "Ciao",
+          "greet.name": "Ciao {nome}",
+          "greet.count": "{count, plural, one {# elemento} other {# elementi}}",
+          "greet.op.add": "Aggiunto",
+          "greet.table": "Tabella",
+          "greet.extra": "Extra"
+        }"#,
+    )
+    .unwrap();
+    root
+}
+
+#[test]
+fn test_repository_translations_are_consistent() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let report = check_translations(root, &[]).unwrap();
+
+    let errors: Vec<&I18nIssue> = report.errors().collect();
+    assert!(errors.is_empty(), "translation errors: {:#?}", errors);
+    assert_eq!(report.locales, vec!["en", "it"]);
+    assert!(report.keys_used > 0);
+}
+
+#[test]
+fn test_check_reports_each_problem() {
+    let root = fixture_tree();
+    let extra = root.join("pt.json");
+    fs::write(&extra, r#"{ "greet.hello": "Olá" }"#).unwrap();
+
+    let report = check_translations(&root, std::slice::from_ref(&extra)).unwrap();
+    let has = |f: &dyn Fn(&I18nIssue) -> bool| report.issues.iter().any(f);
+
+    assert_eq!(report.files, 1);
+    assert_eq!(report.locales, vec!["en", "it", "pt"]);
+    assert!(has(&|i| matches!(
+        i,
+        I18nIssue::MissingKey { key, locale, line, .. }
+            if key == "greet.missing" && locale == "it" && *line == 6
+    )));
+    assert!(has(&|i| matches!(
+        i,
+        I18nIssue::MissingArgument { key, argument, .. }
+            if key == "greet.count" && argument == "count"
+    )));
+    assert!(has(&|i| matches!(
+        i,
+        I18nIssue::PlaceholderMismatch { key, found, .. }
+            if key == "greet.name" && found == &vec!["nome".to_string()]
+    )));
+    assert!(has(&|i| matches!(
+        i,
+        I18nIssue::InvalidMessage { key, locale, .. }
+            if key == "greet.broken" && locale == "en"
+    )));
+    assert!(has(&|i| matches!(
+        i,
+        I18nIssue::UnknownKey { key, .. } if key == "greet.extra"
+    )));
+    assert!(has(&|i| matches!(
+        i,
+        I18nIssue::Untranslated { locale, count } if locale == "pt" && *count == 6
+    )));
+
+    // Chiavi usate tramite prefisso o letterale; commenti e raw string ignorati
+    let unused: Vec<&str> = report
+        .issues
+        .iter()
+        .filter_map(|i| match i {
+            I18nIssue::UnusedKey { key } => Some(key.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(unused, vec!["greet.broken", "greet.unused"]);
+    assert!(!has(&|i| matches!(
+        i,
+        I18nIssue::MissingKey { key, .. } if key == "greet.raw" || key == "greet.commented"
+    )));
+
+    // Una traduzione incompleta passata a parte non è un errore
+    assert_eq!(report.errors().count(), 5);
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_message_arguments() {
+    assert_eq!(
+        message_arguments("{name} has {count, plural, one {# book in {library}} other {# books}}"),
+        Ok(vec![
+            "count".to_string(),
+            "library".to_string(),
+            "name".to_string()
+        ])
+    );
+    assert_eq!(message_arguments("No placeholders"), Ok(vec![]));
+    assert!(message_arguments("{open").is_err());
+    assert!(message_arguments("close}").is_err());
+    assert!(message_arguments("{n, plural, one {# x}}").is_err());
+}
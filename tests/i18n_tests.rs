@@ -0,0 +1,214 @@
+use librius::i18n::{
+    LocaleSource, LocaleWarning, available_languages, check_locale_files, fallback_chain,
+    is_language_available, load_language, normalize_locale_code, set_locales_dir, tr,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// La lingua caricata e la cartella delle traduzioni sono globali: i test che
+/// le modificano non possono girare in parallelo.
+static GLOBAL_I18N: Mutex<()> = Mutex::new(());
+
+/// Cartella `locales/` di prova con traduzioni fornite dall'utente.
+fn locales_fixture(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("librius_test_locales_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("pt.json"),
+        r#"{ "lang.name": "Português", "lang.title": "Idiomas", "log.empty": "Nenhuma entrada." }"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("pt_BR.json"),
+        r#"{ "lang.name": "Português (Brasil)", "lang.title": "Idiomas (Brasil)" }"#,
+    )
+    .unwrap();
+    fs::write(dir.join("it.json"), r#"{ "log.empty": "Registro vuoto." }"#).unwrap();
+    fs::write(dir.join("fr.json"), "{ not json").unwrap();
+    dir
+}
+
+#[test]
+fn test_locale_codes_and_fallback_chain() {
+    assert_eq!(normalize_locale_code("PT_br"), "pt-BR");
+    assert_eq!(normalize_locale_code(" IT "), "it");
+    assert_eq!(normalize_locale_code("zh-hant-tw"), "zh-Hant-TW");
+
+    assert_eq!(fallback_chain("pt-BR"), vec!["pt-BR", "pt", "en"]);
+    assert_eq!(fallback_chain("en-GB"), vec!["en-GB", "en"]);
+    assert_eq!(fallback_chain("it"), vec!["it", "en"]);
+}
+
+#[test]
+fn test_external_locales_extend_and_override() {
+    let _guard = GLOBAL_I18N.lock().unwrap();
+    let dir = locales_fixture("extend");
+    set_locales_dir(Some(dir.clone()));
+
+    // pt-BR → pt → en: ogni chiave arriva dal livello più specifico
+    let warnings = load_language("pt-BR");
+    assert!(warnings.is_empty());
+    assert_eq!(tr("lang.title"), "Idiomas (Brasil)");
+    assert_eq!(tr("log.empty"), "Nenhuma entrada.");
+    assert_eq!(tr("log.header.date"), "Date");
+
+    // Un file esterno sovrascrive solo le proprie chiavi di una lingua inclusa
+    load_language("it");
+    assert_eq!(tr("log.empty"), "Registro vuoto.");
+    assert_eq!(tr("log.header.date"), "Data");
+
+    assert!(is_language_available("pt-PT"));
+    assert!(is_language_available("pt_br"));
+    assert!(!is_language_available("fr"));
+    assert!(!is_language_available("de"));
+
+    let _ = fs::remove_dir_all(&dir);
+    set_locales_dir(None);
+    load_language("en");
+}
+
+#[test]
+fn test_available_languages_and_invalid_files() {
+    let _guard = GLOBAL_I18N.lock().unwrap();
+    let dir = locales_fixture("list");
+    set_locales_dir(Some(dir.clone()));
+
+    let languages = available_languages();
+    let codes: Vec<&str> = languages.iter().map(|l| l.code.as_str()).collect();
+    assert_eq!(codes, vec!["en", "it", "pt", "pt-BR"]);
+
+    let source = |code: &str| languages.iter().find(|l| l.code == code).unwrap().source;
+    assert_eq!(source("en"), LocaleSource::Embedded);
+    assert_eq!(source("it"), LocaleSource::Extended);
+    assert_eq!(source("pt-BR"), LocaleSource::External);
+
+    let en = &languages[0];
+    assert_eq!(en.translated, en.total);
+    let pt_br = &languages[3];
+    assert_eq!(pt_br.name, "Português (Brasil)");
+    // pt-BR eredita da traduzione di pt
+    assert_eq!(pt_br.translated, 3);
+
+    let invalid = check_locale_files();
+    assert_eq!(invalid.len(), 1);
+    assert!(
+        matches!(&invalid[0], LocaleWarning::Invalid { path, .. } if path.ends_with("fr.json"))
+    );
+
+    // Lingua sconosciuta: resta l'inglese, con un avviso
+    let warnings = load_language("fr-CA");
+    assert_eq!(warnings.len(), 2);
+    assert!(matches!(&warnings[1], LocaleWarning::NotFound { code } if code == "fr-CA"));
+    assert_eq!(tr("log.header.date"), "Date");
+
+    let _ = fs::remove_dir_all(&dir);
+    set_locales_dir(None);
+    load_language("en");
+}
+
+#[test]
+fn test_plural_categories() {
+    use librius::i18n::PluralCategory::*;
+    use librius::i18n::plural_category;
+
+    assert_eq!(plural_category("en", 1), One);
+    assert_eq!(plural_category("en", 0), Other);
+    assert_eq!(plural_category("it", 2), Other);
+    assert_eq!(plural_category("fr", 0), One);
+    assert_eq!(plural_category("pt-BR", 0), One);
+    assert_eq!(plural_category("pt-PT", 0), Other);
+    assert_eq!(plural_category("ru", 21), One);
+    assert_eq!(plural_category("ru", 23), Few);
+    assert_eq!(plural_category("ru", 11), Many);
+    assert_eq!(plural_category("pl", 22), Few);
+    assert_eq!(plural_category("ar", 2), Two);
+    assert_eq!(plural_category("ja", 1), Other);
+}
+
+#[test]
+fn test_messages_follow_the_active_locale() {
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use librius::i18n::{FmtArg, format_message, format_timestamp, tr_fmt, tr_with};
+
+    let _guard = GLOBAL_I18N.lock().unwrap();
+    set_locales_dir(None);
+
+    let books = "{count, plural, =0 {no books} one {# book} other {# books}}";
+    let args = |n: i64| [("count", FmtArg::from(n))];
+
+    load_language("en");
+    assert_eq!(format_message(books, &args(0)), "no books");
+    assert_eq!(format_message(books, &args(1)), "1 book");
+    assert_eq!(format_message(books, &args(1234)), "1,234 books");
+    assert_eq!(
+        tr_fmt("log.prune.done", &[("count", 1.into())]),
+        "1 log entry deleted"
+    );
+    // tr_with: i numeri come testo selezionano comunque il plurale
+    assert_eq!(
+        tr_with("log.prune.done", &[("count", "2500")]),
+        "2,500 log entries deleted"
+    );
+    // Segnaposto senza argomento: restano invariati
+    assert_eq!(format_message("{missing} {count}", &args(3)), "{missing} 3");
+
+    let date = NaiveDate::from_ymd_opt(2025, 10, 13).unwrap();
+    assert_eq!(format_message("{d}", &[("d", date.into())]), "2025-10-13");
+
+    load_language("it");
+    assert_eq!(
+        tr_fmt("log.prune.done", &[("count", 1.into())]),
+        "Eliminata 1 voce del log"
+    );
+    assert_eq!(
+        tr_fmt("log.prune.done", &[("count", 12_000.into())]),
+        "Eliminate 12.000 voci del log"
+    );
+    assert_eq!(format_message("{x}", &[("x", 1234.5.into())]), "1.234,5");
+    assert_eq!(format_message("{d}", &[("d", date.into())]), "13/10/2025");
+
+    let dt = Utc.with_ymd_and_hms(2025, 10, 13, 12, 0, 0).unwrap();
+    let local = dt.with_timezone(&chrono::Local);
+    assert_eq!(
+        format_message("{when, date}", &[("when", dt.into())]),
+        local.format("%d/%m/%Y").to_string()
+    );
+    assert_eq!(
+        format_timestamp("2025-10-13T12:00:00+00:00"),
+        local.format("%d/%m/%Y %H:%M:%S").to_string()
+    );
+    assert_eq!(format_timestamp("not a date"), "not a date");
+
+    load_language("en");
+}
+
+#[test]
+fn test_external_locale_sets_plurals_and_formats() {
+    use librius::i18n::{FmtArg, tr_fmt};
+
+    let _guard = GLOBAL_I18N.lock().unwrap();
+    let dir = std::env::temp_dir().join("librius_test_locales_format");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("ru.json"),
+        r#"{
+          "format.number.group": " ",
+          "log.prune.done": "{count, plural, one {Удалена # запись} few {Удалены # записи} many {Удалено # записей} other {Удалено # записи}}"
+        }"#,
+    )
+    .unwrap();
+    set_locales_dir(Some(dir.clone()));
+
+    load_language("ru");
+    let done = |n: i64| tr_fmt("log.prune.done", &[("count", FmtArg::from(n))]);
+    assert_eq!(done(21), "Удалена 21 запись");
+    assert_eq!(done(3), "Удалены 3 записи");
+    assert_eq!(done(12_345), "Удалено 12 345 записей");
+
+    let _ = fs::remove_dir_all(&dir);
+    set_locales_dir(None);
+    load_language("en");
+}
@@ -0,0 +1,274 @@
+mod common;
+use common::setup_temp_db;
+use librius::commands::handle_import_csv;
+use librius::db::{find_book, find_book_by_id, insert_book, trash_book};
+use librius::models::Book;
+use librius::utils::{
+    ImportOptions, MappingOptions, OnConflict, RejectReason, rejected_file_path, validate_book,
+};
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HEADER: &str = "title,author,editor,year,isbn,pages";
+const DUNE: &str = "Dune,Frank Herbert,Chilton,1965,978-0-441-01359-3,412";
+const HOBBIT: &str = "The Hobbit,J. R. R. Tolkien,Mariner,2012,9780547928227,";
+/// ISBN con cifra di controllo errata
+const BAD_ISBN: &str = "Emma,Jane Austen,Penguin,2003,9780141439588,";
+
+/// Scrive un CSV di prova in una cartella temporanea dedicata.
+fn write_csv(name: &str, rows: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("librius_test_import_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("libri.csv");
+    let mut content = vec![HEADER];
+    content.extend(rows);
+    fs::write(&path, content.join("\n")).unwrap();
+    path
+}
+
+fn import(conn: &mut Connection, path: &Path, options: &ImportOptions) {
+    handle_import_csv(
+        conn,
+        path.to_str().unwrap(),
+        ',',
+        &MappingOptions::default(),
+        options,
+    )
+    .unwrap();
+}
+
+fn count(conn: &Connection, sql: &str) -> i64 {
+    conn.query_row(sql, [], |r| r.get(0)).unwrap()
+}
+
+fn existing_dune(conn: &Connection) -> i64 {
+    let dune = Book {
+        title: "Dune".to_string(),
+        author: "Frank Herbert".to_string(),
+        editor: "Ace".to_string(),
+        year: 2005,
+        isbn: "9780441013593".to_string(),
+        ..Default::default()
+    };
+    insert_book(conn, &dune).unwrap()
+}
+
+#[test]
+fn test_validate_book_rules() {
+    let mut book = Book {
+        title: "Dune".to_string(),
+        author: "Frank Herbert".to_string(),
+        year: 1965,
+        isbn: "978-0-441-01359-3".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(validate_book(&mut book), Ok(()));
+    assert_eq!(book.isbn, "9780441013593");
+
+    let check = |change: fn(&mut Book)| {
+        let mut invalid = book.clone();
+        change(&mut invalid);
+        validate_book(&mut invalid)
+    };
+    assert_eq!(
+        check(|b| b.author = " ".to_string()),
+        Err(RejectReason::MissingAuthor)
+    );
+    // ISBN vuoto = nessun ISBN
+    assert_eq!(check(|b| b.isbn = " ".to_string()), Ok(()));
+    assert!(matches!(
+        check(|b| b.isbn = "9780441013594".to_string()),
+        Err(RejectReason::InvalidIsbn(_))
+    ));
+    assert!(matches!(
+        check(|b| b.year = 3000),
+        Err(RejectReason::InvalidField { field, .. }) if field == "year"
+    ));
+    assert!(matches!(
+        check(|b| b.rating = Some(9)),
+        Err(RejectReason::InvalidField { field, .. }) if field == "rating"
+    ));
+    assert!(matches!(
+        check(|b| b.read_status = Some("maybe".to_string())),
+        Err(RejectReason::InvalidField { field, .. }) if field == "read_status"
+    ));
+    // Anno 0 = sconosciuto
+    assert_eq!(check(|b| b.year = 0), Ok(()));
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let mut conn = setup_temp_db("import_dry_run");
+    let path = write_csv("dry_run", &[DUNE, BAD_ISBN]);
+    let options = ImportOptions {
+        dry_run: true,
+        ..Default::default()
+    };
+    import(&mut conn, &path, &options);
+
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 0);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM book_history"), 0);
+    assert!(!rejected_file_path(&path, "csv").exists());
+}
+
+#[test]
+fn test_rejected_rows_cancel_the_import_unless_partial() {
+    let mut conn = setup_temp_db("import_all_or_nothing");
+    let path = write_csv("all_or_nothing", &[DUNE, BAD_ISBN, HOBBIT]);
+
+    import(&mut conn, &path, &ImportOptions::default());
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 0);
+
+    // Il file degli scartati conserva la riga originale e il motivo
+    let rejected = fs::read_to_string(rejected_file_path(&path, "csv")).unwrap();
+    let lines: Vec<&str> = rejected.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with(",rejected_line,rejected_reason"));
+    assert!(lines[1].starts_with(&format!("{},3,", BAD_ISBN)));
+
+    let partial = ImportOptions {
+        partial: true,
+        ..Default::default()
+    };
+    import(&mut conn, &path, &partial);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 2);
+    let dune = find_book(&conn, "9780441013593", true).unwrap().unwrap();
+    assert_eq!(dune.pages, Some(412));
+
+    // Corretto l'ISBN, il file degli scartati si importa così com'è
+    let fixed = rejected.replace("9780141439588", "9780141439587");
+    fs::write(rejected_file_path(&path, "csv"), fixed).unwrap();
+    import(
+        &mut conn,
+        &rejected_file_path(&path, "csv"),
+        &ImportOptions::default(),
+    );
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 3);
+
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_on_conflict_skip_and_fail() {
+    let mut conn = setup_temp_db("import_conflict_skip");
+    let id = existing_dune(&conn);
+    let path = write_csv("conflict_skip", &[DUNE, HOBBIT]);
+
+    import(&mut conn, &path, &ImportOptions::default());
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 2);
+    let dune = find_book(&conn, "9780441013593", true).unwrap().unwrap();
+    assert_eq!(dune.id.map(i64::from), Some(id));
+    assert_eq!(dune.editor, "Ace");
+
+    // Con fail il conflitto annulla anche i libri nuovi
+    let mut conn = setup_temp_db("import_conflict_fail");
+    existing_dune(&conn);
+    let fail = ImportOptions {
+        on_conflict: OnConflict::Fail,
+        ..Default::default()
+    };
+    import(&mut conn, &path, &fail);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 1);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM book_history"), 0);
+
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_on_conflict_update_merges_and_records_history() {
+    let mut conn = setup_temp_db("import_conflict_update");
+    let id = existing_dune(&conn);
+    let path = write_csv("conflict_update", &[DUNE]);
+    let update = ImportOptions {
+        on_conflict: OnConflict::Update,
+        ..Default::default()
+    };
+
+    import(&mut conn, &path, &update);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 1);
+    let dune = find_book(&conn, "9780441013593", true).unwrap().unwrap();
+    assert_eq!(dune.id.map(i64::from), Some(id));
+    assert_eq!(dune.editor, "Chilton");
+    assert_eq!(dune.year, 1965);
+    assert_eq!(dune.pages, Some(412));
+    let updates = "SELECT COUNT(*) FROM book_history WHERE operation = 'UPDATE'";
+    assert_eq!(count(&conn, updates), 1);
+
+    // Reimportare gli stessi dati non cambia nulla
+    import(&mut conn, &path, &update);
+    assert_eq!(count(&conn, updates), 1);
+
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_books_in_the_trash_are_never_merged_or_skipped() {
+    let path = write_csv("conflict_trashed", &[DUNE, HOBBIT]);
+    for on_conflict in [OnConflict::Skip, OnConflict::Update, OnConflict::Fail] {
+        let mut conn = setup_temp_db("import_conflict_trashed");
+        let id = existing_dune(&conn);
+        let dune = find_book_by_id(&conn, id).unwrap().unwrap();
+        trash_book(&conn, &dune).unwrap();
+
+        let options = ImportOptions {
+            on_conflict,
+            ..Default::default()
+        };
+        import(&mut conn, &path, &options);
+        // Il libro nel cestino resta com'era, gli altri sono importati
+        assert_eq!(find_book_by_id(&conn, id).unwrap(), Some(dune));
+        assert_eq!(
+            count(&conn, "SELECT COUNT(*) FROM books WHERE deleted_at IS NULL"),
+            1
+        );
+        let updates = "SELECT COUNT(*) FROM book_history WHERE operation = 'UPDATE'";
+        assert_eq!(count(&conn, updates), 0);
+    }
+
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_rows_without_isbn_are_imported_and_matched_on_title_author_year() {
+    let dir = std::env::temp_dir().join("librius_test_import_no_isbn");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("n.csv");
+    fs::write(
+        &path,
+        "title;author;year\nNoIsbn;Someone;2001\nOther;Someone;2001\n",
+    )
+    .unwrap();
+    let import_semicolon = |conn: &mut Connection, options: &ImportOptions| {
+        handle_import_csv(
+            conn,
+            path.to_str().unwrap(),
+            ';',
+            &MappingOptions::default(),
+            options,
+        )
+        .unwrap()
+    };
+
+    let mut conn = setup_temp_db("import_no_isbn");
+    import_semicolon(&mut conn, &ImportOptions::default());
+    assert_eq!(
+        count(&conn, "SELECT COUNT(*) FROM books WHERE isbn = ''"),
+        2
+    );
+
+    // Reimportare non duplica: titolo, autore e anno riconoscono il libro
+    import_semicolon(&mut conn, &ImportOptions::default());
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 2);
+
+    let fail = ImportOptions {
+        on_conflict: OnConflict::Fail,
+        ..Default::default()
+    };
+    import_semicolon(&mut conn, &fail);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM books"), 2);
+
+    let _ = fs::remove_dir_all(&dir);
+}
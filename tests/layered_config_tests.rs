@@ -0,0 +1,157 @@
+use librius::config::layered::{ConfigSource, ConfigWarning, env_var_for, read_layers, resolve};
+use librius::config::{CliOverrides, Layers};
+use std::collections::HashMap;
+
+const FILE: &str = "\
+database: /data/home.sqlite
+language: en
+backup:
+  keep_last: 3
+libraries:
+  office:
+    database: /data/office.sqlite
+    language: it
+";
+
+fn layers(file: &str, env: &[(&str, &str)], cli: CliOverrides) -> Layers {
+    Layers {
+        file: Some(serde_yaml::from_str(file).unwrap()),
+        env: env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>(),
+        cli,
+    }
+}
+
+#[test]
+fn test_file_values_fill_in_over_defaults() {
+    let effective = resolve(&layers(FILE, &[], CliOverrides::default())).unwrap();
+    assert_eq!(effective.config.database, "/data/home.sqlite");
+    assert_eq!(effective.config.backup.keep_last, 3);
+    // Chiave assente nel file: vale il predefinito
+    assert_eq!(effective.config.backup.keep_daily, 7);
+    assert_eq!(effective.source("backup.keep_last"), ConfigSource::File);
+    assert_eq!(effective.source("backup.keep_daily"), ConfigSource::Default);
+}
+
+#[test]
+fn test_partial_file_is_accepted() {
+    let effective = resolve(&layers("language: it\n", &[], CliOverrides::default())).unwrap();
+    assert_eq!(effective.config.language, "it");
+    assert!(!effective.config.database.is_empty());
+    assert_eq!(effective.source("database"), ConfigSource::Default);
+}
+
+#[test]
+fn test_env_overrides_file_and_library() {
+    let env = [
+        ("LIBRIUS_DATABASE", "/env/db.sqlite"),
+        ("LIBRIUS_BACKUP_KEEP_LAST", "9"),
+        ("LIBRIUS_BACKUP_FORMAT", "tar.gz"),
+    ];
+    let cli = CliOverrides {
+        library: Some("office".to_string()),
+        ..CliOverrides::default()
+    };
+    let effective = resolve(&layers(FILE, &env, cli)).unwrap();
+
+    assert_eq!(effective.config.database, "/env/db.sqlite");
+    assert_eq!(effective.config.language, "it");
+    assert_eq!(effective.config.backup.keep_last, 9);
+    assert_eq!(effective.config.backup.format.ext(), "tar.gz");
+    assert_eq!(
+        effective.source("database"),
+        ConfigSource::Env("LIBRIUS_DATABASE".to_string())
+    );
+    assert_eq!(
+        effective.source("language"),
+        ConfigSource::Library("office".to_string())
+    );
+    assert_eq!(
+        effective.source("active_library"),
+        ConfigSource::Cli("--library".to_string())
+    );
+}
+
+#[test]
+fn test_cli_flags_win_over_env() {
+    let env = [
+        ("LIBRIUS_DB_PATH", "/legacy.sqlite"),
+        ("LIBRIUS_LANGUAGE", "it"),
+    ];
+    let cli = CliOverrides {
+        database: Some("/cli.sqlite".to_string()),
+        language: Some("en".to_string()),
+        ..CliOverrides::default()
+    };
+    let effective = resolve(&layers(FILE, &env, cli)).unwrap();
+    assert_eq!(effective.config.database, "/cli.sqlite");
+    assert_eq!(effective.config.language, "en");
+    assert_eq!(
+        effective.source("database"),
+        ConfigSource::Cli("--db".to_string())
+    );
+
+    // Senza --db vale l'alias storico LIBRIUS_DB_PATH
+    let effective = resolve(&layers(FILE, &env, CliOverrides::default())).unwrap();
+    assert_eq!(effective.config.database, "/legacy.sqlite");
+    assert_eq!(effective.config.language, "it");
+}
+
+#[test]
+fn test_invalid_env_values_are_ignored_with_warning() {
+    let env = [
+        ("LIBRIUS_LANGUAGE", "xx"),
+        ("LIBRIUS_BACKUP_KEEP_DAILY", "many"),
+    ];
+    let effective = resolve(&layers(FILE, &env, CliOverrides::default())).unwrap();
+    assert_eq!(effective.config.language, "en");
+    assert_eq!(effective.config.backup.keep_daily, 7);
+    assert_eq!(effective.warnings.len(), 2);
+    assert!(
+        effective
+            .warnings
+            .iter()
+            .all(|w| matches!(w, ConfigWarning::InvalidOverride(_)))
+    );
+}
+
+#[test]
+fn test_unknown_library_is_an_error_only_when_requested() {
+    let env = [("LIBRIUS_ACTIVE_LIBRARY", "missing")];
+    assert!(resolve(&layers(FILE, &env, CliOverrides::default())).is_err());
+
+    let file = format!("{}active_library: missing\n", FILE);
+    let effective = resolve(&layers(&file, &[], CliOverrides::default())).unwrap();
+    assert_eq!(effective.config.database, "/data/home.sqlite");
+    assert!(matches!(
+        effective.warnings.as_slice(),
+        [ConfigWarning::UnknownLibrary(_)]
+    ));
+}
+
+#[test]
+fn test_env_var_names() {
+    assert_eq!(env_var_for("database"), "LIBRIUS_DATABASE");
+    assert_eq!(env_var_for("backup.keep_last"), "LIBRIUS_BACKUP_KEEP_LAST");
+}
+
+#[test]
+fn test_missing_config_file_is_not_created() {
+    let dir = std::env::temp_dir().join("librius_test_missing_config");
+    let _ = std::fs::remove_dir_all(&dir);
+    let cli = CliOverrides {
+        config_file: Some(dir.join("librius.conf")),
+        database: Some("/cli.sqlite".to_string()),
+        ..CliOverrides::default()
+    };
+
+    let layers = read_layers(cli).unwrap();
+    assert!(layers.file.is_none());
+    let effective = resolve(&layers).unwrap();
+    assert_eq!(effective.config.database, "/cli.sqlite");
+    assert_eq!(effective.config.backup.keep_last, 5);
+    // Né il file né la sua cartella vengono creati
+    assert!(!dir.exists());
+}
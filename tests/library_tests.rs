@@ -0,0 +1,182 @@
+use librius::config::{
+    AppConfig, BackupConfig, DEFAULT_LIBRARY, LibraryError, LibraryProfile, write_libraries,
+};
+
+fn base_config() -> AppConfig {
+    serde_yaml::from_str("database: /data/home.sqlite\nlanguage: en\n").expect("parse config")
+}
+
+fn profile(db: &str, language: Option<&str>) -> LibraryProfile {
+    LibraryProfile {
+        database: db.to_string(),
+        language: language.map(str::to_string),
+        backup: None,
+    }
+}
+
+#[test]
+fn test_config_without_libraries_uses_default() {
+    let cfg = base_config();
+    assert!(cfg.libraries.is_empty());
+    assert_eq!(cfg.library_name(), DEFAULT_LIBRARY);
+
+    let effective = cfg.for_library(None).unwrap();
+    assert_eq!(effective.database, "/data/home.sqlite");
+    assert_eq!(effective.active_library, None);
+}
+
+#[test]
+fn test_for_library_applies_profile_overrides() {
+    let mut cfg = base_config();
+    let backup = BackupConfig {
+        keep_last: 1,
+        ..BackupConfig::default()
+    };
+    cfg.add_library(
+        "office",
+        LibraryProfile {
+            backup: Some(backup.clone()),
+            ..profile("/data/office.sqlite", Some("it"))
+        },
+    )
+    .unwrap();
+
+    let effective = cfg.for_library(Some("office")).unwrap();
+    assert_eq!(effective.database, "/data/office.sqlite");
+    assert_eq!(effective.language, "it");
+    assert_eq!(effective.backup, backup);
+    assert_eq!(effective.library_name(), "office");
+
+    // `--library default` ignora active_library
+    cfg.use_library("office").unwrap();
+    let effective = cfg.for_library(Some(DEFAULT_LIBRARY)).unwrap();
+    assert_eq!(effective.database, "/data/home.sqlite");
+    assert_eq!(
+        cfg.for_library(None).unwrap().database,
+        "/data/office.sqlite"
+    );
+
+    assert_eq!(
+        cfg.for_library(Some("missing")).unwrap_err(),
+        LibraryError::NotFound("missing".to_string())
+    );
+}
+
+#[test]
+fn test_profile_without_language_keeps_global_one() {
+    let mut cfg = base_config();
+    cfg.add_library("office", profile("/data/office.sqlite", None))
+        .unwrap();
+    let effective = cfg.for_library(Some("office")).unwrap();
+    assert_eq!(effective.language, "en");
+}
+
+#[test]
+fn test_add_rejects_invalid_duplicate_and_reserved_names() {
+    let mut cfg = base_config();
+    cfg.add_library("office", profile("/a", None)).unwrap();
+
+    assert_eq!(
+        cfg.add_library("office", profile("/b", None)),
+        Err(LibraryError::AlreadyExists("office".to_string()))
+    );
+    assert_eq!(
+        cfg.add_library("my office", profile("/b", None)),
+        Err(LibraryError::InvalidName("my office".to_string()))
+    );
+    assert_eq!(
+        cfg.add_library("", profile("/b", None)),
+        Err(LibraryError::InvalidName(String::new()))
+    );
+    assert_eq!(
+        cfg.add_library(DEFAULT_LIBRARY, profile("/b", None)),
+        Err(LibraryError::Reserved)
+    );
+}
+
+#[test]
+fn test_rename_and_remove_follow_active_library() {
+    let mut cfg = base_config();
+    cfg.add_library("office", profile("/a", None)).unwrap();
+    cfg.add_library("lab", profile("/b", None)).unwrap();
+    cfg.use_library("office").unwrap();
+
+    assert_eq!(
+        cfg.rename_library("office", "lab"),
+        Err(LibraryError::AlreadyExists("lab".to_string()))
+    );
+    cfg.rename_library("office", "work").unwrap();
+    assert_eq!(cfg.active_library.as_deref(), Some("work"));
+    assert!(cfg.libraries.contains_key("work"));
+    assert!(!cfg.libraries.contains_key("office"));
+
+    let removed = cfg.remove_library("work").unwrap();
+    assert_eq!(removed.database, "/a");
+    assert_eq!(cfg.active_library, None);
+    assert_eq!(
+        cfg.remove_library("work"),
+        Err(LibraryError::NotFound("work".to_string()))
+    );
+    assert_eq!(
+        cfg.remove_library(DEFAULT_LIBRARY),
+        Err(LibraryError::Reserved)
+    );
+
+    cfg.use_library("lab").unwrap();
+    cfg.use_library(DEFAULT_LIBRARY).unwrap();
+    assert_eq!(cfg.active_library, None);
+}
+
+#[test]
+fn test_libraries_yaml_round_trip() {
+    let mut cfg = base_config();
+    cfg.add_library("office", profile("/data/office.sqlite", Some("it")))
+        .unwrap();
+    cfg.use_library("office").unwrap();
+
+    let yaml = serde_yaml::to_string(&cfg).unwrap();
+    assert!(yaml.contains("active_library: office"));
+    let parsed: AppConfig = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(parsed.libraries, cfg.libraries);
+    assert_eq!(parsed.active_library, cfg.active_library);
+
+    // Senza librerie con nome le nuove chiavi non compaiono nel file
+    let yaml = serde_yaml::to_string(&base_config()).unwrap();
+    assert!(!yaml.contains("libraries"));
+    assert!(!yaml.contains("active_library"));
+}
+
+#[test]
+fn test_library_changes_keep_comments_and_key_order() {
+    let text = "# Librius\nconfig_version: 4\ndatabase: /data/home.sqlite # casa\nlanguage: en\nbackup:\n  keep_last: 5 # ultimi\n";
+    let mut cfg: AppConfig = serde_yaml::from_str(text).unwrap();
+
+    cfg.add_library("office", profile("/data/office.sqlite", Some("it")))
+        .unwrap();
+    cfg.use_library("office").unwrap();
+    let added = write_libraries(text, &cfg).unwrap();
+    assert_eq!(
+        added,
+        format!(
+            "{}libraries:\n  office:\n    database: /data/office.sqlite\n    language: it\nactive_library: office\n",
+            text
+        )
+    );
+
+    // Un commento dentro il profilo sopravvive alle modifiche degli altri
+    let commented = added.replace("language: it\n", "language: it # ufficio\n");
+    cfg.add_library("attic", profile("/data/attic.sqlite", None))
+        .unwrap();
+    cfg.use_library(DEFAULT_LIBRARY).unwrap();
+    let updated = write_libraries(&commented, &cfg).unwrap();
+    assert!(updated.starts_with(text));
+    assert!(
+        updated
+            .contains("    language: it # ufficio\n  attic:\n    database: /data/attic.sqlite\n")
+    );
+    assert!(!updated.contains("active_library"));
+
+    cfg.remove_library("office").unwrap();
+    cfg.remove_library("attic").unwrap();
+    assert_eq!(write_libraries(&updated, &cfg).unwrap(), text);
+}
@@ -21,7 +21,14 @@ fn exercise_list_handler() -> Result<(), Box<dyn Error>> {
             shelf TEXT,
             row TEXT,
             position TEXT,
-            added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            deleted_at TEXT,
+            rating INTEGER,
+            read_status TEXT,
+            read_at TEXT,
+            tags TEXT,
+            review TEXT,
+            format TEXT
         );",
         [],
     )?;
@@ -39,7 +46,7 @@ fn exercise_list_handler() -> Result<(), Box<dyn Error>> {
         ],
     )?;
 
-    handle_list(&conn, false, None, false, false)?;
+    handle_list(&conn, false, None, false, false, None)?;
     Ok(())
 }
 
@@ -62,7 +69,14 @@ fn exercise_list_handler_short() -> Result<(), Box<dyn Error>> {
             shelf TEXT,
             row TEXT,
             position TEXT,
-            added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            deleted_at TEXT,
+            rating INTEGER,
+            read_status TEXT,
+            read_at TEXT,
+            tags TEXT,
+            review TEXT,
+            format TEXT
         );",
         [],
     )?;
@@ -80,6 +94,6 @@ fn exercise_list_handler_short() -> Result<(), Box<dyn Error>> {
         ],
     )?;
 
-    handle_list(&conn, true, None, false, false)?;
+    handle_list(&conn, true, None, false, false, None)?;
     Ok(())
 }
@@ -0,0 +1,173 @@
+use chrono::{Duration, Utc};
+use librius::db::log::{LogFilter, count_log_before, list_log, log_stats, prune_log};
+use librius::utils::{OutputFormat, parse_instant, write_log, write_log_with, write_records};
+use rusqlite::Connection;
+use serde_json::json;
+
+/// Log con alcune voci di esempio, una delle quali vecchia di 100 giorni.
+fn sample_log() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    write_log(&conn, "DB_MIGRATION_UP", "DB", "PATCH_001").unwrap();
+    write_log(&conn, "DB_MIGRATION_OK", "DB", "Applied database patch").unwrap();
+    let details = json!({ "title": "Dune", "isbn": "9780441013593" });
+    write_log_with(&conn, "ADD_BOOK", "BOOKS", "Dune", Some(1), Some(&details)).unwrap();
+    write_log_with(&conn, "ADD_BOOK", "BOOKS", "Emma", Some(2), None).unwrap();
+    write_log_with(&conn, "EDIT_BOOK", "BOOKS", "Dune", Some(1), None).unwrap();
+
+    let old = (Utc::now() - Duration::days(100)).to_rfc3339();
+    conn.execute(
+        "INSERT INTO log (date, operation, target, message) VALUES (?1, 'DB_INIT_OK', 'DB', 'old')",
+        [&old],
+    )
+    .unwrap();
+    conn
+}
+
+fn operations(conn: &Connection, filter: &LogFilter) -> Vec<String> {
+    list_log(conn, filter)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.operation)
+        .collect()
+}
+
+#[test]
+fn test_structured_entry_round_trip() {
+    let conn = sample_log();
+    let entries = list_log(
+        &conn,
+        &LogFilter {
+            book_id: Some(1),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].operation, "ADD_BOOK");
+    assert_eq!(entries[0].target, "BOOKS");
+    assert_eq!(entries[0].book_id, Some(1));
+    assert_eq!(
+        entries[0].details.as_ref().unwrap()["isbn"],
+        "9780441013593"
+    );
+    assert!(entries[1].details.is_none());
+}
+
+#[test]
+fn test_filters_and_limit() {
+    let conn = sample_log();
+
+    let db = LogFilter {
+        operation: Some("db_*".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        operations(&conn, &db),
+        vec!["DB_MIGRATION_UP", "DB_MIGRATION_OK", "DB_INIT_OK"]
+    );
+
+    let books = LogFilter {
+        target: Some("books".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(operations(&conn, &books).len(), 3);
+
+    // Il limite tiene le voci più recenti, sempre dalla più vecchia
+    let last_two = LogFilter {
+        limit: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(
+        operations(&conn, &last_two),
+        vec!["EDIT_BOOK", "DB_INIT_OK"]
+    );
+
+    let recent = LogFilter {
+        since: parse_instant("7d", false),
+        ..Default::default()
+    };
+    assert_eq!(operations(&conn, &recent).len(), 5);
+
+    let old = LogFilter {
+        until: parse_instant("30d", false),
+        ..Default::default()
+    };
+    assert_eq!(operations(&conn, &old), vec!["DB_INIT_OK"]);
+}
+
+#[test]
+fn test_stats_count_per_operation() {
+    let conn = sample_log();
+    let stats = log_stats(&conn, &LogFilter::default()).unwrap();
+
+    assert_eq!(stats.len(), 5);
+    assert_eq!(stats[0].operation, "ADD_BOOK");
+    assert_eq!(stats[0].count, 2);
+    assert_eq!(stats.iter().map(|s| s.count).sum::<i64>(), 6);
+
+    let books = LogFilter {
+        target: Some("BOOKS".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(log_stats(&conn, &books).unwrap().len(), 2);
+}
+
+#[test]
+fn test_prune_removes_only_old_entries() {
+    let conn = sample_log();
+    let cutoff = Utc::now() - Duration::days(90);
+
+    assert_eq!(count_log_before(&conn, &cutoff).unwrap(), 1);
+    assert_eq!(prune_log(&conn, &cutoff).unwrap(), 1);
+    assert_eq!(count_log_before(&conn, &cutoff).unwrap(), 0);
+    assert_eq!(list_log(&conn, &LogFilter::default()).unwrap().len(), 5);
+}
+
+#[test]
+fn test_machine_readable_formats() {
+    let conn = sample_log();
+    let filter = LogFilter {
+        book_id: Some(1),
+        limit: Some(1),
+        ..Default::default()
+    };
+    let entries = list_log(&conn, &filter).unwrap();
+
+    let mut csv = Vec::new();
+    write_records(&mut csv, OutputFormat::Csv, &entries).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("id,date,operation,target,message,book_id,details")
+    );
+    assert!(lines.next().unwrap().contains(",EDIT_BOOK,BOOKS,Dune,1,"));
+
+    let entries = list_log(&conn, &LogFilter::default()).unwrap();
+    let mut jsonl = Vec::new();
+    write_records(&mut jsonl, OutputFormat::Jsonl, &entries).unwrap();
+    let jsonl = String::from_utf8(jsonl).unwrap();
+    assert_eq!(jsonl.lines().count(), entries.len());
+
+    let mut out = Vec::new();
+    write_records(&mut out, OutputFormat::Json, &entries).unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(value.as_array().unwrap().len(), entries.len());
+    assert_eq!(value[2]["details"]["title"], "Dune");
+}
+
+#[test]
+fn test_parse_instant() {
+    let rfc = parse_instant("2025-10-13T18:00:00+02:00", false).unwrap();
+    assert_eq!(rfc.to_rfc3339(), "2025-10-13T16:00:00+00:00");
+
+    let start = parse_instant("2025-10-13", false).unwrap();
+    let end = parse_instant("2025-10-13", true).unwrap();
+    assert_eq!(end - start, Duration::days(1));
+
+    let week = parse_instant("7d", false).unwrap();
+    assert!((Utc::now() - week - Duration::days(7)).num_seconds().abs() < 5);
+
+    assert!(parse_instant("yesterday", false).is_none());
+}
@@ -0,0 +1,129 @@
+mod common;
+use common::setup_temp_db;
+use librius::db::history::{get_history, record_history};
+use librius::db::maintenance::{Issue, check_database, collect_stats, repair_issues, vacuum};
+use librius::db::run_migrations;
+use librius::models::HistoryOperation;
+use rusqlite::Connection;
+
+fn insert(conn: &Connection, isbn: &str, year: i32, pages: Option<i32>) -> i64 {
+    conn.execute(
+        "INSERT INTO books (title, author, editor, year, isbn, pages) VALUES ('T', 'A', 'E', ?1, ?2, ?3)",
+        (year, isbn, pages),
+    )
+    .unwrap();
+    conn.last_insert_rowid()
+}
+
+#[test]
+fn test_healthy_database_has_no_issues() {
+    let conn = setup_temp_db("maint_healthy");
+    insert(&conn, "9780441013593", 1965, Some(412));
+    insert(&conn, "9780593098233", 0, None); // anno 0 = sconosciuto
+    insert(&conn, "", 2001, None); // nessun ISBN
+
+    assert!(check_database(&conn).unwrap().is_empty());
+}
+
+#[test]
+fn test_check_reports_every_issue_class() {
+    let conn = setup_temp_db("maint_issues");
+    let invalid = insert(&conn, "12345", 1965, None);
+    let hyphen = insert(&conn, "978-0-441-01359-3", 1965, None);
+    let future = insert(&conn, "9780593098233", 3000, None);
+    let negative = insert(&conn, "9788820382698", 2001, Some(-10));
+    record_history(&conn, HistoryOperation::Update, 99, None, None).unwrap();
+
+    let issues = check_database(&conn).unwrap();
+    assert!(issues.contains(&Issue::InvalidIsbn {
+        id: invalid,
+        isbn: "12345".into()
+    }));
+    assert!(issues.contains(&Issue::NonCanonicalIsbn {
+        id: hyphen,
+        isbn: "978-0-441-01359-3".into(),
+        canonical: "9780441013593".into()
+    }));
+    assert!(issues.contains(&Issue::ImpossibleYear {
+        id: future,
+        year: 3000
+    }));
+    assert!(issues.contains(&Issue::NegativePages {
+        id: negative,
+        pages: -10
+    }));
+    assert!(issues.contains(&Issue::OrphanHistory {
+        book_id: 99,
+        rows: 1
+    }));
+    assert_eq!(issues.len(), 5);
+}
+
+#[test]
+fn test_history_of_deleted_book_is_not_orphan() {
+    let conn = setup_temp_db("maint_deleted_history");
+    record_history(&conn, HistoryOperation::Delete, 42, None, None).unwrap();
+
+    assert!(check_database(&conn).unwrap().is_empty());
+}
+
+#[test]
+fn test_repair_fixes_only_safe_issues() {
+    let conn = setup_temp_db("maint_repair");
+    insert(&conn, "12345", 1965, None);
+    let hyphen = insert(&conn, "978-0-441-01359-3", 1965, None);
+    let negative = insert(&conn, "9788820382698", 2001, Some(-10));
+    record_history(&conn, HistoryOperation::Update, 99, None, None).unwrap();
+
+    let issues = check_database(&conn).unwrap();
+    assert_eq!(repair_issues(&conn, &issues).unwrap(), 3);
+
+    let remaining = check_database(&conn).unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert!(matches!(remaining[0], Issue::InvalidIsbn { .. }));
+
+    let isbn: String = conn
+        .query_row("SELECT isbn FROM books WHERE id = ?1", [hyphen], |r| {
+            r.get(0)
+        })
+        .unwrap();
+    assert_eq!(isbn, "9780441013593");
+
+    // Le riparazioni sui libri sono annullabili
+    let history = get_history(&conn, negative).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].operation, HistoryOperation::Update);
+}
+
+#[test]
+fn test_repair_skips_isbn_already_taken() {
+    let conn = setup_temp_db("maint_repair_taken");
+    insert(&conn, "9780441013593", 1965, None);
+    let hyphen = insert(&conn, "978-0-441-01359-3", 1965, None);
+
+    let issues = check_database(&conn).unwrap();
+    assert_eq!(repair_issues(&conn, &issues).unwrap(), 0);
+    assert!(
+        check_database(&conn)
+            .unwrap()
+            .iter()
+            .any(|i| matches!(i, Issue::NonCanonicalIsbn { id, .. } if *id == hyphen))
+    );
+}
+
+#[test]
+fn test_stats_and_vacuum() {
+    let conn = setup_temp_db("maint_stats");
+    run_migrations(&conn).unwrap();
+    insert(&conn, "9780441013593", 1965, None);
+
+    let stats = collect_stats(&conn).unwrap();
+    assert!(stats.file_size.unwrap() > 0);
+    assert_eq!(stats.schema_version, stats.latest_version);
+    assert_eq!(stats.migrations.len(), stats.latest_version as usize);
+    assert!(stats.log_rows > 0);
+    assert!(stats.tables.contains(&("books".to_string(), 1)));
+
+    vacuum(&conn).unwrap();
+    assert!(check_database(&conn).unwrap().is_empty());
+}
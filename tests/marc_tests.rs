@@ -0,0 +1,267 @@
+mod common;
+use chrono::{TimeZone, Utc};
+use common::setup_temp_db;
+use librius::commands::handle_import_marc;
+use librius::db::find_book;
+use librius::models::Book;
+use librius::utils::marc::{
+    MarcField, MarcRecord, parse_iso2709, parse_marcxml, record_to_book, write_iso2709,
+    write_marcxml,
+};
+use librius::utils::{
+    ImportOptions, RejectReason, book_to_record, lost_on_export, map_marc_records, parse_marc,
+    rejected_file_path,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/marc/catalogo.xml")
+}
+
+fn dune() -> Book {
+    Book {
+        id: Some(7),
+        title: "Dune".to_string(),
+        author: "Frank Herbert, Brian Herbert".to_string(),
+        editor: "Ace".to_string(),
+        year: 2005,
+        isbn: "9780441013593".to_string(),
+        language: Some("English".to_string()),
+        pages: Some(412),
+        genre: Some("Fantascienza".to_string()),
+        summary: Some("Spice & <sand>, \"worms\".".to_string()),
+        room: Some("Studio".to_string()),
+        shelf: Some("B2".to_string()),
+        row: Some("3".to_string()),
+        position: Some("12".to_string()),
+        added_at: Some(Utc.with_ymd_and_hms(2021, 3, 4, 10, 20, 30).unwrap()),
+        rating: Some(5),
+        tags: Some("classics, sci-fi".to_string()),
+        format: Some("ebook".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_book_to_record_uses_standard_tags() {
+    let record = book_to_record(&dune());
+    let tags: Vec<&str> = record.fields.iter().map(MarcField::tag).collect();
+    assert_eq!(
+        tags,
+        vec![
+            "001", "008", "020", "041", "100", "700", "245", "264", "300", "338", "520", "650",
+            "650", "655", "852"
+        ]
+    );
+    let fixed = record
+        .fields
+        .iter()
+        .find_map(|f| match f {
+            MarcField::Control { tag, value } if tag == "008" => Some(value.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(fixed.len(), 40);
+    assert!(fixed.starts_with("210304s2005"));
+    assert_eq!(&fixed[35..38], "eng");
+}
+
+#[test]
+fn test_mapped_fields_survive_a_round_trip() {
+    let book = dune();
+    let back = record_to_book(&book_to_record(&book)).unwrap();
+    assert_eq!(
+        back,
+        Book {
+            id: None,
+            added_at: None,
+            rating: None,
+            ..book.clone()
+        }
+    );
+    assert_eq!(lost_on_export(&book), vec!["added_at", "rating"]);
+
+    // Lingua senza codice MARC: nota 546 in chiaro
+    let klingon = Book {
+        language: Some("Klingon".to_string()),
+        ..book
+    };
+    let record = book_to_record(&klingon);
+    assert!(record.fields.iter().any(|f| f.tag() == "546"));
+    assert_eq!(
+        record_to_book(&record).unwrap().language.as_deref(),
+        Some("Klingon")
+    );
+}
+
+#[test]
+fn test_iso2709_and_marcxml_round_trips() {
+    let records = vec![
+        book_to_record(&dune()),
+        book_to_record(&Book {
+            title: "La coscienza di Zeno".to_string(),
+            author: "Italo Svevo".to_string(),
+            isbn: "9788807900112".to_string(),
+            language: Some("Italian".to_string()),
+            ..Default::default()
+        }),
+    ];
+
+    let (binary, too_long) = write_iso2709(&records);
+    assert!(too_long.is_empty());
+    let parsed: Vec<_> = parse_iso2709(&binary)
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(parsed.len(), 2);
+    // Il leader riporta lunghezza del record e indirizzo base effettivi
+    let first_len: usize = parsed[0].leader[..5].parse().unwrap();
+    assert_eq!(binary[first_len - 1], 0x1D);
+    assert_eq!(parsed[0].fields, records[0].fields);
+    assert_eq!(parsed[1].fields, records[1].fields);
+    assert_eq!(write_iso2709(&parsed).0, binary);
+
+    let xml = write_marcxml(&records).unwrap();
+    let parsed = parse_marcxml(std::str::from_utf8(&xml).unwrap()).unwrap();
+    assert_eq!(parsed, records);
+}
+
+#[test]
+fn test_damaged_iso2709_record_is_rejected_alone() {
+    let (mut binary, _) = write_iso2709(&[book_to_record(&dune())]);
+    binary.extend(b"00042nam a22BROKEN i 4500\x1E\x1D");
+    let records = parse_iso2709(&binary);
+    assert_eq!(records.len(), 2);
+    assert!(records[0].is_ok());
+
+    let import = map_marc_records(&records).import;
+    assert_eq!(import.books.len(), 1);
+    assert_eq!(import.rejected[0].line, 2);
+    assert!(matches!(
+        import.rejected[0].reason,
+        RejectReason::Unreadable(_)
+    ));
+}
+
+#[test]
+fn test_non_ascii_directory_entry_is_rejected_alone() {
+    let (mut binary, _) = write_iso2709(&[book_to_record(&dune())]);
+    // Una voce di directory con un carattere di due byte a cavallo delle posizioni
+    binary.extend("00050nam a2200037 i 4500ab\u{e9}0004000000\x1Eab\x1E\x1D".as_bytes());
+    let records = parse_iso2709(&binary);
+    assert_eq!(records.len(), 2);
+    assert!(records[0].is_ok());
+    assert_eq!(records[1].as_ref().unwrap_err(), "invalid directory entry");
+}
+
+#[test]
+fn test_records_over_the_iso2709_limits_are_left_out() {
+    let long_field = book_to_record(&Book {
+        summary: Some("x".repeat(10_000)),
+        ..dune()
+    });
+    let many_fields = MarcRecord {
+        leader: String::new(),
+        fields: (0..12)
+            .map(|_| MarcField::Control {
+                tag: "009".to_string(),
+                value: "y".repeat(9_000),
+            })
+            .collect(),
+    };
+    let bad_tag = MarcRecord {
+        leader: String::new(),
+        fields: vec![MarcField::Control {
+            tag: "0010".to_string(),
+            value: "id".to_string(),
+        }],
+    };
+    let records = [long_field, book_to_record(&dune()), many_fields, bad_tag];
+    let (binary, too_long) = write_iso2709(&records);
+    assert_eq!(too_long, vec![0, 2, 3]);
+    assert_eq!(binary, write_iso2709(&records[1..2]).0);
+}
+
+#[test]
+fn test_leader_is_always_24_bytes() {
+    let record = MarcRecord {
+        leader: "\u{fffd}\u{fffd}nam \u{fffd}".to_string(),
+        ..book_to_record(&dune())
+    };
+    let (binary, _) = write_iso2709(&[record]);
+    let parsed = parse_iso2709(&binary).remove(0).unwrap();
+    assert_eq!(parsed.leader.len(), 24);
+    assert!(parsed.leader.is_ascii());
+    let length: usize = parsed.leader[..5].parse().unwrap();
+    assert_eq!(length, binary.len());
+}
+
+#[test]
+fn test_library_records_are_mapped_and_losses_reported() {
+    let data = fs::read(fixture()).unwrap();
+    let records = parse_marc(&fixture(), &data).unwrap();
+    let result = map_marc_records(&records);
+    assert_eq!(result.lost_tags.get("082"), Some(&1));
+    assert_eq!(result.lost_tags.get("490"), Some(&2));
+
+    let (line, dune) = &result.import.books[0];
+    assert_eq!(*line, 1);
+    assert_eq!(dune.title, "Dune: a novel");
+    assert_eq!(dune.author, "Frank Herbert, J. R. R. Tolkien");
+    assert_eq!(dune.isbn, "0441013597");
+    assert_eq!(dune.editor, "Ace Books");
+    assert_eq!(dune.year, 2005);
+    assert_eq!(dune.pages, Some(412));
+    assert_eq!(dune.language.as_deref(), Some("English"));
+    assert_eq!(dune.tags.as_deref(), Some("Science fiction, Deserts"));
+    assert_eq!(dune.room.as_deref(), Some("Sala lettura"));
+    assert_eq!(dune.shelf.as_deref(), Some("A3"));
+
+    // Anno e lingua dal campo 008
+    let zeno = &result.import.books[1].1;
+    assert_eq!(zeno.title, "La coscienza di Zeno");
+    assert_eq!(zeno.author, "Italo Svevo");
+    assert_eq!(zeno.year, 1923);
+    assert_eq!(zeno.language.as_deref(), Some("Italian"));
+}
+
+#[test]
+fn test_marc_import_saves_rejected_records_in_the_same_format() {
+    let dir = std::env::temp_dir().join("librius_test_marc_import");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("catalogo.xml");
+    // Un ISBN malformato nel record di Zeno, che nel fixture non ne ha
+    let xml = fs::read_to_string(fixture()).unwrap().replace(
+        "<marc:subfield code=\"a\">Svevo, Italo.</marc:subfield>\n    </marc:datafield>",
+        "<marc:subfield code=\"a\">Svevo, Italo.</marc:subfield>\n    </marc:datafield>\n    \
+         <marc:datafield tag=\"020\" ind1=\" \" ind2=\" \">\n      \
+         <marc:subfield code=\"a\">9780441013590</marc:subfield>\n    </marc:datafield>",
+    );
+    fs::write(&source, xml).unwrap();
+
+    let mut conn = setup_temp_db("marc_import");
+    let partial = ImportOptions {
+        partial: true,
+        ..Default::default()
+    };
+    handle_import_marc(&mut conn, source.to_str().unwrap(), &partial).unwrap();
+
+    let dune = find_book(&conn, "0441013597", true).unwrap().unwrap();
+    assert_eq!(dune.shelf.as_deref(), Some("A3"));
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM books", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 1);
+
+    // Il record con l'ISBN non valido torna in MARCXML, pronto da correggere
+    let rejected = rejected_file_path(&source, "xml");
+    let records = parse_marc(&rejected, &fs::read(&rejected).unwrap()).unwrap();
+    assert_eq!(records.len(), 1);
+    let zeno = record_to_book(records[0].as_ref().unwrap()).unwrap();
+    assert_eq!(zeno.title, "La coscienza di Zeno");
+    assert!(!Path::new(&rejected_file_path(&source, "mrc")).exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,220 @@
+mod common;
+use common::setup_temp_db;
+use librius::db::MigrationResult;
+use librius::db::migrations::{
+    MigrationError, check_not_rolled_back, check_schema_version, migration_status, pinned_version,
+    plan_migration,
+};
+use librius::db::{current_version, ensure_schema, latest_version, migrate_to, run_migrations};
+use rusqlite::Connection;
+
+fn columns(conn: &Connection, table: &str) -> Vec<String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info(?1) ORDER BY name")
+        .unwrap();
+    stmt.query_map([table], |r| r.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn table_exists(conn: &Connection, name: &str) -> bool {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = ?1)",
+        [name],
+        |r| r.get(0),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_fresh_database_reaches_latest_version() {
+    let conn = Connection::open_in_memory().unwrap();
+    assert!(matches!(
+        run_migrations(&conn).unwrap(),
+        MigrationResult::Applied(_)
+    ));
+    assert_eq!(current_version(&conn).unwrap(), latest_version());
+    assert!(matches!(
+        run_migrations(&conn).unwrap(),
+        MigrationResult::None
+    ));
+}
+
+#[test]
+fn test_migrated_schema_matches_test_schema() {
+    // Lo schema di test in common.rs deve restare allineato alle migrazioni
+    let migrated = Connection::open_in_memory().unwrap();
+    ensure_schema(&migrated).unwrap();
+    let reference = setup_temp_db("migration_schema_match");
+
+    assert_eq!(columns(&migrated, "books"), columns(&reference, "books"));
+    assert_eq!(
+        columns(&migrated, "book_history"),
+        columns(&reference, "book_history")
+    );
+}
+
+#[test]
+fn test_legacy_log_patches_are_adopted() {
+    let conn = Connection::open_in_memory().unwrap();
+    migrate_to(&conn, 3).unwrap();
+    // Simula un database creato da una versione che registrava le patch solo nel log
+    conn.execute_batch(
+        "DROP TABLE schema_migrations;
+         DELETE FROM log;
+         INSERT INTO log (date, operation, target, message) VALUES
+            ('2025-01-01T10:00:00+01:00', 'PATCH_001', 'DB', 'Migration applied successfully'),
+            ('2025-01-01T10:00:00+01:00', 'PATCH_002', 'DB', 'Migration applied successfully'),
+            ('2025-02-01T10:00:00+01:00', 'PATCH_003', 'DB', 'Migration applied successfully');",
+    )
+    .unwrap();
+
+    assert_eq!(current_version(&conn).unwrap(), 3);
+    let status = migration_status(&conn).unwrap();
+    assert_eq!(
+        status[2].applied_at.as_deref(),
+        Some("2025-02-01T10:00:00+01:00")
+    );
+
+    match run_migrations(&conn).unwrap() {
+        MigrationResult::Applied(names) => {
+            assert_eq!(
+                names,
+                vec![
+                    "PATCH_004",
+                    "PATCH_005",
+                    "PATCH_006",
+                    "PATCH_007",
+                    "PATCH_008",
+                    "PATCH_009"
+                ]
+            )
+        }
+        MigrationResult::None => panic!("expected pending migrations"),
+    }
+
+    // Svuotare il log non deve più far rieseguire le migrazioni
+    conn.execute("DELETE FROM log", []).unwrap();
+    assert!(matches!(
+        run_migrations(&conn).unwrap(),
+        MigrationResult::None
+    ));
+}
+
+#[test]
+fn test_rollback_and_reapply() {
+    let conn = Connection::open_in_memory().unwrap();
+    run_migrations(&conn).unwrap();
+
+    let steps = migrate_to(&conn, 3).unwrap();
+    assert_eq!(steps.len() as u32, latest_version() - 3);
+    assert_eq!(current_version(&conn).unwrap(), 3);
+    assert!(!table_exists(&conn, "book_history"));
+    assert!(!columns(&conn, "books").contains(&"deleted_at".to_string()));
+    assert!(!columns(&conn, "log").contains(&"details".to_string()));
+
+    migrate_to(&conn, latest_version()).unwrap();
+    assert!(table_exists(&conn, "book_history"));
+    assert!(columns(&conn, "books").contains(&"deleted_at".to_string()));
+    assert!(columns(&conn, "log").contains(&"book_id".to_string()));
+    assert!(columns(&conn, "log").contains(&"details".to_string()));
+    assert!(columns(&conn, "books").contains(&"rating".to_string()));
+    assert!(columns(&conn, "books").contains(&"read_status".to_string()));
+    assert!(columns(&conn, "books").contains(&"format".to_string()));
+}
+
+#[test]
+fn test_rollback_is_not_undone_by_run_migrations() {
+    let conn = Connection::open_in_memory().unwrap();
+    run_migrations(&conn).unwrap();
+    assert_eq!(pinned_version(&conn).unwrap(), None);
+
+    migrate_to(&conn, 4).unwrap();
+    assert_eq!(pinned_version(&conn).unwrap(), Some(4));
+    assert!(matches!(
+        run_migrations(&conn),
+        Err(MigrationError::RolledBack { version: 4, .. })
+    ));
+    assert_eq!(current_version(&conn).unwrap(), 4);
+
+    // Salire di un passo mantiene il pin sulla nuova versione
+    migrate_to(&conn, 6).unwrap();
+    assert_eq!(pinned_version(&conn).unwrap(), Some(6));
+
+    migrate_to(&conn, latest_version()).unwrap();
+    assert!(check_not_rolled_back(&conn).is_ok());
+    assert!(!table_exists(&conn, "schema_pin"));
+}
+
+#[test]
+fn test_partial_migration_of_new_database_is_not_pinned() {
+    let conn = Connection::open_in_memory().unwrap();
+    migrate_to(&conn, 3).unwrap();
+    assert_eq!(pinned_version(&conn).unwrap(), None);
+}
+
+#[test]
+fn test_rollback_past_irreversible_migration_is_refused() {
+    let conn = Connection::open_in_memory().unwrap();
+    run_migrations(&conn).unwrap();
+
+    let err = plan_migration(&conn, 0).err().expect("plan must fail");
+    assert!(matches!(
+        err,
+        MigrationError::Irreversible { version: 1, .. }
+    ));
+    // Nessuna modifica applicata
+    assert_eq!(current_version(&conn).unwrap(), latest_version());
+}
+
+#[test]
+fn test_unknown_target_is_refused() {
+    let conn = Connection::open_in_memory().unwrap();
+    let err = plan_migration(&conn, latest_version() + 1).err().unwrap();
+    assert!(matches!(err, MigrationError::UnknownVersion { .. }));
+}
+
+#[test]
+fn test_newer_schema_is_refused() {
+    let conn = Connection::open_in_memory().unwrap();
+    run_migrations(&conn).unwrap();
+    conn.execute(
+        "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, 'PATCH_FUTURE', 'now')",
+        [latest_version() + 1],
+    )
+    .unwrap();
+
+    assert!(matches!(
+        check_schema_version(&conn),
+        Err(MigrationError::SchemaTooNew { .. })
+    ));
+    assert!(run_migrations(&conn).is_err());
+}
+
+#[test]
+fn test_failed_migration_is_rolled_back() {
+    let conn = Connection::open_in_memory().unwrap();
+    migrate_to(&conn, 2).unwrap();
+
+    // ISBN duplicati: l'indice UNIQUE di PATCH_003 non può essere creato
+    conn.execute_batch(
+        "INSERT INTO books (title, author, editor, year, isbn) VALUES ('A', 'X', 'Y', 2000, '123');
+         INSERT INTO books (title, author, editor, year, isbn) VALUES ('B', 'X', 'Y', 2000, '123');",
+    )
+    .unwrap();
+
+    assert!(migrate_to(&conn, latest_version()).is_err());
+    assert_eq!(current_version(&conn).unwrap(), 2);
+    assert!(!table_exists(&conn, "idx_books_isbn"));
+}
+
+#[test]
+fn test_dry_run_plan_changes_nothing() {
+    let conn = Connection::open_in_memory().unwrap();
+    migrate_to(&conn, 2).unwrap();
+
+    let plan = plan_migration(&conn, latest_version()).unwrap();
+    assert_eq!(plan.len(), (latest_version() - 2) as usize);
+    assert_eq!(current_version(&conn).unwrap(), 2);
+}
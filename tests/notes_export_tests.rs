@@ -0,0 +1,202 @@
+use chrono::NaiveDate;
+use librius::i18n::load_language;
+use librius::models::Book;
+use librius::utils::notes::{
+    INDEX_NOTE, MARKDOWN_MARKER, ORG_MARKER, after_marker, front_matter, front_matter_of,
+};
+use librius::utils::{NotesReport, OrgDocument, org_document, write_markdown_notes};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cartella temporanea dedicata a un test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("librius_test_notes_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn catalog() -> Vec<Book> {
+    vec![
+        Book {
+            id: Some(1),
+            title: "Dune".to_string(),
+            author: "Frank Herbert".to_string(),
+            editor: "Chilton".to_string(),
+            year: 1965,
+            isbn: "9780441013593".to_string(),
+            pages: Some(412),
+            summary: Some("Spice.\n* not a heading".to_string()),
+            read_status: Some("read".to_string()),
+            read_at: NaiveDate::from_ymd_opt(2024, 3, 1),
+            tags: Some("classics, sci-fi".to_string()),
+            ..Default::default()
+        },
+        Book {
+            id: Some(4),
+            title: "Dune: the graphic novel".to_string(),
+            author: "Frank Herbert".to_string(),
+            editor: "Abrams".to_string(),
+            year: 2020,
+            ..Default::default()
+        },
+    ]
+}
+
+fn read(dir: &Path, note: &str) -> String {
+    fs::read_to_string(dir.join(note)).unwrap()
+}
+
+#[test]
+fn test_front_matter_has_every_field_with_types() {
+    let map = front_matter(&catalog()[0], &Mapping::new());
+    let keys: Vec<&str> = map.keys().map(|k| k.as_str().unwrap()).collect();
+    assert_eq!(keys.len(), 21);
+    assert_eq!(keys[..3], ["id", "title", "author"]);
+
+    assert_eq!(map["id"], Value::from(1));
+    assert_eq!(map["pages"], Value::from(412));
+    assert_eq!(map["isbn"], Value::from("9780441013593"));
+    assert_eq!(map["read_status"], Value::from("read"));
+    assert_eq!(map["read_at"], Value::from("2024-03-01"));
+    assert_eq!(
+        map["tags"],
+        Value::Sequence(vec!["classics".into(), "sci-fi".into()])
+    );
+    assert_eq!(map["shelf"], Value::Null);
+}
+
+#[test]
+fn test_after_marker() {
+    let note = format!("generated\n{}\nmine\n\nmore", MARKDOWN_MARKER);
+    assert_eq!(after_marker(&note, MARKDOWN_MARKER), Some("mine\n\nmore"));
+    assert_eq!(after_marker("no marker here", MARKDOWN_MARKER), None);
+}
+
+#[test]
+fn test_notes_are_updated_in_place_keeping_user_text() {
+    load_language("en");
+    let dir = temp_dir("rerun");
+    let mut books = catalog();
+    let report = write_markdown_notes(&dir, &books, None).unwrap();
+    assert_eq!(report.created, 2);
+
+    let dune = read(&dir, "Dune.md");
+    assert_eq!(front_matter_of(&dune).unwrap()["id"], Value::from(1));
+    assert!(dune.contains("\n# Dune\n"));
+    assert!(dune.ends_with(&format!("{}\n", MARKDOWN_MARKER)));
+    // i due punti non sono ammessi nei nomi dei file
+    assert!(dir.join("Dune the graphic novel.md").is_file());
+    let index = read(&dir, INDEX_NOTE);
+    assert!(index.contains("| [Dune](Dune.md) | Frank Herbert | 1965 | Read |"));
+    assert!(index.contains("(Dune%20the%20graphic%20novel.md)"));
+
+    // Testo e proprietà dell'utente sopravvivono alla nuova esportazione
+    let edited =
+        format!("{}My notes.\n", dune).replace("format: null\n", "format: null\nmood: happy\n");
+    fs::write(dir.join("Dune.md"), &edited).unwrap();
+    books[0].rating = Some(5);
+    let report = write_markdown_notes(&dir, &books, None).unwrap();
+    assert_eq!((report.updated, report.unchanged), (1, 1));
+    let dune = read(&dir, "Dune.md");
+    assert!(dune.contains("rating: 5\n"));
+    assert!(dune.contains("mood: happy\n"));
+    assert!(dune.ends_with(&format!("{}\nMy notes.\n", MARKDOWN_MARKER)));
+
+    // Nessuna modifica: nessun file riscritto
+    let report = write_markdown_notes(&dir, &books, None).unwrap();
+    assert_eq!(report.unchanged, 2);
+
+    // Un titolo nuovo rinomina la nota
+    books[0].title = "Dune Messiah".to_string();
+    write_markdown_notes(&dir, &books, None).unwrap();
+    assert!(!dir.join("Dune.md").exists());
+    assert!(read(&dir, "Dune Messiah.md").ends_with("My notes.\n"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_notes_never_overwrite_files_they_do_not_own() {
+    let dir = temp_dir("foreign");
+    fs::write(dir.join("Dune.md"), "my own Dune essay").unwrap();
+    let mut books = catalog();
+    write_markdown_notes(&dir, &books, None).unwrap();
+    assert_eq!(read(&dir, "Dune.md"), "my own Dune essay");
+    assert!(dir.join("Dune (1).md").is_file());
+
+    // Note orfane o senza segnaposto restano come sono
+    let graphic = read(&dir, "Dune the graphic novel.md").replace(MARKDOWN_MARKER, "");
+    fs::write(dir.join("Dune the graphic novel.md"), &graphic).unwrap();
+    books[1].year = 2021;
+    let report = write_markdown_notes(&dir, &books[1..], None).unwrap();
+    assert_eq!(
+        report,
+        NotesReport {
+            orphaned: 1,
+            skipped: vec!["Dune the graphic novel.md".to_string()],
+            ..Default::default()
+        }
+    );
+    assert_eq!(read(&dir, "Dune the graphic novel.md"), graphic);
+    assert!(dir.join("Dune (1).md").is_file());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_org_document_carries_over_user_text() {
+    load_language("en");
+    let books = catalog();
+    let first = org_document(&books, None, Some("home")).text;
+    assert!(first.starts_with("#+TITLE: Library catalogue — home\n#+LANGUAGE: en\n\n"));
+    assert!(first.contains(
+        "* Dune :classics:sci_fi:\n:PROPERTIES:\n:LIBRIUS_ID: 1\n:AUTHOR: Frank Herbert\n"
+    ));
+    assert!(first.contains(":READ_AT: [2024-03-01 Fri]\n"));
+    assert!(first.contains(":TAGS: classics, sci-fi\n:END:\n"));
+    // una riga della trama non diventa un titolo
+    assert!(first.contains("\n * not a heading\n"));
+    assert!(!first.contains(":SHELF:"));
+
+    let edited = first.replacen(
+        &format!("{}\n", ORG_MARKER),
+        &format!(
+            "{}\nRe-read in 2025.\n** Quotes\nFear is the mind-killer.\n",
+            ORG_MARKER
+        ),
+        1,
+    ) + "* Reading plan\nNext year.\n";
+    let second = org_document(&books[..1], Some(&edited), Some("home")).text;
+    assert!(second.contains(&format!(
+        "{}\nRe-read in 2025.\n** Quotes\nFear is the mind-killer.\n",
+        ORG_MARKER
+    )));
+    // Sezioni non generate da Librius o di libri rimossi restano in fondo
+    assert!(second.contains("* Dune: the graphic novel\n"));
+    assert!(second.ends_with("* Reading plan\nNext year.\n"));
+    assert_eq!(
+        org_document(&books[..1], Some(&second), Some("home")).text,
+        second
+    );
+}
+
+#[test]
+fn test_org_section_without_marker_is_kept_in_place() {
+    load_language("en");
+    let books = catalog();
+    let first = org_document(&books, None, Some("home")).text;
+
+    // Il segnaposto della prima sezione è stato cancellato a mano
+    let edited = first.replacen(&format!("{}\n", ORG_MARKER), "My own Dune notes.\n", 1);
+    let second = org_document(&books, Some(&edited), Some("home"));
+    assert_eq!(
+        second,
+        OrgDocument {
+            text: edited.clone(),
+            skipped: vec!["Dune".to_string()],
+        }
+    );
+    assert_eq!(second.text.matches(":LIBRIUS_ID: 1\n").count(), 1);
+}
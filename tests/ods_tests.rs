@@ -0,0 +1,155 @@
+use chrono::NaiveDate;
+use librius::utils::ods::ODS_MIMETYPE;
+use librius::utils::{OdsCell, read_ods_rows, write_ods};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+use zip::write::SimpleFileOptions;
+
+/// Cartella temporanea dedicata a un test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("librius_test_ods_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn entry(path: &Path, name: &str) -> String {
+    let mut archive = ZipArchive::new(File::open(path).unwrap()).unwrap();
+    let mut text = String::new();
+    archive
+        .by_name(name)
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    text
+}
+
+#[test]
+fn test_written_sheet_has_typed_cells_and_bold_header() {
+    let dir = temp_dir("write");
+    let path = dir.join("books.ods");
+    let added = NaiveDate::from_ymd_opt(2021, 3, 4)
+        .unwrap()
+        .and_hms_opt(10, 20, 30)
+        .unwrap();
+    write_ods(
+        &path,
+        "Sheet1",
+        &["title", "year", "added_at", "read_at", "summary"],
+        &[vec![
+            OdsCell::Text("Dune".to_string()),
+            OdsCell::Number(1965.0),
+            OdsCell::DateTime(added),
+            OdsCell::Date(NaiveDate::from_ymd_opt(2022, 1, 9).unwrap()),
+            OdsCell::Text(" Spice  &  sand\n\tworms".to_string()),
+        ]],
+    )
+    .unwrap();
+
+    // il mimetype è la prima voce, non compressa
+    let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+    let first = archive.by_index(0).unwrap();
+    assert_eq!(first.name(), "mimetype");
+    assert_eq!(first.compression(), zip::CompressionMethod::Stored);
+    drop(first);
+    assert_eq!(entry(&path, "mimetype"), ODS_MIMETYPE);
+    assert!(entry(&path, "META-INF/manifest.xml").contains("manifest:full-path=\"content.xml\""));
+
+    let content = entry(&path, "content.xml");
+    assert!(content.contains("<style:text-properties fo:font-weight=\"bold\""));
+    assert!(content.contains(
+        "<table:table-cell table:style-name=\"header\" office:value-type=\"string\"><text:p>title</text:p>"
+    ));
+    assert!(content.contains("office:value-type=\"float\" office:value=\"1965\""));
+    assert!(
+        content.contains("office:value-type=\"date\" office:date-value=\"2021-03-04T10:20:30\"")
+    );
+    assert!(content.contains("office:date-value=\"2022-01-09\""));
+    assert!(content.contains("<text:p><text:s text:c=\"1\"/>Spice <text:s text:c=\"1\"/>&amp; <text:s text:c=\"1\"/>sand</text:p><text:p><text:tab/>worms</text:p>"));
+
+    assert_eq!(
+        read_ods_rows(&path).unwrap(),
+        vec![
+            vec!["title", "year", "added_at", "read_at", "summary"],
+            vec![
+                "Dune",
+                "1965",
+                "2021-03-04 10:20:30",
+                "2022-01-09",
+                " Spice  &  sand\n\tworms"
+            ],
+        ]
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_reads_sheets_saved_by_spreadsheet_applications() {
+    let dir = temp_dir("read");
+    let path = dir.join("catalogo.ods");
+
+    // Come li salva LibreOffice: celle e righe ripetute, valori mostrati nel
+    // formato locale, commenti, un secondo foglio
+    let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" office:version="1.3">
+ <office:body>
+  <office:spreadsheet>
+   <table:table table:name="Libri">
+    <table:table-column table:number-columns-repeated="4"/>
+    <table:table-row>
+     <table:table-cell office:value-type="string"><text:p>Titolo</text:p></table:table-cell>
+     <table:table-cell office:value-type="string"><text:p>Anno</text:p></table:table-cell>
+     <table:table-cell office:value-type="string"><text:p>Aggiunto</text:p></table:table-cell>
+     <table:table-cell office:value-type="string"><text:p>Note</text:p></table:table-cell>
+     <table:table-cell table:number-columns-repeated="1020"/>
+    </table:table-row>
+    <table:table-row>
+     <table:table-cell office:value-type="string"><text:p>Il <text:span>Gattopardo</text:span></text:p><office:annotation><text:p>da rileggere</text:p></office:annotation></table:table-cell>
+     <table:table-cell office:value-type="float" office:value="1958"><text:p>1.958</text:p></table:table-cell>
+     <table:table-cell office:value-type="date" office:date-value="2023-05-14T08:30:00.250"><text:p>14/05/23</text:p></table:table-cell>
+     <table:table-cell office:value-type="string"><text:p>prima<text:s text:c="2"/>riga</text:p><text:p>seconda<text:line-break/>terza</text:p></table:table-cell>
+    </table:table-row>
+    <table:table-row table:number-rows-repeated="2"><table:table-cell table:number-columns-repeated="1024"/></table:table-row>
+    <table:table-row>
+     <table:table-cell table:number-columns-repeated="2" office:value-type="string"><text:p>x</text:p></table:table-cell>
+     <table:covered-table-cell/>
+     <table:table-cell office:value-type="boolean" office:boolean-value="true"><text:p>VERO</text:p></table:table-cell>
+    </table:table-row>
+    <table:table-row table:number-rows-repeated="1048570"><table:table-cell table:number-columns-repeated="1024"/></table:table-row>
+   </table:table>
+   <table:table table:name="Altro"><table:table-row><table:table-cell office:value-type="string"><text:p>no</text:p></table:table-cell></table:table-row></table:table>
+  </office:spreadsheet>
+ </office:body>
+</office:document-content>"#;
+
+    let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+    zip.start_file("mimetype", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(ODS_MIMETYPE.as_bytes()).unwrap();
+    zip.start_file("content.xml", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(content.as_bytes()).unwrap();
+    zip.finish().unwrap();
+
+    let rows = read_ods_rows(&path).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            vec!["Titolo", "Anno", "Aggiunto", "Note"],
+            vec![
+                "Il Gattopardo",
+                "1958",
+                "2023-05-14 08:30:00",
+                "prima  riga\nseconda\nterza"
+            ],
+            vec!["", "", "", ""],
+            vec!["", "", "", ""],
+            vec!["x", "x", "", "true"],
+        ]
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,173 @@
+mod common;
+use chrono::Duration;
+use common::setup_temp_db;
+use librius::db::find_book_by_id;
+use librius::db::history::{get_history, last_undoable, undo_entry};
+use librius::db::trash::{
+    find_trashed, list_trash, purge_book, restore_book, trash_book, trashed_id,
+};
+use librius::db::{
+    ensure_schema, get_book_fields, run_migrations, search_books, update_book_by_id,
+    update_book_by_isbn,
+};
+use librius::models::{Book, HistoryOperation};
+use librius::utils::parse_age;
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+fn insert_book(conn: &Connection, title: &str, isbn: &str) -> Book {
+    conn.execute(
+        "INSERT INTO books (title, author, editor, year, isbn) VALUES (?1, 'Frank Herbert', 'Chilton', 1965, ?2)",
+        (title, isbn),
+    )
+    .unwrap();
+    find_book_by_id(conn, conn.last_insert_rowid())
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn test_trashed_books_are_hidden_from_search() {
+    let conn = setup_temp_db("trash_search");
+    let dune = insert_book(&conn, "Dune", "9780441013593");
+    insert_book(&conn, "Dune Messiah", "9780593098233");
+
+    assert!(trash_book(&conn, &dune).unwrap());
+    // Un secondo spostamento nel cestino non ha effetto
+    assert!(!trash_book(&conn, &dune).unwrap());
+
+    let results = search_books(&conn, "Dune").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].title, "Dune Messiah");
+
+    let trash = list_trash(&conn).unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].book, dune);
+}
+
+#[test]
+fn test_restore_from_trash() {
+    let conn = setup_temp_db("trash_restore");
+    let dune = insert_book(&conn, "Dune", "9780441013593");
+    let id = dune.id.unwrap() as i64;
+
+    assert!(!restore_book(&conn, id).unwrap(), "not in trash yet");
+    trash_book(&conn, &dune).unwrap();
+    assert!(restore_book(&conn, id).unwrap());
+
+    assert!(find_trashed(&conn, id).unwrap().is_none());
+    assert_eq!(search_books(&conn, "Dune").unwrap(), vec![dune]);
+
+    let ops: Vec<HistoryOperation> = get_history(&conn, id)
+        .unwrap()
+        .iter()
+        .map(|h| h.operation)
+        .collect();
+    assert_eq!(
+        ops,
+        vec![HistoryOperation::Trash, HistoryOperation::Restore]
+    );
+}
+
+#[test]
+fn test_purge_removes_book_and_undo_restores_it() {
+    let conn = setup_temp_db("trash_purge");
+    let dune = insert_book(&conn, "Dune", "9780441013593");
+    let id = dune.id.unwrap() as i64;
+
+    trash_book(&conn, &dune).unwrap();
+    assert!(purge_book(&conn, &dune).unwrap());
+    assert!(find_book_by_id(&conn, id).unwrap().is_none());
+    assert!(list_trash(&conn).unwrap().is_empty());
+
+    let entries = last_undoable(&conn, 1).unwrap();
+    assert_eq!(entries[0].operation, HistoryOperation::Delete);
+    assert!(entries[0].deleted_at.is_some());
+    undo_entry(&conn, &entries[0]).unwrap();
+    assert_eq!(find_book_by_id(&conn, id).unwrap(), Some(dune));
+
+    // Il libro eliminato dal cestino torna nel cestino, non nella libreria
+    let trash = list_trash(&conn).unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].book.id.map(i64::from), Some(id));
+    assert!(search_books(&conn, "Dune").unwrap().is_empty());
+}
+
+#[test]
+fn test_undo_purge_of_active_book_restores_it_to_library() {
+    let conn = setup_temp_db("trash_purge_active");
+    let dune = insert_book(&conn, "Dune", "9780441013593");
+
+    assert!(purge_book(&conn, &dune).unwrap());
+    let entries = last_undoable(&conn, 1).unwrap();
+    assert!(entries[0].deleted_at.is_none());
+    undo_entry(&conn, &entries[0]).unwrap();
+
+    assert!(list_trash(&conn).unwrap().is_empty());
+    assert_eq!(search_books(&conn, "Dune").unwrap().len(), 1);
+}
+
+#[test]
+fn test_undo_trash() {
+    let conn = setup_temp_db("trash_undo");
+    let dune = insert_book(&conn, "Dune", "9780441013593");
+
+    trash_book(&conn, &dune).unwrap();
+    let entries = last_undoable(&conn, 1).unwrap();
+    undo_entry(&conn, &entries[0]).unwrap();
+
+    assert!(list_trash(&conn).unwrap().is_empty());
+    assert_eq!(search_books(&conn, "Dune").unwrap().len(), 1);
+}
+
+#[test]
+fn test_trashed_books_cannot_be_edited() {
+    let conn = setup_temp_db("trash_edit");
+    let dune = insert_book(&conn, "Dune", "9780441013593");
+    let id = i64::from(dune.id.unwrap());
+    trash_book(&conn, &dune).unwrap();
+
+    assert_eq!(trashed_id(&conn, &id.to_string(), false).unwrap(), Some(id));
+    assert_eq!(trashed_id(&conn, "9780441013593", true).unwrap(), Some(id));
+
+    let fields = HashMap::from([("title".to_string(), "Dune Messiah".to_string())]);
+    assert_eq!(update_book_by_id(&conn, id, &fields).unwrap(), 0);
+    assert_eq!(
+        update_book_by_isbn(&conn, "9780441013593", &fields).unwrap(),
+        0
+    );
+    let old = get_book_fields(&conn, &id.to_string(), &["title".to_string()], false).unwrap();
+    assert_eq!(old["title"], None);
+
+    // Dopo il ripristino il libro si modifica di nuovo
+    restore_book(&conn, id).unwrap();
+    assert_eq!(trashed_id(&conn, &id.to_string(), false).unwrap(), None);
+    assert_eq!(update_book_by_id(&conn, id, &fields).unwrap(), 1);
+}
+
+#[test]
+fn test_migration_adds_deleted_at() {
+    let conn = Connection::open_in_memory().unwrap();
+    ensure_schema(&conn).unwrap();
+    run_migrations(&conn).unwrap();
+
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('books') WHERE name = 'deleted_at'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_parse_age() {
+    assert_eq!(parse_age("30d"), Some(Duration::days(30)));
+    assert_eq!(parse_age("2w"), Some(Duration::weeks(2)));
+    assert_eq!(parse_age("12H"), Some(Duration::hours(12)));
+    assert_eq!(parse_age("7"), Some(Duration::days(7)));
+    assert_eq!(parse_age(""), None);
+    assert_eq!(parse_age("-3d"), None);
+    assert_eq!(parse_age("3 months"), None);
+}